@@ -0,0 +1,53 @@
+import type { SnowFallDocument as WasmDocument } from "../../pkg/snowfall_core";
+import { SnowFallError } from "./SnowFallError";
+import type { EditResult, ISnowFallError, ParserResult } from "./types";
+
+/**
+ * 編集に応じて増分解析する文書
+ *
+ * ソースコードはWasm側で保持するため、編集ごとに全文を受け渡す必要はない。
+ * 位置はすべてUTF-8のバイト単位。使用後は `free()` で解放すること。
+ */
+export class SnowFallDocument {
+	private readonly _document: WasmDocument;
+
+	constructor(document: WasmDocument) {
+		this._document = document;
+	}
+
+	/** 現在のソースコード */
+	public get source(): string {
+		return this._document.source();
+	}
+
+	/**
+	 * `start..end` を `text` に置き換え、影響を受けた部分のみを解析し直す
+	 * @param start 置き換える範囲の開始位置 (編集前のバイト位置)
+	 * @param end 置き換える範囲の終了位置 (編集前のバイト位置)
+	 * @param text 挿入する文字列
+	 * @returns 解析し直した範囲と、一部のみを解析し直したかどうか
+	 */
+	public apply_edit(start: number, end: number, text: string): EditResult {
+		return this._document.apply_edit(start, end, text);
+	}
+
+	/**
+	 * 現在の解析結果
+	 * @returns AST、または構文エラー・意味解析のエラー
+	 */
+	public result(): ParserResult {
+		const result = this._document.result() as ParserResult;
+		if (result.errors) {
+			return {
+				...result,
+				errors: result.errors.map((err: ISnowFallError) => new SnowFallError(err)),
+			};
+		}
+		return result;
+	}
+
+	/** Wasm側で保持している文書を解放する */
+	public free(): void {
+		this._document.free();
+	}
+}
@@ -1,10 +1,17 @@
-import type { ISnowFallError, SnowFallErrorContext } from "../common/types";
+import type { DiagnosticLabel, ISnowFallError, Severity, SnowFallErrorContext, Span, Suggestion } from "../common/types";
 
 export class SnowFallError extends Error implements ISnowFallError {
 	public readonly type: string;
 	public readonly code: string;
 	public readonly line: number;
 	public readonly column: number;
+	public readonly severity: Severity;
+	public readonly span?: Span;
+	public readonly end_line: number;
+	public readonly end_column: number;
+	public readonly labels?: DiagnosticLabel[];
+	public readonly notes?: string[];
+	public readonly suggestions?: Suggestion[];
 	public readonly trace: string[];
 	public readonly context?: SnowFallErrorContext;
 
@@ -15,6 +22,13 @@ export class SnowFallError extends Error implements ISnowFallError {
 		this.code = error.code;
 		this.line = error.line;
 		this.column = error.column;
+		this.severity = error.severity;
+		this.span = error.span;
+		this.end_line = error.end_line;
+		this.end_column = error.end_column;
+		this.labels = error.labels;
+		this.notes = error.notes;
+		this.suggestions = error.suggestions;
 		this.trace = error.trace;
 		this.context = error.context;
 
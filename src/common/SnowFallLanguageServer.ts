@@ -0,0 +1,54 @@
+import type { SnowFallLanguageServer as WasmLanguageServer } from "../../pkg/snowfall_core";
+import type { JsonRpcMessage } from "./types";
+
+/**
+ * LSP互換のサーバー
+ *
+ * 入出力はメモリ上で完結する。受け取ったメッセージを `send` に渡し、返されたメッセージをクライアントへ送り返す。
+ * 標準入出力などのバイト列をそのまま扱う場合は `receive` を使用する。
+ * 使用後は `free()` で解放すること。
+ */
+export class SnowFallLanguageServer {
+	private readonly _server: WasmLanguageServer;
+
+	constructor(server: WasmLanguageServer) {
+		this._server = server;
+	}
+
+	/**
+	 * メッセージを1つ処理する
+	 * @param message JSON-RPCのメッセージ
+	 * @returns 送り返す応答と通知 (要求には必ず1つの応答を含む)
+	 */
+	public send(message: JsonRpcMessage): JsonRpcMessage[] {
+		return this.handle(JSON.stringify(message)).map((body) => JSON.parse(body) as JsonRpcMessage);
+	}
+
+	/**
+	 * メッセージの本文を1つ処理する
+	 * @param body JSON-RPCのメッセージの本文 (Content-Length ヘッダを除く)
+	 * @returns 送り返すメッセージの本文
+	 */
+	public handle(body: string): string[] {
+		return this._server.handle(body) as string[];
+	}
+
+	/**
+	 * ヘッダ付きのメッセージのバイト列を処理する
+	 * @param data 受け取ったバイト列 (メッセージの途中で区切られていてもよい)
+	 * @returns 送り返すヘッダ付きのメッセージのバイト列 (揃ったメッセージがなければ空)
+	 */
+	public receive(data: Uint8Array): Uint8Array {
+		return this._server.receive(data);
+	}
+
+	/** `exit` を受け取った場合の終了コード (`shutdown` の後なら0、そうでなければ1) */
+	public get exit_code(): number | undefined {
+		return this._server.exit_code();
+	}
+
+	/** Wasm側で保持しているサーバーを解放する */
+	public free(): void {
+		this._server.free();
+	}
+}
@@ -11,16 +11,91 @@ export interface SnowFallErrorContext {
 	[key: string]: string;
 }
 
+export type Severity = "Error" | "Warning" | "Info" | "Hint";
+
+/** ソースコード上の範囲と、その開始・終了位置の行・列 (1ベース) */
+export interface SourceRange {
+	span: Span;
+	line: number;
+	column: number;
+	end_line: number;
+	end_column: number;
+}
+
+/** 診断に付随する補助的な範囲 */
+export interface DiagnosticLabel {
+	range: SourceRange;
+	message: string;
+}
+
+/** `range` を `replacement` で置き換える編集 (挿入は空の範囲) */
+export interface TextEdit {
+	range: SourceRange;
+	replacement: string;
+}
+
+/** 機械的に適用できる修正案 */
+export interface Suggestion {
+	message: string;
+	edits: TextEdit[];
+}
+
 export interface ISnowFallError {
 	type: string;
 	message: string;
 	code: string;
 	line: number;
 	column: number;
+	severity: Severity;
+	/** 診断の対象範囲 (`line` / `column` はその開始位置) */
+	span?: Span;
+	end_line: number;
+	end_column: number;
+	labels?: DiagnosticLabel[];
+	notes?: string[];
+	suggestions?: Suggestion[];
 	trace: string[];
 	context?: SnowFallErrorContext;
 }
 
+/** 診断レポートの整形設定 */
+export interface RenderOptions {
+	/** ANSIエスケープシーケンスで色付けする (既定値は false) */
+	color?: boolean;
+	/** 位置情報に表示するファイル名 (既定値は "<input>") */
+	file_name?: string;
+}
+
+/** エラーコードの分類 (Lexer: SF0001-0009 / Parser: SF0010-0099 / Semantic: SF0100-0199 / Warning: SF0200-0299 / Runtime: SF0300-0399) */
+export type ErrorCategory = "Lexer" | "Parser" | "Semantic" | "Warning" | "Runtime";
+
+/** エラーコードの詳しい説明 */
+export interface ErrorCodeInfo {
+	/** コード (例: "SF0010") */
+	code: string;
+	/** 名前 (例: "UnexpectedToken") */
+	name: string;
+	number: number;
+	category: ErrorCategory;
+	/** 原因と対処方法の説明 */
+	explanation: string;
+	/** この診断が報告されるソースコードの例 */
+	example: string;
+}
+
+/** 診断メッセージの言語 */
+export type Locale = "en" | "ja";
+
+export interface CompileOptions {
+	debug_info?: boolean;
+	/** 診断メッセージの言語 (既定値は "en") */
+	locale?: Locale;
+	/** 報告する構文エラーの上限 (既定値は 100、超えた場合は SF0021 を報告して解析を打ち切る) */
+	max_errors?: number;
+	/** 正規化 (最適化) で実行するパスの設定 */
+	optimization?: OptimizationOptions;
+}
+
 /* ================================================== */
 /* Lexer使用 */
 /* ================================================== */
@@ -96,7 +171,18 @@ export type KeywordToken = {
 		| "False"
 		| "Null"
 		| "And"
-		| "Or";
+		| "Or"
+		| "Const"
+		| "Readonly";
+};
+
+export type TriviaToken = {
+	type:
+		| "Whitespace" //
+		| "Newline"
+		| "LineComment"
+		| "BlockComment"
+		| "Skipped";
 };
 
 export type TokenKind =
@@ -105,7 +191,8 @@ export type TokenKind =
 	| { type: "Literal"; value: LiteralToken }
 	| { type: "Operator"; value: OperatorToken }
 	| { type: "Delimiter"; value: DelimiterToken }
-	| { type: "Keyword"; value: KeywordToken };
+	| { type: "Keyword"; value: KeywordToken }
+	| { type: "Trivia"; value: TriviaToken };
 
 export interface Token {
 	kind: TokenKind;
@@ -116,6 +203,9 @@ export interface Token {
 /* Parser使用 */
 /* ================================================== */
 
+/** 構文木内で一意なノードID (パース後に合成されたノードは 4294967295) */
+export type NodeId = number;
+
 export type PrefixOperator =
 	| "Plus" //
 	| "Minus"
@@ -165,22 +255,16 @@ export interface Cast {
 	target_type: string;
 	expression: Expression;
 }
-export interface Index {
-	left: Expression;
-	index: Expression;
-}
-export interface Member {
-	left: Expression;
-	property: string;
-}
 export interface Assignment {
 	left: Expression;
 	right: Expression;
 }
+export type MemberProperty =
+	| { type: "Named"; value: string } // `object.name`
+	| { type: "Computed"; value: Expression }; // `object[expr]`
 export interface MemberAccess {
 	object: Expression;
-	property: Expression;
-	computed: Boolean;
+	property: MemberProperty;
 }
 export interface New {
 	class: Expression;
@@ -200,7 +284,6 @@ export type ExpressionKind =
 	| { type: "NullLiteral" }
 	| { type: "ArrayLiteral"; value: Expression[] }
 	| { type: "ObjectLiteral"; value: [Expression, Expression][] }
-	| { type: "Index"; value: Index }
 	| { type: "Assignment"; value: Assignment }
 	| { type: "MemberAccess"; value: MemberAccess }
 	| { type: "New"; value: New };
@@ -208,6 +291,7 @@ export type ExpressionKind =
 export interface Expression {
 	kind: ExpressionKind;
 	span: Span;
+	id: NodeId;
 }
 
 export interface VariableDeclarator {
@@ -218,12 +302,14 @@ export interface Parameter {
 	name: string;
 	type_name: string;
 	value?: Expression;
+	span: Span;
 }
 export interface Binding {
 	name: string;
 	type_name: string;
 }
 export type FunctionKind = "Function" | "Sub";
+export type Mutability = "Mutable" | "Const" | "Readonly";
 export type ForEachKind = "In" | "Of";
 export interface SwitchCase {
 	values: Expression[];
@@ -233,6 +319,7 @@ export interface SwitchCase {
 export interface VariableDeclaration {
 	type_name: string;
 	declarators: VariableDeclarator[];
+	mutability: Mutability;
 }
 export interface FunctionDeclaration {
 	kind: FunctionKind;
@@ -286,14 +373,249 @@ export type StatementKind =
 export interface Statement {
 	kind: StatementKind;
 	span: Span;
+	id: NodeId;
 }
 
 export interface ProgramAst {
 	statements: Statement[];
 	span: Span;
+	id: NodeId;
 }
 
 export interface ParserResult {
 	ast?: ProgramAst;
 	errors?: ISnowFallError[];
 }
+
+/** 文書の編集結果 */
+export interface EditResult {
+	/** 解析し直した範囲 (編集後のバイト位置) */
+	reparsed: Span;
+	/** 一部のみを解析し直したかどうか (false の場合は全体を解析し直した) */
+	incremental: boolean;
+}
+
+/* ================================================== */
+/* Normalizer使用 */
+/* ================================================== */
+export type OptimizationPass =
+	| "ConstantPropagation" //
+	| "FunctionInlining"
+	| "ConstantFolding"
+	| "StrengthReduction"
+	| "CommutativeCanonicalization"
+	| "CommonSubexpressionElimination"
+	| "DeadCodeElimination";
+
+/** 最適化レベル (O0: 正規化しない / O1: 評価順や評価回数を変えないパスのみ / O2: 全てのパス) */
+export type OptLevel = "O0" | "O1" | "O2";
+
+export interface OptimizationOptions {
+	/** 既定値は "O2" */
+	level?: OptLevel;
+	/** 実行するパスの並び (指定した場合は `level` より優先する) */
+	passes?: OptimizationPass[];
+	/** 各パスの実行後のASTを記録する */
+	dump_passes?: boolean;
+}
+
+/** パス実行後のASTの記録 */
+export interface PassDump {
+	pass: OptimizationPass;
+	/** パスの並びを繰り返した回数 (0ベース) */
+	iteration: number;
+	program: ProgramAst;
+}
+
+export type OriginKind = "Folded" | "ConstantReference" | "Inlined";
+
+/** 正規化によって合成されたノードの由来 */
+export interface Origin {
+	kind: OriginKind;
+	/** 値の元になったソースコード上の範囲 */
+	sources: Span[];
+}
+
+export interface NormalizeResult {
+	ast?: ProgramAst;
+	/** 合成されたノードのIDと由来の組 (ID順) */
+	origins?: [NodeId, Origin][];
+	/** 到達不能なコードなどの警告 (`type` は "CompilationWarning") */
+	warnings?: ISnowFallError[];
+	/** 各パスの実行後のAST (`dump_passes` が有効な場合のみ) */
+	dumps?: PassDump[];
+	errors?: ISnowFallError[];
+}
+
+/* ================================================== */
+/* CST使用 */
+/* ================================================== */
+
+export interface Trivia {
+	kind: TriviaToken;
+	text: string;
+	span: Span;
+}
+
+export interface CstToken {
+	kind: TokenKind;
+	text: string;
+	span: Span;
+	leading_trivia: Trivia[];
+	trailing_trivia: Trivia[];
+}
+
+export type CstNodeKind = "Program" | "Statement" | "Expression";
+
+export type CstElement =
+	| { type: "Node"; value: CstNode } //
+	| { type: "Token"; value: CstToken };
+
+export interface CstNode {
+	kind: CstNodeKind;
+	span: Span;
+	/** 対応するASTノードの識別子 (構文エラーでASTがない場合は省略) */
+	ast_id?: NodeId;
+	children: CstElement[];
+}
+
+export interface ConcreteSyntaxTree {
+	root: CstNode;
+}
+
+export interface CstResult {
+	cst: ConcreteSyntaxTree;
+	errors?: ISnowFallError[];
+}
+
+/* ================================================== */
+/* Formatter使用 */
+/* ================================================== */
+
+export type BraceStyle = "SameLine" | "NextLine";
+
+export interface FormatOptions {
+	/** インデント幅 (空白数) */
+	indent_width?: number;
+	/** 1行の最大幅 */
+	max_line_width?: number;
+	/** 波括弧の配置 */
+	brace_style?: BraceStyle;
+	/** 構文エラーのメッセージの言語 (既定値は "en") */
+	locale?: Locale;
+}
+
+export interface FormatResult {
+	code?: string;
+	errors?: ISnowFallError[];
+}
+
+/* ================================================== */
+/* Linter使用 */
+/* ================================================== */
+
+export type RuleName =
+	| "unused-variable"
+	| "unused-parameter"
+	| "shadowing"
+	| "loose-equality"
+	| "assignment-in-condition"
+	| "empty-block"
+	| "unreachable-code"
+	| "constant-condition";
+
+/** ルールの報告レベル (off: 検査しない / warn: 警告 / error: エラー) */
+export type RuleLevel = "off" | "warn" | "error";
+
+export interface LintConfig {
+	/** ルールごとの報告レベル (指定のないルールは "warn") */
+	rules?: Partial<Record<RuleName, RuleLevel>>;
+	/** 診断メッセージの言語 (既定値は "en") */
+	locale?: Locale;
+}
+
+export interface LintResult {
+	/** 有効なルールの診断 (位置順、`context.rule` にルール名を持つ) */
+	diagnostics: ISnowFallError[];
+	/** 構文エラーまたは意味解析のエラー */
+	errors?: ISnowFallError[];
+}
+
+/* ================================================== */
+/* Semantic Tokens使用 */
+/* ================================================== */
+
+export type SemanticTokenKind =
+	| "keyword"
+	| "type"
+	| "variable"
+	| "parameter"
+	| "function"
+	| "class"
+	| "property"
+	| "number"
+	| "string"
+	| "comment"
+	| "operator";
+
+/** トークンの修飾子 (static は現在出力されない) */
+export type SemanticTokenModifier = "declaration" | "readonly" | "static";
+
+/** シンタックスハイライト用に分類したトークン */
+export interface SemanticToken {
+	span: Span;
+	kind: SemanticTokenKind;
+	/** 修飾子 (ない場合は省略) */
+	modifiers?: SemanticTokenModifier[];
+}
+
+/* ================================================== */
+/* Completion使用 */
+/* ================================================== */
+
+/** 補完する位置の文脈 (None: コメントや文字列の中、宣言する名前の位置など補完しない位置) */
+export type CompletionContext = "Statement" | "ClassMember" | "Expression" | "Operator" | "Member" | "Type" | "None";
+
+/** 補完候補の種類 (Type: 組み込みの型 / Function: 関数とサブルーチン) */
+export type CompletionKind = "Keyword" | "Type" | "Variable" | "Constant" | "Parameter" | "Function" | "Class" | "Field" | "Method";
+
+export interface CompletionItem {
+	label: string;
+	kind: CompletionKind;
+	/** 宣言の表記 (例: "function Int add(Int a, Int b)") */
+	detail?: string;
+}
+
+export interface CompletionList {
+	context: CompletionContext;
+	/** 候補で置き換える範囲 (入力中の名前、なければカーソル位置の空の範囲) */
+	replace: Span;
+	/** 順位の高い順の候補 */
+	items: CompletionItem[];
+}
+
+/* ================================================== */
+/* Language Server使用 */
+/* ================================================== */
+
+/** JSON-RPCのメッセージ (要求・通知・応答) */
+export interface JsonRpcMessage {
+	jsonrpc: "2.0";
+	/** 要求と応答の識別子 (通知には存在しない) */
+	id?: number | string | null;
+	/** メソッド名 (応答には存在しない) */
+	method?: string;
+	params?: unknown;
+	result?: unknown;
+	error?: { code: number; message: string };
+}
+
+/** `initialize` の `initializationOptions` で指定する設定 */
+export interface LanguageServerOptions {
+	/** 診断メッセージの言語 (既定値は "en") */
+	locale?: Locale;
+	/** 報告する構文エラーの上限 (既定値は100) */
+	max_errors?: number;
+	/** リントルールごとの報告レベル (指定のないルールは "warn") */
+	lint?: Partial<Record<RuleName, RuleLevel>>;
+}
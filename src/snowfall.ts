@@ -1,6 +1,8 @@
 import init, * as wasm from "../pkg/snowfall_core";
+import { SnowFallDocument } from "./common/SnowFallDocument";
 import { SnowFallError } from "./common/SnowFallError";
-import type { ISnowFallError, ParserResult, Token } from "./common/types";
+import { SnowFallLanguageServer } from "./common/SnowFallLanguageServer";
+import type { CompileOptions, CompletionList, CstResult, ErrorCodeInfo, FormatOptions, FormatResult, ISnowFallError, LintConfig, LintResult, NormalizeResult, ParserResult, RenderOptions, SemanticToken, Token } from "./common/types";
 import { Logger } from "./libs/Logger";
 import { compareVersion, parseSemVer } from "./libs/version_check";
 import { VERSION } from "./version";
@@ -63,6 +65,114 @@ export class SnowFall {
 	/* 公開機能 */
 	/* ================================================== */
 
+	/**
+	 * ソースコードの整形
+	 * @param input ソースコードの文字列
+	 * @param options 整形設定 (省略時は既定値)
+	 * @returns 整形後のソースコード、または構文エラー
+	 */
+	public format(input: string, options?: FormatOptions): FormatResult {
+		const wasm = this.ensureInitialized();
+		const result = wasm.format(input, options) as FormatResult;
+		if (result.errors) {
+			return {
+				...result,
+				errors: result.errors.map((err: ISnowFallError) => new SnowFallError(err)),
+			};
+		}
+		return result;
+	}
+
+	/**
+	 * 増分解析する文書を開く
+	 * @param input ソースコードの文字列
+	 * @param options 診断の言語と構文エラーの上限 (`locale` と `max_errors` のみを参照)
+	 * @returns 編集のたびに影響を受けた部分のみを解析し直す文書 (使用後は `free()` で解放する)
+	 */
+	public open_document(input: string, options?: CompileOptions): SnowFallDocument {
+		this.ensureInitialized();
+		return new SnowFallDocument(new wasm.SnowFallDocument(input, options));
+	}
+
+	/**
+	 * LSP互換のサーバーを生成する
+	 *
+	 * 設定は `initialize` の `initializationOptions` (`LanguageServerOptions`) で指定する
+	 * @returns JSON-RPCのメッセージを処理するサーバー (使用後は `free()` で解放する)
+	 */
+	public create_language_server(): SnowFallLanguageServer {
+		this.ensureInitialized();
+		return new SnowFallLanguageServer(new wasm.SnowFallLanguageServer());
+	}
+
+	/**
+	 * ソースコードをリント
+	 * @param input ソースコードの文字列
+	 * @param config ルールごとの報告レベルと診断の言語 (省略時はすべてのルールを警告として検査)
+	 * @returns リントの診断、または構文エラー
+	 */
+	public lint(input: string, config?: LintConfig): LintResult {
+		const wasm = this.ensureInitialized();
+		const result = wasm.lint(input, config) as LintResult;
+		return {
+			...result,
+			diagnostics: result.diagnostics.map((err: ISnowFallError) => new SnowFallError(err)),
+			errors: result.errors?.map((err: ISnowFallError) => new SnowFallError(err)),
+		};
+	}
+
+	/**
+	 * ソースコードのトークンをシンタックスハイライト用に分類
+	 * @param input ソースコードの文字列
+	 * @returns 位置順のトークン (構文エラーがある場合は前後のトークンから推定して分類)
+	 */
+	public semantic_tokens(input: string): SemanticToken[] {
+		const wasm = this.ensureInitialized();
+		return wasm.semantic_tokens(input) as SemanticToken[];
+	}
+
+	/**
+	 * カーソル位置に入力する補完候補を取得
+	 * @param input ソースコードの文字列 (入力途中で構文エラーがあってもよい)
+	 * @param offset カーソル位置 (バイト位置)
+	 * @returns 補完する位置の文脈と、順位の高い順の候補
+	 */
+	public complete(input: string, offset: number): CompletionList {
+		const wasm = this.ensureInitialized();
+		return wasm.complete(input, offset) as CompletionList;
+	}
+
+	/**
+	 * 診断をソースコードの抜粋付きのレポートに整形
+	 * @param input 診断の元になったソースコードの文字列
+	 * @param errors 整形する診断の配列
+	 * @param options 色付けやファイル名の設定 (省略時は色付けしない)
+	 * @returns コンパイラ形式のレポート
+	 */
+	public render_diagnostics(input: string, errors: ISnowFallError[], options?: RenderOptions): string {
+		const wasm = this.ensureInitialized();
+		return wasm.render_diagnostics(input, errors, options);
+	}
+
+	/**
+	 * エラーコードの説明を取得
+	 * @param code エラーコード (例: "SF0010")
+	 * @returns コードの分類・説明・例 (未知のコードの場合は undefined)
+	 */
+	public explain(code: string): ErrorCodeInfo | undefined {
+		const wasm = this.ensureInitialized();
+		return wasm.explain(code) ?? undefined;
+	}
+
+	/**
+	 * すべてのエラーコードの説明を取得
+	 * @returns コード番号順の説明の配列
+	 */
+	public error_codes(): ErrorCodeInfo[] {
+		const wasm = this.ensureInitialized();
+		return wasm.error_codes();
+	}
+
 	/* ================================================== */
 	/* デバッグ用機能 */
 	/* ================================================== */
@@ -81,12 +191,13 @@ export class SnowFall {
 	/**
 	 * デバッグ用のParser(Lexer含む)関数
 	 * @param input ソースコードの文字列
+	 * @param options 診断メッセージの言語などの設定 (省略時は英語)
 	 * @returns トークンの配列
 	 * @deprecated 開発・デバッグ用の関数です。本番環境では使用しないでください
 	 */
-	public dev_parser(input: string): ParserResult {
+	public dev_parser(input: string, options?: CompileOptions): ParserResult {
 		const wasm = this.ensureInitialized();
-		const result = wasm.parser(input) as ParserResult;
+		const result = wasm.parser(input, options) as ParserResult;
 		if (result.errors) {
 			return {
 				...result,
@@ -99,12 +210,54 @@ export class SnowFall {
 	/**
 	 * デバッグ用のParser(normalize済)関数
 	 * @param input ソースコードの文字列
+	 * @param options 実行するパスなどの設定 (省略時は全てのパスを実行する)
 	 * @returns トークンの配列
 	 * @deprecated 開発・デバッグ用の関数です。本番環境では使用しないでください
 	 */
-	public dev_normalize(input: string): ParserResult {
+	public dev_normalize(input: string, options?: CompileOptions): NormalizeResult {
+		const wasm = this.ensureInitialized();
+		const result = wasm.normalize(input, options) as NormalizeResult;
+		if (result.errors) {
+			return {
+				...result,
+				errors: result.errors.map((err: ISnowFallError) => new SnowFallError(err)),
+			};
+		}
+		return {
+			...result,
+			warnings: result.warnings?.map((warning: ISnowFallError) => new SnowFallError(warning)),
+		};
+	}
+
+	/**
+	 * デバッグ用のUnparser関数
+	 * @param input ソースコードの文字列
+	 * @param normalize 真の場合は正規化後のASTを出力する
+	 * @param options 診断の言語と構文エラーの上限 (`locale` と `max_errors` のみを参照)
+	 * @returns ASTから再生成したソースコード
+	 * @deprecated 開発・デバッグ用の関数です。本番環境では使用しないでください
+	 */
+	public dev_unparse(input: string, normalize: boolean = false, options?: CompileOptions): FormatResult {
+		const wasm = this.ensureInitialized();
+		const result = wasm.unparse(input, normalize, options) as FormatResult;
+		if (result.errors) {
+			return {
+				...result,
+				errors: result.errors.map((err: ISnowFallError) => new SnowFallError(err)),
+			};
+		}
+		return result;
+	}
+
+	/**
+	 * デバッグ用のCST(具象構文木)取得関数
+	 * @param input ソースコードの文字列
+	 * @returns トリビア(空白・コメント)を保持した具象構文木
+	 * @deprecated 開発・デバッグ用の関数です。本番環境では使用しないでください
+	 */
+	public dev_cst(input: string): CstResult {
 		const wasm = this.ensureInitialized();
-		const result = wasm.normalize(input) as ParserResult;
+		const result = wasm.cst(input) as CstResult;
 		if (result.errors) {
 			return {
 				...result,
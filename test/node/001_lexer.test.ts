@@ -157,6 +157,8 @@ test("Lexer Test", async (t) => {
 			null: "Null",
 			and: "And",
 			or: "Or",
+			const: "Const",
+			readonly: "Readonly",
 		};
 		const input = Object.keys(data).join(" ");
 		const expectedTokens = Object.values(data).map((type) => ({ type }));
@@ -49,6 +49,52 @@ test("Parser Test", async (t) => {
 		assert.strictEqual(stmt3.kind.value.declarators[0].value, undefined);
 		assert.strictEqual(stmt3.kind.value.declarators[1].name, "b");
 		deepStrictEqual(stmt3.kind.value.declarators[1].value?.kind, { type: "IntLiteral", value: 10 });
+		assert.strictEqual(stmt3.kind.value.mutability, "Mutable");
+	});
+
+	await t.test("should parse const declarations", () => {
+		const input = `
+            const Int MAX = 10;
+            const String A = "a", B = "b";
+        `;
+		const { ast, errors } = sf.dev_parser(input);
+		assert.ok(!errors, `Compilation failed with errors: ${JSON.stringify(errors, null, 2)}`);
+		assert.ok(ast);
+		assert.strictEqual(ast.statements.length, 2);
+
+		const stmt1 = ast.statements[0];
+		assert.strictEqual(stmt1.kind.type, "VariableDeclaration");
+		assert.strictEqual(stmt1.kind.value.mutability, "Const");
+		assert.strictEqual(stmt1.kind.value.type_name, "Int");
+		assert.strictEqual(stmt1.kind.value.declarators[0].name, "MAX");
+		deepStrictEqual(stmt1.kind.value.declarators[0].value?.kind, { type: "IntLiteral", value: 10 });
+
+		const stmt2 = ast.statements[1];
+		assert.strictEqual(stmt2.kind.type, "VariableDeclaration");
+		assert.strictEqual(stmt2.kind.value.mutability, "Const");
+		assert.strictEqual(stmt2.kind.value.declarators.length, 2);
+	});
+
+	await t.test("should parse class fields", () => {
+		const input = `
+			class Config {
+				Int count = 0;
+				readonly String name = "config";
+				sub reset() { count = 0; }
+			}
+		`;
+		const { ast, errors } = sf.dev_parser(input);
+		assert.ok(!errors, `Compilation failed: ${JSON.stringify(errors, null, 2)}`);
+		assert.ok(ast);
+		const classStmt = ast.statements[0];
+		assert.strictEqual(classStmt.kind.type, "ClassDeclaration");
+		const members = classStmt.kind.value.members;
+		assert.strictEqual(members.length, 3);
+		assert.strictEqual(members[0].kind.type, "VariableDeclaration");
+		assert.strictEqual(members[0].kind.value.mutability, "Mutable");
+		assert.strictEqual(members[1].kind.type, "VariableDeclaration");
+		assert.strictEqual(members[1].kind.value.mutability, "Readonly");
+		assert.strictEqual(members[2].kind.type, "FunctionDeclaration");
 	});
 
 	await t.test("should parse Return statements", () => {
@@ -105,6 +151,8 @@ test("Parser Test", async (t) => {
 			{ input: "5 == 5;", left: 5, operator: "Equals", right: 5 },
 			{ input: "5 != 5;", left: 5, operator: "NotEquals", right: 5 },
 			{ input: "true == true;", left: true, operator: "Equals", right: true, literalType: "Boolean" },
+			{ input: "true and false;", left: true, operator: "LogicalAnd", right: false, literalType: "Boolean" },
+			{ input: "true or false;", left: true, operator: "LogicalOr", right: false, literalType: "Boolean" },
 		];
 
 		for (const tt of tests) {
@@ -197,6 +245,24 @@ test("Parser Test", async (t) => {
 		deepStrictEqual(func.kind.value.params[1].value?.kind, { type: "StringLiteral", value: "default" });
 	});
 
+	await t.test("should parse For statements with omitted clauses", () => {
+		const tests: [string, boolean, boolean, boolean][] = [
+			["for (;;) { x(); }", false, false, false],
+			["for (; false;) { x(); }", false, true, false],
+			["for (Int i = 0;; i = i + 1) { x(); }", true, false, true],
+			["for (Int i = 0; i < 3; i = i + 1) { x(); }", true, true, true],
+		];
+		for (const [input, init, condition, update] of tests) {
+			const { ast, errors } = sf.dev_parser(input);
+			assert.strictEqual(errors, undefined, `Failed to parse ${input}`);
+			const stmt = ast!.statements[0].kind;
+			if (stmt.type !== "For") assert.fail(`Expected a For statement for ${input}`);
+			assert.strictEqual(stmt.value.init != null, init, `init of ${input}`);
+			assert.strictEqual(stmt.value.condition != null, condition, `condition of ${input}`);
+			assert.strictEqual(stmt.value.update != null, update, `update of ${input}`);
+		}
+	});
+
 	await t.test("should parse Call expressions", () => {
 		const input = "add(1, 2 * 3, 4 + 5);";
 		const { ast, errors } = sf.dev_parser(input);
@@ -210,6 +276,36 @@ test("Parser Test", async (t) => {
 		assert.strictEqual(stmt.kind.value.kind.value.arguments.length, 3);
 	});
 
+	await t.test("should parse member and index access", () => {
+		const input = "a.b[c].d = f(x)[0];";
+		const { ast, errors } = sf.dev_parser(input);
+		assert.ok(!errors, `Compilation failed: ${JSON.stringify(errors, null, 2)}`);
+		assert.ok(ast);
+		const stmt = ast.statements[0];
+		assert.strictEqual(stmt.kind.type, "Expression");
+		const assignment = stmt.kind.value.kind;
+		assert.strictEqual(assignment.type, "Assignment");
+
+		// a.b[c].d
+		const left = assignment.value.left.kind;
+		assert.strictEqual(left.type, "MemberAccess");
+		deepStrictEqual(left.value.property, { type: "Named", value: "d" });
+		const computed = left.value.object.kind;
+		assert.strictEqual(computed.type, "MemberAccess");
+		assert.strictEqual(computed.value.property.type, "Computed");
+		assert.strictEqual(computed.value.property.value.kind.value, "c");
+		const named = computed.value.object.kind;
+		assert.strictEqual(named.type, "MemberAccess");
+		deepStrictEqual(named.value.property, { type: "Named", value: "b" });
+		deepStrictEqual(named.value.object.kind, { type: "Identifier", value: "a" });
+
+		// f(x)[0]
+		const right = assignment.value.right.kind;
+		assert.strictEqual(right.type, "MemberAccess");
+		assert.strictEqual(right.value.object.kind.type, "Call");
+		deepStrictEqual(right.value.property.value.kind, { type: "IntLiteral", value: 0 });
+	});
+
 	await t.test("should parse ClassDeclarations", { skip: "Class実装後に検証" }, () => {
 		const input = `
 			class Person extends Human {
@@ -232,11 +328,33 @@ test("Parser Test", async (t) => {
 		assert.strictEqual(classStmt.kind.value.members[1].kind.value.name, "getName");
 	});
 
+	await t.test("should assign unique node ids", () => {
+		const input = "Int a = 1 + 2; function Int f(Int x) { if (x > 0) { return f(x - 1); } return a; }";
+		const { ast, errors } = sf.dev_parser(input);
+		assert.ok(!errors, `Compilation failed: ${JSON.stringify(errors, null, 2)}`);
+		assert.ok(ast);
+
+		// 文・式・プログラムの全ノードからIDを収集する
+		const ids: number[] = [];
+		const collect = (value: unknown) => {
+			if (Array.isArray(value)) {
+				value.forEach(collect);
+			} else if (value && typeof value === "object") {
+				if ("id" in value && "span" in value) ids.push((value as { id: number }).id);
+				Object.values(value).forEach(collect);
+			}
+		};
+		collect(ast);
+
+		assert.ok(ids.length > 10, "Expected ids on every node");
+		assert.strictEqual(new Set(ids).size, ids.length, "Node ids must be unique");
+	});
+
 	await t.test("should report parsing errors", { skip: "Class実装後に検証" }, () => {
 		const tests = [
 			"Int x 5;",
 			"function test(Int a,)",
-			"class MyClass { Int x = 1; }", // メソッドのみが許可される
+			"class MyClass { 1; }", // メソッドとフィールドのみが許可される
 			"if (x > 5) return 1 else return 2", // 中かっこがありません
 		];
 
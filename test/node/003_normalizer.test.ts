@@ -8,7 +8,7 @@ const deepStrictEqual = (actual: any, expected: any, message?: string) => {
 	assert.deepStrictEqual(JSON.parse(JSON.stringify(actual)), JSON.parse(JSON.stringify(expected)), message);
 };
 
-// 「span」プロパティを再帰的に削除するヘルパー関数
+// 「span」「id」プロパティを再帰的に削除するヘルパー関数
 const stripMetadata = (obj: any): any => {
 	if (obj === null || typeof obj !== "object") {
 		return obj;
@@ -18,7 +18,7 @@ const stripMetadata = (obj: any): any => {
 	}
 	const newObj: any = {};
 	for (const key in obj) {
-		if (key !== "span") {
+		if (key !== "span" && key !== "id") {
 			newObj[key] = stripMetadata(obj[key]);
 		}
 	}
@@ -30,7 +30,7 @@ test("Normalizer Test", async (t) => {
 	await sf.init(wasmBuffer);
 
 	await t.test("should remove parentheses", () => {
-		const input = "((1));";
+		const input = "x = ((1));";
 		const { ast, errors } = sf.dev_normalize(input);
 		assert.ok(!errors, `Compilation failed with errors: ${JSON.stringify(errors, null, 2)}`);
 
@@ -41,8 +41,11 @@ test("Normalizer Test", async (t) => {
 						type: "Expression",
 						value: {
 							kind: {
-								type: "IntLiteral",
-								value: 1,
+								type: "Assignment",
+								value: {
+									left: { kind: { type: "Identifier", value: "x" } },
+									right: { kind: { type: "IntLiteral", value: 1 } },
+								},
 							},
 						},
 					},
@@ -77,6 +80,7 @@ test("Normalizer Test", async (t) => {
 									type: "VariableDeclaration",
 									value: {
 										type_name: "Int",
+										mutability: "Mutable",
 										declarators: [
 											{
 												name: "a",
@@ -111,6 +115,7 @@ test("Normalizer Test", async (t) => {
 						type: "VariableDeclaration",
 						value: {
 							type_name: "Int",
+							mutability: "Mutable",
 							declarators: [
 								{
 									name: "a",
@@ -131,7 +136,8 @@ test("Normalizer Test", async (t) => {
 	});
 
 	await t.test("should sort commutative operations", () => {
-		const input = "Int val = c + a + b;";
+		// 並べ替えは型が分かるオペランドに限られるため、先に宣言しておく
+		const input = "Int a; Int b; Int c; Int val = c + a + b;";
 		const { ast, errors } = sf.dev_normalize(input);
 		assert.ok(!errors, `Compilation failed with errors: ${JSON.stringify(errors, null, 2)}`);
 
@@ -142,6 +148,7 @@ test("Normalizer Test", async (t) => {
 						type: "VariableDeclaration",
 						value: {
 							type_name: "Int",
+							mutability: "Mutable",
 							declarators: [
 								{
 									name: "val",
@@ -186,7 +193,7 @@ test("Normalizer Test", async (t) => {
 				},
 			],
 		};
-		deepStrictEqual(stripMetadata(ast), expectedAst);
+		deepStrictEqual({ statements: stripMetadata(ast).statements.slice(3) }, expectedAst);
 	});
 
 	await t.test("should fold prefix operators", () => {
@@ -204,6 +211,7 @@ test("Normalizer Test", async (t) => {
 						type: "VariableDeclaration",
 						value: {
 							type_name: "Int",
+							mutability: "Mutable",
 							declarators: [
 								{
 									name: "a",
@@ -223,6 +231,7 @@ test("Normalizer Test", async (t) => {
 						type: "VariableDeclaration",
 						value: {
 							type_name: "Int",
+							mutability: "Mutable",
 							declarators: [
 								{
 									name: "b",
@@ -261,4 +270,398 @@ test("Normalizer Test", async (t) => {
 		deepStrictEqual(stripMetadata(declarators[3].value?.kind), { type: "FloatLiteral", value: 2.5 });
 		deepStrictEqual(stripMetadata(declarators[4].value?.kind), { type: "FloatLiteral", value: 3.5 });
 	});
+
+	await t.test("should propagate constant values", () => {
+		const input = `
+      const Int MAX = 10;
+      Int a = MAX * 2;
+      {
+        Int MAX = f();
+        Int b = MAX;
+      }
+    `;
+		const { ast, errors } = sf.dev_normalize(input);
+		assert.ok(!errors, `Compilation failed with errors: ${JSON.stringify(errors, null, 2)}`);
+		assert.ok(ast, "Compilation failed");
+
+		const a = (ast.statements[1].kind as { type: "VariableDeclaration"; value: VariableDeclaration }).value.declarators[0];
+		deepStrictEqual(stripMetadata(a.value?.kind), { type: "IntLiteral", value: 20 });
+
+		// ブロック内で値が定数でない同名の変数を宣言すると、外側の定数は伝播されない
+		const block = ast.statements[2].kind as { type: "Block"; value: any[] };
+		const b = block.value[1].kind.value.declarators[0];
+		deepStrictEqual(stripMetadata(b.value?.kind), { type: "Identifier", value: "MAX" });
+	});
+
+	await t.test("should propagate variables that are never reassigned", () => {
+		const cases: [string, string][] = [
+			["Int x = 2; Int y = x * 3;", "Int x = 2;\nInt y = 6;\n"],
+			// 再代入される変数は伝播しない (代入が後続の関数内にあっても同じ)
+			["Int x = 2; x = 3; Int y = x * 3;", "Int x = 2;\nx = 3;\nInt y = x * 3;\n"],
+			["Int x = 2; Int y = x; sub s() { x = 1; }", "Int x = 2;\nInt y = x;\nsub s() {\n    x = 1;\n}\n"],
+			// 可変なフィールドはメソッドから書き換えられるため伝播しない
+			["class A { Int c = 0; function Int get() { return c; } }", "class A {\n    Int c = 0;\n    function Int get() {\n        return c;\n    }\n}\n"],
+			// 同名の定数があっても、呼び出す関数の名前は置き換えない
+			["function Int f(Int x) { return x + 1; } Int f = 3; Int y = f(2);", "function Int f(Int x) {\n    return x + 1;\n}\nInt f = 3;\nInt y = f(2);\n"],
+		];
+		for (const [input, expected] of cases) {
+			const { code, errors } = sf.dev_unparse(input, true);
+			assert.ok(!errors, `Compilation failed with errors: ${JSON.stringify(errors, null, 2)}`);
+			assert.strictEqual(code, expected, `Unexpected output for ${JSON.stringify(input)}`);
+		}
+	});
+
+	await t.test("should inline small functions called with literal arguments", () => {
+		const cases: [string, string][] = [
+			// 全ての呼び出しを展開した関数は削除される
+			["function Int sq(Int a) { return a * a; } Int y = sq(3) + 1;", "Int y = 10;\n"],
+			["function Int add(Int a, Int b = 10) { return a + b; } const Int K = 4; Int s = add(K);", "const Int K = 4;\nInt s = 14;\n"],
+			['function String greet(String n) { return "hi " + n; } String g = greet("bob");', 'String g = "hi bob";\n'],
+			// リテラルでない引数の呼び出しは残すため、関数も残る
+			["function Int sq(Int a) { return a * a; } Int y = sq(3) + sq(z);", "function Int sq(Int a) {\n    return a * a;\n}\nInt y = 9 + sq(z);\n"],
+			// 引数の型が一致しない (暗黙の型変換が必要な) 呼び出しは展開しない
+			["function Float half(Float a) { return a / 2; } Float h = half(3);", "function Float half(Float a) {\n    return a / 2;\n}\nFloat h = half(3);\n"],
+			// 再帰する関数や呼び出しを含む関数は展開しない
+			["function Int f(Int n) { return f(n - 1); } Int r = f(3);", "function Int f(Int n) {\n    return f(n - 1);\n}\nInt r = f(3);\n"],
+		];
+		for (const [input, expected] of cases) {
+			const { code, errors } = sf.dev_unparse(input, true);
+			assert.ok(!errors, `Compilation failed with errors: ${JSON.stringify(errors, null, 2)}`);
+			assert.strictEqual(code, expected, `Unexpected output for ${JSON.stringify(input)}`);
+		}
+
+		// 展開した式は呼び出しの由来を持つ
+		const input = "function Int sq(Int a) { return a * a; } Int y = sq(z) + sq(3);";
+		const { ast, origins } = sf.dev_normalize(input, { optimization: { passes: ["FunctionInlining"] } });
+		assert.ok(ast && origins);
+		const value = (ast.statements[1].kind as { type: "VariableDeclaration"; value: VariableDeclaration }).value.declarators[0].value!;
+		assert.ok(value.kind.type === "Infix");
+		const inlined = value.kind.value.right;
+		assert.strictEqual(input.slice(inlined.span.start, inlined.span.end), "sq(3)");
+		const origin = origins.find(([id]) => id === inlined.id);
+		assert.ok(origin);
+		assert.strictEqual(origin[1].kind, "Inlined");
+		assert.deepStrictEqual(
+			origin[1].sources.map((span) => input.slice(span.start, span.end)),
+			["a * a"],
+		);
+	});
+
+	await t.test("should normalize inside loops, functions and literals", () => {
+		const input = `
+      const Int K = 2;
+      function Int f(Int K) { return K + 1 * 2; }
+      while (x < 1 + 1) { y = K * 3; }
+      for (Int i = 0; i < 2 * 5; i = i + 1) { z = [1 + 1, K]; }
+      w = { "a": K + 1 };
+    `;
+		const { code, errors } = sf.dev_unparse(input, true);
+		assert.ok(!errors, `Compilation failed with errors: ${JSON.stringify(errors, null, 2)}`);
+		const expected = [
+			"const Int K = 2;",
+			"function Int f(Int K) {",
+			"    return K + 2;", // 引数は外側の定数を隠蔽する
+			"}",
+			"while (x < 2) {",
+			"    y = 6;",
+			"}",
+			"for (Int i = 0; i < 10; i = i + 1) {",
+			"    z = [2, 2];",
+			"}",
+			'w = { "a": 3 };',
+			"",
+		].join("\n");
+		assert.strictEqual(code, expected);
+	});
+
+	await t.test("should compute covering spans for reordered operands", () => {
+		const input = "Int a; Int b; Int z = a + 1 + b;";
+		const { ast, errors } = sf.dev_normalize(input);
+		assert.ok(!errors, `Compilation failed with errors: ${JSON.stringify(errors, null, 2)}`);
+		assert.ok(ast);
+		const root = (ast.statements[2].kind as { type: "VariableDeclaration"; value: VariableDeclaration }).value.declarators[0].value!;
+		assert.ok(root.kind.type === "Infix");
+
+		// (a + b) + 1 の内側の式は a から b までを覆う
+		const inner = root.kind.value.left;
+		assert.strictEqual(input.slice(inner.span.start, inner.span.end), "a + 1 + b");
+		assert.strictEqual(input.slice(root.span.start, root.span.end), "a + 1 + b");
+	});
+
+	await t.test("should only reorder pure operands of commutative operators", () => {
+		const cases: [string, string][] = [
+			// 呼び出しやメンバーアクセスを含む場合は評価順を保つ
+			["Int a; Int b; x = b + f() + a;", "x = b + f() + a;"],
+			["Int a; x = o.b * a;", "x = o.b * a;"],
+			// 文字列の連結は可換ではない
+			["String s; String t; u = t + s;", "u = t + s;"],
+			// 型の分からないオペランドは並べ替えない
+			["x = c + a;", "x = c + a;"],
+			// Int は結合則も成り立つため全体を並べ替える
+			["Int a; Int b; Int c; x = c * (b * a);", "x = a * b * c;"],
+			// Float は2つのオペランドのみ並べ替える
+			["Float f; Float g; x = g + f;", "x = f + g;"],
+			["Float f; Float g; Float h; x = h + (g + f);", "x = h + (f + g);"],
+			// 引数の型は関数内で有効
+			["function Int f(Int b, Int a) { return b + a; }", "function Int f(Int b, Int a) {\n    return a + b;\n}"],
+		];
+		for (const [input, expected] of cases) {
+			const { code, errors } = sf.dev_unparse(input, true);
+			assert.ok(!errors, `Compilation failed with errors: ${JSON.stringify(errors, null, 2)}`);
+			assert.ok(code!.trimEnd().endsWith(expected), `Unexpected output for ${JSON.stringify(input)}: ${JSON.stringify(code)}`);
+		}
+	});
+
+	await t.test("should record origins of folded constants", () => {
+		const input = "const Int K = 2; Int y = K * 3 + -1; Int w = K;";
+		const { ast, origins, errors } = sf.dev_normalize(input);
+		assert.ok(!errors, `Compilation failed with errors: ${JSON.stringify(errors, null, 2)}`);
+		assert.ok(ast && origins);
+		const originOf = (id: number) => origins.find(([nodeId]) => nodeId === id)?.[1];
+		const sourcesOf = (id: number) => originOf(id)?.sources.map((span) => input.slice(span.start, span.end));
+
+		const valueOf = (index: number) => (ast.statements[index].kind as { type: "VariableDeclaration"; value: VariableDeclaration }).value.declarators[0].value!;
+
+		// 畳み込んだ値は元の式の範囲を保ち、材料となった値の範囲を由来に持つ
+		const y = valueOf(1);
+		deepStrictEqual(y.kind, { type: "IntLiteral", value: 5 });
+		assert.strictEqual(input.slice(y.span.start, y.span.end), "K * 3 + -1");
+		assert.strictEqual(originOf(y.id)?.kind, "Folded");
+		deepStrictEqual(sourcesOf(y.id), ["2", "3", "-1"]);
+
+		// 定数の参照は初期化式を由来に持つ
+		const w = valueOf(2);
+		assert.strictEqual(input.slice(w.span.start, w.span.end), "K");
+		assert.strictEqual(originOf(w.id)?.kind, "ConstantReference");
+		deepStrictEqual(sourcesOf(w.id), ["2"]);
+
+		// 畳み込みで木から消えた材料の由来は残らない
+		assert.strictEqual(origins.length, 2);
+	});
+
+	await t.test("should fold all constant operators", () => {
+		const cases: [string, string][] = [
+			["a = 7 % 3;", "a = 1;"],
+			["a = 2 ** 10;", "a = 1024;"],
+			["a = 1 < 2;", "a = true;"],
+			["a = 2.5 >= 3;", "a = false;"],
+			["a = 1 == 1.0;", "a = true;"],
+			['a = "x" !== "y";', "a = true;"],
+			["a = null == null;", "a = true;"],
+			["a = true && false;", "a = false;"],
+			["a = false or true;", "a = true;"],
+			["a = 6 & 3 | 8 ^ 1;", "a = 11;"],
+			["a = 1 << 3;", "a = 8;"],
+			["a = -8 >> 1;", "a = -4;"],
+			["a = -8 >>> 60;", "a = 15;"],
+			['a = "snow" + "fall";', 'a = "snowfall";'],
+			["a = !true;", "a = false;"],
+			["a = ~5;", "a = -6;"],
+			["a = 2.5 % 1;", "a = 0.5;"],
+		];
+		for (const [input, expected] of cases) {
+			const { code, errors } = sf.dev_unparse(input, true);
+			assert.ok(!errors, `Compilation failed with errors: ${JSON.stringify(errors, null, 2)}`);
+			assert.strictEqual(code, `${expected}\n`, `Unexpected result for ${input}`);
+		}
+	});
+
+	await t.test("should leave overflowing or undefined operations unfolded", () => {
+		const cases: [string, string][] = [
+			["a = 9223372036854775807 + 1;", "a = 1 + 9223372036854775807;"],
+			["a = 1 / 0;", "a = 1 / 0;"],
+			["a = 5 % 0;", "a = 5 % 0;"],
+			["a = 2 ** -1;", "a = 2 ** -1;"],
+			["a = 1 << 64;", "a = 1 << 64;"],
+			["a = 1.0 / 0.0;", "a = 1.0 / 0.0;"],
+			["a = 1 === 1.0;", "a = 1 === 1.0;"],
+		];
+		for (const [input, expected] of cases) {
+			const { code, errors } = sf.dev_unparse(input, true);
+			assert.ok(!errors, `Compilation failed with errors: ${JSON.stringify(errors, null, 2)}`);
+			assert.strictEqual(code, `${expected}\n`, `Unexpected result for ${input}`);
+		}
+	});
+
+	await t.test("should print the minimum integer in a form that can be parsed again", () => {
+		// i64の最小値の絶対値は整数リテラルで表せない
+		for (const input of ["Int a = 1 << 63;", "Int a = 0 - 9223372036854775807 - 1;"]) {
+			const { code, errors } = sf.dev_unparse(input, true);
+			assert.ok(!errors, `Compilation failed with errors: ${JSON.stringify(errors, null, 2)}`);
+			assert.strictEqual(code, "Int a = (-9223372036854775807 - 1);\n", `Unexpected result for ${input}`);
+			assert.strictEqual(sf.dev_parser(code as string).errors, undefined);
+		}
+	});
+
+	await t.test("should remove unreachable statements with a warning", () => {
+		const input = "function Int f(Int x) {\n    if (x) { return 1; } else { return 2; }\n    x = 3;\n    g();\n}";
+		const { code, errors } = sf.dev_unparse(input, true);
+		assert.ok(!errors, `Compilation failed with errors: ${JSON.stringify(errors, null, 2)}`);
+		assert.strictEqual(code, "function Int f(Int x) {\n    if (x) {\n        return 1;\n    } else {\n        return 2;\n    }\n}\n");
+
+		const { warnings } = sf.dev_normalize(input);
+		assert.ok(warnings);
+		assert.strictEqual(warnings.length, 1);
+		assert.strictEqual(warnings[0].type, "CompilationWarning");
+		assert.strictEqual(warnings[0].severity, "Warning");
+		assert.strictEqual(warnings[0].code, "SF0200");
+		assert.strictEqual(warnings[0].message, "Unreachable code");
+		assert.strictEqual(warnings[0].line, 3);
+		assert.strictEqual(warnings[0].column, 5);
+	});
+
+	await t.test("should remove loops that never run", () => {
+		const input = `
+      while (false) { a(); }
+      for (Int i = 0; false; i = i + 1) { b(); }
+      for (x = g(); 1 > 2; x = x + 1) { c(); }
+      for (Int j = g(); false; j = j + 1) { d(); }
+    `;
+		const { code, errors } = sf.dev_unparse(input, true);
+		assert.ok(!errors, `Compilation failed with errors: ${JSON.stringify(errors, null, 2)}`);
+		// 初期化に副作用がある場合は初期化のみ残す
+		assert.strictEqual(code, "x = g();\n{\n    Int j = g();\n}\n");
+	});
+
+	await t.test("should warn about branches and loops that never run", () => {
+		const input = "for (; false;) { a(); }\nwhile (false) { b(); }\nif (false) { c(); }\nif (true) { d(); } else { e(); }\nwhile (false) {}";
+		const { code } = sf.dev_unparse(input, true);
+		assert.strictEqual(code, "{\n    d();\n}\n");
+
+		const { warnings } = sf.dev_normalize(input);
+		assert.ok(warnings);
+		// 空の本体は警告しない
+		assert.deepStrictEqual(
+			warnings.map((w) => [w.code, w.line, w.column]),
+			[
+				["SF0200", 1, 16],
+				["SF0200", 2, 15],
+				["SF0200", 3, 12],
+				["SF0200", 4, 25],
+			],
+		);
+	});
+
+	await t.test("should remove pure expression statements and empty blocks", () => {
+		const input = "1 + 2; x; f(1); y = 2; 1 / x; { } { { } } { { c(); } } if (x) { Int a = 1; { } }";
+		const { code, errors } = sf.dev_unparse(input, true);
+		assert.ok(!errors, `Compilation failed with errors: ${JSON.stringify(errors, null, 2)}`);
+		// ゼロ除算の可能性がある式は副作用ありとみなして残す
+		assert.strictEqual(code, "f(1);\ny = 2;\n1 / x;\n{\n    c();\n}\nif (x) {\n    Int a = 1;\n}\n");
+	});
+
+	await t.test("should select passes by optimization level", () => {
+		const input = "Int a; Int b; x = b + 1 + a;";
+
+		// O0 は木を変更しない
+		const { ast: original } = sf.dev_parser(input);
+		const { ast: unchanged } = sf.dev_normalize(input, { optimization: { level: "O0" } });
+		deepStrictEqual(unchanged, original);
+
+		// 可換な演算の並べ替えは O2 のみで行う
+		const operands = (level: "O1" | "O2") => {
+			const { ast, errors } = sf.dev_normalize(input, { optimization: { level } });
+			assert.ok(!errors, `Compilation failed with errors: ${JSON.stringify(errors, null, 2)}`);
+			const collect = (expr: any): string[] => {
+				if (expr.kind.type === "Infix") return [...collect(expr.kind.value.left), ...collect(expr.kind.value.right)];
+				return [expr.kind.value];
+			};
+			return collect((ast!.statements[2].kind.value as any).kind.value.right);
+		};
+		deepStrictEqual(operands("O1"), ["b", 1, "a"]);
+		deepStrictEqual(operands("O2"), ["a", "b", 1]);
+	});
+
+	await t.test("should run only the selected passes", () => {
+		const input = "const Int K = 2; x = K * 3; if (false) { y(); }";
+		const { ast, errors } = sf.dev_normalize(input, { optimization: { passes: ["ConstantPropagation"] } });
+		assert.ok(!errors, `Compilation failed with errors: ${JSON.stringify(errors, null, 2)}`);
+		assert.ok(ast);
+		// 伝播のみ行い、畳み込みと不要コードの削除は行わない
+		assert.strictEqual(ast.statements.length, 3);
+		const assignment = (ast.statements[1].kind.value as any).kind.value;
+		deepStrictEqual(stripMetadata(assignment.right.kind), {
+			type: "Infix",
+			value: { left: { kind: { type: "IntLiteral", value: 2 } }, operator: "Multiply", right: { kind: { type: "IntLiteral", value: 3 } } },
+		});
+	});
+
+	await t.test("should reduce the strength of operations guarded by types", () => {
+		const cases: [string, string][] = [
+			["Int x; Int a = x * 2; Int b = 1 * x; Int c = x + 0; Int d = x ** 2;", "Int a = x + x;\nInt b = x;\nInt c = x;\nInt d = x * x;\n"],
+			["Float f; Float a = f * 2.0; Float b = f * 1;", "Float a = f + f;\nFloat b = f;\n"],
+			// -0.0 + 0 は 0.0 になり、Float の累乗は丸めが一致する保証がないため置き換えない
+			["Float f; Float a = f + 0; Float b = f ** 2;", "Float a = f + 0;\nFloat b = f ** 2;\n"],
+			// 結果の型が変わる置き換えは行わない
+			["Int x; Float a = x * 1.0;", "Float a = x * 1.0;\n"],
+			// 副作用のあるオペランドは複製しない
+			["class A { Int b = 1; } A a; Int p = a.b * 2; Int q = a.b * 1;", "Int p = a.b * 2;\nInt q = a.b;\n"],
+			// 型の分からないオペランドは置き換えない
+			["x = y * 2 + g() * 1;", "x = y * 2 + g() * 1;\n"],
+		];
+		for (const [input, expected] of cases) {
+			const { code, errors } = sf.dev_unparse(input, true);
+			assert.ok(!errors, `Compilation failed with errors: ${JSON.stringify(errors, null, 2)}`);
+			assert.ok(code!.endsWith(expected), `Unexpected output for ${JSON.stringify(input)}: ${JSON.stringify(code)}`);
+		}
+	});
+
+	await t.test("should eliminate common subexpressions", () => {
+		const cases: [string, string][] = [
+			// フィールドの型から一時変数の型を推論する
+			["class A { Int b = 1; } A a; Int c; Int d; Int r = a.b * c + a.b * d;", "const Int _cse0 = a.b;\nInt r = _cse0 * c + _cse0 * d;\n"],
+			["class A { Int b = 1; } function Int f(A a, Int c, Int d) { return a.b * c + a.b * d; }", "const Int _cse0 = a.b;\n    return _cse0 * c + _cse0 * d;\n}\n"],
+			// 最も大きい共通部分式を置き換える
+			["Int x; Int y; return (x + y) * 3 - (x + y) * 3;", "const Int _cse0 = 3 * (x + y);\nreturn _cse0 - _cse0;\n"],
+			// 既存の名前とは衝突しない
+			["Float _cse0; Float x; Float z = x / 2 * (x / 2);", "const Float _cse1 = x / 2;\nFloat z = _cse1 * _cse1;\n"],
+		];
+		for (const [input, expected] of cases) {
+			const { code, errors } = sf.dev_unparse(input, true);
+			assert.ok(!errors, `Compilation failed with errors: ${JSON.stringify(errors, null, 2)}`);
+			assert.ok(code!.endsWith(expected), `Unexpected output for ${JSON.stringify(input)}: ${JSON.stringify(code)}`);
+		}
+
+		// 評価の順序や回数が観測できる場合は置き換えない
+		for (const input of [
+			"class A { Int b = 1; } A a; Int r = f() + a.b + a.b;", // 呼び出しを含む文
+			"class A { Int b = 1; } A a; Int c; Int r = c / 0 + a.b + a.b;", // 他に実行時エラーの可能性がある式を含む文
+			"Int x; Int y; Bool q = x > 0 && x * y > 1 && x * y < 5;", // 短絡評価で評価されない場合がある式
+			"Int r = u.v + u.v;", // 型の分からない式
+			"a.b * c + a.b * d;", // 宣言のない変数を含む式 (一時変数の型名が書けない)
+			"Int x; Int y = 1, z = y * x + y * x;", // 同じ文で宣言される変数を参照する式
+		]) {
+			const { code } = sf.dev_unparse(input, true);
+			assert.ok(!code!.includes("_cse"), `Unexpected temporary for ${JSON.stringify(input)}: ${JSON.stringify(code)}`);
+		}
+	});
+
+	await t.test("should dump the AST after each pass", () => {
+		const input = "const Int A = 1 + 1; Int b = A * 2;";
+		const { dumps, errors } = sf.dev_normalize(input, { optimization: { dump_passes: true } });
+		assert.ok(!errors, `Compilation failed with errors: ${JSON.stringify(errors, null, 2)}`);
+		assert.ok(dumps);
+		deepStrictEqual(
+			dumps.slice(0, 7).map((dump) => [dump.pass, dump.iteration]),
+			[
+				["ConstantPropagation", 0],
+				["FunctionInlining", 0],
+				["ConstantFolding", 0],
+				["StrengthReduction", 0],
+				["CommutativeCanonicalization", 0],
+				["CommonSubexpressionElimination", 0],
+				["DeadCodeElimination", 0],
+			],
+		);
+		// 1回目の畳み込みで A が定数になり、2回目の伝播で b に反映される
+		const valueOf = (program: any) => program.statements[1].kind.value.declarators[0].value.kind;
+		deepStrictEqual(stripMetadata(valueOf(dumps[2].program)), {
+			type: "Infix",
+			value: { left: { kind: { type: "Identifier", value: "A" } }, operator: "Multiply", right: { kind: { type: "IntLiteral", value: 2 } } },
+		});
+		deepStrictEqual(valueOf(dumps[dumps.length - 1].program), { type: "IntLiteral", value: 4 });
+
+		// 記録を要求しない場合は返さない
+		assert.strictEqual(sf.dev_normalize(input).dumps, undefined);
+	});
 });
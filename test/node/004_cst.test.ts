@@ -0,0 +1,89 @@
+import assert from "node:assert";
+import { test } from "node:test";
+
+import { SnowFall, type CstNode, type CstToken } from "../../dist/snowfall";
+import { wasmBuffer } from "./lib/getWasm";
+
+// CSTをトークン順に辿り、トリビアを含めたテキストを復元するヘルパー関数
+const collectTokens = (node: CstNode, out: CstToken[] = []): CstToken[] => {
+	for (const child of node.children) {
+		if (child.type === "Node") {
+			collectTokens(child.value, out);
+		} else {
+			out.push(child.value);
+		}
+	}
+	return out;
+};
+const toSource = (node: CstNode): string =>
+	collectTokens(node)
+		.map((t) => [...t.leading_trivia.map((tr) => tr.text), t.text, ...t.trailing_trivia.map((tr) => tr.text)].join(""))
+		.join("");
+
+test("CST Test", async (t) => {
+	const sf = new SnowFall();
+	await sf.init(wasmBuffer);
+
+	await t.test("should round-trip source text", () => {
+		const inputs = [
+			"Int a = 1;",
+			"// comment only",
+			"  Int   a=1 ;  /* trailing */\n\n",
+			"/* header */\nfunction Int add(Int a, Int b) {\r\n\treturn a + b; // sum\r\n}\n",
+			"if (x < y) { return x; } else { return y; }",
+			"/* unterminated",
+		];
+		for (const input of inputs) {
+			const { cst } = sf.dev_cst(input);
+			assert.strictEqual(toSource(cst.root), input, `Round-trip mismatch for ${JSON.stringify(input)}`);
+		}
+	});
+
+	await t.test("should round-trip source with syntax errors", () => {
+		const input = "Int a = 1 +; # x";
+		const { cst, errors } = sf.dev_cst(input);
+		assert.ok(errors, "Expected a compiler error");
+		assert.strictEqual(toSource(cst.root), input);
+	});
+
+	await t.test("should attach trivia to tokens", () => {
+		const input = "// lead\nInt a = 1; // trail\n";
+		const { cst } = sf.dev_cst(input);
+		const tokens = collectTokens(cst.root);
+
+		const first = tokens[0];
+		assert.strictEqual(first.text, "Int");
+		assert.deepStrictEqual(
+			first.leading_trivia.map((tr) => tr.kind.type),
+			["LineComment", "Newline"],
+		);
+
+		const semicolon = tokens.find((tok) => tok.text === ";");
+		assert.ok(semicolon);
+		assert.deepStrictEqual(
+			semicolon.trailing_trivia.map((tr) => tr.kind.type),
+			["Whitespace", "LineComment"],
+		);
+
+		// ファイル末尾のトリビアはEOFトークンが保持する
+		const eof = tokens[tokens.length - 1];
+		assert.strictEqual(eof.kind.type, "Eof");
+		assert.deepStrictEqual(
+			eof.leading_trivia.map((tr) => tr.kind.type),
+			["Newline"],
+		);
+	});
+
+	await t.test("should map CST nodes to AST nodes", () => {
+		const input = "Int a = 1 + 2;";
+		const { cst } = sf.dev_cst(input);
+		const { ast } = sf.dev_parser(input);
+		assert.ok(ast);
+
+		const stmtNode = cst.root.children.find((c) => c.type === "Node");
+		assert.ok(stmtNode && stmtNode.type === "Node");
+		assert.strictEqual(stmtNode.value.kind, "Statement");
+		assert.deepStrictEqual(stmtNode.value.span, ast.statements[0].span);
+		assert.strictEqual(stmtNode.value.ast_id, ast.statements[0].id);
+	});
+});
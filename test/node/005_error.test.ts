@@ -24,11 +24,375 @@ test("Error Test", async (t) => {
 	});
 
 	await t.test("should handle unexpected tokens", () => {
-		const code = `(Int) a = 1 +;`;
+		const code = `(Int) a = 1 + * 2;`;
 		const { errors } = sf.dev_parser(code);
 		assert.ok(errors, "Expected a compiler error");
 		assert.strictEqual(errors[0].code, "SF0015");
-		assert.strictEqual(errors[0].message, "Unexpected token for expression: Token { kind: Delimiter(Semicolon), span: Span { start: 13, end: 14 } }");
+		assert.strictEqual(errors[0].message, "Unexpected token for expression: Token { kind: Operator(Asterisk), span: Span { start: 14, end: 15 } }");
+	});
+
+	await t.test("should handle missing expressions", () => {
+		const code = `(Int) a = 1 +;`;
+		const { errors } = sf.dev_parser(code);
+		assert.ok(errors, "Expected a compiler error");
+		assert.strictEqual(errors[0].code, "SF0018");
+		assert.strictEqual(errors[0].message, "Expected expression");
+		assert.strictEqual(errors[0].column, 14);
+	});
+
+	await t.test("should require type names after const and readonly", () => {
+		for (const code of ["const = 1;", "const MAX = 1;", "class A { readonly = 1; }"]) {
+			const { errors } = sf.dev_parser(code);
+			assert.strictEqual(errors?.[0].code, "SF0012", code);
+		}
+		const { errors } = sf.dev_parser("function f() { return 1; }");
+		assert.strictEqual(errors?.[0].code, "SF0013");
+	});
+
+	await t.test("should reject const declarations without initializer", () => {
+		const code = `const Int MAX;`;
+		const { errors } = sf.dev_parser(code);
+		assert.ok(errors, "Expected a compiler error");
+		assert.strictEqual(errors[0].code, "SF0019");
+
+		// 初期化式のない変数名の位置に報告する
+		const multiple = sf.dev_parser("const Int A = 1,\n    B;").errors;
+		assert.ok(multiple, "Expected a compiler error");
+		assert.deepStrictEqual([multiple[0].line, multiple[0].column, multiple[0].span], [2, 5, { start: 21, end: 22 }]);
+	});
+
+	await t.test("should reject assignments to constants", () => {
+		const code = `const Int MAX = 10;\nMAX = 20;`;
+		const { errors } = sf.dev_parser(code);
+		assert.ok(errors, "Expected a compiler error");
+		assert.strictEqual(errors[0].code, "SF0100");
+		assert.strictEqual(errors[0].message, "Cannot assign to constant 'MAX'");
+		assert.strictEqual(errors[0].line, 2);
+		assert.strictEqual(errors[0].column, 1);
+	});
+
+	await t.test("should reject assignments to readonly fields", () => {
+		const code = `class A { readonly Int x = 1; sub f() { x = 2; } }`;
+		const { errors } = sf.dev_parser(code);
+		assert.ok(errors, "Expected a compiler error");
+		assert.strictEqual(errors[0].code, "SF0100");
+		assert.strictEqual(errors[0].message, "Cannot assign to readonly field 'x'");
+	});
+
+	await t.test("should reject assignments to readonly fields through member access", () => {
+		for (const [code, line, column] of [
+			["class A { readonly Int x = 1; sub f() { this.x = 2; } }", 1, 41],
+			["class A { readonly Int x = 1; }\nA p;\np.x = 1;", 3, 1],
+			["class A { readonly Int x = 1; }\nclass B extends A { }\nB p;\np.x = 1;", 4, 1],
+		] as const) {
+			const { errors } = sf.dev_parser(code);
+			assert.ok(errors, `Expected a compiler error for ${code}`);
+			assert.strictEqual(errors[0].code, "SF0100");
+			assert.strictEqual(errors[0].message, "Cannot assign to readonly field 'x'");
+			assert.deepStrictEqual([errors[0].line, errors[0].column], [line, column]);
+		}
+
+		const { errors } = sf.dev_parser("class A { Int x = 1; }\nA p;\np.x = 1;");
+		assert.ok(!errors, `Compilation failed: ${JSON.stringify(errors, null, 2)}`);
+	});
+
+	await t.test("should reject invalid assignment targets", () => {
+		for (const code of ["1 = 2;", "a + b = c;", "f() = 1;"]) {
+			const { errors } = sf.dev_parser(code);
+			assert.ok(errors, `Expected a compiler error for ${code}`);
+			assert.strictEqual(errors[0].code, "SF0020");
+			assert.strictEqual(errors[0].message, "Invalid assignment target");
+			assert.strictEqual(errors[0].line, 1);
+			assert.strictEqual(errors[0].column, 1);
+		}
+
+		const { errors } = sf.dev_parser("a.b = 1; a[0] = 2;");
+		assert.ok(!errors, `Compilation failed: ${JSON.stringify(errors, null, 2)}`);
+	});
+
+	await t.test("should localize error messages", () => {
+		const code = `const Int MAX = 10;\nMAX = 20;`;
+		const en = sf.dev_parser(code, { locale: "en" }).errors;
+		const ja = sf.dev_parser(code, { locale: "ja" }).errors;
+		assert.ok(en && ja, "Expected a compiler error");
+		assert.strictEqual(en[0].message, "Cannot assign to constant 'MAX'");
+		assert.strictEqual(ja[0].message, "定数 'MAX' には代入できません");
+		assert.strictEqual(ja[0].code, en[0].code);
+		assert.deepStrictEqual(ja[0].context, { name: "MAX", kind: "const" });
+
+		const readonly = sf.dev_parser(`class A { readonly Int x = 1; sub f() { x = 2; } }`, { locale: "ja" }).errors;
+		assert.ok(readonly, "Expected a compiler error");
+		assert.strictEqual(readonly[0].message, "読み取り専用フィールド 'x' には代入できません");
+
+		const unexpected = sf.dev_parser("#", { locale: "ja" }).errors;
+		assert.ok(unexpected, "Expected a compiler error");
+		assert.strictEqual(unexpected[0].message, "予期しない文字です: #");
+	});
+
+	await t.test("should localize labels, notes and suggestions", () => {
+		const constant = sf.dev_parser("const Int MAX = 10;\nMAX = 20;", { locale: "ja" }).errors;
+		assert.ok(constant, "Expected a compiler error");
+		assert.deepStrictEqual(constant[0].labels?.map((label) => label.message), ["'MAX' はここで宣言されています"]);
+		assert.deepStrictEqual(constant[0].suggestions?.map((suggestion) => suggestion.message), [
+			"'const' を取り除くと 'MAX' に代入できるようになります",
+		]);
+
+		const missing = sf.dev_parser("const Int C;", { locale: "ja" }).errors;
+		assert.ok(missing, "Expected a compiler error");
+		assert.deepStrictEqual(missing[0].notes, ["定数は宣言と同時に初期化する必要があります"]);
+
+		const semicolon = sf.dev_parser("Int a = 1\nInt b = 2;", { locale: "ja" }).errors;
+		assert.ok(semicolon, "Expected a compiler error");
+		assert.deepStrictEqual(semicolon[0].labels?.map((label) => label.message), ["この後ろに ';' が必要です"]);
+		assert.deepStrictEqual(semicolon[0].suggestions?.map((suggestion) => suggestion.message), ["ここに ';' を挿入します"]);
+	});
+
+	await t.test("should localize warnings", () => {
+		const input = "function Int f(Int x) {\n    if (x) { return 1; } else { return 2; }\n    x = 3;\n}";
+		const { warnings } = sf.dev_normalize(input, { locale: "ja" });
+		assert.ok(warnings);
+		assert.strictEqual(warnings[0].code, "SF0200");
+		assert.strictEqual(warnings[0].message, "到達できないコードです");
+		assert.strictEqual(warnings[0].suggestions?.[0].message, "到達できないコードを削除します");
+	});
+
+	await t.test("should report the range of a diagnostic", () => {
+		const { errors } = sf.dev_parser("Int a = 1 # 2;");
+		assert.ok(errors, "Expected a compiler error");
+		assert.strictEqual(errors[0].code, "SF0001");
+		assert.strictEqual(errors[0].severity, "Error");
+		assert.deepStrictEqual(errors[0].span, { start: 10, end: 11 });
+		assert.strictEqual(errors[0].line, 1);
+		assert.strictEqual(errors[0].column, 11);
+		assert.strictEqual(errors[0].end_line, 1);
+		assert.strictEqual(errors[0].end_column, 12);
+	});
+
+	await t.test("should suggest inserting a missing semicolon", () => {
+		const { errors } = sf.dev_parser("Int a = 1\nInt b = 2;");
+		assert.ok(errors, "Expected a compiler error");
+		assert.strictEqual(errors[0].code, "SF0010");
+		assert.strictEqual(errors[0].line, 2);
+		assert.strictEqual(errors[0].column, 1);
+
+		assert.ok(errors[0].labels);
+		assert.strictEqual(errors[0].labels[0].message, "expected ';' after this");
+		assert.deepStrictEqual(errors[0].labels[0].range.span, { start: 8, end: 9 });
+
+		assert.ok(errors[0].suggestions);
+		assert.strictEqual(errors[0].suggestions[0].message, "insert ';' here");
+		assert.deepStrictEqual(errors[0].suggestions[0].edits, [
+			{ range: { span: { start: 9, end: 9 }, line: 1, column: 10, end_line: 1, end_column: 10 }, replacement: ";" },
+		]);
+	});
+
+	await t.test("should point at the declaration of an assigned constant", () => {
+		const { errors } = sf.dev_parser("const Int MAX = 10;\nMAX = 20;");
+		assert.ok(errors, "Expected a compiler error");
+		assert.strictEqual(errors[0].code, "SF0100");
+		assert.deepStrictEqual(errors[0].span, { start: 20, end: 23 });
+
+		assert.ok(errors[0].labels);
+		assert.strictEqual(errors[0].labels[0].message, "'MAX' is declared here");
+		assert.strictEqual(errors[0].labels[0].range.line, 1);
+
+		assert.ok(errors[0].suggestions);
+		assert.strictEqual(errors[0].suggestions[0].message, "remove 'const' to make 'MAX' mutable");
+		assert.deepStrictEqual(errors[0].suggestions[0].edits[0].range.span, { start: 0, end: 6 });
+		assert.strictEqual(errors[0].suggestions[0].edits[0].replacement, "");
+	});
+
+	await t.test("should attach notes to diagnostics", () => {
+		const { errors } = sf.dev_parser("const Int MAX;");
+		assert.ok(errors, "Expected a compiler error");
+		assert.strictEqual(errors[0].code, "SF0019");
+		assert.deepStrictEqual(errors[0].notes, ["constants must be initialized where they are declared"]);
+	});
+
+	await t.test("should render diagnostics with source snippets", () => {
+		const code = "Int a = 1\nInt b = 2;";
+		const { errors } = sf.dev_parser(code);
+		assert.ok(errors, "Expected a compiler error");
+
+		const report = sf.render_diagnostics(code, errors, { file_name: "main.sf" });
+		assert.strictEqual(
+			report,
+			[
+				`error[SF0010]: ${errors[0].message}`,
+				" --> main.sf:2:1",
+				"  |",
+				"1 | Int a = 1",
+				"  |         - expected ';' after this",
+				"2 | Int b = 2;",
+				"  | ^^^",
+				"  |",
+				"  = help: insert ';' here",
+				"",
+			].join("\n"),
+		);
+	});
+
+	await t.test("should size underlines by display width", () => {
+		const code = 'String s = "雪だるま" 1;';
+		const { errors } = sf.dev_parser(code);
+		assert.ok(errors, "Expected a compiler error");
+
+		// 全角文字は2列分として下線を引く
+		const report = sf.render_diagnostics(code, errors);
+		assert.ok(report.includes(`1 | ${code}\n  |            ---------- expected ';' after this\n  |                       ^\n`), report);
+	});
+
+	await t.test("should render notes and colors", () => {
+		const code = "const Int MAX;";
+		const { errors } = sf.dev_parser(code);
+		assert.ok(errors, "Expected a compiler error");
+
+		const plain = sf.render_diagnostics(code, errors);
+		assert.ok(plain.includes(" --> <input>:1:11\n"));
+		assert.ok(plain.includes("1 | const Int MAX;\n  |           ^^^\n"));
+		assert.ok(plain.includes("  = note: constants must be initialized where they are declared\n"));
+		assert.ok(!plain.includes("\x1b["));
+
+		const colored = sf.render_diagnostics(code, errors, { color: true });
+		assert.ok(colored.startsWith("\x1b[1;31merror[SF0019]\x1b[0m"));
+		assert.strictEqual(colored.replace(/\x1b\[[0-9;]*m/g, ""), plain);
+	});
+
+	await t.test("should explain error codes", () => {
+		const info = sf.explain("SF0100");
+		assert.ok(info, "Expected an explanation");
+		assert.strictEqual(info.name, "AssignmentToConstant");
+		assert.strictEqual(info.number, 100);
+		assert.strictEqual(info.category, "Semantic");
+		assert.strictEqual(info.example, "const Int MAX = 10;\nMAX = 20;");
+
+		assert.strictEqual(sf.explain("SF9999"), undefined);
+	});
+
+	await t.test("should register every error code uniquely with documentation", () => {
+		const ranges: Record<string, [number, number]> = {
+			Lexer: [1, 9],
+			Parser: [10, 99],
+			Semantic: [100, 199],
+			Warning: [200, 299],
+			Runtime: [300, 399],
+		};
+		const codes = sf.error_codes();
+		assert.ok(codes.length > 0);
+
+		const seenCodes = new Set<string>();
+		const seenNames = new Set<string>();
+		for (const info of codes) {
+			assert.match(info.code, /^SF\d{4}$/);
+			assert.strictEqual(info.number, Number(info.code.slice(2)), `Mismatched number for ${info.code}`);
+			assert.ok(!seenCodes.has(info.code), `Duplicated code ${info.code}`);
+			assert.ok(!seenNames.has(info.name), `Duplicated name ${info.name}`);
+			seenCodes.add(info.code);
+			seenNames.add(info.name);
+
+			const [min, max] = ranges[info.category];
+			assert.ok(min <= info.number && info.number <= max, `${info.code} is out of the ${info.category} range`);
+			assert.ok(info.explanation.length > 0, `Missing explanation for ${info.code}`);
+			assert.ok(info.example.length > 0, `Missing example for ${info.code}`);
+			assert.deepStrictEqual(sf.explain(info.code), info);
+		}
+
+		const numbers = codes.map((info) => info.number);
+		assert.deepStrictEqual(
+			numbers,
+			[...numbers].sort((a, b) => a - b),
+			"Expected codes in numeric order",
+		);
+	});
+
+	await t.test("should report the documented code for each example", () => {
+		for (const info of sf.error_codes()) {
+			// SF0021 の例は上限を3件にした場合に報告される
+			const { errors } = sf.dev_parser(info.example, { max_errors: 3 });
+			const { warnings } = sf.dev_normalize(info.example);
+			const { diagnostics } = sf.lint(info.example);
+			const reported = [...(errors ?? []), ...(warnings ?? []), ...diagnostics].map((err) => err.code);
+			const message = `Example for ${info.code} reports ${reported.join(", ")}`;
+			if (info.code === "SF0021") {
+				// 上限に達するまでのエラーの後に報告される
+				assert.strictEqual(reported[reported.length - 1], info.code, message);
+			} else {
+				assert.deepStrictEqual([...new Set(reported)], [info.code], message);
+			}
+		}
+	});
+
+	await t.test("should merge consecutive unexpected characters", () => {
+		const { errors } = sf.dev_parser("# # #\nInt a = 1;");
+		assert.ok(errors);
+		assert.strictEqual(errors.length, 1);
+		assert.strictEqual(errors[0].code, "SF0001");
+		assert.strictEqual(errors[0].message, "Unexpected character: # # #");
+		assert.deepStrictEqual(errors[0].span, { start: 0, end: 5 });
+		assert.strictEqual(errors[0].context?.count, "3");
+	});
+
+	await t.test("should count merged unexpected characters per character", () => {
+		const { errors } = sf.dev_parser("🎉🎉 ##");
+		assert.ok(errors);
+		assert.strictEqual(errors.length, 1);
+		assert.strictEqual(errors[0].message, "Unexpected character: 🎉🎉 ##");
+		assert.strictEqual(errors[0].context?.count, "4");
+	});
+
+	await t.test("should truncate the characters of a long unexpected run", () => {
+		const { errors } = sf.dev_parser("#".repeat(20000));
+		assert.ok(errors);
+		assert.strictEqual(errors.length, 1);
+		assert.strictEqual(errors[0].message, "Unexpected character: ########…");
+		assert.deepStrictEqual(errors[0].span, { start: 0, end: 20000 });
+		assert.strictEqual(errors[0].context?.count, "20000");
+	});
+
+	await t.test("should resume after the statement that has an error", () => {
+		const input = "function f() {\n    if (a) { return 1; }\n}\nInt a = ;\nclass A { 1 }\nInt b = 2;";
+		const { errors } = sf.dev_parser(input);
+		assert.ok(errors);
+		assert.deepStrictEqual(
+			errors.map((err) => [err.code, err.line, err.column]),
+			[
+				["SF0013", 1, 10],
+				["SF0018", 4, 9],
+				["SF0011", 5, 11],
+			],
+		);
+	});
+
+	await t.test("should not report errors after an unterminated string", () => {
+		const { errors } = sf.dev_parser('String s = "hello;\nInt a = ;\nx = 1');
+		assert.ok(errors);
+		assert.deepStrictEqual(
+			errors.map((err) => [err.code, err.line, err.column]),
+			[["SF0003", 1, 12]],
+		);
+	});
+
+	await t.test("should stop after the maximum number of errors", () => {
+		const input = "0b2 0b2 0b2 0b2 0b2 0b2 0b2 0b2 0b2 0b2";
+		const { errors } = sf.dev_parser(input, { max_errors: 3 });
+		assert.ok(errors);
+		assert.deepStrictEqual(
+			errors.map((err) => [err.code, err.column]),
+			[
+				["SF0002", 1],
+				["SF0002", 5],
+				["SF0002", 9],
+				["SF0021", 13],
+			],
+		);
+		assert.strictEqual(errors[3].message, "Too many errors; stopped after 3 errors");
+	});
+
+	await t.test("should limit errors to 100 by default", () => {
+		const { errors } = sf.dev_parser("Int a = 1 # 2;\n".repeat(200));
+		assert.ok(errors);
+		assert.strictEqual(errors.length, 101);
+		assert.strictEqual(errors[100].code, "SF0021");
 	});
 
 	await t.test("should return an instance of Error", () => {
@@ -0,0 +1,92 @@
+import assert from "node:assert";
+import { test } from "node:test";
+
+import { SnowFall } from "../../dist/snowfall";
+import { wasmBuffer } from "./lib/getWasm";
+
+test("Formatter Test", async (t) => {
+	const sf = new SnowFall();
+	await sf.init(wasmBuffer);
+
+	await t.test("should format statements", () => {
+		const input = "Int x=1+2*3;\nfunction Int add(Int a,Int b){return a+b;}\nif(x>1){print(x);}else{print(0);}";
+		const expected = [
+			"Int x = 1 + 2 * 3;",
+			"function Int add(Int a, Int b) {",
+			"    return a + b;",
+			"}",
+			"if (x > 1) {",
+			"    print(x);",
+			"} else {",
+			"    print(0);",
+			"}",
+			"",
+		].join("\n");
+		const { code, errors } = sf.format(input);
+		assert.strictEqual(errors, undefined);
+		assert.strictEqual(code, expected);
+	});
+
+	await t.test("should keep only required parentheses", () => {
+		const { code } = sf.format("Int y = ((1 + 2)) * 3 - (4 - 5) + (a * b);");
+		assert.strictEqual(code, "Int y = (1 + 2) * 3 - (4 - 5) + a * b;\n");
+	});
+
+	await t.test("should preserve comments and blank lines", () => {
+		const input = "// head\nInt a = 1;   // trailing\n\n\n/* block */\nsub s() {\n  // inside\n}\n";
+		const expected = "// head\nInt a = 1; // trailing\n\n/* block */\nsub s() {\n    // inside\n}\n";
+		const { code } = sf.format(input);
+		assert.strictEqual(code, expected);
+	});
+
+	await t.test("should keep comments inside statement headers", () => {
+		const cases = [
+			["if (a) // c\n    x = 1;", "if (a) // c\n    x = 1;\n"],
+			["function Int f(Int a /* p */, Int b) {\n    return a;\n}", "function Int f(Int a /* p */, Int b) {\n    return a;\n}\n"],
+			["f(1, // one\n2);", "f(\n    1, // one\n    2\n);\n"],
+		];
+		for (const [input, expected] of cases) {
+			const { code } = sf.format(input);
+			assert.strictEqual(code, expected);
+			assert.strictEqual(sf.format(expected).code, expected);
+		}
+	});
+
+	await t.test("should respect indent width and brace style", () => {
+		const { code } = sf.format("while (true) { x = x - 1; }", { indent_width: 2, brace_style: "NextLine" });
+		assert.strictEqual(code, "while (true)\n{\n  x = x - 1;\n}\n");
+	});
+
+	await t.test("should break long argument lists", () => {
+		const { code } = sf.format("call(aaaaaaaa, bbbbbbbb, cccccccc);", { max_line_width: 20 });
+		assert.strictEqual(code, "call(\n    aaaaaaaa,\n    bbbbbbbb,\n    cccccccc\n);\n");
+	});
+
+	await t.test("should be idempotent", () => {
+		const inputs = [
+			"Int x=1+2*3;   // trailing\n/* block\n   comment */\nfunction Int add(Int a,Int b){return a+b;}",
+			"if(x>1){print(x);}else if(x<0){print(0);}else print(1);",
+			"class A extends B { Int f; readonly Int g; function Int m(){ return g; } }",
+			"for(Int i=0;i<10;i=i+1){}\nconst Int c = 0x1F;\nInt z = { \"a\": 1, \"b\": [1,2,3] };",
+			"callSomething(aaaaaaaaaaaaaaaaaaaaaa, bbbbbbbbbbbbbbbbbbbbbbbbbbbbb, cccccccccccccccccccccccccccc, dddddddddddddddddd);",
+		];
+		for (const input of inputs) {
+			const once = sf.format(input).code;
+			assert.ok(once !== undefined, `Failed to format ${JSON.stringify(input)}`);
+			assert.strictEqual(sf.format(once).code, once, `Not idempotent for ${JSON.stringify(input)}`);
+		}
+	});
+
+	await t.test("should return errors for invalid source", () => {
+		const { code, errors } = sf.format("Int a = ;");
+		assert.strictEqual(code, undefined);
+		assert.ok(errors && errors.length > 0, "Expected a compiler error");
+	});
+
+	await t.test("should localize errors", () => {
+		const { errors } = sf.format("Int a = ;", { locale: "ja" });
+		assert.ok(errors, "Expected a compiler error");
+		assert.strictEqual(errors[0].code, "SF0018");
+		assert.strictEqual(errors[0].message, "式が必要です");
+	});
+});
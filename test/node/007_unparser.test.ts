@@ -0,0 +1,95 @@
+import assert from "node:assert";
+import { test } from "node:test";
+
+import { SnowFall } from "../../dist/snowfall";
+import { wasmBuffer } from "./lib/getWasm";
+
+// 位置情報(span)とノードID(id)を再帰的に取り除くヘルパー関数
+const stripSpans = (value: unknown): unknown => {
+	if (Array.isArray(value)) return value.map(stripSpans);
+	if (value && typeof value === "object") {
+		return Object.fromEntries(
+			Object.entries(value)
+				.filter(([key]) => key !== "span" && key !== "id")
+				.map(([key, v]) => [key, stripSpans(v)]),
+		);
+	}
+	return value;
+};
+
+const inputs = [
+	"Int x = 1 + 2 * 3;",
+	"Int y = (a + b) * (c - d) / -e;",
+	"z = a - (b - c); w = a = b = 3;",
+	"x = (a ** b) ** c; y = a ** b ** c;",
+	"Bool t = !(a && b) || c and d or e;",
+	"x = (a | b) & c ^ d << 2;",
+	"String s = 'say \"hi\"'; Float q = 1.5 + 2.0;",
+	"Int z = { \"a\": [1, 2, 3] };",
+	"function Int f(Int a, Int b = 2) { return a ** b; }",
+	"if (a) if (b) x(); else y();",
+	"if (a) { if (b) x(); } else y();",
+	"if (a) { x(); } else if (b) { y(); } else { z(); }",
+	"for (Int i = 0; i < 3; i = i + 1) { while (x) {} }",
+	"class C extends D { Int a; readonly Int b; function Int m() { return -(-a); } }",
+	"const Int k = 7; Int m = k * 2;",
+	"a.b[c + 1].d = f(x)[0].y; Int n = (-a).b;",
+];
+
+test("Unparser Test", async (t) => {
+	const sf = new SnowFall();
+	await sf.init(wasmBuffer);
+
+	await t.test("should round-trip parsed ASTs", () => {
+		for (const input of inputs) {
+			const { ast } = sf.dev_parser(input);
+			const { code, errors } = sf.dev_unparse(input);
+			assert.strictEqual(errors, undefined, `Failed to unparse ${JSON.stringify(input)}`);
+
+			const reparsed = sf.dev_parser(code!);
+			assert.strictEqual(reparsed.errors, undefined, `Failed to reparse ${JSON.stringify(code)}`);
+			assert.deepStrictEqual(stripSpans(reparsed.ast), stripSpans(ast), `Round-trip mismatch for ${JSON.stringify(input)}`);
+		}
+	});
+
+	await t.test("should round-trip normalized ASTs", () => {
+		// 負の数値リテラルは前置 `-` として再解析されるため、再度正規化して比較する
+		for (const input of [...inputs, "Int c = -5 * x; Int d = 2 - 5;"]) {
+			const { ast } = sf.dev_normalize(input);
+			const { code } = sf.dev_unparse(input, true);
+
+			const reparsed = sf.dev_normalize(code!);
+			assert.strictEqual(reparsed.errors, undefined, `Failed to reparse ${JSON.stringify(code)}`);
+			assert.deepStrictEqual(stripSpans(reparsed.ast), stripSpans(ast), `Round-trip mismatch for ${JSON.stringify(input)}`);
+		}
+	});
+
+	await t.test("should round-trip folded string literals", () => {
+		const cases = [
+			[`String s = "it's" + 'say "hi"';`, `String s = "it's" + 'say "hi"';\n`],
+			[`Bool b = "\\"" == '"';`, `Bool b = "\\"" == '"';\n`],
+			[`String t = "a" + 'b"c';`, `String t = 'ab"c';\n`],
+		];
+		for (const [input, expected] of cases) {
+			const { code } = sf.dev_unparse(input, true);
+			assert.strictEqual(code, expected);
+
+			const reparsed = sf.dev_normalize(code!);
+			assert.strictEqual(reparsed.errors, undefined, `Failed to reparse ${JSON.stringify(code)}`);
+			assert.deepStrictEqual(stripSpans(reparsed.ast), stripSpans(sf.dev_normalize(input).ast));
+		}
+	});
+
+	await t.test("should print normalized source with minimal parentheses", () => {
+		const { code } = sf.dev_unparse("Int x; Int a = (1 + 2) * x; Int b = a * (2 + 3);", true);
+		assert.strictEqual(code, "Int x;\nInt a = x * 3;\nInt b = a * 5;\n");
+	});
+
+	await t.test("should localize errors", () => {
+		const { code, errors } = sf.dev_unparse("Int a = ;", false, { locale: "ja" });
+		assert.strictEqual(code, undefined);
+		assert.ok(errors, "Expected a compiler error");
+		assert.strictEqual(errors[0].code, "SF0018");
+		assert.strictEqual(errors[0].message, "式が必要です");
+	});
+});
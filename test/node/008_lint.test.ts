@@ -0,0 +1,106 @@
+import assert from "node:assert";
+import { test } from "node:test";
+
+import { SnowFall } from "../../dist/snowfall";
+import { wasmBuffer } from "./lib/getWasm";
+
+test("Lint Test", async (t) => {
+	const sf = new SnowFall();
+	await sf.init(wasmBuffer);
+
+	const codes = (input: string) => sf.lint(input).diagnostics.map((d) => [d.code, d.line, d.column]);
+
+	await t.test("should report unused variables and trailing unused parameters", () => {
+		const input = ["Int a = 1, b = a;", "b = 3;", "sub g(Int _x, Int y, Int z) { print(y); }", "g(1, 2, 3);"].join("\n");
+		const { diagnostics, errors } = sf.lint(input);
+		assert.strictEqual(errors, undefined);
+		assert.deepStrictEqual(
+			diagnostics.map((d) => [d.code, d.message, d.context?.rule]),
+			[
+				["SF0201", "'b' is declared but never used", "unused-variable"],
+				["SF0202", "Parameter 'z' is never used", "unused-parameter"],
+			],
+		);
+		assert.deepStrictEqual(diagnostics[0].span, { start: 11, end: 12 });
+		assert.strictEqual(diagnostics[0].severity, "Warning");
+	});
+
+	await t.test("should report shadowed names with the outer declaration", () => {
+		const input = "Int x = 1;\nsub f() {\n    Int x = 2;\n    print(x);\n}\nprint(x);";
+		const [diagnostic] = sf.lint(input).diagnostics;
+		assert.strictEqual(diagnostic.code, "SF0203");
+		assert.deepStrictEqual([diagnostic.line, diagnostic.column], [3, 9]);
+		assert.deepStrictEqual(diagnostic.labels?.[0].range.span, { start: 4, end: 5 });
+	});
+
+	await t.test("should suggest strict comparisons", () => {
+		const input = "sub f(Int x) {\n    if (x == 1) {}\n}";
+		const { diagnostics } = sf.lint(input);
+		assert.deepStrictEqual(
+			diagnostics.map((d) => d.code),
+			["SF0204", "SF0206"],
+		);
+		assert.strictEqual(diagnostics[0].message, "Use '===' instead of '=='");
+		assert.deepStrictEqual(diagnostics[0].suggestions?.[0].edits[0].range.span, { start: 25, end: 27 });
+		assert.strictEqual(diagnostics[0].suggestions?.[0].edits[0].replacement, "===");
+	});
+
+	await t.test("should report conditions and unreachable code", () => {
+		assert.deepStrictEqual(codes("if (a = b) {\n    f();\n}"), [["SF0205", 1, 5]]);
+		assert.deepStrictEqual(codes("if (1 < 2) {\n    f();\n}"), [["SF0207", 1, 5]]);
+		assert.deepStrictEqual(codes("while (true) {\n    f();\n}"), []);
+		assert.deepStrictEqual(codes("function Int f() {\n    return 1;\n    f();\n}"), [["SF0200", 3, 5]]);
+	});
+
+	await t.test("should not report empty function bodies or commented blocks", () => {
+		assert.deepStrictEqual(codes("sub f() {}\nf();"), []);
+		assert.deepStrictEqual(codes("if (a) {\n    // 何もしない\n}"), []);
+		assert.deepStrictEqual(codes("if (a) {} else {}"), [
+			["SF0206", 1, 8],
+			["SF0206", 1, 16],
+		]);
+	});
+
+	await t.test("should honor rule levels", () => {
+		const input = "if (a == b) {}";
+		const { diagnostics } = sf.lint(input, { rules: { "empty-block": "off", "loose-equality": "error" } });
+		assert.deepStrictEqual(
+			diagnostics.map((d) => [d.code, d.severity, d.type]),
+			[["SF0204", "Error", "CompilationError"]],
+		);
+	});
+
+	await t.test("should honor suppression comments", () => {
+		const input = [
+			"// snowfall-disable-next-line loose-equality",
+			"if (a != 2) { print(a); }",
+			"// snowfall-disable-next-line",
+			"Int q = 0;",
+			"// snowfall-disable-next-line empty-block",
+			"Int r = 0;",
+		].join("\n");
+		assert.deepStrictEqual(codes(input), [["SF0201", 6, 5]]);
+	});
+
+	await t.test("should localize diagnostics", () => {
+		const { diagnostics } = sf.lint("sub f() {\n    Int unused = 1;\n}", { locale: "ja" });
+		assert.strictEqual(diagnostics[0].message, "'unused' は宣言されていますが使用されていません");
+
+		// ラベルと修正案も指定した言語で出力する
+		const input = "Int a = 1;\nBool b = a == 1;\nsub f() {\n    Int a = 2;\n    print(a);\n}\nf();\nprint(b);";
+		const localized = sf.lint(input, { locale: "ja" }).diagnostics;
+		assert.deepStrictEqual(
+			localized.map((d) => [d.code, d.labels?.map((l) => l.message), d.suggestions?.map((s) => s.message)]),
+			[
+				["SF0204", undefined, ["'==' を '===' に置き換えます"]],
+				["SF0203", ["'a' は最初にここで宣言されています"], undefined],
+			],
+		);
+	});
+
+	await t.test("should return parse errors without diagnostics", () => {
+		const { diagnostics, errors } = sf.lint("Int a = 1 +;");
+		assert.deepStrictEqual(diagnostics, []);
+		assert.strictEqual(errors?.[0].code, "SF0018");
+	});
+});
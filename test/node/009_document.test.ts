@@ -0,0 +1,98 @@
+import assert from "node:assert";
+import { test } from "node:test";
+
+import { SnowFall } from "../../dist/snowfall";
+import { wasmBuffer } from "./lib/getWasm";
+
+/** ノードIDを取り除く (増分解析ではIDの割り当て順が全体の解析と異なる) */
+function withoutIds(value: unknown): unknown {
+	if (Array.isArray(value)) {
+		return value.map(withoutIds);
+	}
+	if (value !== null && typeof value === "object") {
+		return Object.fromEntries(
+			Object.entries(value)
+				.filter(([key]) => key !== "id")
+				.map(([key, v]) => [key, withoutIds(v)]),
+		);
+	}
+	return value;
+}
+
+test("Document Test", async (t) => {
+	const sf = new SnowFall();
+	await sf.init(wasmBuffer);
+
+	const input = "Int a = 1;\nsub f() {\n    print(a);\n}\nprint(2);";
+
+	await t.test("should reparse only the edited block", () => {
+		const doc = sf.open_document(input);
+		try {
+			// `print(a)` を `print(a + 1)` に書き換える
+			const result = doc.apply_edit(32, 32, " + 1");
+			assert.deepStrictEqual(result, { reparsed: { start: 20, end: 39 }, incremental: true });
+			assert.strictEqual(doc.source, "Int a = 1;\nsub f() {\n    print(a + 1);\n}\nprint(2);");
+
+			const { ast, errors } = doc.result();
+			assert.strictEqual(errors, undefined);
+			assert.deepStrictEqual(withoutIds(ast), withoutIds(sf.dev_parser(doc.source).ast));
+		} finally {
+			doc.free();
+		}
+	});
+
+	await t.test("should reparse only the edited top-level statement", () => {
+		const doc = sf.open_document(input);
+		try {
+			const result = doc.apply_edit(43, 44, "3");
+			assert.deepStrictEqual(result, { reparsed: { start: 36, end: 46 }, incremental: true });
+			assert.deepStrictEqual(withoutIds(doc.result().ast), withoutIds(sf.dev_parser(doc.source).ast));
+		} finally {
+			doc.free();
+		}
+	});
+
+	await t.test("should fall back to a full parse around syntax errors", () => {
+		const doc = sf.open_document(input);
+		try {
+			const broken = doc.apply_edit(8, 9, "");
+			assert.strictEqual(broken.incremental, false);
+			const { ast, errors } = doc.result();
+			assert.strictEqual(ast, undefined);
+			assert.strictEqual(errors?.[0].code, "SF0018");
+
+			const fixed = doc.apply_edit(8, 8, "5");
+			assert.strictEqual(fixed.incremental, false);
+			assert.strictEqual(doc.source, input.replace("= 1", "= 5"));
+			assert.deepStrictEqual(withoutIds(doc.result().ast), withoutIds(sf.dev_parser(doc.source).ast));
+		} finally {
+			doc.free();
+		}
+	});
+
+	await t.test("should keep node ids unique across edits", () => {
+		const doc = sf.open_document(input);
+		try {
+			doc.apply_edit(32, 32, " + 1");
+			doc.apply_edit(0, 0, "Int b = 2;\n");
+			const ids: number[] = [];
+			JSON.stringify(doc.result().ast, (key, value) => {
+				if (key === "id") ids.push(value);
+				return value;
+			});
+			assert.strictEqual(new Set(ids).size, ids.length);
+		} finally {
+			doc.free();
+		}
+	});
+
+	await t.test("should reject invalid edit ranges", () => {
+		const doc = sf.open_document(input);
+		try {
+			assert.throws(() => doc.apply_edit(0, input.length + 1, ""));
+			assert.strictEqual(doc.source, input);
+		} finally {
+			doc.free();
+		}
+	});
+});
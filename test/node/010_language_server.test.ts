@@ -0,0 +1,343 @@
+import assert from "node:assert";
+import { test } from "node:test";
+
+import { SnowFall } from "../../dist/snowfall";
+import { wasmBuffer } from "./lib/getWasm";
+
+test("Language Server Test", async (t) => {
+	const sf = new SnowFall();
+	await sf.init(wasmBuffer);
+
+	const uri = "file:///main.sf";
+	const input = [
+		"class Point {",
+		"    Int x;",
+		"    function Int getX() {",
+		"        return x;",
+		"    }",
+		"}",
+		"const Int LIMIT = 10;",
+		"function Int add(Int a, Int b) {",
+		"    return a + b;",
+		"}",
+		"Int total = add(1, LIMIT);",
+		"total = total + 1;",
+		"print(total == 2);",
+	].join("\n");
+
+	/** 初期化して文書を開いたサーバーと、開いたときの通知 */
+	const open = (options?: { locale?: string }) => {
+		const server = sf.create_language_server();
+		server.send({ jsonrpc: "2.0", id: 0, method: "initialize", params: { initializationOptions: options } });
+		const published = server.send({
+			jsonrpc: "2.0",
+			method: "textDocument/didOpen",
+			params: { textDocument: { uri, languageId: "snowfall", version: 1, text: input } },
+		});
+		let id = 1;
+		const request = (method: string, params: unknown) => {
+			const [response] = server.send({ jsonrpc: "2.0", id: id++, method, params });
+			return response;
+		};
+		const at = (line: number, character: number) => ({ textDocument: { uri }, position: { line, character } });
+		return { server, published, request, at };
+	};
+
+	await t.test("should require initialize and report capabilities", () => {
+		const server = sf.create_language_server();
+		try {
+			const [early] = server.send({ jsonrpc: "2.0", id: 1, method: "textDocument/hover", params: {} });
+			assert.strictEqual(early.error?.code, -32002);
+
+			const [response] = server.send({ jsonrpc: "2.0", id: 2, method: "initialize", params: {} });
+			const { capabilities } = response.result as { capabilities: Record<string, unknown> };
+			assert.deepStrictEqual(capabilities.textDocumentSync, { openClose: true, change: 2 });
+			assert.strictEqual(capabilities.hoverProvider, true);
+		} finally {
+			server.free();
+		}
+	});
+
+	await t.test("should publish diagnostics when a document is opened", () => {
+		const { server, published } = open();
+		try {
+			assert.deepStrictEqual(published, [
+				{
+					jsonrpc: "2.0",
+					method: "textDocument/publishDiagnostics",
+					params: {
+						uri,
+						version: 1,
+						diagnostics: [
+							{
+								range: { start: { line: 12, character: 6 }, end: { line: 12, character: 16 } },
+								severity: 2,
+								code: "SF0204",
+								source: "snowfall",
+								message: "Use '===' instead of '=='",
+							},
+						],
+					},
+				},
+			]);
+		} finally {
+			server.free();
+		}
+	});
+
+	await t.test("should show declared types on hover", () => {
+		const { server, request, at } = open();
+		try {
+			assert.deepStrictEqual(request("textDocument/hover", at(10, 13)).result, {
+				contents: { kind: "markdown", value: "```snowfall\nfunction Int add(Int a, Int b)\n```" },
+				range: { start: { line: 10, character: 12 }, end: { line: 10, character: 15 } },
+			});
+			const parameter = request("textDocument/hover", at(8, 11)).result as { contents: { value: string } };
+			assert.strictEqual(parameter.contents.value, "```snowfall\n(parameter) Int a\n```");
+			const field = request("textDocument/hover", at(3, 15)).result as { contents: { value: string } };
+			assert.strictEqual(field.contents.value, "```snowfall\n(field) Int x\n```");
+			assert.strictEqual(request("textDocument/hover", at(12, 1)).result, null);
+		} finally {
+			server.free();
+		}
+	});
+
+	await t.test("should find definitions and references", () => {
+		const { server, request, at } = open();
+		try {
+			assert.deepStrictEqual(request("textDocument/definition", at(10, 20)).result, {
+				uri,
+				range: { start: { line: 6, character: 10 }, end: { line: 6, character: 15 } },
+			});
+
+			const references = request("textDocument/references", { ...at(11, 0), context: { includeDeclaration: true } });
+			assert.deepStrictEqual(
+				(references.result as { range: { start: unknown } }[]).map((location) => location.range.start),
+				[
+					{ line: 10, character: 4 },
+					{ line: 11, character: 0 },
+					{ line: 11, character: 8 },
+					{ line: 12, character: 6 },
+				],
+			);
+		} finally {
+			server.free();
+		}
+	});
+
+	await t.test("should list functions and classes as document symbols", () => {
+		const { server, request } = open();
+		try {
+			const symbols = request("textDocument/documentSymbol", { textDocument: { uri } }).result as {
+				name: string;
+				kind: number;
+				detail: string;
+				children?: { name: string; kind: number }[];
+			}[];
+			assert.deepStrictEqual(
+				symbols.map(({ name, kind, detail, children }) => [name, kind, detail, children?.map((c) => [c.name, c.kind])]),
+				[
+					["Point", 5, "class Point", [["getX", 6]]],
+					["add", 12, "function Int add(Int a, Int b)", undefined],
+				],
+			);
+		} finally {
+			server.free();
+		}
+	});
+
+	await t.test("should complete keywords and names in scope", () => {
+		const { server, request, at } = open();
+		try {
+			const items = request("textDocument/completion", at(8, 4)).result as { label: string; kind: number }[];
+			// 関数の中では引数が先頭になり、後ろで宣言される変数は含まない (文の先頭では型名が続く)
+			assert.deepStrictEqual(
+				items.filter((item) => item.kind !== 14).map((item) => item.label),
+				["b", "a", "add", "LIMIT", "Point", "Int", "Float", "String", "Bool"],
+			);
+			assert.ok(items.some((item) => item.label === "return" && item.kind === 14));
+		} finally {
+			server.free();
+		}
+	});
+
+	await t.test("should encode semantic tokens relative to the previous token", () => {
+		const server = sf.create_language_server();
+		try {
+			const [initialized] = server.send({ jsonrpc: "2.0", id: 1, method: "initialize", params: {} });
+			const { capabilities } = initialized.result as { capabilities: { semanticTokensProvider: { legend: { tokenTypes: string[]; tokenModifiers: string[] } } } };
+			const { tokenTypes, tokenModifiers } = capabilities.semanticTokensProvider.legend;
+			assert.deepStrictEqual(tokenModifiers, ["declaration", "readonly", "static"]);
+
+			const text = 'const Int LIMIT = 10;\n/* a\n b */ print(LIMIT, "é");';
+			server.send({
+				jsonrpc: "2.0",
+				method: "textDocument/didOpen",
+				params: { textDocument: { uri, languageId: "snowfall", version: 1, text } },
+			});
+			const [response] = server.send({ jsonrpc: "2.0", id: 2, method: "textDocument/semanticTokens/full", params: { textDocument: { uri } } });
+			const { data } = response.result as { data: number[] };
+			const tokens = [];
+			for (let i = 0; i < data.length; i += 5) {
+				tokens.push([data[i], data[i + 1], data[i + 2], tokenTypes[data[i + 3]], data[i + 4]]);
+			}
+			// 複数行のコメントは行ごとに分割し、長さはUTF-16のコード単位で数える
+			assert.deepStrictEqual(tokens, [
+				[0, 0, 5, "keyword", 0],
+				[0, 6, 3, "type", 0],
+				[0, 4, 5, "variable", 3],
+				[0, 6, 1, "operator", 0],
+				[0, 2, 2, "number", 0],
+				[1, 0, 4, "comment", 0],
+				[1, 0, 5, "comment", 0],
+				[0, 6, 5, "function", 0],
+				[0, 6, 5, "variable", 2],
+				[0, 7, 3, "string", 0],
+			]);
+		} finally {
+			server.free();
+		}
+	});
+
+	await t.test("should apply incremental changes", () => {
+		const { server, request, at } = open({ locale: "ja" });
+		try {
+			// `total + 1` の `1` を削除する
+			const [broken] = server.send({
+				jsonrpc: "2.0",
+				method: "textDocument/didChange",
+				params: {
+					textDocument: { uri, version: 2 },
+					contentChanges: [{ range: { start: { line: 11, character: 16 }, end: { line: 11, character: 17 } }, text: "" }],
+				},
+			});
+			const { version, diagnostics } = broken.params as { version: number; diagnostics: { code: string; severity: number }[] };
+			assert.strictEqual(version, 2);
+			assert.deepStrictEqual(
+				diagnostics.map((d) => [d.code, d.severity]),
+				[["SF0018", 1]],
+			);
+
+			// 構文エラーの間も、編集中の文を除いて解析し直した結果から補完する
+			const items = request("textDocument/completion", at(11, 8)).result as { label: string; sortText: string }[];
+			assert.deepStrictEqual(
+				items.slice(0, 3).map((item) => [item.label, item.sortText]),
+				[
+					["total", "0000"],
+					["add", "0001"],
+					["LIMIT", "0002"],
+				],
+			);
+		} finally {
+			server.free();
+		}
+	});
+
+	await t.test("should keep resolving names while a syntax error is present", () => {
+		const { server, request, at } = open();
+		try {
+			// 先頭に構文エラーのある行を挿入する
+			server.send({
+				jsonrpc: "2.0",
+				method: "textDocument/didChange",
+				params: {
+					textDocument: { uri, version: 2 },
+					contentChanges: [{ range: { start: { line: 0, character: 0 }, end: { line: 0, character: 0 } }, text: "Int y = ;\n" }],
+				},
+			});
+
+			// 最後に解析できたときの結果を、編集に合わせてずらして使う
+			assert.deepStrictEqual(request("textDocument/hover", at(11, 13)).result, {
+				contents: { kind: "markdown", value: "```snowfall\nfunction Int add(Int a, Int b)\n```" },
+				range: { start: { line: 11, character: 12 }, end: { line: 11, character: 15 } },
+			});
+			assert.deepStrictEqual(request("textDocument/definition", at(11, 20)).result, {
+				uri,
+				range: { start: { line: 7, character: 10 }, end: { line: 7, character: 15 } },
+			});
+			const references = request("textDocument/references", { ...at(12, 0), context: { includeDeclaration: true } });
+			assert.deepStrictEqual(
+				(references.result as { range: { start: unknown } }[]).map((location) => location.range.start),
+				[
+					{ line: 11, character: 4 },
+					{ line: 12, character: 0 },
+					{ line: 12, character: 8 },
+					{ line: 13, character: 6 },
+				],
+			);
+		} finally {
+			server.free();
+		}
+	});
+
+	await t.test("should reject requests until the full text is sent after an invalid change", () => {
+		const { server, request, at } = open();
+		try {
+			const change = (version: number, contentChanges: unknown[]) =>
+				server.send({ jsonrpc: "2.0", method: "textDocument/didChange", params: { textDocument: { uri, version }, contentChanges } });
+
+			// 開始と終了が逆転した範囲は適用できず、古い内容に対する診断を消す
+			const [log, published] = change(2, [{ range: { start: { line: 3, character: 0 }, end: { line: 0, character: 0 } }, text: "x" }]);
+			assert.strictEqual(log.method, "window/logMessage");
+			assert.strictEqual((log.params as { type: number }).type, 1);
+			assert.deepStrictEqual(published.params, { uri, version: 2, diagnostics: [] });
+			assert.strictEqual(request("textDocument/hover", at(10, 13)).error?.code, -32602);
+
+			// 以降の差分の変更は無視し、全文を受け取ると再び応答する
+			change(3, [{ range: { start: { line: 0, character: 0 }, end: { line: 0, character: 0 } }, text: "x" }]);
+			assert.strictEqual(request("textDocument/hover", at(10, 13)).error?.code, -32602);
+			change(4, [{ text: input }]);
+			const hover = request("textDocument/hover", at(10, 13)).result as { contents: { value: string } };
+			assert.strictEqual(hover.contents.value, "```snowfall\nfunction Int add(Int a, Int b)\n```");
+		} finally {
+			server.free();
+		}
+	});
+
+	await t.test("should exchange messages with Content-Length headers", () => {
+		const server = sf.create_language_server();
+		try {
+			const encoder = new TextEncoder();
+			const decoder = new TextDecoder();
+			const body = JSON.stringify({ jsonrpc: "2.0", id: 1, method: "initialize", params: {} });
+			const data = encoder.encode(`Content-Length: ${encoder.encode(body).length}\r\n\r\n${body}`);
+
+			// メッセージが揃うまでは何も返さない
+			assert.strictEqual(server.receive(data.subarray(0, 30)).length, 0);
+			const output = decoder.decode(server.receive(data.subarray(30)));
+			const [header, response] = output.split("\r\n\r\n");
+			assert.strictEqual(header, `Content-Length: ${encoder.encode(response).length}`);
+			assert.strictEqual(JSON.parse(response).id, 1);
+
+			// 扱えない長さのヘッダは読み飛ばし、続くメッセージを処理する
+			const next = JSON.stringify({ jsonrpc: "2.0", id: 2, method: "shutdown" });
+			const invalid = decoder.decode(server.receive(encoder.encode(`Content-Length: 99999999999999999999999\r\n\r\nContent-Length: ${next.length}\r\n\r\n${next}`)));
+			const responses = invalid.split(/Content-Length: \d+\r\n\r\n/).filter((part) => part !== "").map((part) => JSON.parse(part));
+			assert.deepStrictEqual(
+				responses.map((message) => [message.id, message.error?.code]),
+				[
+					[null, -32700],
+					[2, undefined],
+				],
+			);
+		} finally {
+			server.free();
+		}
+	});
+
+	await t.test("should handle errors and shutdown", () => {
+		const { server, request } = open();
+		try {
+			assert.strictEqual(request("textDocument/unknown", {}).error?.code, -32601);
+			assert.strictEqual(request("textDocument/hover", { textDocument: { uri: "file:///other.sf" }, position: { line: 0, character: 0 } }).error?.code, -32602);
+			assert.deepStrictEqual(server.handle("not json").map((body) => JSON.parse(body).error.code), [-32700]);
+
+			assert.strictEqual(request("shutdown", null).result, null);
+			assert.strictEqual(request("textDocument/hover", {}).error?.code, -32600);
+			assert.deepStrictEqual(server.send({ jsonrpc: "2.0", method: "exit" }), []);
+			assert.strictEqual(server.exit_code, 0);
+		} finally {
+			server.free();
+		}
+	});
+});
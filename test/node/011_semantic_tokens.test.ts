@@ -0,0 +1,83 @@
+import assert from "node:assert";
+import { test } from "node:test";
+
+import { SnowFall } from "../../dist/snowfall";
+import { wasmBuffer } from "./lib/getWasm";
+
+test("Semantic Tokens Test", async (t) => {
+	const sf = new SnowFall();
+	await sf.init(wasmBuffer);
+
+	/** トークンを [文字列, 種類, 修飾子] の組に変換する */
+	const classify = (input: string) =>
+		sf.semantic_tokens(input).map(({ span, kind, modifiers }) => [input.slice(span.start, span.end), kind, modifiers ?? []]);
+
+	await t.test("should classify identifiers using name resolution", () => {
+		const input = [
+			"class Point {",
+			"    readonly Int x;",
+			"    function Int getX() {",
+			"        return x;",
+			"    }",
+			"}",
+			"// limit",
+			"const Int LIMIT = 10;",
+			"function Float scale(Int value) {",
+			"    return value * 1.5;",
+			"}",
+			"Point p;",
+			'print(p.getX() + LIMIT, "done", true);',
+		].join("\n");
+
+		assert.deepStrictEqual(classify(input), [
+			["class", "keyword", []],
+			["Point", "class", ["declaration"]],
+			["readonly", "keyword", []],
+			["Int", "type", []],
+			["x", "property", ["declaration", "readonly"]],
+			["function", "keyword", []],
+			["Int", "type", []],
+			["getX", "function", ["declaration"]],
+			["return", "keyword", []],
+			["x", "property", ["readonly"]],
+			["// limit", "comment", []],
+			["const", "keyword", []],
+			["Int", "type", []],
+			["LIMIT", "variable", ["declaration", "readonly"]],
+			["=", "operator", []],
+			["10", "number", []],
+			["function", "keyword", []],
+			["Float", "type", []],
+			["scale", "function", ["declaration"]],
+			["Int", "type", []],
+			["value", "parameter", ["declaration"]],
+			["return", "keyword", []],
+			["value", "parameter", []],
+			["*", "operator", []],
+			["1.5", "number", []],
+			["Point", "class", []],
+			["p", "variable", ["declaration"]],
+			["print", "function", []],
+			["p", "variable", []],
+			["getX", "property", []],
+			["+", "operator", []],
+			["LIMIT", "variable", ["readonly"]],
+			['"done"', "string", []],
+			["true", "keyword", []],
+		]);
+	});
+
+	await t.test("should fall back to neighbouring tokens on syntax errors", () => {
+		assert.deepStrictEqual(classify("Int a = 1 +;\nfoo(a.b);\n/* c */"), [
+			["Int", "type", []],
+			["a", "variable", []],
+			["=", "operator", []],
+			["1", "number", []],
+			["+", "operator", []],
+			["foo", "function", []],
+			["a", "variable", []],
+			["b", "property", []],
+			["/* c */", "comment", []],
+		]);
+	});
+});
@@ -0,0 +1,113 @@
+import assert from "node:assert";
+import { test } from "node:test";
+
+import { SnowFall } from "../../dist/snowfall";
+import { wasmBuffer } from "./lib/getWasm";
+
+test("Completion Test", async (t) => {
+	const sf = new SnowFall();
+	await sf.init(wasmBuffer);
+
+	/** `|` の位置をカーソルとして補完する */
+	const completeAt = (input: string) => {
+		const offset = input.indexOf("|");
+		return sf.complete(input.replace("|", ""), offset);
+	};
+	const labels = (input: string) => completeAt(input).items.map((item) => item.label);
+
+	await t.test("should complete class members after a dot", () => {
+		const input = [
+			"class Point {",
+			"    Int x;",
+			"    function Point next() {",
+			"        return x;",
+			"    }",
+			"}",
+			"class Point3 extends Point {",
+			"    Int z;",
+			"}",
+			"Point3 p;",
+			"Int px = p.next().|",
+		].join("\n");
+
+		const { context, items } = completeAt(input);
+		assert.strictEqual(context, "Member");
+		// 呼び出しの戻り値の型のメンバーを補完する
+		assert.deepStrictEqual(items, [
+			{ label: "x", kind: "Field", detail: "Int x" },
+			{ label: "next", kind: "Method", detail: "function Point next()" },
+		]);
+		// 継承元のメンバーは派生クラスのメンバーの後に続く
+		assert.deepStrictEqual(labels(input.replace("p.next().|", "p.|")), ["z", "x", "next"]);
+	});
+
+	await t.test("should complete members of the enclosing class after this", () => {
+		assert.deepStrictEqual(labels("class C { Int z; function Int m() { return this.| } }"), ["z", "m"]);
+
+		const input = [
+			"class B {",
+			"    Int y;",
+			"}",
+			"class C extends B {",
+			"    Int z;",
+			"    function Int m() {",
+			"        return this.|",
+			"    }",
+			"}",
+		].join("\n");
+		// 継承元のメンバーも含む
+		assert.deepStrictEqual(labels(input), ["z", "m", "y"]);
+	});
+
+	await t.test("should complete names in scope while the code is incomplete", () => {
+		const input = ["Int count = 1;", "function Int f(Int a) {", "    Int b = a + |", "}", "Int later = 2;"].join("\n");
+
+		const { context, items } = completeAt(input);
+		assert.strictEqual(context, "Expression");
+		assert.deepStrictEqual(
+			items.map((item) => [item.label, item.kind]),
+			[
+				["a", "Parameter"],
+				["f", "Function"],
+				["count", "Variable"],
+				["true", "Keyword"],
+				["false", "Keyword"],
+				["null", "Keyword"],
+			],
+		);
+	});
+
+	await t.test("should filter candidates by the word being typed", () => {
+		const input = "Int value = 1;\nprint(VA|";
+		const { items, replace } = completeAt(input);
+		// 大文字小文字が異なる候補も含める
+		assert.deepStrictEqual(items, [{ label: "value", kind: "Variable", detail: "Int value" }]);
+		assert.deepStrictEqual(replace, { start: 21, end: 23 });
+		assert.deepStrictEqual(labels("const In|"), ["Int"]);
+	});
+
+	await t.test("should offer keywords valid at the position", () => {
+		const statement = labels(["Int count = 1;", "function Int f(Int a) {", "    Int b = 1;", "    |", "}"].join("\n"));
+		assert.deepStrictEqual(statement.slice(0, 8), ["b", "a", "f", "count", "Int", "Float", "String", "Bool"]);
+		assert.ok(statement.includes("return"));
+
+		assert.ok(labels("Int a = 1;\nif (a > 0) {\n    a = 2;\n}\n|").includes("else"));
+		assert.ok(!labels("Int a = 1;\n|").includes("return"));
+		assert.deepStrictEqual(labels("class A {\n    |\n}").slice(-3), ["function", "sub", "readonly"]);
+		assert.deepStrictEqual(labels("for (Int x |"), ["in", "of"]);
+		assert.deepStrictEqual(labels("Int a = 1 |"), ["and", "or"]);
+	});
+
+	await t.test("should complete type names in declarations", () => {
+		assert.deepStrictEqual(labels("function Int f(Int a, |"), ["Int", "Float", "String", "Bool"]);
+		assert.deepStrictEqual(labels("class Base {\n}\nclass B extends |"), ["Base"]);
+	});
+
+	await t.test("should not complete inside comments, strings or new names", () => {
+		for (const input of ["// comment |", 'print("ab|c");', "Int a|"]) {
+			const { context, items } = completeAt(input);
+			assert.strictEqual(context, "None");
+			assert.deepStrictEqual(items, []);
+		}
+	});
+});
@@ -0,0 +1,123 @@
+//! `ProgramAst` と `AstArena` の構文解析時間・最大メモリ・保持メモリの比較
+//!
+//! `cargo bench --bench ast` で実行する
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use snowfall_core::compiler::arena::AstArena;
+use snowfall_core::compiler::{Lexer, Parser};
+
+/// 確保中のヒープ量とその最大値を計測するアロケータ
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// 確保量を増やし、最大値を更新する
+fn grow(size: usize) {
+    let allocated = ALLOCATED.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(allocated, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        grow(layout.size());
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        // 移動する場合は確保し直す間だけ両方の領域を保持する
+        grow(new_size);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// 自動生成されたスクリプトを模した、同じ識別子が繰り返し現れるソースを作る
+fn generate_source(functions: usize) -> String {
+    let mut source = String::new();
+    for i in 0..functions {
+        source.push_str(&format!(
+            "function Int compute_{i}(Int value, Int offset) {{\n\
+             \x20   Int result = value * 2 + offset - {i};\n\
+             \x20   if (result > 100 && value != offset) {{\n\
+             \x20       result = result / 3 + compute_{i}(value - 1, offset);\n\
+             \x20   }}\n\
+             \x20   for (Int index = 0; index < 10; index = index + 1) {{\n\
+             \x20       result = result + index * value;\n\
+             \x20   }}\n\
+             \x20   return result;\n\
+             }}\n"
+        ));
+    }
+    source
+}
+
+/// `f` の実行中に増えたヒープ量の最大値と、結果を保持したまま増えたヒープ量を返す
+fn measure_bytes<T>(f: impl FnOnce() -> T) -> (usize, usize, T) {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    let value = f();
+    let after = ALLOCATED.load(Ordering::Relaxed);
+    let peak = PEAK.load(Ordering::Relaxed);
+    (
+        peak.saturating_sub(before),
+        after.saturating_sub(before),
+        value,
+    )
+}
+
+/// `iterations` 回実行した平均時間
+fn average_time<T>(iterations: u32, mut f: impl FnMut() -> T) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(f());
+    }
+    start.elapsed() / iterations
+}
+
+fn main() {
+    println!(
+        "{:>9} | {:>13} {:>13} | {:>13} {:>13} | {:>13} {:>13}",
+        "", "parse time", "", "peak memory", "", "retained", ""
+    );
+    println!(
+        "{:>9} | {:>13} {:>13} | {:>13} {:>13} | {:>13} {:>13}",
+        "functions", "ProgramAst", "AstArena", "ProgramAst", "AstArena", "ProgramAst", "AstArena"
+    );
+    for functions in [100, 1_000, 5_000] {
+        let source = generate_source(functions);
+        let iterations = (20_000 / functions).max(3) as u32;
+
+        let program_time = average_time(iterations, || {
+            Parser::new(Lexer::new(&source)).parse_program().unwrap()
+        });
+        let arena_time = average_time(iterations, || AstArena::parse(&source).unwrap());
+
+        let (program_peak, program_bytes, program) =
+            measure_bytes(|| Parser::new(Lexer::new(&source)).parse_program().unwrap());
+        let (arena_peak, arena_bytes, arena) = measure_bytes(|| AstArena::parse(&source).unwrap());
+        assert_eq!(arena.to_program(), program);
+
+        println!(
+            "{:>9} | {:>13?} {:>13?} | {:>11}KB {:>11}KB | {:>11}KB {:>11}KB",
+            functions,
+            program_time,
+            arena_time,
+            program_peak / 1024,
+            arena_peak / 1024,
+            program_bytes / 1024,
+            arena_bytes / 1024
+        );
+    }
+}
@@ -0,0 +1,318 @@
+//! 診断レポートの整形
+//!
+//! `SnowFallError` の一覧を、ソースコードの抜粋と範囲の下線を添えた
+//! コンパイラ形式のテキストへ整形する。色付けにはANSIエスケープシーケンスを使う。
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::Span;
+use crate::common::error::{Severity, SnowFallError};
+use crate::common::span::offset_to_line_column;
+
+/// 診断レポートの設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenderOptions {
+    /// ANSIエスケープシーケンスで色付けする
+    pub color: bool,
+    /// 見出しの位置情報に表示するファイル名
+    pub file_name: String,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            color: false,
+            file_name: "<input>".to_string(),
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const CYAN: &str = "\x1b[1;36m";
+const GREEN: &str = "\x1b[1;32m";
+const BLUE: &str = "\x1b[1;34m";
+
+/// 色付けの有無を切り替えて文字列を装飾する
+struct Painter {
+    enabled: bool,
+}
+
+impl Painter {
+    fn paint(&self, text: &str, style: &str) -> String {
+        if self.enabled && !text.is_empty() {
+            format!("{}{}{}", style, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+impl Severity {
+    /// 見出しに表示する名前
+    fn label(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+            Severity::Hint => "hint",
+        }
+    }
+
+    fn style(self) -> &'static str {
+        match self {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+            Severity::Info => CYAN,
+            Severity::Hint => GREEN,
+        }
+    }
+}
+
+/// 1行の中で下線を引く範囲 (行頭からのバイトオフセット)
+struct Mark<'a> {
+    start: usize,
+    end: usize,
+    message: Option<&'a str>,
+    primary: bool,
+}
+
+/// ソースコードの各行の開始オフセットと内容 (改行文字を除く)
+struct SourceLines<'a> {
+    lines: Vec<(usize, &'a str)>,
+}
+
+impl<'a> SourceLines<'a> {
+    fn new(source: &'a str) -> Self {
+        let mut offset = 0;
+        let lines = source
+            .split('\n')
+            .map(|line| {
+                let start = offset;
+                offset += line.len() + 1;
+                (start, line.strip_suffix('\r').unwrap_or(line))
+            })
+            .collect();
+        Self { lines }
+    }
+
+    /// 行番号 (1ベース) の行を取得する
+    fn get(&self, line: u32) -> Option<(usize, &'a str)> {
+        self.lines.get(line.checked_sub(1)? as usize).copied()
+    }
+}
+
+/// 診断の一覧をソースコードの抜粋付きのレポートに整形する
+///
+/// 診断ごとに「重大度[コード]: メッセージ」の見出し、`ファイル名:行:列` の位置、
+/// 対象範囲の行と下線 (主たる範囲は `^`、補足のラベルは `-`)、注記と修正案を出力する。
+///
+/// ```
+/// use snowfall_core::common::diagnostic::{RenderOptions, render_diagnostics};
+/// use snowfall_core::compiler::{Lexer, Parser};
+///
+/// let source = "Int a = 1\nInt b = 2;";
+/// let errors = Parser::new(Lexer::new(source)).parse_program().unwrap_err();
+/// let report = render_diagnostics(source, &errors, &RenderOptions::default());
+/// assert!(report.starts_with("error[SF0010]: "));
+/// assert!(report.contains(" --> <input>:2:1\n"));
+/// assert!(report.contains("1 | Int a = 1\n  |         - expected ';' after this\n"));
+/// assert!(report.contains("2 | Int b = 2;\n  | ^^^\n"));
+/// assert!(report.contains("  = help: insert ';' here\n"));
+/// ```
+pub fn render_diagnostics(
+    source: &str,
+    errors: &[SnowFallError],
+    options: &RenderOptions,
+) -> String {
+    let painter = Painter {
+        enabled: options.color,
+    };
+    let lines = SourceLines::new(source);
+    errors
+        .iter()
+        .map(|error| render_diagnostic(source, &lines, error, options, &painter))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_diagnostic(
+    source: &str,
+    lines: &SourceLines,
+    error: &SnowFallError,
+    options: &RenderOptions,
+    painter: &Painter,
+) -> String {
+    let severity_style = error.severity.style();
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{}{}",
+        painter.paint(
+            &format!("{}[{}]", error.severity.label(), error.code),
+            severity_style
+        ),
+        painter.paint(&format!(": {}", error.message), BOLD)
+    );
+
+    // 下線を引く範囲を行ごとにまとめる
+    let mut marked: BTreeMap<u32, Vec<Mark>> = BTreeMap::new();
+    if let Some(span) = error.span {
+        add_marks(&mut marked, source, lines, span, None, true);
+    }
+    for label in &error.labels {
+        add_marks(
+            &mut marked,
+            source,
+            lines,
+            label.range.span,
+            Some(&label.message),
+            false,
+        );
+    }
+
+    let last_line = marked.keys().next_back().copied().unwrap_or(error.line);
+    let width = last_line.max(1).to_string().len();
+    let pad = " ".repeat(width);
+    let gutter = painter.paint("|", BLUE);
+
+    if error.line > 0 {
+        let _ = writeln!(
+            out,
+            "{}{} {}:{}:{}",
+            pad,
+            painter.paint("-->", BLUE),
+            options.file_name,
+            error.line,
+            error.column
+        );
+    }
+
+    if !marked.is_empty() {
+        let _ = writeln!(out, "{} {}", pad, gutter);
+        let mut previous: Option<u32> = None;
+        for (line, marks) in &mut marked {
+            let Some((_, text)) = lines.get(*line) else {
+                continue;
+            };
+            if previous.is_some_and(|previous| *line > previous + 1) {
+                let _ = writeln!(out, "{}", painter.paint("...", BLUE));
+            }
+            previous = Some(*line);
+
+            let _ = writeln!(
+                out,
+                "{} {} {}",
+                painter.paint(&format!("{:>width$}", line), BLUE),
+                gutter,
+                text
+            );
+            marks.sort_by_key(|mark| (mark.start, !mark.primary));
+            for mark in marks.iter() {
+                // 下線の位置と長さは端末での表示幅で数える (タブはそのまま残して揃える)
+                let indent: String = text
+                    .get(..mark.start)
+                    .unwrap_or("")
+                    .chars()
+                    .map(|c| {
+                        if c == '\t' {
+                            "\t".to_string()
+                        } else {
+                            " ".repeat(char_width(c))
+                        }
+                    })
+                    .collect();
+                let length = text
+                    .get(mark.start..mark.end)
+                    .map_or(mark.end - mark.start, |marked| {
+                        marked.chars().map(char_width).sum()
+                    })
+                    .max(1);
+                let (underline, style) = if mark.primary {
+                    ("^", severity_style)
+                } else {
+                    ("-", BLUE)
+                };
+                let mut row = painter.paint(&underline.repeat(length), style);
+                if let Some(message) = mark.message {
+                    row.push(' ');
+                    row.push_str(&painter.paint(message, style));
+                }
+                let _ = writeln!(out, "{} {} {}{}", pad, gutter, indent, row);
+            }
+        }
+    }
+
+    if !error.notes.is_empty() || !error.suggestions.is_empty() {
+        if !marked.is_empty() {
+            let _ = writeln!(out, "{} {}", pad, gutter);
+        }
+        for note in &error.notes {
+            let _ = writeln!(out, "{} = {}: {}", pad, painter.paint("note", BOLD), note);
+        }
+        for suggestion in &error.suggestions {
+            let _ = writeln!(
+                out,
+                "{} = {}: {}",
+                pad,
+                painter.paint("help", BOLD),
+                suggestion.message
+            );
+        }
+    }
+    out
+}
+
+/// 文字の表示幅 (East Asian Width が Wide または Fullwidth の文字は2、それ以外は1)
+fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x1F900..=0x1F9FF
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+/// 範囲が掛かる各行に下線を登録する (メッセージは最後の行にのみ付ける)
+fn add_marks<'a>(
+    marked: &mut BTreeMap<u32, Vec<Mark<'a>>>,
+    source: &str,
+    lines: &SourceLines,
+    span: Span,
+    message: Option<&'a str>,
+    primary: bool,
+) {
+    let (start_line, _) = offset_to_line_column(source, span.start);
+    // 範囲の最後の文字がある行 (改行の直後で終わる範囲は次の行を含めない)
+    let (end_line, _) = offset_to_line_column(source, span.start.max(span.end.saturating_sub(1)));
+    for line in start_line..=end_line {
+        let Some((line_start, text)) = lines.get(line) else {
+            continue;
+        };
+        let line_end = line_start + text.len();
+        let start = span.start.clamp(line_start, line_end) - line_start;
+        let end = span.end.clamp(line_start, line_end) - line_start;
+        marked.entry(line).or_default().push(Mark {
+            start,
+            end: end.max(start),
+            message: (line == end_line).then_some(message).flatten(),
+            primary,
+        });
+    }
+}
@@ -1,11 +1,112 @@
 use ahash::AHashMap;
 use serde::{Deserialize, Serialize};
 
-use crate::common::ErrorCode;
+use crate::common::messages::{self, Detail, Locale};
+use crate::common::span::{LineCursor, offset_to_line_column};
+use crate::common::{ErrorCode, Span};
 
 /// エラーに関連する追加情報（例: 期待された型、見つかった型など）
 pub type SnowFallErrorContext = AHashMap<String, String>;
 
+/// 診断の重大度
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning,
+    Info,
+    Hint,
+}
+
+/// ソースコード上の範囲と、その開始・終了位置の行番号と文字数 (いずれも1ベース)
+///
+/// 終了位置は範囲の直後を指す
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceRange {
+    pub span: Span,
+    pub line: u32,
+    pub column: u32,
+    pub end_line: u32,
+    pub end_column: u32,
+}
+
+impl SourceRange {
+    pub fn new(span: Span, source: &str) -> Self {
+        let (line, column) = offset_to_line_column(source, span.start);
+        let (end_line, end_column) = offset_to_line_column(source, span.end);
+        Self {
+            span,
+            line,
+            column,
+            end_line,
+            end_column,
+        }
+    }
+
+    /// `cursor` を使って行番号と文字数を求める (昇順に求める場合に使用する)
+    pub fn with_cursor(span: Span, source: &str, cursor: &mut LineCursor) -> Self {
+        let (line, column) = cursor.line_column(source, span.start);
+        let (end_line, end_column) = cursor.line_column(source, span.end);
+        Self {
+            span,
+            line,
+            column,
+            end_line,
+            end_column,
+        }
+    }
+}
+
+/// 診断の補足として示すソースコード上の範囲 (例: 定数が宣言された位置)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiagnosticLabel {
+    pub range: SourceRange,
+    pub message: String,
+    /// `message` の雛形 (言語を切り替える際に使う)
+    #[serde(skip)]
+    pub detail: Option<Detail>,
+}
+
+/// ソースコードの書き換え (挿入は空の範囲、削除は空の文字列で表す)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextEdit {
+    pub range: SourceRange,
+    pub replacement: String,
+}
+
+impl TextEdit {
+    /// `offset` の位置に `text` を挿入する
+    pub fn insert(offset: usize, text: &str, source: &str) -> Self {
+        Self::replace(
+            Span {
+                start: offset,
+                end: offset,
+            },
+            text,
+            source,
+        )
+    }
+
+    /// `span` の範囲を `text` に置き換える
+    pub fn replace(span: Span, text: &str, source: &str) -> Self {
+        Self {
+            range: SourceRange::new(span, source),
+            replacement: text.to_string(),
+        }
+    }
+}
+
+/// 機械的に適用できる修正案 (エディタのクイックフィックスに使う)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Suggestion {
+    /// 修正内容の説明 (例: "insert ';' here")
+    pub message: String,
+    pub edits: Vec<TextEdit>,
+    /// `message` の雛形 (言語を切り替える際に使う)
+    #[serde(skip)]
+    pub detail: Option<Detail>,
+}
+
 /// SnowFall言語のすべてのエラーを表現する構造体
 /// この構造体はシリアライズされ、Wasm境界を越えてTypeScript側に渡される
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -25,6 +126,38 @@ pub struct SnowFallError {
     /// エラーが発生したソースコードの文字数 (1ベース)
     pub column: u32,
 
+    /// 重大度
+    #[serde(default)]
+    pub severity: Severity,
+
+    /// 診断の対象となるソースコード上の範囲 (バイト単位)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+
+    /// 範囲の終了位置の行番号 (1ベース、範囲がない場合は `line` と同じ)
+    #[serde(default)]
+    pub end_line: u32,
+
+    /// 範囲の終了位置の文字数 (1ベース、範囲の直後を指す)
+    #[serde(default)]
+    pub end_column: u32,
+
+    /// 関連する位置の補足
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<DiagnosticLabel>,
+
+    /// 補足説明
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+
+    /// `notes` の雛形 (言語を切り替える際に使う)
+    #[serde(skip)]
+    note_details: Vec<Detail>,
+
+    /// 修正案
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<Suggestion>,
+
     /// スタックトレース (関数呼び出しの履歴)
     pub trace: Vec<String>,
 
@@ -42,27 +175,144 @@ impl SnowFallError {
             code,
             line,
             column,
+            severity: Severity::Error,
+            span: None,
+            end_line: line,
+            end_column: column,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            note_details: Vec::new(),
+            suggestions: Vec::new(),
             trace: Vec::new(),
             context: None,
         }
     }
 
     /// `CompilationError` 型の `SnowFallError` を生成するためのファクトリ関数
-    pub fn new_compiler_error(
-        message: Option<String>,
-        code: ErrorCode,
-        line: u32,
-        column: u32,
-    ) -> Self {
-        Self {
-            r#type: "CompilationError".to_string(),
-            message: message.unwrap_or_else(|| code.get_default_message().to_string()),
-            code: code.to_str().to_string(),
+    /// メッセージはカタログの英語の雛形から生成する (引数は `with_arg` で設定する)
+    pub fn new_compiler_error(code: ErrorCode, line: u32, column: u32) -> Self {
+        Self::new(
+            "CompilationError".to_string(),
+            messages::render(&code, Locale::En, None),
+            code.to_str().to_string(),
             line,
             column,
-            trace: Vec::new(),
-            context: None,
+        )
+    }
+
+    /// `CompilationWarning` 型の `SnowFallError` を生成するためのファクトリ関数
+    /// 警告はコンパイルを中断せず、結果とともに報告される
+    pub fn new_compiler_warning(code: ErrorCode, line: u32, column: u32) -> Self {
+        Self {
+            r#type: "CompilationWarning".to_string(),
+            severity: Severity::Warning,
+            ..Self::new_compiler_error(code, line, column)
+        }
+    }
+
+    /// ソースコード上の範囲を対象とする `CompilationError` を生成する
+    pub fn new_compiler_error_at(code: ErrorCode, span: Span, source: &str) -> Self {
+        Self::new_compiler_error(code, 0, 0).with_span(span, source)
+    }
+
+    /// ソースコード上の範囲を対象とする `CompilationWarning` を生成する
+    pub fn new_compiler_warning_at(code: ErrorCode, span: Span, source: &str) -> Self {
+        Self::new_compiler_warning(code, 0, 0).with_span(span, source)
+    }
+
+    /// 診断の対象範囲を設定する (`line` / `column` は範囲の開始位置になる)
+    pub fn with_span(self, span: Span, source: &str) -> Self {
+        self.with_range(SourceRange::new(span, source))
+    }
+
+    /// 行番号と文字数を求め済みの範囲を診断の対象範囲に設定する
+    pub fn with_range(mut self, range: SourceRange) -> Self {
+        self.span = Some(range.span);
+        self.line = range.line;
+        self.column = range.column;
+        self.end_line = range.end_line;
+        self.end_column = range.end_column;
+        self
+    }
+
+    /// 重大度を設定する
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// 関連する位置の補足を追加する
+    pub fn with_label(mut self, span: Span, detail: Detail, source: &str) -> Self {
+        self.labels.push(DiagnosticLabel {
+            range: SourceRange::new(span, source),
+            message: self.render_detail(detail),
+            detail: Some(detail),
+        });
+        self
+    }
+
+    /// 補足説明を追加する
+    pub fn with_note(mut self, detail: Detail) -> Self {
+        self.notes.push(self.render_detail(detail));
+        self.note_details.push(detail);
+        self
+    }
+
+    /// 修正案を追加する
+    pub fn with_suggestion(mut self, detail: Detail, edits: Vec<TextEdit>) -> Self {
+        self.suggestions.push(Suggestion {
+            message: self.render_detail(detail),
+            edits,
+            detail: Some(detail),
+        });
+        self
+    }
+
+    /// ラベル・補足説明・修正案の英語の文面 (`localize` で生成し直す)
+    fn render_detail(&self, detail: Detail) -> String {
+        messages::render_detail(detail, Locale::En, self.context.as_ref())
+    }
+
+    /// メッセージの雛形の引数を設定し、メッセージを生成し直す
+    ///
+    /// 引数は追加情報 (`context`) として保持され、`localize` でも使われる。
+    pub fn with_arg(mut self, key: &str, value: impl Into<String>) -> Self {
+        self.context
+            .get_or_insert_with(SnowFallErrorContext::default)
+            .insert(key.to_string(), value.into());
+        self.localize(Locale::En);
+        self
+    }
+
+    /// メッセージとラベル・補足説明・修正案を指定した言語のカタログから生成し直す
+    ///
+    /// コードがカタログにない診断 (実行時エラーなど) のメッセージと、雛形を持たない文面はそのままにする。
+    pub fn localize(&mut self, locale: Locale) {
+        let context = self.context.as_ref();
+        if let Some(code) = ErrorCode::from_code(&self.code) {
+            self.message = messages::render(&code, locale, context);
         }
+        for label in &mut self.labels {
+            if let Some(detail) = label.detail {
+                label.message = messages::render_detail(detail, locale, context);
+            }
+        }
+        for (note, &detail) in self.notes.iter_mut().zip(&self.note_details) {
+            *note = messages::render_detail(detail, locale, context);
+        }
+        for suggestion in &mut self.suggestions {
+            if let Some(detail) = suggestion.detail {
+                suggestion.message = messages::render_detail(detail, locale, context);
+            }
+        }
+    }
+
+    /// 追加情報を設定する
+    pub fn with_context(mut self, key: &str, value: &str) -> Self {
+        self.context
+            .get_or_insert_with(SnowFallErrorContext::default)
+            .insert(key.to_string(), value.to_string());
+        self
     }
 
     /// `RuntimeError` 型の `SnowFallError` を生成するためのファクトリ関数
@@ -77,13 +327,8 @@ impl SnowFallError {
         trace: Vec<String>,
     ) -> Self {
         Self {
-            r#type: "RuntimeError".to_string(),
-            message,
-            code,
-            line,
-            column,
             trace,
-            context: None,
+            ..Self::new("RuntimeError".to_string(), message, code, line, column)
         }
     }
 }
@@ -1,60 +1,253 @@
+//! エラーコードの一覧
+//!
+//! `error_codes!` の定義から `ErrorCode` と、コード番号・分類・説明・例をまとめた
+//! レジストリ (`ErrorCode::info`) を生成する。
+//! コード番号は公開後に変更しない。分類ごとに番号の範囲が割り当てられている。
+use std::ops::RangeInclusive;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
-pub enum ErrorCode {
-    // Lexer errors
-    UnexpectedCharacter,
-    InvalidNumberFormat,
-    UnterminatedString,
-
-    // Parser errors
-    UnexpectedToken,
-    ExpectedExpression,
-    ExpectedTypeName,
-    ExpectedReturnType,
-    ExpectedParameterType,
-    UnexpectedTokenForExpression,
-    ExpectedIdentifierInForEach,
-    ExpectedInOrOfInForEach,
-    ExpectedMemberForClass,
+/// エラーコードの分類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ErrorCategory {
+    /// 字句解析 (SF0001 - SF0009)
+    Lexer,
+    /// 構文解析 (SF0010 - SF0099)
+    Parser,
+    /// 意味解析 (SF0100 - SF0199)
+    Semantic,
+    /// 警告 (SF0200 - SF0299)
+    Warning,
+    /// 実行時 (SF0300 - SF0399)
+    Runtime,
 }
 
-impl ErrorCode {
-    pub fn to_str(&self) -> &'static str {
+impl ErrorCategory {
+    /// 分類に割り当てられたコード番号の範囲
+    pub fn range(self) -> RangeInclusive<u16> {
         match self {
-            // Lexer
-            ErrorCode::UnexpectedCharacter => "SF0001",
-            ErrorCode::InvalidNumberFormat => "SF0002",
-            ErrorCode::UnterminatedString => "SF0003",
-            // Parser
-            ErrorCode::UnexpectedToken => "SF0010",
-            ErrorCode::ExpectedMemberForClass => "SF0011",
-            ErrorCode::ExpectedTypeName => "SF0012",
-            ErrorCode::ExpectedReturnType => "SF0013",
-            ErrorCode::ExpectedParameterType => "SF0014",
-            ErrorCode::UnexpectedTokenForExpression => "SF0015",
-            ErrorCode::ExpectedIdentifierInForEach => "SF0016",
-            ErrorCode::ExpectedInOrOfInForEach => "SF0017",
-            ErrorCode::ExpectedExpression => "SF0018",
+            ErrorCategory::Lexer => 1..=9,
+            ErrorCategory::Parser => 10..=99,
+            ErrorCategory::Semantic => 100..=199,
+            ErrorCategory::Warning => 200..=299,
+            ErrorCategory::Runtime => 300..=399,
         }
     }
+}
 
-    pub fn get_default_message(&self) -> &'static str {
-        match self {
-            // Lexer
-            ErrorCode::UnexpectedCharacter => "Unexpected character",
-            ErrorCode::InvalidNumberFormat => "Invalid number format",
-            ErrorCode::UnterminatedString => "Unterminated string",
-            // Parser
-            ErrorCode::UnexpectedToken => "Unexpected token",
-            ErrorCode::ExpectedMemberForClass => "Expected 'function' or 'sub' for class member",
-            ErrorCode::ExpectedTypeName => "Expected type name",
-            ErrorCode::ExpectedReturnType => "Expected return type",
-            ErrorCode::ExpectedParameterType => "Expected parameter type",
-            ErrorCode::UnexpectedTokenForExpression => "Unexpected token for expression",
-            ErrorCode::ExpectedIdentifierInForEach => "Expected identifier in for-each loop",
-            ErrorCode::ExpectedInOrOfInForEach => "Expected 'in' or 'of' in for-each loop",
-            ErrorCode::ExpectedExpression => "Expected expression",
+/// エラーコードの詳しい説明
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorCodeInfo {
+    /// コード (例: "SF0010")
+    pub code: &'static str,
+    /// `ErrorCode` の名前 (例: "UnexpectedToken")
+    pub name: &'static str,
+    pub number: u16,
+    pub category: ErrorCategory,
+    /// 原因と対処方法の説明
+    pub explanation: &'static str,
+    /// この診断が報告されるソースコードの例
+    pub example: &'static str,
+}
+
+macro_rules! error_codes {
+    ($(
+        $name:ident = $code:literal in $category:ident {
+            explanation: $explanation:literal,
+            example: $example:literal $(,)?
         }
+    )*) => {
+        #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum ErrorCode {
+            $($name,)*
+        }
+
+        impl ErrorCode {
+            /// すべてのエラーコード (コード番号順)
+            pub const ALL: &'static [ErrorCode] = &[$(ErrorCode::$name,)*];
+
+            pub fn to_str(&self) -> &'static str {
+                match self {
+                    $(ErrorCode::$name => $code,)*
+                }
+            }
+
+            /// コード番号・分類・説明・例
+            pub fn info(&self) -> ErrorCodeInfo {
+                match self {
+                    $(ErrorCode::$name => ErrorCodeInfo {
+                        code: $code,
+                        name: stringify!($name),
+                        number: self.number(),
+                        category: ErrorCategory::$category,
+                        explanation: $explanation,
+                        example: $example,
+                    },)*
+                }
+            }
+        }
+    };
+}
+
+error_codes! {
+    // Lexer
+    UnexpectedCharacter = "SF0001" in Lexer {
+        explanation: "ソースコードに字句として解釈できない文字が含まれています。\
+            演算子や区切り記号として定義されていない記号 (`#` や `@` など) は、文字列リテラルやコメントの中でのみ使用できます。",
+        example: "Int a = 1 #;",
+    }
+    InvalidNumberFormat = "SF0002" in Lexer {
+        explanation: "数値リテラルの形式が正しくありません。\
+            桁区切りの `_` は数字と数字の間にのみ置けます。\
+            16進数 (`0x`) や2進数 (`0b`) のリテラルにはその基数で使える数字のみを書き、値は64ビット整数の範囲に収める必要があります。",
+        example: "Int a = 0b102;",
+    }
+    UnterminatedString = "SF0003" in Lexer {
+        explanation: "文字列リテラルが閉じられないままソースコードの終わりに達しました。\
+            文字列は開始と同じ引用符 (`\"` または `'`) で閉じる必要があります。\
+            引用符そのものを文字列に含める場合は `\\` でエスケープします。",
+        example: "String s = \"hello;",
+    }
+
+    // Parser
+    UnexpectedToken = "SF0010" in Parser {
+        explanation: "文法上必要なトークンの代わりに別のトークンが現れました。\
+            多くの場合、直前の文末の `;` や閉じ括弧の書き忘れが原因です。\
+            報告された位置の直前を確認してください。",
+        example: "Int a = 1\nInt b = 2;",
+    }
+    ExpectedMemberForClass = "SF0011" in Parser {
+        explanation: "クラスの本体には `function` と `sub` によるメソッド、およびフィールド宣言のみを書けます。\
+            文や式をクラスの本体に直接書くことはできません。",
+        example: "class A {\n    return 1;\n}",
+    }
+    ExpectedTypeName = "SF0012" in Parser {
+        explanation: "変数やフィールドの宣言には型名が必要です。\
+            宣言は `Int a = 1;` のように型名・変数名の順に書きます。",
+        example: "const = 1;",
+    }
+    ExpectedReturnType = "SF0013" in Parser {
+        explanation: "`function` で宣言する関数には戻り値の型が必要です。\
+            値を返さない処理は `sub` で宣言します。",
+        example: "function f() { return 1; }",
+    }
+    ExpectedParameterType = "SF0014" in Parser {
+        explanation: "関数の引数には型名が必要です。\
+            引数は `Int x` のように型名・引数名の順に書きます。",
+        example: "function Int f(1) { return 1; }",
+    }
+    UnexpectedTokenForExpression = "SF0015" in Parser {
+        explanation: "式が必要な位置に、式を始められないトークンが現れました。\
+            演算子の重複や、余分なキーワードが主な原因です。\
+            式そのものが欠けている場合は SF0018 として報告します。",
+        example: "Int a = 1 + * 2;",
+    }
+    ExpectedIdentifierInForEach = "SF0016" in Parser {
+        explanation: "for-each文の束縛には変数名が必要です。\
+            `for (x of xs)` のように書きます。",
+        example: "for (1 in xs) {}",
+    }
+    ExpectedInOrOfInForEach = "SF0017" in Parser {
+        explanation: "for-each文の変数名の後には `in` (キーの列挙) または `of` (値の列挙) が必要です。\
+            束縛する変数に型名は書けません。",
+        example: "for (Int x of xs) {}",
+    }
+    ExpectedExpression = "SF0018" in Parser {
+        explanation: "式が必要な位置に式がありません。\
+            二項演算子の右辺や代入する値の書き忘れが主な原因です。",
+        example: "Int a = ;",
+    }
+    MissingConstInitializer = "SF0019" in Parser {
+        explanation: "`const` で宣言する定数は、宣言と同時に初期化する必要があります。\
+            後から値を設定する変数は `const` を付けずに宣言します。",
+        example: "const Int MAX;",
+    }
+    InvalidAssignmentTarget = "SF0020" in Parser {
+        explanation: "代入の左辺に代入できない式が書かれています。\
+            代入できるのは変数とメンバー (`a.b` や `a[0]`) のみです。",
+        example: "1 = 2;",
+    }
+    TooManyErrors = "SF0021" in Parser {
+        explanation: "報告するエラーの数が上限 (`CompileOptions` の `max_errors`、既定値は100件) に達したため、解析を打ち切りました。\
+            先頭のエラーを修正すると、後続のエラーの多くが解消されることがあります。\
+            例は上限を3件にした場合に、4件目のエラーの位置で報告されます。",
+        example: "Int a = 1 # 2;\nInt b = 3 # 4;\nInt c = 5 # 6;\nInt d = 7 # 8;",
+    }
+
+    // Semantic
+    AssignmentToConstant = "SF0100" in Semantic {
+        explanation: "`const` で宣言した定数や `readonly` で宣言したフィールドに再代入しています。\
+            値を変更する必要がある場合は、宣言から `const` または `readonly` を取り除きます。",
+        example: "const Int MAX = 10;\nMAX = 20;",
+    }
+
+    // Warning
+    UnreachableCode = "SF0200" in Warning {
+        explanation: "`return`・`break`・`continue` の後など、実行されることのない文があります。\
+            この文は正規化の際に取り除かれます。",
+        example: "function Int f() {\n    return 1;\n    f();\n}",
+    }
+    UnusedVariable = "SF0201" in Warning {
+        explanation: "宣言した変数が一度も読み取られていません。\
+            代入のみで値を使わない変数もこの警告の対象です。\
+            意図的に使わない変数は名前を `_` で始めると報告されません。",
+        example: "sub f() {\n    Int unused = 1;\n}",
+    }
+    UnusedParameter = "SF0202" in Warning {
+        explanation: "関数の引数が本体で一度も読み取られていません。\
+            最後に使われている引数より後ろの引数のみを報告します。\
+            意図的に使わない引数は名前を `_` で始めると報告されません。",
+        example: "function Int f(Int x, Int y) {\n    return x;\n}",
+    }
+    ShadowedName = "SF0203" in Warning {
+        explanation: "外側のスコープで宣言された名前と同じ名前を内側のスコープで宣言しています。\
+            内側のスコープからは外側の宣言を参照できなくなるため、取り違えの原因になります。",
+        example: "Int x = 1;\nprint(x);\nsub f() {\n    Int x = 2;\n    print(x);\n}",
+    }
+    LooseEquality = "SF0204" in Warning {
+        explanation: "`==` と `!=` は型変換を行ってから比較します。\
+            意図しない型変換を避けるため、型も比較する `===` と `!==` を使用します。",
+        example: "if (a == b) {\n    f();\n}",
+    }
+    AssignmentInCondition = "SF0205" in Warning {
+        explanation: "`if` や `while` などの条件式が代入式になっています。\
+            比較演算子 `===` の書き間違いであることがほとんどです。",
+        example: "if (a = b) {\n    f();\n}",
+    }
+    EmptyBlock = "SF0206" in Warning {
+        explanation: "制御文の本体が空のブロックです。\
+            処理の書き忘れでない場合は、ブロックの中にその理由をコメントで残します。",
+        example: "if (a) {}",
+    }
+    ConstantCondition = "SF0207" in Warning {
+        explanation: "条件式が変数を含まず、常に同じ値になります。\
+            分岐の一方は決して実行されません。\
+            意図的な無限ループの `while (true)` は報告されません。",
+        example: "if (1 < 2) {\n    f();\n}",
+    }
+}
+
+impl ErrorCode {
+    /// コード番号 (例: `SF0010` は 10)
+    pub fn number(&self) -> u16 {
+        self.to_str()[2..].parse().unwrap_or_default()
+    }
+
+    /// `SF0001` のようなコード文字列から `ErrorCode` を求める
+    ///
+    /// ```
+    /// use snowfall_core::common::ErrorCode;
+    /// use snowfall_core::common::error_code::ErrorCategory;
+    ///
+    /// let code = ErrorCode::from_code("SF0100").unwrap();
+    /// assert_eq!(code, ErrorCode::AssignmentToConstant);
+    /// assert_eq!(code.info().category, ErrorCategory::Semantic);
+    /// assert_eq!(ErrorCode::from_code("SF9999"), None);
+    /// ```
+    pub fn from_code(code: &str) -> Option<ErrorCode> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|error_code| error_code.to_str() == code)
     }
 }
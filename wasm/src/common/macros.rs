@@ -3,6 +3,9 @@
 /// # 使用例
 ///
 /// ```rust
+/// use snowfall_core::common::TokenKind;
+/// use snowfall_core::create_token;
+///
 /// let token = create_token!(TokenKind::Identifier("example".to_string()), 0, 7);
 /// ```
 #[macro_export]
@@ -0,0 +1,272 @@
+//! エラーメッセージのカタログ
+//!
+//! `ErrorCode` ごとのメッセージと、診断に付けるラベル・補足説明・修正案 (`Detail`) の雛形を言語別に保持する。
+//! 雛形の `{name}` のような引数は `SnowFallErrorContext` の同名の値で置き換える。
+//! 指定した言語のカタログにない項目は英語のメッセージを使う。
+use serde::{Deserialize, Serialize};
+
+use crate::common::ErrorCode;
+use crate::common::error::SnowFallErrorContext;
+
+/// メッセージの言語
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Ja,
+}
+
+/// 指定した言語のメッセージの雛形を取得する
+///
+/// 同じコードでも追加情報の値によって文面が変わるもの (例: 代入先が定数か読み取り専用フィールドか) は
+/// `context` から選択する。
+pub fn template(
+    code: &ErrorCode,
+    locale: Locale,
+    context: Option<&SnowFallErrorContext>,
+) -> &'static str {
+    let variant = variant(code, context);
+    match locale {
+        Locale::En => None,
+        Locale::Ja => ja(code, variant),
+    }
+    .unwrap_or_else(|| en(code, variant))
+}
+
+/// 雛形の引数を追加情報の値で置き換えたメッセージを生成する
+///
+/// 追加情報にない引数はそのまま残す。
+///
+/// ```
+/// use snowfall_core::common::ErrorCode;
+/// use snowfall_core::common::error::SnowFallErrorContext;
+/// use snowfall_core::common::messages::{Locale, render};
+///
+/// let mut context = SnowFallErrorContext::default();
+/// context.insert("name".to_string(), "MAX".to_string());
+/// context.insert("kind".to_string(), "const".to_string());
+/// let code = ErrorCode::AssignmentToConstant;
+/// assert_eq!(render(&code, Locale::En, Some(&context)), "Cannot assign to constant 'MAX'");
+/// assert_eq!(render(&code, Locale::Ja, Some(&context)), "定数 'MAX' には代入できません");
+/// ```
+pub fn render(code: &ErrorCode, locale: Locale, context: Option<&SnowFallErrorContext>) -> String {
+    fill(template(code, locale, context), context)
+}
+
+/// ラベル・補足説明・修正案の文面
+///
+/// 雛形の引数には診断の追加情報 (`context`) の値を使う。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Detail {
+    /// `MissingConstInitializer` の補足説明
+    ConstInitializerRequired,
+    /// `InvalidAssignmentTarget` の補足説明
+    AssignableTargets,
+    /// `;` が必要な位置を示すラベル
+    ExpectedSemicolonAfter,
+    /// `;` を挿入する修正案
+    InsertSemicolon,
+    /// 定数・読み取り専用フィールドの宣言を示すラベル (`{name}`)
+    DeclaredHere,
+    /// `const` / `readonly` を取り除く修正案 (`{kind}`, `{name}`)
+    RemoveKeyword,
+    /// 到達不能なコードの原因となる文を示すラベル
+    UnreachableAfter,
+    /// 到達不能なコードを削除する修正案
+    RemoveUnreachableCode,
+    /// 条件式の代入を比較に置き換える修正案
+    UseStrictComparison,
+    /// 緩い等価演算子を置き換える修正案 (`{operator}`, `{strict}`)
+    ReplaceOperator,
+    /// 外側のスコープの宣言を示すラベル (`{name}`)
+    FirstDeclaredHere,
+}
+
+/// ラベル・補足説明・修正案の文面を生成する
+///
+/// ```
+/// use snowfall_core::common::error::SnowFallErrorContext;
+/// use snowfall_core::common::messages::{Detail, Locale, render_detail};
+///
+/// let mut context = SnowFallErrorContext::default();
+/// context.insert("name".to_string(), "x".to_string());
+/// assert_eq!(render_detail(Detail::DeclaredHere, Locale::En, Some(&context)), "'x' is declared here");
+/// assert_eq!(render_detail(Detail::DeclaredHere, Locale::Ja, Some(&context)), "'x' はここで宣言されています");
+/// ```
+pub fn render_detail(
+    detail: Detail,
+    locale: Locale,
+    context: Option<&SnowFallErrorContext>,
+) -> String {
+    let template = match locale {
+        Locale::En => en_detail(detail),
+        Locale::Ja => ja_detail(detail),
+    };
+    fill(template, context)
+}
+
+/// 雛形の `{name}` を追加情報の値で置き換える
+fn fill(template: &str, context: Option<&SnowFallErrorContext>) -> String {
+    let mut message = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}').map(|close| open + close) else {
+            break;
+        };
+        message.push_str(&rest[..open]);
+        let key = &rest[open + 1..close];
+        match context.and_then(|context| context.get(key)) {
+            Some(value) => message.push_str(value),
+            None => message.push_str(&rest[open..=close]),
+        }
+        rest = &rest[close + 1..];
+    }
+    message.push_str(rest);
+    message
+}
+
+/// 文面を切り替えるための追加情報の値
+fn variant<'a>(code: &ErrorCode, context: Option<&'a SnowFallErrorContext>) -> Option<&'a str> {
+    let key = match code {
+        ErrorCode::InvalidNumberFormat => "reason",
+        ErrorCode::AssignmentToConstant => "kind",
+        _ => return None,
+    };
+    context?.get(key).map(String::as_str)
+}
+
+/// 英語のカタログ (すべてのコードを含む)
+fn en(code: &ErrorCode, variant: Option<&str>) -> &'static str {
+    match (code, variant) {
+        // Lexer
+        (ErrorCode::UnexpectedCharacter, _) => "Unexpected character: {character}",
+        (ErrorCode::InvalidNumberFormat, Some("misplaced_underscore")) => {
+            "Invalid number format: misplaced underscore"
+        }
+        (ErrorCode::InvalidNumberFormat, Some("invalid_float")) => "Invalid float format",
+        (ErrorCode::InvalidNumberFormat, Some("invalid_digit")) => {
+            "Invalid digit in number literal: {digit}"
+        }
+        (ErrorCode::InvalidNumberFormat, Some("unparsable")) => "Failed to parse number: {literal}",
+        (ErrorCode::InvalidNumberFormat, _) => "Invalid number format",
+        (ErrorCode::UnterminatedString, _) => "Unterminated string",
+        // Parser
+        (ErrorCode::UnexpectedToken, _) => {
+            "Expected next token to be {expected}, got {found} instead"
+        }
+        (ErrorCode::ExpectedMemberForClass, _) => {
+            "Expected 'function', 'sub' or field declaration for class member, got {found}"
+        }
+        (ErrorCode::ExpectedTypeName, _) => "Expected type name",
+        (ErrorCode::ExpectedReturnType, _) => "Expected return type",
+        (ErrorCode::ExpectedParameterType, _) => "Expected parameter type",
+        (ErrorCode::UnexpectedTokenForExpression, _) => "Unexpected token for expression: {token}",
+        (ErrorCode::ExpectedIdentifierInForEach, _) => "Expected identifier in for-each loop",
+        (ErrorCode::ExpectedInOrOfInForEach, _) => "Expected 'in' or 'of' in for-each loop",
+        (ErrorCode::ExpectedExpression, _) => "Expected expression",
+        (ErrorCode::MissingConstInitializer, _) => {
+            "Missing initializer in const declaration: {name}"
+        }
+        (ErrorCode::InvalidAssignmentTarget, _) => "Invalid assignment target",
+        (ErrorCode::TooManyErrors, _) => "Too many errors; stopped after {max} errors",
+        // Semantic
+        (ErrorCode::AssignmentToConstant, Some("readonly")) => {
+            "Cannot assign to readonly field '{name}'"
+        }
+        (ErrorCode::AssignmentToConstant, _) => "Cannot assign to constant '{name}'",
+        // Warning
+        (ErrorCode::UnreachableCode, _) => "Unreachable code",
+        (ErrorCode::UnusedVariable, _) => "'{name}' is declared but never used",
+        (ErrorCode::UnusedParameter, _) => "Parameter '{name}' is never used",
+        (ErrorCode::ShadowedName, _) => "'{name}' shadows a declaration in an outer scope",
+        (ErrorCode::LooseEquality, _) => "Use '{strict}' instead of '{operator}'",
+        (ErrorCode::AssignmentInCondition, _) => "Assignment in a condition",
+        (ErrorCode::EmptyBlock, _) => "Empty block",
+        (ErrorCode::ConstantCondition, _) => "Condition is always the same value",
+    }
+}
+
+/// 日本語のカタログ
+fn ja(code: &ErrorCode, variant: Option<&str>) -> Option<&'static str> {
+    let template = match (code, variant) {
+        // Lexer
+        (ErrorCode::UnexpectedCharacter, _) => "予期しない文字です: {character}",
+        (ErrorCode::InvalidNumberFormat, Some("misplaced_underscore")) => {
+            "数値の形式が正しくありません: '_' の位置が不正です"
+        }
+        (ErrorCode::InvalidNumberFormat, Some("invalid_float")) => {
+            "浮動小数点数の形式が正しくありません"
+        }
+        (ErrorCode::InvalidNumberFormat, Some("invalid_digit")) => {
+            "数値リテラルに使用できない文字です: {digit}"
+        }
+        (ErrorCode::InvalidNumberFormat, Some("unparsable")) => "数値を解釈できません: {literal}",
+        (ErrorCode::InvalidNumberFormat, _) => "数値の形式が正しくありません",
+        (ErrorCode::UnterminatedString, _) => "文字列が閉じられていません",
+        // Parser
+        (ErrorCode::UnexpectedToken, _) => "{expected} が必要ですが {found} が見つかりました",
+        (ErrorCode::ExpectedMemberForClass, _) => {
+            "クラスのメンバーには 'function'、'sub' またはフィールド宣言が必要ですが {found} が見つかりました"
+        }
+        (ErrorCode::ExpectedTypeName, _) => "型名が必要です",
+        (ErrorCode::ExpectedReturnType, _) => "戻り値の型が必要です",
+        (ErrorCode::ExpectedParameterType, _) => "引数の型が必要です",
+        (ErrorCode::UnexpectedTokenForExpression, _) => "式として解釈できないトークンです: {token}",
+        (ErrorCode::ExpectedIdentifierInForEach, _) => "for-each文には識別子が必要です",
+        (ErrorCode::ExpectedInOrOfInForEach, _) => "for-each文には 'in' または 'of' が必要です",
+        (ErrorCode::ExpectedExpression, _) => "式が必要です",
+        (ErrorCode::MissingConstInitializer, _) => "定数の宣言に初期値がありません: {name}",
+        (ErrorCode::InvalidAssignmentTarget, _) => "代入できない式です",
+        (ErrorCode::TooManyErrors, _) => "エラーが多すぎるため、{max} 件で解析を中止しました",
+        // Semantic
+        (ErrorCode::AssignmentToConstant, Some("readonly")) => {
+            "読み取り専用フィールド '{name}' には代入できません"
+        }
+        (ErrorCode::AssignmentToConstant, _) => "定数 '{name}' には代入できません",
+        // Warning
+        (ErrorCode::UnreachableCode, _) => "到達できないコードです",
+        (ErrorCode::UnusedVariable, _) => "'{name}' は宣言されていますが使用されていません",
+        (ErrorCode::UnusedParameter, _) => "引数 '{name}' は使用されていません",
+        (ErrorCode::ShadowedName, _) => "'{name}' は外側のスコープの宣言を隠しています",
+        (ErrorCode::LooseEquality, _) => "'{operator}' ではなく '{strict}' を使用してください",
+        (ErrorCode::AssignmentInCondition, _) => "条件式の中で代入しています",
+        (ErrorCode::EmptyBlock, _) => "空のブロックです",
+        (ErrorCode::ConstantCondition, _) => "条件が常に同じ値です",
+    };
+    Some(template)
+}
+
+/// ラベル・補足説明・修正案の英語のカタログ
+fn en_detail(detail: Detail) -> &'static str {
+    match detail {
+        Detail::ConstInitializerRequired => "constants must be initialized where they are declared",
+        Detail::AssignableTargets => "only variables and members can be assigned to",
+        Detail::ExpectedSemicolonAfter => "expected ';' after this",
+        Detail::InsertSemicolon => "insert ';' here",
+        Detail::DeclaredHere => "'{name}' is declared here",
+        Detail::RemoveKeyword => "remove '{kind}' to make '{name}' mutable",
+        Detail::UnreachableAfter => "any code after this statement is unreachable",
+        Detail::RemoveUnreachableCode => "remove the unreachable code",
+        Detail::UseStrictComparison => "use '===' to compare the values",
+        Detail::ReplaceOperator => "replace '{operator}' with '{strict}'",
+        Detail::FirstDeclaredHere => "'{name}' is first declared here",
+    }
+}
+
+/// ラベル・補足説明・修正案の日本語のカタログ
+fn ja_detail(detail: Detail) -> &'static str {
+    match detail {
+        Detail::ConstInitializerRequired => "定数は宣言と同時に初期化する必要があります",
+        Detail::AssignableTargets => "代入できるのは変数とメンバーのみです",
+        Detail::ExpectedSemicolonAfter => "この後ろに ';' が必要です",
+        Detail::InsertSemicolon => "ここに ';' を挿入します",
+        Detail::DeclaredHere => "'{name}' はここで宣言されています",
+        Detail::RemoveKeyword => "'{kind}' を取り除くと '{name}' に代入できるようになります",
+        Detail::UnreachableAfter => "この文より後ろのコードには到達できません",
+        Detail::RemoveUnreachableCode => "到達できないコードを削除します",
+        Detail::UseStrictComparison => "値を比較するには '===' を使用します",
+        Detail::ReplaceOperator => "'{operator}' を '{strict}' に置き換えます",
+        Detail::FirstDeclaredHere => "'{name}' は最初にここで宣言されています",
+    }
+}
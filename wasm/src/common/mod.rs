@@ -1,9 +1,11 @@
 //! 共通利用モジュール
 //!
 pub mod constants;
+pub mod diagnostic;
 pub mod error;
 pub mod error_code;
 pub mod macros;
+pub mod messages;
 pub mod span;
 pub mod token;
 
@@ -15,3 +17,4 @@ pub use token::LiteralToken;
 pub use token::OperatorToken;
 pub use token::Token;
 pub use token::TokenKind;
+pub use token::TriviaToken;
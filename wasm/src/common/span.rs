@@ -6,3 +6,59 @@ pub struct Span {
     pub start: usize,
     pub end: usize,
 }
+
+impl Span {
+    /// 開始位置の (行, 列) を求める (いずれも1ベース)
+    pub fn line_column(&self, source: &str) -> (u32, u32) {
+        offset_to_line_column(source, self.start)
+    }
+
+    /// 2つの範囲を両方含む最小の範囲を返す
+    pub fn cover(&self, other: Span) -> Span {
+        Span {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+}
+
+/// 前回変換した位置から読み進めてバイトオフセットを (行, 列) に変換する
+///
+/// 昇順に変換する場合は入力を1度しか走査しないため、字句エラーのように大量の位置を求める場合に使用する
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LineCursor {
+    offset: usize,
+    /// `offset` より前にある改行の数
+    newlines: u32,
+    line_start: usize,
+}
+
+impl LineCursor {
+    /// `offset` の (行, 列) を求める (いずれも1ベース)
+    pub fn line_column(&mut self, source: &str, offset: usize) -> (u32, u32) {
+        let offset = offset.min(source.len());
+        if offset < self.offset {
+            *self = Self::default();
+        }
+        for (i, &b) in source.as_bytes()[self.offset..offset].iter().enumerate() {
+            if b == b'\n' {
+                self.newlines += 1;
+                self.line_start = self.offset + i + 1;
+            }
+        }
+        self.offset = offset;
+        (self.newlines + 1, (offset - self.line_start) as u32 + 1)
+    }
+}
+
+/// バイトオフセットを (行, 列) に変換する (いずれも1ベース)
+pub fn offset_to_line_column(source: &str, offset: usize) -> (u32, u32) {
+    let offset = offset.min(source.len());
+    let before = &source.as_bytes()[..offset];
+    let line = before.iter().filter(|&&b| b == b'\n').count() as u32 + 1;
+    let line_start = before
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |pos| pos + 1);
+    (line, (offset - line_start) as u32 + 1)
+}
@@ -20,6 +20,8 @@ pub enum TokenKind {
     Delimiter(DelimiterToken),
     /// 予約語
     Keyword(KeywordToken),
+    /// 空白・改行・コメント (トリビア出力モードでのみ生成される)
+    Trivia(TriviaToken),
 }
 
 /// リテラル (Literals)
@@ -175,6 +177,89 @@ pub enum KeywordToken {
     And,
     /// 論理OR (`or`)
     Or,
+    /// 定数宣言 (`const`)
+    Const,
+    /// 読み取り専用フィールド (`readonly`)
+    Readonly,
+}
+
+impl KeywordToken {
+    /// すべてのキーワード
+    pub const ALL: &'static [KeywordToken] = &[
+        KeywordToken::Function,
+        KeywordToken::Sub,
+        KeywordToken::Class,
+        KeywordToken::Extends,
+        KeywordToken::Constructor,
+        KeywordToken::New,
+        KeywordToken::If,
+        KeywordToken::Else,
+        KeywordToken::For,
+        KeywordToken::While,
+        KeywordToken::In,
+        KeywordToken::Of,
+        KeywordToken::Switch,
+        KeywordToken::Case,
+        KeywordToken::Default,
+        KeywordToken::Break,
+        KeywordToken::Continue,
+        KeywordToken::Return,
+        KeywordToken::True,
+        KeywordToken::False,
+        KeywordToken::Null,
+        KeywordToken::And,
+        KeywordToken::Or,
+        KeywordToken::Const,
+        KeywordToken::Readonly,
+    ];
+
+    /// ソースコード上の表記
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeywordToken::Function => "function",
+            KeywordToken::Sub => "sub",
+            KeywordToken::Class => "class",
+            KeywordToken::Extends => "extends",
+            KeywordToken::Constructor => "constructor",
+            KeywordToken::New => "new",
+            KeywordToken::If => "if",
+            KeywordToken::Else => "else",
+            KeywordToken::For => "for",
+            KeywordToken::While => "while",
+            KeywordToken::In => "in",
+            KeywordToken::Of => "of",
+            KeywordToken::Switch => "switch",
+            KeywordToken::Case => "case",
+            KeywordToken::Default => "default",
+            KeywordToken::Break => "break",
+            KeywordToken::Continue => "continue",
+            KeywordToken::Return => "return",
+            KeywordToken::True => "true",
+            KeywordToken::False => "false",
+            KeywordToken::Null => "null",
+            KeywordToken::And => "and",
+            KeywordToken::Or => "or",
+            KeywordToken::Const => "const",
+            KeywordToken::Readonly => "readonly",
+        }
+    }
+}
+
+/// トリビア (Trivia)
+/// 構文上の意味を持たないが、ソースの完全な復元に必要な要素
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum TriviaToken {
+    /// 空白・タブ
+    Whitespace,
+    /// 改行 (`\n` または `\r\n`)
+    Newline,
+    /// 行コメント (`// ...`)
+    LineComment,
+    /// ブロックコメント (`/* ... */`)
+    BlockComment,
+    /// 字句解析エラーにより読み飛ばされた文字列 (CST構築時のみ生成される)
+    Skipped,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -190,6 +275,11 @@ impl fmt::Display for Token {
 }
 
 impl Token {
+    /// トリビアトークンかどうか
+    pub fn is_trivia(&self) -> bool {
+        matches!(self.kind, TokenKind::Trivia(_))
+    }
+
     /// EOF（入力終端）トークンを生成する
     pub fn eof(pos: usize) -> Self {
         Token {
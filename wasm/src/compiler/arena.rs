@@ -0,0 +1,738 @@
+//! アリーナ確保のAST
+//!
+//! `ProgramAst` は子ノードごとに `Box` を、識別子ごとに `String` を確保するため、
+//! 大きなスクリプトではメモリ使用量と確保回数が増大する。
+//! `AstArena` は全ノードを種類ごとの `Vec` に格納して添字 (`StmtId` / `ExprId`) で参照し、
+//! 識別子・型名・文字列リテラルは `Interner` で重複なく保持する。
+//!
+//! `AstArena::parse` はプログラム直下の文を解析するたびにアリーナへ移すため、
+//! 解析中に保持する `Box` の木は1文分だけで済む。名前解決 (`resolver`) はこの形式を入力とする。
+//! JSONでのデバッグ出力などには `AstArena::to_program` で `ProgramAst` へ変換する。
+use crate::common::Span;
+use crate::common::error::SnowFallError;
+use crate::compiler::ast::*;
+use crate::compiler::interner::{Interner, Symbol};
+use crate::compiler::{Lexer, Parser};
+
+/// アリーナ内の文の添字
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StmtId(u32);
+
+/// アリーナ内の式の添字
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExprId(u32);
+
+/// アリーナ上の文
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArenaStatement {
+    pub kind: ArenaStatementKind,
+    pub span: Span,
+    pub id: NodeId,
+}
+
+/// アリーナ上の文の種類 (`StatementKind` に対応する)
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArenaStatementKind {
+    VariableDeclaration {
+        type_name: Symbol,
+        declarators: Box<[ArenaDeclarator]>,
+        mutability: Mutability,
+    },
+    FunctionDeclaration {
+        kind: FunctionKind,
+        name: Symbol,
+        return_type: Option<Symbol>,
+        params: Box<[ArenaParameter]>,
+        body: StmtId,
+    },
+    ClassDeclaration {
+        name: Symbol,
+        superclass: Option<Symbol>,
+        members: Box<[StmtId]>,
+    },
+    If {
+        condition: ExprId,
+        consequence: StmtId,
+        alternative: Option<StmtId>,
+    },
+    For {
+        init: Option<StmtId>,
+        condition: Option<ExprId>,
+        update: Option<StmtId>,
+        body: StmtId,
+    },
+    ForEach {
+        binding: ArenaBinding,
+        iterable: ExprId,
+        kind: ForEachKind,
+        body: StmtId,
+    },
+    While {
+        condition: ExprId,
+        body: StmtId,
+    },
+    Switch {
+        expression: ExprId,
+        cases: Box<[ArenaSwitchCase]>,
+        default: Option<StmtId>,
+    },
+    Return(Option<ExprId>),
+    Break,
+    Continue,
+    Block(Box<[StmtId]>),
+    Expression(ExprId),
+}
+
+/// 変数宣言の1要素 (`VariableDeclarator` に対応する)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArenaDeclarator {
+    pub name: Symbol,
+    pub value: Option<ExprId>,
+}
+
+/// 関数の引数 (`Parameter` に対応する)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArenaParameter {
+    pub name: Symbol,
+    pub type_name: Symbol,
+    pub value: Option<ExprId>,
+    pub span: Span,
+}
+
+/// for...in / for...of の変数 (`Binding` に対応する)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArenaBinding {
+    pub name: Symbol,
+    pub type_name: Option<Symbol>,
+}
+
+/// switch文のケース (`SwitchCase` に対応する)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArenaSwitchCase {
+    pub values: Box<[ExprId]>,
+    pub body: StmtId,
+}
+
+/// アリーナ上の式
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArenaExpression {
+    pub kind: ArenaExpressionKind,
+    pub span: Span,
+    pub id: NodeId,
+}
+
+/// アリーナ上の式の種類 (`ExpressionKind` に対応する)
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArenaExpressionKind {
+    IntLiteral(i64),
+    FloatLiteral(f64),
+    StringLiteral(Symbol),
+    Boolean(bool),
+    Identifier(Symbol),
+    Prefix {
+        operator: PrefixOperator,
+        right: ExprId,
+    },
+    Infix {
+        left: ExprId,
+        operator: InfixOperator,
+        right: ExprId,
+    },
+    Call {
+        function: ExprId,
+        arguments: Box<[ExprId]>,
+    },
+    Cast {
+        target_type: Symbol,
+        expression: ExprId,
+    },
+    NullLiteral,
+    ArrayLiteral(Box<[ExprId]>),
+    ObjectLiteral {
+        pairs: Box<[(ExprId, ExprId)]>,
+    },
+    Assignment {
+        left: ExprId,
+        right: ExprId,
+    },
+    MemberAccess {
+        object: ExprId,
+        property: ArenaMemberProperty,
+    },
+    New {
+        class: ExprId,
+        arguments: Box<[ExprId]>,
+    },
+}
+
+/// `MemberProperty` のアリーナ版
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArenaMemberProperty {
+    Named(Symbol),
+    Computed(ExprId),
+}
+
+/// 全ノードと文字列を保持するアリーナ
+#[derive(Debug, Clone)]
+pub struct AstArena {
+    /// 識別子・型名・文字列リテラル
+    pub interner: Interner,
+    statements: Vec<ArenaStatement>,
+    expressions: Vec<ArenaExpression>,
+    /// プログラム直下の文
+    root: Box<[StmtId]>,
+    /// プログラム全体の位置情報
+    span: Span,
+    /// プログラムのノードID
+    id: NodeId,
+}
+
+impl AstArena {
+    /// ソースコードを構文解析し、アリーナ形式のASTを返す
+    ///
+    /// ```
+    /// use snowfall_core::compiler::arena::{ArenaStatementKind, AstArena};
+    ///
+    /// let arena = AstArena::parse("Int a = 1;\nInt b = a;").unwrap();
+    /// let ArenaStatementKind::VariableDeclaration { type_name, .. } = &arena.statement(arena.root()[1]).kind
+    /// else {
+    ///     unreachable!();
+    /// };
+    /// assert_eq!(arena.resolve(*type_name), "Int");
+    /// // 同じ型名は1度だけ登録される
+    /// assert_eq!(arena.interner.len(), 3);
+    /// ```
+    pub fn parse(source: &str) -> Result<Self, Vec<SnowFallError>> {
+        Self::parse_with(Parser::new(Lexer::new(source)))
+    }
+
+    /// 設定済みの構文解析器でソースコードを解析し、アリーナ形式のASTを返す
+    ///
+    /// プログラム直下の文は解析するたびにアリーナへ移すため、`ProgramAst` 全体は構築しない
+    pub fn parse_with(mut parser: Parser<'_>) -> Result<Self, Vec<SnowFallError>> {
+        let mut arena = Self::empty();
+        let mut root = Vec::new();
+        let (span, id) =
+            parser.parse_program_with(|stmt| root.push(arena.alloc_statement(&stmt)))?;
+        arena.root = root.into_boxed_slice();
+        arena.span = span;
+        arena.id = id;
+        arena.statements.shrink_to_fit();
+        arena.expressions.shrink_to_fit();
+        Ok(arena)
+    }
+
+    /// `ProgramAst` をアリーナ形式へ変換する
+    pub fn from_program(program: &ProgramAst) -> Self {
+        let mut arena = Self::empty();
+        arena.root = program
+            .statements
+            .iter()
+            .map(|stmt| arena.alloc_statement(stmt))
+            .collect();
+        arena.span = program.span;
+        arena.id = program.id;
+        arena.statements.shrink_to_fit();
+        arena.expressions.shrink_to_fit();
+        arena
+    }
+
+    fn empty() -> Self {
+        AstArena {
+            interner: Interner::new(),
+            statements: Vec::new(),
+            expressions: Vec::new(),
+            root: Box::new([]),
+            span: Span { start: 0, end: 0 },
+            id: NodeId(0),
+        }
+    }
+
+    /// `ProgramAst` へ変換する (JSONでのデバッグ出力用)
+    pub fn to_program(&self) -> ProgramAst {
+        ProgramAst {
+            statements: self
+                .root
+                .iter()
+                .map(|id| self.build_statement(*id))
+                .collect(),
+            span: self.span,
+            id: self.id,
+        }
+    }
+
+    /// プログラム直下の文
+    pub fn root(&self) -> &[StmtId] {
+        &self.root
+    }
+
+    /// プログラム全体の位置情報
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn statement(&self, id: StmtId) -> &ArenaStatement {
+        &self.statements[id.0 as usize]
+    }
+
+    pub fn expression(&self, id: ExprId) -> &ArenaExpression {
+        &self.expressions[id.0 as usize]
+    }
+
+    /// シンボルに対応する文字列
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        self.interner.resolve(symbol)
+    }
+
+    /// 文の総数
+    pub fn statement_count(&self) -> usize {
+        self.statements.len()
+    }
+
+    /// 式の総数
+    pub fn expression_count(&self) -> usize {
+        self.expressions.len()
+    }
+
+    // ===== ProgramAst からの変換 =====
+
+    fn push_statement(&mut self, stmt: ArenaStatement) -> StmtId {
+        self.statements.push(stmt);
+        StmtId((self.statements.len() - 1) as u32)
+    }
+
+    fn push_expression(&mut self, expr: ArenaExpression) -> ExprId {
+        self.expressions.push(expr);
+        ExprId((self.expressions.len() - 1) as u32)
+    }
+
+    fn alloc_statements(&mut self, stmts: &[Statement]) -> Box<[StmtId]> {
+        stmts.iter().map(|s| self.alloc_statement(s)).collect()
+    }
+
+    fn alloc_expressions(&mut self, exprs: &[Expression]) -> Box<[ExprId]> {
+        exprs.iter().map(|e| self.alloc_expression(e)).collect()
+    }
+
+    fn alloc_optional_expression(&mut self, expr: &Option<Expression>) -> Option<ExprId> {
+        expr.as_ref().map(|e| self.alloc_expression(e))
+    }
+
+    fn alloc_optional_statement(&mut self, stmt: &Option<Box<Statement>>) -> Option<StmtId> {
+        stmt.as_ref().map(|s| self.alloc_statement(s))
+    }
+
+    fn intern_optional(&mut self, s: &Option<String>) -> Option<Symbol> {
+        s.as_ref().map(|s| self.interner.intern(s))
+    }
+
+    fn alloc_statement(&mut self, stmt: &Statement) -> StmtId {
+        let kind = match &stmt.kind {
+            StatementKind::VariableDeclaration {
+                type_name,
+                declarators,
+                mutability,
+            } => ArenaStatementKind::VariableDeclaration {
+                type_name: self.interner.intern(type_name),
+                declarators: declarators
+                    .iter()
+                    .map(|decl| ArenaDeclarator {
+                        name: self.interner.intern(&decl.name),
+                        value: self.alloc_optional_expression(&decl.value),
+                    })
+                    .collect(),
+                mutability: *mutability,
+            },
+            StatementKind::FunctionDeclaration {
+                kind,
+                name,
+                return_type,
+                params,
+                body,
+            } => ArenaStatementKind::FunctionDeclaration {
+                kind: kind.clone(),
+                name: self.interner.intern(name),
+                return_type: self.intern_optional(return_type),
+                params: params
+                    .iter()
+                    .map(|param| ArenaParameter {
+                        name: self.interner.intern(&param.name),
+                        type_name: self.interner.intern(&param.type_name),
+                        value: self.alloc_optional_expression(&param.value),
+                        span: param.span,
+                    })
+                    .collect(),
+                body: self.alloc_statement(body),
+            },
+            StatementKind::ClassDeclaration {
+                name,
+                superclass,
+                members,
+            } => ArenaStatementKind::ClassDeclaration {
+                name: self.interner.intern(name),
+                superclass: self.intern_optional(superclass),
+                members: self.alloc_statements(members),
+            },
+            StatementKind::If {
+                condition,
+                consequence,
+                alternative,
+            } => ArenaStatementKind::If {
+                condition: self.alloc_expression(condition),
+                consequence: self.alloc_statement(consequence),
+                alternative: self.alloc_optional_statement(alternative),
+            },
+            StatementKind::For {
+                init,
+                condition,
+                update,
+                body,
+            } => ArenaStatementKind::For {
+                init: self.alloc_optional_statement(init),
+                condition: self.alloc_optional_expression(condition),
+                update: self.alloc_optional_statement(update),
+                body: self.alloc_statement(body),
+            },
+            StatementKind::ForEach {
+                binding,
+                iterable,
+                kind,
+                body,
+            } => ArenaStatementKind::ForEach {
+                binding: ArenaBinding {
+                    name: self.interner.intern(&binding.name),
+                    type_name: self.intern_optional(&binding.type_name),
+                },
+                iterable: self.alloc_expression(iterable),
+                kind: kind.clone(),
+                body: self.alloc_statement(body),
+            },
+            StatementKind::While { condition, body } => ArenaStatementKind::While {
+                condition: self.alloc_expression(condition),
+                body: self.alloc_statement(body),
+            },
+            StatementKind::Switch {
+                expression,
+                cases,
+                default,
+            } => ArenaStatementKind::Switch {
+                expression: self.alloc_expression(expression),
+                cases: cases
+                    .iter()
+                    .map(|case| ArenaSwitchCase {
+                        values: self.alloc_expressions(&case.values),
+                        body: self.alloc_statement(&case.body),
+                    })
+                    .collect(),
+                default: self.alloc_optional_statement(default),
+            },
+            StatementKind::Return(value) => {
+                ArenaStatementKind::Return(self.alloc_optional_expression(value))
+            }
+            StatementKind::Break => ArenaStatementKind::Break,
+            StatementKind::Continue => ArenaStatementKind::Continue,
+            StatementKind::Block(stmts) => ArenaStatementKind::Block(self.alloc_statements(stmts)),
+            StatementKind::Expression(expr) => {
+                ArenaStatementKind::Expression(self.alloc_expression(expr))
+            }
+        };
+        self.push_statement(ArenaStatement {
+            kind,
+            span: stmt.span,
+            id: stmt.id,
+        })
+    }
+
+    fn alloc_expression(&mut self, expr: &Expression) -> ExprId {
+        let kind = match &expr.kind {
+            ExpressionKind::IntLiteral(v) => ArenaExpressionKind::IntLiteral(*v),
+            ExpressionKind::FloatLiteral(v) => ArenaExpressionKind::FloatLiteral(*v),
+            ExpressionKind::StringLiteral(s) => {
+                ArenaExpressionKind::StringLiteral(self.interner.intern(s))
+            }
+            ExpressionKind::Boolean(v) => ArenaExpressionKind::Boolean(*v),
+            ExpressionKind::Identifier(name) => {
+                ArenaExpressionKind::Identifier(self.interner.intern(name))
+            }
+            ExpressionKind::Prefix { operator, right } => ArenaExpressionKind::Prefix {
+                operator: operator.clone(),
+                right: self.alloc_expression(right),
+            },
+            ExpressionKind::Infix {
+                left,
+                operator,
+                right,
+            } => ArenaExpressionKind::Infix {
+                left: self.alloc_expression(left),
+                operator: operator.clone(),
+                right: self.alloc_expression(right),
+            },
+            ExpressionKind::Call {
+                function,
+                arguments,
+            } => ArenaExpressionKind::Call {
+                function: self.alloc_expression(function),
+                arguments: self.alloc_expressions(arguments),
+            },
+            ExpressionKind::Cast {
+                target_type,
+                expression,
+            } => ArenaExpressionKind::Cast {
+                target_type: self.interner.intern(target_type),
+                expression: self.alloc_expression(expression),
+            },
+            ExpressionKind::NullLiteral => ArenaExpressionKind::NullLiteral,
+            ExpressionKind::ArrayLiteral(elements) => {
+                ArenaExpressionKind::ArrayLiteral(self.alloc_expressions(elements))
+            }
+            ExpressionKind::ObjectLiteral { pairs } => ArenaExpressionKind::ObjectLiteral {
+                pairs: pairs
+                    .iter()
+                    .map(|(key, value)| (self.alloc_expression(key), self.alloc_expression(value)))
+                    .collect(),
+            },
+            ExpressionKind::Assignment { left, right } => ArenaExpressionKind::Assignment {
+                left: self.alloc_expression(left),
+                right: self.alloc_expression(right),
+            },
+            ExpressionKind::MemberAccess { object, property } => {
+                ArenaExpressionKind::MemberAccess {
+                    object: self.alloc_expression(object),
+                    property: match property {
+                        MemberProperty::Named(name) => {
+                            ArenaMemberProperty::Named(self.interner.intern(name))
+                        }
+                        MemberProperty::Computed(index) => {
+                            ArenaMemberProperty::Computed(self.alloc_expression(index))
+                        }
+                    },
+                }
+            }
+            ExpressionKind::New { class, arguments } => ArenaExpressionKind::New {
+                class: self.alloc_expression(class),
+                arguments: self.alloc_expressions(arguments),
+            },
+        };
+        self.push_expression(ArenaExpression {
+            kind,
+            span: expr.span,
+            id: expr.id,
+        })
+    }
+
+    // ===== ProgramAst への変換 =====
+
+    fn string(&self, symbol: Symbol) -> String {
+        self.resolve(symbol).to_string()
+    }
+
+    fn build_statements(&self, ids: &[StmtId]) -> Vec<Statement> {
+        ids.iter().map(|id| self.build_statement(*id)).collect()
+    }
+
+    fn build_expressions(&self, ids: &[ExprId]) -> Vec<Expression> {
+        ids.iter().map(|id| self.build_expression(*id)).collect()
+    }
+
+    fn build_boxed_statement(&self, id: StmtId) -> Box<Statement> {
+        Box::new(self.build_statement(id))
+    }
+
+    fn build_boxed_expression(&self, id: ExprId) -> Box<Expression> {
+        Box::new(self.build_expression(id))
+    }
+
+    fn build_statement(&self, id: StmtId) -> Statement {
+        let stmt = self.statement(id);
+        let kind = match &stmt.kind {
+            ArenaStatementKind::VariableDeclaration {
+                type_name,
+                declarators,
+                mutability,
+            } => StatementKind::VariableDeclaration {
+                type_name: self.string(*type_name),
+                declarators: declarators
+                    .iter()
+                    .map(|decl| VariableDeclarator {
+                        name: self.string(decl.name),
+                        value: decl.value.map(|v| self.build_expression(v)),
+                    })
+                    .collect(),
+                mutability: *mutability,
+            },
+            ArenaStatementKind::FunctionDeclaration {
+                kind,
+                name,
+                return_type,
+                params,
+                body,
+            } => StatementKind::FunctionDeclaration {
+                kind: kind.clone(),
+                name: self.string(*name),
+                return_type: return_type.map(|t| self.string(t)),
+                params: params
+                    .iter()
+                    .map(|param| Parameter {
+                        name: self.string(param.name),
+                        type_name: self.string(param.type_name),
+                        value: param.value.map(|v| self.build_expression(v)),
+                        span: param.span,
+                    })
+                    .collect(),
+                body: self.build_boxed_statement(*body),
+            },
+            ArenaStatementKind::ClassDeclaration {
+                name,
+                superclass,
+                members,
+            } => StatementKind::ClassDeclaration {
+                name: self.string(*name),
+                superclass: superclass.map(|s| self.string(s)),
+                members: self.build_statements(members),
+            },
+            ArenaStatementKind::If {
+                condition,
+                consequence,
+                alternative,
+            } => StatementKind::If {
+                condition: self.build_expression(*condition),
+                consequence: self.build_boxed_statement(*consequence),
+                alternative: alternative.map(|s| self.build_boxed_statement(s)),
+            },
+            ArenaStatementKind::For {
+                init,
+                condition,
+                update,
+                body,
+            } => StatementKind::For {
+                init: init.map(|s| self.build_boxed_statement(s)),
+                condition: condition.map(|e| self.build_expression(e)),
+                update: update.map(|s| self.build_boxed_statement(s)),
+                body: self.build_boxed_statement(*body),
+            },
+            ArenaStatementKind::ForEach {
+                binding,
+                iterable,
+                kind,
+                body,
+            } => StatementKind::ForEach {
+                binding: Binding {
+                    name: self.string(binding.name),
+                    type_name: binding.type_name.map(|t| self.string(t)),
+                },
+                iterable: self.build_expression(*iterable),
+                kind: kind.clone(),
+                body: self.build_boxed_statement(*body),
+            },
+            ArenaStatementKind::While { condition, body } => StatementKind::While {
+                condition: self.build_expression(*condition),
+                body: self.build_boxed_statement(*body),
+            },
+            ArenaStatementKind::Switch {
+                expression,
+                cases,
+                default,
+            } => StatementKind::Switch {
+                expression: self.build_expression(*expression),
+                cases: cases
+                    .iter()
+                    .map(|case| SwitchCase {
+                        values: self.build_expressions(&case.values),
+                        body: self.build_boxed_statement(case.body),
+                    })
+                    .collect(),
+                default: default.map(|s| self.build_boxed_statement(s)),
+            },
+            ArenaStatementKind::Return(value) => {
+                StatementKind::Return(value.map(|v| self.build_expression(v)))
+            }
+            ArenaStatementKind::Break => StatementKind::Break,
+            ArenaStatementKind::Continue => StatementKind::Continue,
+            ArenaStatementKind::Block(stmts) => StatementKind::Block(self.build_statements(stmts)),
+            ArenaStatementKind::Expression(expr) => {
+                StatementKind::Expression(self.build_expression(*expr))
+            }
+        };
+        Statement {
+            kind,
+            span: stmt.span,
+            id: stmt.id,
+        }
+    }
+
+    fn build_expression(&self, id: ExprId) -> Expression {
+        let expr = self.expression(id);
+        let kind = match &expr.kind {
+            ArenaExpressionKind::IntLiteral(v) => ExpressionKind::IntLiteral(*v),
+            ArenaExpressionKind::FloatLiteral(v) => ExpressionKind::FloatLiteral(*v),
+            ArenaExpressionKind::StringLiteral(s) => ExpressionKind::StringLiteral(self.string(*s)),
+            ArenaExpressionKind::Boolean(v) => ExpressionKind::Boolean(*v),
+            ArenaExpressionKind::Identifier(name) => ExpressionKind::Identifier(self.string(*name)),
+            ArenaExpressionKind::Prefix { operator, right } => ExpressionKind::Prefix {
+                operator: operator.clone(),
+                right: self.build_boxed_expression(*right),
+            },
+            ArenaExpressionKind::Infix {
+                left,
+                operator,
+                right,
+            } => ExpressionKind::Infix {
+                left: self.build_boxed_expression(*left),
+                operator: operator.clone(),
+                right: self.build_boxed_expression(*right),
+            },
+            ArenaExpressionKind::Call {
+                function,
+                arguments,
+            } => ExpressionKind::Call {
+                function: self.build_boxed_expression(*function),
+                arguments: self.build_expressions(arguments),
+            },
+            ArenaExpressionKind::Cast {
+                target_type,
+                expression,
+            } => ExpressionKind::Cast {
+                target_type: self.string(*target_type),
+                expression: self.build_boxed_expression(*expression),
+            },
+            ArenaExpressionKind::NullLiteral => ExpressionKind::NullLiteral,
+            ArenaExpressionKind::ArrayLiteral(elements) => {
+                ExpressionKind::ArrayLiteral(self.build_expressions(elements))
+            }
+            ArenaExpressionKind::ObjectLiteral { pairs } => ExpressionKind::ObjectLiteral {
+                pairs: pairs
+                    .iter()
+                    .map(|(key, value)| {
+                        (self.build_expression(*key), self.build_expression(*value))
+                    })
+                    .collect(),
+            },
+            ArenaExpressionKind::Assignment { left, right } => ExpressionKind::Assignment {
+                left: self.build_boxed_expression(*left),
+                right: self.build_boxed_expression(*right),
+            },
+            ArenaExpressionKind::MemberAccess { object, property } => {
+                ExpressionKind::MemberAccess {
+                    object: self.build_boxed_expression(*object),
+                    property: match property {
+                        ArenaMemberProperty::Named(name) => {
+                            MemberProperty::Named(self.string(*name))
+                        }
+                        ArenaMemberProperty::Computed(index) => {
+                            MemberProperty::Computed(self.build_boxed_expression(*index))
+                        }
+                    },
+                }
+            }
+            ArenaExpressionKind::New { class, arguments } => ExpressionKind::New {
+                class: self.build_boxed_expression(*class),
+                arguments: self.build_expressions(arguments),
+            },
+        };
+        Expression {
+            kind,
+            span: expr.span,
+            id: expr.id,
+        }
+    }
+}
@@ -6,6 +6,54 @@ use serde::{Deserialize, Serialize};
 pub struct ProgramAst {
     pub statements: Vec<Statement>,
     pub span: Span,
+    pub id: NodeId,
+}
+
+/// ASTノードの識別子
+///
+/// パーサーが構文木内で一意な値を割り当てる。
+/// 名前解決や型推論などの解析結果は、木を変更せずに `SideTable` へこのIDをキーとして格納する。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct NodeId(pub u32);
+
+impl NodeId {
+    /// 最適化などでパース後に合成されたノードのID (一意ではない)
+    pub const DUMMY: NodeId = NodeId(u32::MAX);
+
+    /// パース後に合成されたノードかどうか
+    pub fn is_dummy(&self) -> bool {
+        *self == NodeId::DUMMY
+    }
+}
+
+/// ASTノードへの参照
+/// ノードの種類を問わず扱う必要がある処理 (CSTとの対応付けなど) で使用する
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AstNodeRef<'a> {
+    Program(&'a ProgramAst),
+    Statement(&'a Statement),
+    Expression(&'a Expression),
+}
+
+impl AstNodeRef<'_> {
+    /// ノードの位置情報
+    pub fn span(&self) -> Span {
+        match self {
+            AstNodeRef::Program(p) => p.span,
+            AstNodeRef::Statement(s) => s.span,
+            AstNodeRef::Expression(e) => e.span,
+        }
+    }
+
+    /// ノードの識別子
+    pub fn id(&self) -> NodeId {
+        match self {
+            AstNodeRef::Program(p) => p.id,
+            AstNodeRef::Statement(s) => s.id,
+            AstNodeRef::Expression(e) => e.id,
+        }
+    }
 }
 
 /// 文 (Statement)
@@ -14,16 +62,18 @@ pub struct ProgramAst {
 pub struct Statement {
     pub kind: StatementKind,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// 抽象構文木の文ノード
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "value")]
 pub enum StatementKind {
-    /// 変数宣言: `Int a = 1, b = 2;`
+    /// 変数宣言: `Int a = 1, b = 2;` / `const Int MAX = 10;`
     VariableDeclaration {
         type_name: String,
         declarators: Vec<VariableDeclarator>,
+        mutability: Mutability,
     },
     /// 関数定義 `function Int add(Int a, Int b) /* ... */`
     FunctionDeclaration {
@@ -34,6 +84,7 @@ pub enum StatementKind {
         body: Box<Statement>, // Block
     },
     /// クラス定義 `class MyClass extends Base { /* ... */ }`
+    /// メンバーはメソッド (`FunctionDeclaration`) またはフィールド (`VariableDeclaration`)
     ClassDeclaration {
         name: String,
         superclass: Option<String>,
@@ -86,6 +137,22 @@ pub enum StatementKind {
     Expression(Expression),
 }
 
+impl Statement {
+    /// 必ず制御を外へ移す (後続の文が実行されない) 文かどうかを判定する
+    pub fn always_exits(&self) -> bool {
+        match &self.kind {
+            StatementKind::Return(_) | StatementKind::Break | StatementKind::Continue => true,
+            StatementKind::Block(body) => body.iter().any(Statement::always_exits),
+            StatementKind::If {
+                consequence,
+                alternative: Some(alternative),
+                ..
+            } => consequence.always_exits() && alternative.always_exits(),
+            _ => false,
+        }
+    }
+}
+
 /// 変数宣言の1要素 (例: `a = 1`)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VariableDeclarator {
@@ -98,6 +165,26 @@ pub struct Parameter {
     pub name: String,
     pub type_name: String, // 引数は型必須と仮定 (`Int a`)
     pub value: Option<Expression>,
+    /// 型名から既定値までの位置情報
+    pub span: Span,
+}
+
+/// 変数・フィールドの可変性
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Mutability {
+    /// 再代入可能な通常の宣言
+    Mutable,
+    /// `const` 宣言 (再代入不可)
+    Const,
+    /// `readonly` フィールド (再代入不可)
+    Readonly,
+}
+
+impl Mutability {
+    /// 再代入が禁止されているかどうか
+    pub fn is_immutable(&self) -> bool {
+        !matches!(self, Mutability::Mutable)
+    }
 }
 
 /// 変数のバインディング情報
@@ -133,6 +220,7 @@ pub struct SwitchCase {
 pub struct Expression {
     pub kind: ExpressionKind,
     pub span: Span,
+    pub id: NodeId,
 }
 
 /// 抽象構文木の式ノード
@@ -178,25 +266,16 @@ pub enum ExpressionKind {
     ObjectLiteral {
         pairs: Vec<(Expression, Expression)>,
     },
-    Index {
-        left: Box<Expression>,
-        index: Box<Expression>,
-    },
-    /// メンバーアクセス: `obj.prop`
-    Member {
-        left: Box<Expression>,
-        property: String,
-    },
     /// 代入式 `a = 10`, `obj.prop = 20`
+    /// 代入先 (`left`) は `Identifier` または `MemberAccess` のみ (パーサーが検証する)
     Assignment {
-        left: Box<Expression>, // Identifier or MemberAccess
+        left: Box<Expression>,
         right: Box<Expression>,
     },
-    /// メンバーアクセス `obj.prop` or `arr[0]`
+    /// メンバーアクセス `obj.prop` / 添字アクセス `arr[0]`
     MemberAccess {
         object: Box<Expression>,
-        property: Box<Expression>, // Identifier or Literal
-        computed: bool,            // true for `[]`, false for `.`
+        property: MemberProperty,
     },
     /// `new` 式 `new MyClass()`
     New {
@@ -205,6 +284,26 @@ pub enum ExpressionKind {
     },
 }
 
+/// メンバーアクセスのプロパティ
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value")]
+pub enum MemberProperty {
+    /// 名前による参照 `obj.prop`
+    Named(String),
+    /// 式による参照 `arr[0]`, `obj["key"]`
+    Computed(Box<Expression>),
+}
+
+impl Expression {
+    /// 代入先として有効な式かどうか (識別子またはメンバーアクセス)
+    pub fn is_assignable(&self) -> bool {
+        matches!(
+            self.kind,
+            ExpressionKind::Identifier(_) | ExpressionKind::MemberAccess { .. }
+        )
+    }
+}
+
 /// 前置演算子一覧
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum PrefixOperator {
@@ -214,6 +313,18 @@ pub enum PrefixOperator {
     BitwiseNot,
 }
 
+impl PrefixOperator {
+    /// ソースコード上の表記
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PrefixOperator::Plus => "+",
+            PrefixOperator::Minus => "-",
+            PrefixOperator::Bang => "!",
+            PrefixOperator::BitwiseNot => "~",
+        }
+    }
+}
+
 /// 中置演算子一覧
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum InfixOperator {
@@ -243,3 +354,456 @@ pub enum InfixOperator {
     BitwiseUnsignedLeftShift,
     BitwiseUnsignedRightShift,
 }
+
+impl InfixOperator {
+    /// ソースコード上の表記
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InfixOperator::Add => "+",
+            InfixOperator::Subtract => "-",
+            InfixOperator::Multiply => "*",
+            InfixOperator::Divide => "/",
+            InfixOperator::Modulo => "%",
+            InfixOperator::Power => "**",
+            InfixOperator::Equals => "==",
+            InfixOperator::NotEquals => "!=",
+            InfixOperator::StrictEquals => "===",
+            InfixOperator::StrictNotEquals => "!==",
+            InfixOperator::LessThan => "<",
+            InfixOperator::GreaterThan => ">",
+            InfixOperator::LessThanOrEqual => "<=",
+            InfixOperator::GreaterThanOrEqual => ">=",
+            InfixOperator::LogicalAnd => "and",
+            InfixOperator::LogicalOr => "or",
+            InfixOperator::LogicalAndAlso => "&&",
+            InfixOperator::LogicalOrElse => "||",
+            InfixOperator::BitwiseAnd => "&",
+            InfixOperator::BitwiseOr => "|",
+            InfixOperator::BitwiseXor => "^",
+            InfixOperator::BitwiseLeftShift => "<<",
+            InfixOperator::BitwiseRightShift => ">>",
+            InfixOperator::BitwiseUnsignedLeftShift => "<<<",
+            InfixOperator::BitwiseUnsignedRightShift => ">>>",
+        }
+    }
+}
+
+// ===== 走査 =====
+
+/// ASTを読み取り専用で走査するトレイト
+///
+/// 各メソッドの既定実装は対応する `walk_*` 関数で子ノードを走査する。
+/// メソッドを上書きした場合、子ノードも走査するには `walk_*` を明示的に呼び出すこと。
+/// `'ast` は走査するASTの借用期間で、訪問したノードへの参照を保持できる。
+pub trait Visitor<'ast> {
+    fn visit_program(&mut self, program: &'ast ProgramAst) {
+        walk_program(self, program);
+    }
+
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        walk_statement(self, stmt);
+    }
+
+    fn visit_expression(&mut self, expr: &'ast Expression) {
+        walk_expression(self, expr);
+    }
+}
+
+/// プログラム直下の文を走査する
+pub fn walk_program<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, program: &'ast ProgramAst) {
+    for stmt in &program.statements {
+        visitor.visit_statement(stmt);
+    }
+}
+
+/// 文の子ノード (文・式) をソース上の出現順に走査する
+pub fn walk_statement<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, stmt: &'ast Statement) {
+    match &stmt.kind {
+        StatementKind::VariableDeclaration { declarators, .. } => {
+            for value in declarators.iter().filter_map(|d| d.value.as_ref()) {
+                visitor.visit_expression(value);
+            }
+        }
+        StatementKind::FunctionDeclaration { params, body, .. } => {
+            for value in params.iter().filter_map(|p| p.value.as_ref()) {
+                visitor.visit_expression(value);
+            }
+            visitor.visit_statement(body);
+        }
+        StatementKind::ClassDeclaration { members, .. } => {
+            for member in members {
+                visitor.visit_statement(member);
+            }
+        }
+        StatementKind::If {
+            condition,
+            consequence,
+            alternative,
+        } => {
+            visitor.visit_expression(condition);
+            visitor.visit_statement(consequence);
+            if let Some(alt) = alternative {
+                visitor.visit_statement(alt);
+            }
+        }
+        StatementKind::For {
+            init,
+            condition,
+            update,
+            body,
+        } => {
+            if let Some(init) = init {
+                visitor.visit_statement(init);
+            }
+            if let Some(condition) = condition {
+                visitor.visit_expression(condition);
+            }
+            if let Some(update) = update {
+                visitor.visit_statement(update);
+            }
+            visitor.visit_statement(body);
+        }
+        StatementKind::ForEach { iterable, body, .. } => {
+            visitor.visit_expression(iterable);
+            visitor.visit_statement(body);
+        }
+        StatementKind::While { condition, body } => {
+            visitor.visit_expression(condition);
+            visitor.visit_statement(body);
+        }
+        StatementKind::Switch {
+            expression,
+            cases,
+            default,
+        } => {
+            visitor.visit_expression(expression);
+            for case in cases {
+                for value in &case.values {
+                    visitor.visit_expression(value);
+                }
+                visitor.visit_statement(&case.body);
+            }
+            if let Some(default) = default {
+                visitor.visit_statement(default);
+            }
+        }
+        StatementKind::Return(value) => {
+            if let Some(value) = value {
+                visitor.visit_expression(value);
+            }
+        }
+        StatementKind::Break | StatementKind::Continue => {}
+        StatementKind::Block(stmts) => {
+            for stmt in stmts {
+                visitor.visit_statement(stmt);
+            }
+        }
+        StatementKind::Expression(expr) => visitor.visit_expression(expr),
+    }
+}
+
+/// 式の子ノードをソース上の出現順に走査する
+pub fn walk_expression<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, expr: &'ast Expression) {
+    match &expr.kind {
+        ExpressionKind::IntLiteral(_)
+        | ExpressionKind::FloatLiteral(_)
+        | ExpressionKind::StringLiteral(_)
+        | ExpressionKind::Boolean(_)
+        | ExpressionKind::NullLiteral
+        | ExpressionKind::Identifier(_) => {}
+        ExpressionKind::Prefix { right, .. } => visitor.visit_expression(right),
+        ExpressionKind::Infix { left, right, .. } | ExpressionKind::Assignment { left, right } => {
+            visitor.visit_expression(left);
+            visitor.visit_expression(right);
+        }
+        ExpressionKind::Call {
+            function,
+            arguments,
+        }
+        | ExpressionKind::New {
+            class: function,
+            arguments,
+        } => {
+            visitor.visit_expression(function);
+            for arg in arguments {
+                visitor.visit_expression(arg);
+            }
+        }
+        ExpressionKind::Cast { expression, .. } => visitor.visit_expression(expression),
+        ExpressionKind::ArrayLiteral(elements) => {
+            for element in elements {
+                visitor.visit_expression(element);
+            }
+        }
+        ExpressionKind::ObjectLiteral { pairs } => {
+            for (key, value) in pairs {
+                visitor.visit_expression(key);
+                visitor.visit_expression(value);
+            }
+        }
+        ExpressionKind::MemberAccess { object, property } => {
+            visitor.visit_expression(object);
+            if let MemberProperty::Computed(index) = property {
+                visitor.visit_expression(index);
+            }
+        }
+    }
+}
+
+/// ASTを所有権ごと受け取り、書き換えたASTを返すトレイト
+///
+/// 各メソッドの既定実装は対応する `fold_*_children` 関数で子ノードを書き換える。
+/// `fold_statement` が `None` を返した文は削除される。
+/// ただし if の本体などの必須の位置では、同じ位置の空ブロックに置き換えられる。
+pub trait Folder {
+    fn fold_program(&mut self, program: ProgramAst) -> ProgramAst {
+        fold_program_children(self, program)
+    }
+
+    fn fold_statement(&mut self, stmt: Statement) -> Option<Statement> {
+        Some(fold_statement_children(self, stmt))
+    }
+
+    fn fold_expression(&mut self, expr: Expression) -> Expression {
+        fold_expression_children(self, expr)
+    }
+}
+
+/// プログラム直下の文を書き換える
+pub fn fold_program_children<F: Folder + ?Sized>(
+    folder: &mut F,
+    program: ProgramAst,
+) -> ProgramAst {
+    ProgramAst {
+        statements: fold_statements(folder, program.statements),
+        span: program.span,
+        id: program.id,
+    }
+}
+
+/// 文の並びを書き換え、削除された文を取り除く
+fn fold_statements<F: Folder + ?Sized>(folder: &mut F, stmts: Vec<Statement>) -> Vec<Statement> {
+    stmts
+        .into_iter()
+        .filter_map(|stmt| folder.fold_statement(stmt))
+        .collect()
+}
+
+/// 省略できない位置の文を書き換える (削除された場合は空ブロックにする)
+pub fn fold_required_statement<F: Folder + ?Sized>(
+    folder: &mut F,
+    stmt: Statement,
+) -> Box<Statement> {
+    let span = stmt.span;
+    let folded = folder.fold_statement(stmt).unwrap_or(Statement {
+        kind: StatementKind::Block(vec![]),
+        span,
+        id: NodeId::DUMMY,
+    });
+    Box::new(folded)
+}
+
+/// 省略可能な位置の文を書き換える
+pub fn fold_optional_statement<F: Folder + ?Sized>(
+    folder: &mut F,
+    stmt: Option<Box<Statement>>,
+) -> Option<Box<Statement>> {
+    stmt.and_then(|stmt| folder.fold_statement(*stmt).map(Box::new))
+}
+
+fn fold_boxed_expression<F: Folder + ?Sized>(folder: &mut F, expr: Expression) -> Box<Expression> {
+    Box::new(folder.fold_expression(expr))
+}
+
+fn fold_expressions<F: Folder + ?Sized>(folder: &mut F, exprs: Vec<Expression>) -> Vec<Expression> {
+    exprs
+        .into_iter()
+        .map(|expr| folder.fold_expression(expr))
+        .collect()
+}
+
+/// 文の子ノード (文・式) をソース上の出現順に書き換える
+pub fn fold_statement_children<F: Folder + ?Sized>(folder: &mut F, stmt: Statement) -> Statement {
+    let kind = match stmt.kind {
+        StatementKind::VariableDeclaration {
+            type_name,
+            declarators,
+            mutability,
+        } => StatementKind::VariableDeclaration {
+            type_name,
+            declarators: declarators
+                .into_iter()
+                .map(|decl| VariableDeclarator {
+                    name: decl.name,
+                    value: decl.value.map(|v| folder.fold_expression(v)),
+                })
+                .collect(),
+            mutability,
+        },
+        StatementKind::FunctionDeclaration {
+            kind,
+            name,
+            return_type,
+            params,
+            body,
+        } => StatementKind::FunctionDeclaration {
+            kind,
+            name,
+            return_type,
+            params: params
+                .into_iter()
+                .map(|param| Parameter {
+                    name: param.name,
+                    type_name: param.type_name,
+                    value: param.value.map(|v| folder.fold_expression(v)),
+                    span: param.span,
+                })
+                .collect(),
+            body: fold_required_statement(folder, *body),
+        },
+        StatementKind::ClassDeclaration {
+            name,
+            superclass,
+            members,
+        } => StatementKind::ClassDeclaration {
+            name,
+            superclass,
+            members: fold_statements(folder, members),
+        },
+        StatementKind::If {
+            condition,
+            consequence,
+            alternative,
+        } => StatementKind::If {
+            condition: folder.fold_expression(condition),
+            consequence: fold_required_statement(folder, *consequence),
+            alternative: fold_optional_statement(folder, alternative),
+        },
+        StatementKind::For {
+            init,
+            condition,
+            update,
+            body,
+        } => StatementKind::For {
+            init: fold_optional_statement(folder, init),
+            condition: condition.map(|c| folder.fold_expression(c)),
+            update: fold_optional_statement(folder, update),
+            body: fold_required_statement(folder, *body),
+        },
+        StatementKind::ForEach {
+            binding,
+            iterable,
+            kind,
+            body,
+        } => StatementKind::ForEach {
+            binding,
+            iterable: folder.fold_expression(iterable),
+            kind,
+            body: fold_required_statement(folder, *body),
+        },
+        StatementKind::While { condition, body } => StatementKind::While {
+            condition: folder.fold_expression(condition),
+            body: fold_required_statement(folder, *body),
+        },
+        StatementKind::Switch {
+            expression,
+            cases,
+            default,
+        } => StatementKind::Switch {
+            expression: folder.fold_expression(expression),
+            cases: cases
+                .into_iter()
+                .map(|case| SwitchCase {
+                    values: fold_expressions(folder, case.values),
+                    body: fold_required_statement(folder, *case.body),
+                })
+                .collect(),
+            default: fold_optional_statement(folder, default),
+        },
+        StatementKind::Return(value) => {
+            StatementKind::Return(value.map(|v| folder.fold_expression(v)))
+        }
+        StatementKind::Break => StatementKind::Break,
+        StatementKind::Continue => StatementKind::Continue,
+        StatementKind::Block(stmts) => StatementKind::Block(fold_statements(folder, stmts)),
+        StatementKind::Expression(expr) => StatementKind::Expression(folder.fold_expression(expr)),
+    };
+    Statement {
+        kind,
+        span: stmt.span,
+        id: stmt.id,
+    }
+}
+
+/// 式の子ノードをソース上の出現順に書き換える
+pub fn fold_expression_children<F: Folder + ?Sized>(
+    folder: &mut F,
+    expr: Expression,
+) -> Expression {
+    let kind = match expr.kind {
+        kind @ (ExpressionKind::IntLiteral(_)
+        | ExpressionKind::FloatLiteral(_)
+        | ExpressionKind::StringLiteral(_)
+        | ExpressionKind::Boolean(_)
+        | ExpressionKind::NullLiteral
+        | ExpressionKind::Identifier(_)) => kind,
+        ExpressionKind::Prefix { operator, right } => ExpressionKind::Prefix {
+            operator,
+            right: fold_boxed_expression(folder, *right),
+        },
+        ExpressionKind::Infix {
+            left,
+            operator,
+            right,
+        } => ExpressionKind::Infix {
+            left: fold_boxed_expression(folder, *left),
+            operator,
+            right: fold_boxed_expression(folder, *right),
+        },
+        ExpressionKind::Call {
+            function,
+            arguments,
+        } => ExpressionKind::Call {
+            function: fold_boxed_expression(folder, *function),
+            arguments: fold_expressions(folder, arguments),
+        },
+        ExpressionKind::Cast {
+            target_type,
+            expression,
+        } => ExpressionKind::Cast {
+            target_type,
+            expression: fold_boxed_expression(folder, *expression),
+        },
+        ExpressionKind::ArrayLiteral(elements) => {
+            ExpressionKind::ArrayLiteral(fold_expressions(folder, elements))
+        }
+        ExpressionKind::ObjectLiteral { pairs } => ExpressionKind::ObjectLiteral {
+            pairs: pairs
+                .into_iter()
+                .map(|(key, value)| (folder.fold_expression(key), folder.fold_expression(value)))
+                .collect(),
+        },
+        ExpressionKind::Assignment { left, right } => ExpressionKind::Assignment {
+            left: fold_boxed_expression(folder, *left),
+            right: fold_boxed_expression(folder, *right),
+        },
+        ExpressionKind::MemberAccess { object, property } => ExpressionKind::MemberAccess {
+            object: fold_boxed_expression(folder, *object),
+            property: match property {
+                MemberProperty::Named(name) => MemberProperty::Named(name),
+                MemberProperty::Computed(index) => {
+                    MemberProperty::Computed(fold_boxed_expression(folder, *index))
+                }
+            },
+        },
+        ExpressionKind::New { class, arguments } => ExpressionKind::New {
+            class: fold_boxed_expression(folder, *class),
+            arguments: fold_expressions(folder, arguments),
+        },
+    };
+    Expression {
+        kind,
+        span: expr.span,
+        id: expr.id,
+    }
+}
@@ -0,0 +1,846 @@
+//! コード補完
+//!
+//! カーソルの直前のトークンから補完する位置の文脈 (文の先頭・式の途中・メンバー・型名など) を判定し、
+//! その位置で有効なキーワード・参照できる名前・型名を候補として順位付けする。
+//!
+//! 入力途中のソースコードは構文解析に失敗することが多い。その場合はカーソルを含む文を空白で塗りつぶしたもの、
+//! カーソルより後ろを切り捨てて閉じていない `{` を閉じたものの順に解析し直し、成功した結果で名前を解決する。
+//! 塗りつぶしてもバイト数は変わらないため、カーソルより前の位置はそのまま対応する。
+use serde::Serialize;
+
+use crate::common::{DelimiterToken, KeywordToken, LiteralToken, Span, TokenKind, TriviaToken};
+use crate::compiler::arena::AstArena;
+use crate::compiler::cst::{self, CstToken};
+use crate::compiler::resolver::{self, Resolution, Symbol, SymbolId, SymbolKind};
+
+/// 組み込みの型名
+pub const BUILTIN_TYPES: &[&str] = &["Int", "Float", "String", "Bool"];
+
+/// 文の先頭で有効なキーワード (`return` と `else` は位置によって加える)
+const STATEMENT_KEYWORDS: &[KeywordToken] = &[
+    KeywordToken::Function,
+    KeywordToken::Sub,
+    KeywordToken::Class,
+    KeywordToken::If,
+    KeywordToken::For,
+    KeywordToken::While,
+    KeywordToken::Const,
+    KeywordToken::True,
+    KeywordToken::False,
+    KeywordToken::Null,
+];
+
+const CLASS_MEMBER_KEYWORDS: &[KeywordToken] = &[
+    KeywordToken::Function,
+    KeywordToken::Sub,
+    KeywordToken::Readonly,
+];
+
+const EXPRESSION_KEYWORDS: &[KeywordToken] =
+    &[KeywordToken::True, KeywordToken::False, KeywordToken::Null];
+
+const OPERATOR_KEYWORDS: &[KeywordToken] = &[KeywordToken::And, KeywordToken::Or];
+
+/// `for (Int x` の直後のキーワード
+const FOR_EACH_KEYWORDS: &[KeywordToken] = &[KeywordToken::In, KeywordToken::Of];
+
+/// 補完する位置の文脈
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CompletionContext {
+    /// 文の先頭 (文のキーワード・名前・変数宣言の型名)
+    Statement,
+    /// クラス本体のメンバーの先頭 (メンバーのキーワードと型名)
+    ClassMember,
+    /// 式の途中 (名前と値のキーワード)
+    Expression,
+    /// 式の直後 (`and`・`or` などのキーワード)
+    Operator,
+    /// `.` の直後 (クラスのメンバー)
+    Member,
+    /// 型名を書く位置
+    Type,
+    /// 補完しない位置 (コメントや文字列の中、宣言する名前の位置など)
+    None,
+}
+
+/// 補完候補の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CompletionKind {
+    Keyword,
+    /// 組み込みの型
+    Type,
+    Variable,
+    /// `const` 宣言された変数
+    Constant,
+    Parameter,
+    /// 関数とサブルーチン
+    Function,
+    Class,
+    Field,
+    Method,
+}
+
+impl From<SymbolKind> for CompletionKind {
+    fn from(kind: SymbolKind) -> Self {
+        match kind {
+            SymbolKind::Variable => CompletionKind::Variable,
+            SymbolKind::Constant => CompletionKind::Constant,
+            SymbolKind::Parameter => CompletionKind::Parameter,
+            SymbolKind::Function | SymbolKind::Sub => CompletionKind::Function,
+            SymbolKind::Class => CompletionKind::Class,
+            SymbolKind::Field => CompletionKind::Field,
+            SymbolKind::Method => CompletionKind::Method,
+        }
+    }
+}
+
+/// 補完候補
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CompletionItem {
+    pub label: String,
+    pub kind: CompletionKind,
+    /// 宣言の表記 (例: `function Int add(Int a, Int b)`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// 補完の結果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CompletionList {
+    pub context: CompletionContext,
+    /// 候補で置き換える範囲 (入力中の名前、なければカーソル位置の空の範囲)
+    pub replace: Span,
+    /// 順位の高い順の候補
+    pub items: Vec<CompletionItem>,
+}
+
+/// `offset` (UTF-8のバイト位置) に入力する候補を求める
+///
+/// 候補はカーソルより前に入力された名前に前方一致するものに絞り込む (大文字小文字の違いは順位を下げて含める)。
+/// 一致の度合い → 名前・型名・キーワードの順 → 内側のスコープから順、に並べる。
+///
+/// ```
+/// use snowfall_core::compiler::completion::{CompletionContext, CompletionKind, complete};
+///
+/// let source = "class Point {\n    Int x;\n}\nPoint p;\nInt px = p.";
+/// let list = complete(source, source.len());
+/// assert_eq!(list.context, CompletionContext::Member);
+/// assert_eq!(list.items[0].label, "x");
+/// assert_eq!(list.items[0].kind, CompletionKind::Field);
+///
+/// // 構文エラーがあっても、その位置から参照できる名前を補完する
+/// let source = "Int count = 1;\nprint(co";
+/// let list = complete(source, source.len());
+/// assert_eq!(list.items[0].label, "count");
+/// assert_eq!(&source[list.replace.start..list.replace.end], "co");
+/// ```
+pub fn complete(source: &str, offset: usize) -> CompletionList {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+
+    let all_tokens = cst::lex_with_trivia(source);
+    if in_comment_or_string(&all_tokens, offset) {
+        return CompletionList {
+            context: CompletionContext::None,
+            replace: Span {
+                start: offset,
+                end: offset,
+            },
+            items: Vec::new(),
+        };
+    }
+    // 末尾の `Eof` を除く
+    let tokens = &all_tokens[..all_tokens.len() - 1];
+
+    let word = tokens.iter().position(|token| {
+        token.span.start < offset && offset <= token.span.end && is_word(&token.kind)
+    });
+    let (before, replace) = match word {
+        Some(index) => (&tokens[..index], tokens[index].span),
+        None => {
+            let count = tokens
+                .iter()
+                .take_while(|token| token.span.end <= offset)
+                .count();
+            (
+                &tokens[..count],
+                Span {
+                    start: offset,
+                    end: offset,
+                },
+            )
+        }
+    };
+    let prefix = &source[replace.start..offset];
+
+    let scan = Scan::new(before);
+    let site = Site::new(before, &scan);
+    let resolution = if site.needs_resolution() {
+        resolve_tolerant(source, tokens, &scan, offset)
+    } else {
+        None
+    };
+
+    CompletionList {
+        context: site.context,
+        replace,
+        items: rank(site.candidates(resolution.as_ref(), offset), prefix),
+    }
+}
+
+/// 候補になる名前のトークン (入力途中のキーワードを含む)
+fn is_word(kind: &TokenKind) -> bool {
+    matches!(kind, TokenKind::Identifier(_) | TokenKind::Keyword(_))
+}
+
+/// `offset` がコメントまたは文字列の中にあるかどうか
+fn in_comment_or_string(tokens: &[CstToken], offset: usize) -> bool {
+    tokens.iter().any(|token| {
+        let in_string = matches!(token.kind, TokenKind::Literal(LiteralToken::String(_)))
+            && token.span.start < offset
+            && offset < token.span.end;
+        in_string
+            || token
+                .leading_trivia
+                .iter()
+                .chain(&token.trailing_trivia)
+                .any(|trivia| {
+                    let span = trivia.span;
+                    if offset <= span.start || offset > span.end {
+                        return false;
+                    }
+                    match trivia.kind {
+                        TriviaToken::LineComment => true,
+                        // 閉じていないコメントは末尾まで続く
+                        TriviaToken::BlockComment => {
+                            offset < span.end
+                                || trivia.text.len() < 4
+                                || !trivia.text.ends_with("*/")
+                        }
+                        // 閉じていない文字列は字句解析のエラーとして読み飛ばされる
+                        TriviaToken::Skipped => {
+                            trivia.text.starts_with('"') || trivia.text.starts_with('\'')
+                        }
+                        _ => false,
+                    }
+                })
+    })
+}
+
+fn delimiter(token: &CstToken) -> Option<&DelimiterToken> {
+    match &token.kind {
+        TokenKind::Delimiter(delimiter) => Some(delimiter),
+        _ => None,
+    }
+}
+
+fn is_delimiter(token: Option<&CstToken>, expected: DelimiterToken) -> bool {
+    token.and_then(delimiter) == Some(&expected)
+}
+
+fn is_keyword(token: Option<&CstToken>, expected: KeywordToken) -> bool {
+    matches!(token.map(|token| &token.kind), Some(TokenKind::Keyword(keyword)) if *keyword == expected)
+}
+
+fn is_identifier(token: Option<&CstToken>) -> bool {
+    matches!(
+        token.map(|token| &token.kind),
+        Some(TokenKind::Identifier(_))
+    )
+}
+
+/// `{` で始まるブロックの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Brace {
+    /// 関数・サブルーチン・メソッドの本体
+    Function,
+    Class,
+    /// `if` 文の本体 (閉じた直後に `else` を書ける)
+    If,
+    /// オブジェクトリテラル
+    Object,
+    Other,
+}
+
+impl Brace {
+    /// `index` の `{` の種類を直前のトークンから判定する
+    fn at(tokens: &[CstToken], index: usize) -> Self {
+        let before = &tokens[..index];
+        match before.last().map(|token| &token.kind) {
+            Some(TokenKind::Operator(_))
+            | Some(TokenKind::Keyword(KeywordToken::Return))
+            | Some(TokenKind::Delimiter(
+                DelimiterToken::LParen
+                | DelimiterToken::Comma
+                | DelimiterToken::LBracket
+                | DelimiterToken::Colon,
+            )) => return Brace::Object,
+            _ => {}
+        }
+
+        // 文の区切りまで遡り、括弧の外にある最も近いキーワードで判定する
+        let mut depth = 0usize;
+        for token in before.iter().rev() {
+            match &token.kind {
+                TokenKind::Delimiter(DelimiterToken::RParen) => depth += 1,
+                TokenKind::Delimiter(DelimiterToken::LParen) => depth = depth.saturating_sub(1),
+                TokenKind::Delimiter(
+                    DelimiterToken::Semicolon | DelimiterToken::LBrace | DelimiterToken::RBrace,
+                ) if depth == 0 => break,
+                TokenKind::Keyword(keyword) if depth == 0 => match keyword {
+                    KeywordToken::Function | KeywordToken::Sub => return Brace::Function,
+                    KeywordToken::Class => return Brace::Class,
+                    KeywordToken::If => return Brace::If,
+                    KeywordToken::Else | KeywordToken::For | KeywordToken::While => {
+                        return Brace::Other;
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+        Brace::Other
+    }
+}
+
+/// カーソルより前のトークンの括弧の対応
+struct Scan {
+    /// 閉じていない `{` (外側から順)
+    braces: Vec<Brace>,
+    /// 閉じていない `(` の添字 (外側から順)
+    parens: Vec<usize>,
+    /// 最後に閉じた `{`
+    closed: Option<Brace>,
+    /// カーソルを含む文の最初のトークンの添字
+    statement_start: usize,
+    /// カーソルを含む文の外側で閉じていない `{` の数
+    statement_depth: usize,
+}
+
+impl Scan {
+    fn new(tokens: &[CstToken]) -> Self {
+        let mut scan = Scan {
+            braces: Vec::new(),
+            parens: Vec::new(),
+            closed: None,
+            statement_start: 0,
+            statement_depth: 0,
+        };
+        for (index, token) in tokens.iter().enumerate() {
+            let boundary = match delimiter(token) {
+                Some(DelimiterToken::LParen) => {
+                    scan.parens.push(index);
+                    false
+                }
+                Some(DelimiterToken::RParen) => {
+                    scan.parens.pop();
+                    false
+                }
+                Some(DelimiterToken::LBrace) => {
+                    scan.braces.push(Brace::at(tokens, index));
+                    true
+                }
+                Some(DelimiterToken::RBrace) => {
+                    scan.closed = scan.braces.pop();
+                    true
+                }
+                Some(DelimiterToken::Semicolon) => true,
+                _ => false,
+            };
+            // `for` の見出しの `;` などの括弧の中の区切りは文の区切りとみなさない
+            if boundary && scan.parens.is_empty() {
+                scan.statement_start = index + 1;
+                scan.statement_depth = scan.braces.len();
+            }
+        }
+        scan
+    }
+
+    /// 関数の本体の中かどうか
+    fn in_function(&self) -> bool {
+        self.braces.contains(&Brace::Function)
+    }
+}
+
+/// `index` の `(` が関数の引数リストを開くかどうか (`function Int f(` または `sub f(`)
+fn is_parameter_list(tokens: &[CstToken], index: usize) -> bool {
+    if index < 2 || !is_identifier(tokens.get(index - 1)) {
+        return false;
+    }
+    let before_name = tokens.get(index - 2);
+    is_keyword(before_name, KeywordToken::Sub)
+        || (is_identifier(before_name)
+            && index >= 3
+            && is_keyword(tokens.get(index - 3), KeywordToken::Function))
+}
+
+/// `.` の前のメンバーアクセスの連なりの要素 (例: `a.b().` の `a` と `b()`)
+#[derive(Debug)]
+struct Segment {
+    name: String,
+    call: bool,
+}
+
+/// `.` で終わるトークン列から、`.` の前の式を名前と呼び出しの連なりとして取り出す
+///
+/// 名前と呼び出し以外を含む式の場合は `None` を返す。
+fn receiver(tokens: &[CstToken]) -> Option<Vec<Segment>> {
+    let mut segments = Vec::new();
+    let mut dot = tokens.len() - 1;
+    loop {
+        let mut end = dot.checked_sub(1)?;
+        let mut call = false;
+        if is_delimiter(tokens.get(end), DelimiterToken::RParen) {
+            let mut depth = 0usize;
+            loop {
+                match delimiter(&tokens[end]) {
+                    Some(DelimiterToken::RParen) => depth += 1,
+                    Some(DelimiterToken::LParen) => {
+                        depth -= 1;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+                end = end.checked_sub(1)?;
+            }
+            end = end.checked_sub(1)?;
+            call = true;
+        }
+        let TokenKind::Identifier(name) = &tokens[end].kind else {
+            return None;
+        };
+        segments.push(Segment {
+            name: name.clone(),
+            call,
+        });
+        match end.checked_sub(1) {
+            Some(previous) if is_delimiter(tokens.get(previous), DelimiterToken::Dot) => {
+                dot = previous;
+            }
+            _ => break,
+        }
+    }
+    segments.reverse();
+    Some(segments)
+}
+
+/// 候補に含める型名
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Types {
+    None,
+    /// 組み込み型とクラス
+    All,
+    /// クラスのみ (`extends` の後)
+    Classes,
+}
+
+/// 補完する位置の判定結果
+struct Site {
+    context: CompletionContext,
+    keywords: Vec<KeywordToken>,
+    /// 参照できる名前 (クラスを除く) を含めるかどうか
+    names: bool,
+    types: Types,
+    /// `Member` の場合の `.` の前の式
+    receiver: Option<Vec<Segment>>,
+}
+
+impl Site {
+    fn new(tokens: &[CstToken], scan: &Scan) -> Self {
+        let Some(last) = tokens.last() else {
+            return Self::statement(scan, false);
+        };
+        match &last.kind {
+            TokenKind::Delimiter(DelimiterToken::Dot) => Site {
+                receiver: receiver(tokens),
+                ..Self::of(CompletionContext::Member, &[])
+            },
+            TokenKind::Delimiter(DelimiterToken::Semicolon) if !scan.parens.is_empty() => {
+                Self::expression()
+            }
+            TokenKind::Delimiter(DelimiterToken::LBrace)
+                if scan.braces.last() == Some(&Brace::Object) =>
+            {
+                Self::of(CompletionContext::None, &[])
+            }
+            TokenKind::Delimiter(DelimiterToken::Semicolon | DelimiterToken::LBrace) => {
+                Self::statement(scan, false)
+            }
+            TokenKind::Delimiter(DelimiterToken::RBrace) => {
+                Self::statement(scan, scan.closed == Some(Brace::If))
+            }
+            TokenKind::Delimiter(DelimiterToken::LParen) => {
+                let index = tokens.len() - 1;
+                if is_parameter_list(tokens, index) {
+                    Self::types(Types::All)
+                } else if index > 0 && is_keyword(tokens.get(index - 1), KeywordToken::For) {
+                    // `for (` の直後は変数宣言も書ける
+                    Site {
+                        types: Types::All,
+                        ..Self::expression()
+                    }
+                } else {
+                    Self::expression()
+                }
+            }
+            TokenKind::Delimiter(DelimiterToken::Comma) => match scan.parens.last() {
+                Some(&paren) if is_parameter_list(tokens, paren) => Self::types(Types::All),
+                _ => Self::expression(),
+            },
+            TokenKind::Delimiter(DelimiterToken::RParen) => {
+                if Self::closes_header(tokens) {
+                    Self::of(CompletionContext::None, &[])
+                } else {
+                    Self::of(CompletionContext::Operator, OPERATOR_KEYWORDS)
+                }
+            }
+            TokenKind::Delimiter(DelimiterToken::RBracket) | TokenKind::Literal(_) => {
+                Self::of(CompletionContext::Operator, OPERATOR_KEYWORDS)
+            }
+            TokenKind::Delimiter(_) | TokenKind::Operator(_) => Self::expression(),
+            TokenKind::Keyword(keyword) => match keyword {
+                KeywordToken::Else => Self::statement(scan, false),
+                KeywordToken::Function | KeywordToken::Const | KeywordToken::Readonly => {
+                    Self::types(Types::All)
+                }
+                KeywordToken::Extends => Self::types(Types::Classes),
+                KeywordToken::Return
+                | KeywordToken::In
+                | KeywordToken::Of
+                | KeywordToken::And
+                | KeywordToken::Or => Self::expression(),
+                KeywordToken::True | KeywordToken::False | KeywordToken::Null => {
+                    Self::of(CompletionContext::Operator, OPERATOR_KEYWORDS)
+                }
+                _ => Self::of(CompletionContext::None, &[]),
+            },
+            TokenKind::Identifier(_) => Self::after_identifier(tokens),
+            TokenKind::Trivia(_) | TokenKind::Eof => Self::of(CompletionContext::None, &[]),
+        }
+    }
+
+    fn of(context: CompletionContext, keywords: &[KeywordToken]) -> Self {
+        Site {
+            context,
+            keywords: keywords.to_vec(),
+            names: false,
+            types: Types::None,
+            receiver: None,
+        }
+    }
+
+    fn expression() -> Self {
+        Site {
+            names: true,
+            ..Self::of(CompletionContext::Expression, EXPRESSION_KEYWORDS)
+        }
+    }
+
+    fn types(types: Types) -> Self {
+        Site {
+            types,
+            ..Self::of(CompletionContext::Type, &[])
+        }
+    }
+
+    /// 文の先頭 (クラス本体の中ではメンバーの先頭)
+    fn statement(scan: &Scan, after_if: bool) -> Self {
+        if scan.braces.last() == Some(&Brace::Class) {
+            return Site {
+                types: Types::All,
+                ..Self::of(CompletionContext::ClassMember, CLASS_MEMBER_KEYWORDS)
+            };
+        }
+        let mut keywords = STATEMENT_KEYWORDS.to_vec();
+        if scan.in_function() {
+            keywords.push(KeywordToken::Return);
+        }
+        if after_if {
+            keywords.push(KeywordToken::Else);
+        }
+        Site {
+            context: CompletionContext::Statement,
+            keywords,
+            names: true,
+            types: Types::All,
+            receiver: None,
+        }
+    }
+
+    /// 最後の `)` が `if (...)` などの見出しや引数リストを閉じるかどうか
+    fn closes_header(tokens: &[CstToken]) -> bool {
+        // 最後の `)` に対応する `(` を探す
+        let mut depth = 0usize;
+        let Some(open) = tokens.iter().rposition(|token| {
+            match delimiter(token) {
+                Some(DelimiterToken::RParen) => depth += 1,
+                Some(DelimiterToken::LParen) => {
+                    depth -= 1;
+                    return depth == 0;
+                }
+                _ => {}
+            }
+            false
+        }) else {
+            return false;
+        };
+        let keyword = open.checked_sub(1).map(|index| &tokens[index].kind);
+        matches!(
+            keyword,
+            Some(TokenKind::Keyword(
+                KeywordToken::If | KeywordToken::While | KeywordToken::For
+            ))
+        ) || is_parameter_list(tokens, open)
+    }
+
+    /// 識別子の直後
+    ///
+    /// 識別子が宣言の型名であれば続きは宣言する名前のため補完しない。
+    fn after_identifier(tokens: &[CstToken]) -> Self {
+        let index = tokens.len() - 1;
+        let previous = index.checked_sub(1).map(|i| &tokens[i]);
+        let is_type_position = match previous.map(|token| &token.kind) {
+            None => true,
+            Some(TokenKind::Delimiter(
+                DelimiterToken::Semicolon | DelimiterToken::LBrace | DelimiterToken::RBrace,
+            )) => true,
+            Some(TokenKind::Keyword(
+                KeywordToken::Const | KeywordToken::Readonly | KeywordToken::Function,
+            )) => true,
+            Some(TokenKind::Delimiter(DelimiterToken::LParen)) => {
+                is_parameter_list(tokens, index - 1)
+                    || is_keyword(index.checked_sub(2).map(|i| &tokens[i]), KeywordToken::For)
+            }
+            Some(TokenKind::Identifier(_)) => {
+                // `for (Int x` の後は `in` または `of`
+                let open = index.checked_sub(2);
+                let is_for_each = open.is_some_and(|open| {
+                    is_delimiter(tokens.get(open), DelimiterToken::LParen)
+                        && open > 0
+                        && is_keyword(tokens.get(open - 1), KeywordToken::For)
+                });
+                return if is_for_each {
+                    Self::of(CompletionContext::Operator, FOR_EACH_KEYWORDS)
+                } else {
+                    Self::of(CompletionContext::None, &[])
+                };
+            }
+            _ => false,
+        };
+        if is_type_position {
+            Self::of(CompletionContext::None, &[])
+        } else {
+            Self::of(CompletionContext::Operator, OPERATOR_KEYWORDS)
+        }
+    }
+
+    fn needs_resolution(&self) -> bool {
+        self.names || self.types != Types::None || self.receiver.is_some()
+    }
+
+    /// 候補と分類 (名前: 0, 型名: 1, キーワード: 2)
+    fn candidates(
+        &self,
+        resolution: Option<&Resolution>,
+        offset: usize,
+    ) -> Vec<(u8, CompletionItem)> {
+        let mut candidates = Vec::new();
+        let symbol_item = |symbol: &Symbol| CompletionItem {
+            label: symbol.name.clone(),
+            kind: symbol.kind.into(),
+            detail: Some(symbol.signature.clone()),
+        };
+
+        if let Some(resolution) = resolution {
+            if let Some(class) = self
+                .receiver
+                .as_deref()
+                .and_then(|segments| receiver_class(resolution, offset, segments))
+            {
+                for member in resolution.members(class) {
+                    candidates.push((0, symbol_item(resolution.symbol(member))));
+                }
+            }
+
+            let visible = resolution.visible_at(offset);
+            if self.names {
+                for &id in &visible {
+                    let symbol = resolution.symbol(id);
+                    if symbol.kind != SymbolKind::Class {
+                        candidates.push((0, symbol_item(symbol)));
+                    }
+                }
+            }
+            if self.types != Types::None {
+                for &id in &visible {
+                    let symbol = resolution.symbol(id);
+                    if symbol.kind == SymbolKind::Class {
+                        candidates.push((1, symbol_item(symbol)));
+                    }
+                }
+            }
+        }
+        if self.types == Types::All {
+            candidates.extend(BUILTIN_TYPES.iter().map(|name| {
+                let item = CompletionItem {
+                    label: name.to_string(),
+                    kind: CompletionKind::Type,
+                    detail: None,
+                };
+                (1, item)
+            }));
+        }
+        candidates.extend(self.keywords.iter().map(|keyword| {
+            let item = CompletionItem {
+                label: keyword.as_str().to_string(),
+                kind: CompletionKind::Keyword,
+                detail: None,
+            };
+            (2, item)
+        }));
+        candidates
+    }
+}
+
+/// `.` の前の式の型のクラス
+fn receiver_class(
+    resolution: &Resolution,
+    offset: usize,
+    segments: &[Segment],
+) -> Option<SymbolId> {
+    let (first, rest) = segments.split_first()?;
+    let mut type_name = if first.name == "this" && !first.call {
+        // `this` は囲んでいるクラス (継承元のメンバーも含む)
+        let class = resolution.enclosing_class(offset)?;
+        resolution.symbol(class).name.as_str()
+    } else {
+        let symbol = resolution
+            .visible_at(offset)
+            .into_iter()
+            .find(|&id| resolution.symbol(id).name == first.name)?;
+        value_type(resolution.symbol(symbol), first.call)?
+    };
+    for segment in rest {
+        let class = resolution.class_named(type_name)?;
+        let member = resolution
+            .members(class)
+            .into_iter()
+            .find(|&id| resolution.symbol(id).name == segment.name)?;
+        type_name = value_type(resolution.symbol(member), segment.call)?;
+    }
+    resolution.class_named(type_name)
+}
+
+/// 名前の値の型 (関数は呼び出した場合の戻り値の型)
+fn value_type(symbol: &Symbol, call: bool) -> Option<&str> {
+    let callable = matches!(
+        symbol.kind,
+        SymbolKind::Function | SymbolKind::Sub | SymbolKind::Method
+    );
+    match symbol.kind {
+        SymbolKind::Class => None,
+        _ if callable == call => symbol.type_name.as_deref(),
+        _ => None,
+    }
+}
+
+/// 入力中の名前で候補を絞り込み、順位の高い順に並べる (同じ名前は最初の候補のみ残す)
+fn rank(candidates: Vec<(u8, CompletionItem)>, prefix: &str) -> Vec<CompletionItem> {
+    let lower = prefix.to_lowercase();
+    let mut ranked: Vec<((u8, u8), CompletionItem)> = candidates
+        .into_iter()
+        .filter_map(|(category, item)| {
+            let exact = if item.label.starts_with(prefix) {
+                0
+            } else if item.label.to_lowercase().starts_with(&lower) {
+                1
+            } else {
+                return None;
+            };
+            Some(((exact, category), item))
+        })
+        .collect();
+    ranked.sort_by_key(|(key, _)| *key);
+
+    let mut items: Vec<CompletionItem> = Vec::new();
+    for (_, item) in ranked {
+        if items.iter().all(|existing| existing.label != item.label) {
+            items.push(item);
+        }
+    }
+    items
+}
+
+/// 名前解決の結果を求める
+///
+/// そのままでは構文解析に失敗する場合、カーソルを含む文を空白で塗りつぶしたもの、
+/// カーソルより後ろを切り捨てて閉じていない `{` を閉じたものの順に解析し直す。
+fn resolve_tolerant(
+    source: &str,
+    tokens: &[CstToken],
+    scan: &Scan,
+    offset: usize,
+) -> Option<Resolution> {
+    let parse = |text: &str| {
+        AstArena::parse(text)
+            .ok()
+            .map(|arena| resolver::resolve(&arena, text))
+    };
+    if let Some(resolution) = parse(source) {
+        return Some(resolution);
+    }
+
+    let start = tokens
+        .get(scan.statement_start)
+        .map_or(offset, |token| token.span.start.min(offset));
+    if start < offset {
+        let end = statement_end(tokens, scan.statement_start)
+            .unwrap_or(source.len())
+            .max(offset);
+        let blanked = format!(
+            "{}{}{}",
+            &source[..start],
+            blank(&source[start..end]),
+            &source[end..]
+        );
+        if let Some(resolution) = parse(&blanked) {
+            return Some(resolution);
+        }
+    }
+
+    let truncated = format!(
+        "{}{}{}",
+        &source[..start],
+        blank(&source[start..offset]),
+        "}".repeat(scan.statement_depth)
+    );
+    parse(&truncated)
+}
+
+/// `start` から始まる文の終了位置 (`;` の直後、または文を閉じる `{`・`}` の直前)
+fn statement_end(tokens: &[CstToken], start: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for token in tokens.get(start..)? {
+        match delimiter(token) {
+            Some(DelimiterToken::LParen) => depth += 1,
+            Some(DelimiterToken::RParen) => depth = depth.saturating_sub(1),
+            Some(DelimiterToken::Semicolon) if depth == 0 => return Some(token.span.end),
+            Some(DelimiterToken::LBrace | DelimiterToken::RBrace) if depth == 0 => {
+                return Some(token.span.start);
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 改行以外を空白に置き換える (バイト数は変えない)
+fn blank(text: &str) -> String {
+    text.bytes()
+        .map(|byte| if byte == b'\n' { '\n' } else { ' ' })
+        .collect()
+}
@@ -0,0 +1,369 @@
+//! 具象構文木 (Concrete Syntax Tree)
+//!
+//! 空白・改行・コメントをトリビアとしてトークンに付与し、
+//! 元のソースコードをバイト単位で完全に復元できる木構造を提供する。
+//! 各ノードは対応する `ProgramAst` のノードを参照できる。
+use ahash::AHashMap;
+use serde::Serialize;
+
+use crate::common::{Span, Token, TokenKind, TriviaToken};
+use crate::compiler::Lexer;
+use crate::compiler::ast::*;
+
+/// トークンに付随するトリビア
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Trivia {
+    pub kind: TriviaToken,
+    pub text: String,
+    pub span: Span,
+}
+
+/// 前後のトリビアを保持するトークン
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CstToken {
+    pub kind: TokenKind,
+    pub text: String,
+    pub span: Span,
+    /// トークンの直前にあるトリビア (前のトークンの行末以降)
+    pub leading_trivia: Vec<Trivia>,
+    /// トークンの直後から行末 (改行の手前) までのトリビア
+    pub trailing_trivia: Vec<Trivia>,
+}
+
+impl CstToken {
+    /// トリビアを含めたテキストを書き出す
+    fn write_to(&self, out: &mut String) {
+        for trivia in &self.leading_trivia {
+            out.push_str(&trivia.text);
+        }
+        out.push_str(&self.text);
+        for trivia in &self.trailing_trivia {
+            out.push_str(&trivia.text);
+        }
+    }
+}
+
+/// CSTノードの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CstNodeKind {
+    Program,
+    Statement,
+    Expression,
+}
+
+/// CSTの子要素
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", content = "value")]
+pub enum CstElement {
+    Node(CstNode),
+    Token(CstToken),
+}
+
+/// CSTのノード
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CstNode {
+    pub kind: CstNodeKind,
+    /// 対応するASTノードの位置情報 (トリビアを含まない)
+    pub span: Span,
+    /// 対応するASTノードの識別子 (ASTなしで構築された場合は `None`)
+    pub ast_id: Option<NodeId>,
+    pub children: Vec<CstElement>,
+}
+
+impl CstNode {
+    /// トリビアを含めたノードのテキストを返す
+    pub fn text(&self) -> String {
+        let mut out = String::new();
+        self.write_to(&mut out);
+        out
+    }
+
+    fn write_to(&self, out: &mut String) {
+        for child in &self.children {
+            match child {
+                CstElement::Node(node) => node.write_to(out),
+                CstElement::Token(token) => token.write_to(out),
+            }
+        }
+    }
+
+    /// 子ノードのみを列挙する
+    pub fn child_nodes(&self) -> impl Iterator<Item = &CstNode> {
+        self.children.iter().filter_map(|child| match child {
+            CstElement::Node(node) => Some(node),
+            CstElement::Token(_) => None,
+        })
+    }
+
+    /// このノードに対応する `ProgramAst` 内のノードを返す
+    ///
+    /// `table` はCSTの構築に使用したASTから作成したものである必要がある
+    pub fn ast_node<'p>(&self, table: &AstNodeTable<'p>) -> Option<AstNodeRef<'p>> {
+        table.get(self.ast_id?)
+    }
+}
+
+/// `NodeId` から `ProgramAst` 内のノードを引く表
+///
+/// ASTを一度だけ走査して構築するため、CSTの全ノードを対応付けても線形時間で済む
+///
+/// ```
+/// use snowfall_core::compiler::cst::{AstNodeTable, ConcreteSyntaxTree};
+/// use snowfall_core::compiler::{Lexer, Parser};
+///
+/// let source = "Int a = 1 + 2;";
+/// let program = Parser::new(Lexer::new(source)).parse_program().unwrap();
+/// let cst = ConcreteSyntaxTree::build(source, Some(&program));
+/// let table = AstNodeTable::new(&program);
+///
+/// let stmt = cst.root.child_nodes().next().unwrap();
+/// let node = stmt.ast_node(&table).unwrap();
+/// assert_eq!(node.span(), program.statements[0].span);
+/// ```
+pub struct AstNodeTable<'p> {
+    nodes: AHashMap<NodeId, AstNodeRef<'p>>,
+}
+
+impl<'p> AstNodeTable<'p> {
+    pub fn new(program: &'p ProgramAst) -> Self {
+        let nodes = collect_ast_nodes(program)
+            .into_iter()
+            .map(|node| (node.id(), node))
+            .collect();
+        AstNodeTable { nodes }
+    }
+
+    /// 識別子に対応するノード
+    pub fn get(&self, id: NodeId) -> Option<AstNodeRef<'p>> {
+        self.nodes.get(&id).copied()
+    }
+}
+
+/// 具象構文木
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConcreteSyntaxTree {
+    pub root: CstNode,
+}
+
+impl ConcreteSyntaxTree {
+    /// ソースコードとそのASTからCSTを構築する
+    ///
+    /// `program` が `None` の場合 (構文エラー時など) はトークンのみを持つ平坦な木になる
+    pub fn build(source: &str, program: Option<&ProgramAst>) -> Self {
+        let tokens = lex_with_trivia(source);
+        let nodes: Vec<(CstNodeKind, Span, NodeId)> = match program {
+            Some(program) => collect_ast_nodes(program)
+                .into_iter()
+                .map(|node| {
+                    let kind = match node {
+                        AstNodeRef::Program(_) => CstNodeKind::Program,
+                        AstNodeRef::Statement(_) => CstNodeKind::Statement,
+                        AstNodeRef::Expression(_) => CstNodeKind::Expression,
+                    };
+                    (kind, node.span(), node.id())
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let mut builder = TreeBuilder {
+            tokens: tokens.into_iter().peekable(),
+            nodes: nodes.into_iter().skip(1).peekable(),
+        };
+        let root_span = Span {
+            start: 0,
+            end: source.len(),
+        };
+        let ast_id = program.map(|program| program.id);
+        let root = builder.build_node(CstNodeKind::Program, root_span, ast_id);
+        ConcreteSyntaxTree { root }
+    }
+
+    /// CSTから元のソースコードを復元する
+    pub fn to_source(&self) -> String {
+        self.root.text()
+    }
+}
+
+/// トリビア出力モードで字句解析し、トリビアを各トークンへ付与する
+///
+/// 最後の要素は常に `Eof` トークンで、ファイル末尾のトリビアを保持する
+pub fn lex_with_trivia(source: &str) -> Vec<CstToken> {
+    let mut lexer = Lexer::new_with_trivia(source);
+    let mut raw: Vec<Token> = Vec::new();
+
+    loop {
+        let start = lexer.position();
+        match lexer.next_token() {
+            Ok(token) if token.kind == TokenKind::Eof => {
+                // NUL文字は入力終端として扱われるため、残りは読み飛ばし扱いにする
+                if start < source.len() {
+                    push_skipped(&mut raw, start, source.len());
+                }
+                break;
+            }
+            Ok(token) => {
+                let end = lexer.position();
+                raw.push(Token {
+                    kind: token.kind,
+                    span: Span { start, end },
+                });
+            }
+            Err(_) => push_skipped(&mut raw, start, lexer.position()),
+        }
+    }
+
+    let mut tokens = Vec::new();
+    let mut leading: Vec<Trivia> = Vec::new();
+    let mut iter = raw.into_iter().peekable();
+    while let Some(token) = iter.next() {
+        if let TokenKind::Trivia(kind) = token.kind {
+            leading.push(make_trivia(source, kind, token.span));
+            continue;
+        }
+
+        // 行末 (改行の手前) までのトリビアは後続トリビアとして扱う
+        let mut trailing = Vec::new();
+        while let Some(Token {
+            kind: TokenKind::Trivia(kind),
+            ..
+        }) = iter.peek()
+        {
+            if *kind == TriviaToken::Newline {
+                break;
+            }
+            let next = iter.next().unwrap();
+            if let TokenKind::Trivia(kind) = next.kind {
+                trailing.push(make_trivia(source, kind, next.span));
+            }
+        }
+
+        tokens.push(CstToken {
+            text: source[token.span.start..token.span.end].to_string(),
+            kind: token.kind,
+            span: token.span,
+            leading_trivia: std::mem::take(&mut leading),
+            trailing_trivia: trailing,
+        });
+    }
+
+    tokens.push(CstToken {
+        kind: TokenKind::Eof,
+        text: String::new(),
+        span: Span {
+            start: source.len(),
+            end: source.len(),
+        },
+        leading_trivia: leading,
+        trailing_trivia: Vec::new(),
+    });
+    tokens
+}
+
+/// 読み飛ばされた範囲を記録する (直前も読み飛ばしであれば結合する)
+///
+/// マルチバイト文字は1バイトずつエラーになるため、結合することで文字境界を保つ
+fn push_skipped(raw: &mut Vec<Token>, start: usize, end: usize) {
+    if let Some(last) = raw.last_mut()
+        && last.kind == TokenKind::Trivia(TriviaToken::Skipped)
+        && last.span.end == start
+    {
+        last.span.end = end;
+        return;
+    }
+    raw.push(Token {
+        kind: TokenKind::Trivia(TriviaToken::Skipped),
+        span: Span { start, end },
+    });
+}
+
+fn make_trivia(source: &str, kind: TriviaToken, span: Span) -> Trivia {
+    Trivia {
+        kind,
+        text: source[span.start..span.end].to_string(),
+        span,
+    }
+}
+
+/// ASTノードの範囲に従ってトークンを木構造へ組み立てる
+struct TreeBuilder<T, N>
+where
+    T: Iterator<Item = CstToken>,
+    N: Iterator<Item = (CstNodeKind, Span, NodeId)>,
+{
+    tokens: std::iter::Peekable<T>,
+    nodes: std::iter::Peekable<N>,
+}
+
+impl<T, N> TreeBuilder<T, N>
+where
+    T: Iterator<Item = CstToken>,
+    N: Iterator<Item = (CstNodeKind, Span, NodeId)>,
+{
+    fn build_node(&mut self, kind: CstNodeKind, span: Span, ast_id: Option<NodeId>) -> CstNode {
+        let mut children = Vec::new();
+        loop {
+            let next_token_start = self
+                .tokens
+                .peek()
+                .map_or(usize::MAX, |token| token.span.start);
+
+            // 次のASTノードがこのノードの内側から始まるなら子ノードとして構築する
+            if let Some(&(_, child_span, _)) = self.nodes.peek()
+                && child_span.start <= next_token_start
+                && child_span.start >= span.start
+                && child_span.end <= span.end
+            {
+                let (child_kind, child_span, child_id) = self.nodes.next().unwrap();
+                children.push(CstElement::Node(self.build_node(
+                    child_kind,
+                    child_span,
+                    Some(child_id),
+                )));
+                continue;
+            }
+
+            match self.tokens.peek() {
+                Some(token) if token.span.end <= span.end => {
+                    children.push(CstElement::Token(self.tokens.next().unwrap()));
+                }
+                _ => break,
+            }
+        }
+        CstNode {
+            kind,
+            span,
+            ast_id,
+            children,
+        }
+    }
+}
+
+/// ASTの全ノードを前順 (親が先、子はソース上の出現順) で列挙する
+fn collect_ast_nodes(program: &ProgramAst) -> Vec<AstNodeRef<'_>> {
+    let mut collector = AstNodeCollector { nodes: Vec::new() };
+    collector.visit_program(program);
+    collector.nodes
+}
+
+/// 訪問したノードを順に記録する
+struct AstNodeCollector<'a> {
+    nodes: Vec<AstNodeRef<'a>>,
+}
+
+impl<'a> Visitor<'a> for AstNodeCollector<'a> {
+    fn visit_program(&mut self, program: &'a ProgramAst) {
+        self.nodes.push(AstNodeRef::Program(program));
+        walk_program(self, program);
+    }
+
+    fn visit_statement(&mut self, stmt: &'a Statement) {
+        self.nodes.push(AstNodeRef::Statement(stmt));
+        walk_statement(self, stmt);
+    }
+
+    fn visit_expression(&mut self, expr: &'a Expression) {
+        self.nodes.push(AstNodeRef::Expression(expr));
+        walk_expression(self, expr);
+    }
+}
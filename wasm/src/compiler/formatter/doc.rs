@@ -0,0 +1,155 @@
+//! 整形用の中間表現 (Wadler方式の簡易プリティプリンタ)
+//!
+//! `Group` 内が1行に収まる場合は `Line` を空白として、
+//! 収まらない場合は改行として出力する。
+
+/// 整形用ドキュメント
+#[derive(Debug, Clone)]
+pub enum Doc {
+    /// そのまま出力する文字列 (改行を含まないこと)
+    Text(String),
+    /// 1行に収まる場合は空白、収まらない場合は改行
+    Line,
+    /// 1行に収まる場合は何も出力せず、収まらない場合は改行
+    SoftLine,
+    /// 常に改行
+    HardLine,
+    /// 連結
+    Concat(Vec<Doc>),
+    /// 1行に収まるかどうかを判定する単位
+    Group(Box<Doc>),
+    /// 改行後のインデントを1段深くする
+    Indent(Box<Doc>),
+}
+
+impl Doc {
+    pub fn text(s: impl Into<String>) -> Doc {
+        Doc::Text(s.into())
+    }
+
+    pub fn concat(docs: Vec<Doc>) -> Doc {
+        Doc::Concat(docs)
+    }
+
+    pub fn group(doc: Doc) -> Doc {
+        Doc::Group(Box::new(doc))
+    }
+
+    pub fn indent(doc: Doc) -> Doc {
+        Doc::Indent(Box::new(doc))
+    }
+
+    /// 区切り文書を挟んで連結する
+    pub fn join(docs: Vec<Doc>, separator: Doc) -> Doc {
+        let mut out = Vec::with_capacity(docs.len() * 2);
+        for (i, doc) in docs.into_iter().enumerate() {
+            if i > 0 {
+                out.push(separator.clone());
+            }
+            out.push(doc);
+        }
+        Doc::Concat(out)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+/// ドキュメントを文字列へ出力する
+pub fn print(doc: &Doc, indent_width: usize, max_width: usize) -> String {
+    let mut out = String::new();
+    let mut column = 0;
+    let mut stack: Vec<(usize, Mode, &Doc)> = vec![(0, Mode::Break, doc)];
+
+    while let Some((indent, mode, doc)) = stack.pop() {
+        match doc {
+            Doc::Text(s) => {
+                out.push_str(s);
+                column += s.chars().count();
+            }
+            Doc::Line | Doc::SoftLine if mode == Mode::Flat => {
+                if matches!(doc, Doc::Line) {
+                    out.push(' ');
+                    column += 1;
+                }
+            }
+            Doc::Line | Doc::SoftLine | Doc::HardLine => {
+                // 行末の空白は出力しない
+                while out.ends_with(' ') {
+                    out.pop();
+                }
+                out.push('\n');
+                out.push_str(&" ".repeat(indent));
+                column = indent;
+            }
+            Doc::Concat(docs) => {
+                for d in docs.iter().rev() {
+                    stack.push((indent, mode, d));
+                }
+            }
+            Doc::Indent(d) => stack.push((indent + indent_width, mode, d)),
+            Doc::Group(d) => {
+                let next_mode =
+                    if mode == Mode::Flat || fits(d, max_width.saturating_sub(column), &stack) {
+                        Mode::Flat
+                    } else {
+                        Mode::Break
+                    };
+                stack.push((indent, next_mode, d));
+            }
+        }
+    }
+    out
+}
+
+/// `doc` を平坦に出力したとき、後続を含めて次の改行までが `width` に収まるかを判定する
+fn fits(doc: &Doc, width: usize, rest: &[(usize, Mode, &Doc)]) -> bool {
+    let mut remaining = width as isize;
+    let mut stack: Vec<(Mode, &Doc)> = vec![(Mode::Flat, doc)];
+    let mut rest_index = rest.len();
+    let mut in_rest = false;
+
+    loop {
+        let (mode, doc) = match stack.pop() {
+            Some(item) => item,
+            None => {
+                // グループの後続 (同じ行に続く部分) も考慮する
+                if rest_index == 0 {
+                    return true;
+                }
+                rest_index -= 1;
+                in_rest = true;
+                let (_, mode, doc) = rest[rest_index];
+                (mode, doc)
+            }
+        };
+        match doc {
+            Doc::Text(s) => {
+                remaining -= s.chars().count() as isize;
+                if remaining < 0 {
+                    return false;
+                }
+            }
+            Doc::Line if mode == Mode::Flat => {
+                remaining -= 1;
+                if remaining < 0 {
+                    return false;
+                }
+            }
+            Doc::SoftLine if mode == Mode::Flat => {}
+            // 強制改行を含むグループは1行に収まらない
+            Doc::HardLine if !in_rest => return false,
+            Doc::Line | Doc::SoftLine | Doc::HardLine => return true,
+            Doc::Concat(docs) => {
+                for d in docs.iter().rev() {
+                    stack.push((mode, d));
+                }
+            }
+            Doc::Indent(d) => stack.push((mode, d)),
+            Doc::Group(d) => stack.push((mode, d)),
+        }
+    }
+}
@@ -0,0 +1,894 @@
+//! ソースコードフォーマッタ
+//!
+//! `ProgramAst` を正規の書式のSnowFallソースへ整形する。
+//! コメントはトリビアから収集し、文の前または行末に配置し直す。
+//! 整形結果を再度整形しても結果は変わらない (冪等)。
+pub mod doc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::error::SnowFallError;
+use crate::common::messages::Locale;
+use crate::common::{Span, TriviaToken};
+use crate::compiler::ast::*;
+use crate::compiler::cst::{Trivia, lex_with_trivia};
+use crate::compiler::parser::Precedence;
+use crate::compiler::{Lexer, Parser};
+
+use self::doc::Doc;
+
+/// 波括弧の配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BraceStyle {
+    /// 宣言・制御文と同じ行に置く `if (a) {`
+    SameLine,
+    /// 次の行に置く
+    NextLine,
+}
+
+/// フォーマッタの設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormatOptions {
+    /// インデント幅 (空白数)
+    pub indent_width: usize,
+    /// 1行の最大幅 (超える場合は引数や要素を折り返す)
+    pub max_line_width: usize,
+    /// 波括弧の配置
+    pub brace_style: BraceStyle,
+    /// 構文エラーのメッセージの言語
+    pub locale: Locale,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent_width: 4,
+            max_line_width: 100,
+            brace_style: BraceStyle::SameLine,
+            locale: Locale::default(),
+        }
+    }
+}
+
+/// ソースコードを整形する
+///
+/// 構文エラーがある場合は整形せずにエラーを返す
+pub fn format(source: &str, options: &FormatOptions) -> Result<String, Vec<SnowFallError>> {
+    let lexer = Lexer::new(source);
+    let mut parser = Parser::new(lexer);
+    let program = parser.parse_program()?;
+
+    let comments: Vec<Trivia> = lex_with_trivia(source)
+        .into_iter()
+        .flat_map(|token| {
+            token
+                .leading_trivia
+                .into_iter()
+                .chain(token.trailing_trivia)
+        })
+        .filter(|trivia| {
+            matches!(
+                trivia.kind,
+                TriviaToken::LineComment | TriviaToken::BlockComment
+            )
+        })
+        .collect();
+
+    Ok(format_program(&program, source, comments, options))
+}
+
+/// 解析済みのプログラムを整形する
+///
+/// `source` はコメント位置や空行、リテラルの元の表記を参照するために使用する。
+/// 空文字列を渡した場合はASTのみから出力する。
+pub(crate) fn format_program(
+    program: &ProgramAst,
+    source: &str,
+    comments: Vec<Trivia>,
+    options: &FormatOptions,
+) -> String {
+    let mut formatter = Formatter {
+        source,
+        options,
+        comments,
+        next_comment: 0,
+    };
+    let body = formatter.statement_list(&program.statements, source.len());
+
+    let mut out = doc::print(&body, options.indent_width, options.max_line_width);
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+/// 整形処理の内部状態
+struct Formatter<'a> {
+    source: &'a str,
+    options: &'a FormatOptions,
+    /// ソース中の全コメント (出現順)
+    comments: Vec<Trivia>,
+    /// 次に出力するコメントの位置
+    next_comment: usize,
+}
+
+impl Formatter<'_> {
+    // ===== コメント =====
+
+    /// `pos` より前にある未出力のコメントを取り出す
+    fn take_comments_before(&mut self, pos: usize) -> Vec<Trivia> {
+        let start = self.next_comment;
+        while self.next_comment < self.comments.len()
+            && self.comments[self.next_comment].span.start < pos
+        {
+            self.next_comment += 1;
+        }
+        self.comments[start..self.next_comment].to_vec()
+    }
+
+    /// `end` と同じ行にあり `limit` より前にある後続の行コメントを取り出す
+    fn take_trailing_line_comment(&mut self, end: usize, limit: usize) -> Option<Trivia> {
+        let comment = self.comments.get(self.next_comment)?;
+        if comment.span.start >= limit || comment.kind != TriviaToken::LineComment {
+            return None;
+        }
+        self.take_trailing_comment(end)
+    }
+
+    /// `end` と同じ行にある後続コメントを取り出す
+    fn take_trailing_comment(&mut self, end: usize) -> Option<Trivia> {
+        let comment = self.comments.get(self.next_comment)?;
+        match self.source.get(end..comment.span.start) {
+            Some(between) if !between.contains('\n') => {}
+            _ => return None,
+        }
+        self.next_comment += 1;
+        Some(comment.clone())
+    }
+
+    /// `pos` より前にある未出力のコメントを、直前のトークンに続けて出力する
+    ///
+    /// 最後が行コメントの場合は真を返す (後続のトークンは次の行に置く必要がある)
+    fn trailing_comments(&mut self, pos: usize) -> (Doc, bool) {
+        let mut parts = Vec::new();
+        let mut after_line_comment = false;
+        for comment in self.take_comments_before(pos) {
+            parts.push(if after_line_comment {
+                Doc::HardLine
+            } else {
+                Doc::text(" ")
+            });
+            parts.push(comment_doc(&comment));
+            after_line_comment = comment.kind == TriviaToken::LineComment;
+        }
+        (Doc::concat(parts), after_line_comment)
+    }
+
+    /// `pos` より前にある未出力のコメントを、後続のトークンの前に出力する
+    fn leading_comments(&mut self, pos: usize) -> Doc {
+        let mut parts = Vec::new();
+        for comment in self.take_comments_before(pos) {
+            let separator = if comment.kind == TriviaToken::LineComment {
+                Doc::HardLine
+            } else {
+                Doc::text(" ")
+            };
+            parts.push(comment_doc(&comment));
+            parts.push(separator);
+        }
+        Doc::concat(parts)
+    }
+
+    /// `start..end` でコメントの外にある最初の `token` の位置
+    ///
+    /// ASTノードの間にある区切り記号やキーワードの位置を求めるために使用する
+    fn token_position(&self, start: usize, end: usize, token: &str) -> Option<usize> {
+        let text = self.source.get(start..end)?;
+        text.match_indices(token)
+            .map(|(offset, _)| start + offset)
+            .find(|&pos| !self.in_comment(pos))
+    }
+
+    /// `start..end` でコメントの外にある最後の `token` の位置
+    fn last_token_position(&self, start: usize, end: usize, token: &str) -> Option<usize> {
+        let text = self.source.get(start..end)?;
+        text.rmatch_indices(token)
+            .map(|(offset, _)| start + offset)
+            .find(|&pos| !self.in_comment(pos))
+    }
+
+    fn in_comment(&self, pos: usize) -> bool {
+        self.comments
+            .iter()
+            .any(|comment| comment.span.start <= pos && pos < comment.span.end)
+    }
+
+    /// 区切りリストの要素の後ろ (次の `,` または `close` の手前) にあるコメントを取り出す
+    fn list_item(&mut self, doc: Doc, end: usize, next: Option<usize>, close: usize) -> ListItem {
+        let comma = next.and_then(|next| self.token_position(end, next, ","));
+        let (mut comments, mut line_comment) = self.trailing_comments(comma.unwrap_or(close));
+        // `,` と同じ行にある行コメントもこの要素に続けて置く
+        let same_line = comma
+            .zip(next)
+            .and_then(|(comma, next)| self.take_trailing_line_comment(comma + 1, next));
+        if let Some(comment) = same_line {
+            let separator = if line_comment {
+                Doc::HardLine
+            } else {
+                Doc::text(" ")
+            };
+            comments = Doc::concat(vec![comments, separator, comment_doc(&comment)]);
+            line_comment = comment.kind == TriviaToken::LineComment;
+        }
+        ListItem {
+            doc,
+            comments,
+            line_comment,
+        }
+    }
+
+    /// 2つの位置の間に空行があるかどうか
+    fn has_blank_line_between(&self, start: usize, end: usize) -> bool {
+        self.source
+            .get(start..end)
+            .is_some_and(|text| text.matches('\n').count() >= 2)
+    }
+
+    // ===== 文 =====
+
+    /// 文の並びを整形する (`end` までのコメントも含める)
+    fn statement_list(&mut self, stmts: &[Statement], end: usize) -> Doc {
+        let mut lines: Vec<Doc> = Vec::new();
+        let mut last_end: Option<usize> = None;
+
+        for stmt in stmts {
+            for comment in self.take_comments_before(stmt.span.start) {
+                self.push_line(
+                    &mut lines,
+                    &mut last_end,
+                    comment.span,
+                    comment_doc(&comment),
+                );
+            }
+
+            let doc = self.statement(stmt);
+            // 式に付けられなかった文中のコメントは文の後ろに置く
+            let (rest, after_line_comment) = self.trailing_comments(stmt.span.end);
+            let mut doc = Doc::concat(vec![doc, rest]);
+            let mut span = stmt.span;
+            if let Some(comment) = self.take_trailing_comment(stmt.span.end) {
+                let separator = if after_line_comment {
+                    Doc::HardLine
+                } else {
+                    Doc::text(" ")
+                };
+                doc = Doc::concat(vec![doc, separator, comment_doc(&comment)]);
+                span.end = comment.span.end;
+            }
+            self.push_line(&mut lines, &mut last_end, span, doc);
+        }
+
+        for comment in self.take_comments_before(end) {
+            self.push_line(
+                &mut lines,
+                &mut last_end,
+                comment.span,
+                comment_doc(&comment),
+            );
+        }
+
+        Doc::concat(lines)
+    }
+
+    /// 1行分の要素を追加する (元のソースの空行は1行まで保持する)
+    fn push_line(&self, lines: &mut Vec<Doc>, last_end: &mut Option<usize>, span: Span, doc: Doc) {
+        if let Some(prev) = *last_end {
+            lines.push(Doc::HardLine);
+            if self.has_blank_line_between(prev, span.start) {
+                lines.push(Doc::HardLine);
+            }
+        }
+        lines.push(doc);
+        *last_end = Some(span.end);
+    }
+
+    fn statement(&mut self, stmt: &Statement) -> Doc {
+        match &stmt.kind {
+            StatementKind::VariableDeclaration { .. } | StatementKind::Expression(_) => {
+                Doc::concat(vec![self.inline_statement(stmt), Doc::text(";")])
+            }
+            StatementKind::FunctionDeclaration {
+                kind,
+                name,
+                return_type,
+                params,
+                body,
+            } => {
+                let mut header = match kind {
+                    FunctionKind::Function => vec![Doc::text("function ")],
+                    FunctionKind::Sub => vec![Doc::text("sub ")],
+                };
+                if let Some(return_type) = return_type {
+                    header.push(Doc::text(format!("{} ", return_type)));
+                }
+                header.push(Doc::text(name.clone()));
+                let close = self
+                    .last_token_position(stmt.span.start, body.span.start, ")")
+                    .unwrap_or(body.span.start);
+                let mut items = Vec::new();
+                for (i, param) in params.iter().enumerate() {
+                    let mut parts = vec![
+                        self.leading_comments(param.span.start),
+                        Doc::text(format!("{} {}", param.type_name, param.name)),
+                    ];
+                    if let Some(value) = &param.value {
+                        parts.push(Doc::text(" = "));
+                        parts.push(self.expression(value));
+                    }
+                    let next = params.get(i + 1).map(|next| next.span.start);
+                    items.push(self.list_item(Doc::concat(parts), param.span.end, next, close));
+                }
+                header.push(delimited_list("(", items, ")"));
+                self.with_body(Doc::concat(header), body)
+            }
+            StatementKind::ClassDeclaration {
+                name,
+                superclass,
+                members,
+            } => {
+                let mut header = format!("class {}", name);
+                if let Some(superclass) = superclass {
+                    header.push_str(&format!(" extends {}", superclass));
+                }
+                let block = self.block(members, stmt.span.end);
+                Doc::concat(vec![Doc::text(header), self.brace_separator(), block])
+            }
+            StatementKind::If {
+                condition,
+                consequence,
+                alternative,
+            } => {
+                let header = Doc::concat(vec![
+                    Doc::text("if "),
+                    self.parenthesized(condition, consequence.span.start),
+                ]);
+                let mut parts = if alternative.is_some() && ends_with_dangling_if(consequence) {
+                    // `else` が内側の if に結び付かないようブロックで囲む
+                    let block = self.block(
+                        std::slice::from_ref(consequence.as_ref()),
+                        consequence.span.end,
+                    );
+                    vec![header, self.brace_separator(), block]
+                } else {
+                    vec![self.with_body(header, consequence)]
+                };
+                if let Some(alt) = alternative {
+                    // `else` の前のコメントは本体の後ろに置く
+                    let else_position = self
+                        .token_position(consequence.span.end, alt.span.start, "else")
+                        .unwrap_or(alt.span.start);
+                    let (comments, after_line_comment) = self.trailing_comments(else_position);
+                    parts.push(comments);
+                    // `} else {` のように閉じ括弧と同じ行に置けるかどうか
+                    let same_line = (matches!(consequence.kind, StatementKind::Block(_))
+                        || ends_with_dangling_if(consequence))
+                        && self.options.brace_style == BraceStyle::SameLine
+                        && !after_line_comment;
+                    parts.push(if same_line {
+                        Doc::text(" ")
+                    } else {
+                        Doc::HardLine
+                    });
+                    match &alt.kind {
+                        // `else if` は連鎖として出力する
+                        StatementKind::If { .. } => {
+                            let (comments, after_line_comment) =
+                                self.trailing_comments(alt.span.start);
+                            parts.push(Doc::text("else"));
+                            parts.push(comments);
+                            parts.push(if after_line_comment {
+                                Doc::HardLine
+                            } else {
+                                Doc::text(" ")
+                            });
+                            parts.push(self.statement(alt));
+                        }
+                        _ => parts.push(self.with_body(Doc::text("else"), alt)),
+                    }
+                }
+                Doc::concat(parts)
+            }
+            StatementKind::For {
+                init,
+                condition,
+                update,
+                body,
+            } => {
+                let mut header = vec![Doc::text("for (")];
+                if let Some(init) = init {
+                    header.push(self.inline_statement(init));
+                }
+                header.push(Doc::text(";"));
+                if let Some(condition) = condition {
+                    header.push(Doc::text(" "));
+                    header.push(self.expression(condition));
+                }
+                header.push(Doc::text(";"));
+                if let Some(update) = update {
+                    header.push(Doc::text(" "));
+                    header.push(self.inline_statement(update));
+                }
+                let close = self
+                    .last_token_position(stmt.span.start, body.span.start, ")")
+                    .unwrap_or(body.span.start);
+                header.push(self.trailing_comments(close).0);
+                header.push(Doc::text(")"));
+                self.with_body(Doc::concat(header), body)
+            }
+            StatementKind::ForEach {
+                binding,
+                iterable,
+                kind,
+                body,
+            } => {
+                let binding = match &binding.type_name {
+                    Some(type_name) => format!("{} {}", type_name, binding.name),
+                    None => binding.name.clone(),
+                };
+                let keyword = match kind {
+                    ForEachKind::In => "in",
+                    ForEachKind::Of => "of",
+                };
+                let close = self
+                    .last_token_position(iterable.span.end, body.span.start, ")")
+                    .unwrap_or(body.span.start);
+                let header = Doc::concat(vec![
+                    Doc::text(format!("for ({} {} ", binding, keyword)),
+                    self.expression(iterable),
+                    self.trailing_comments(close).0,
+                    Doc::text(")"),
+                ]);
+                self.with_body(header, body)
+            }
+            StatementKind::While { condition, body } => {
+                let header = Doc::concat(vec![
+                    Doc::text("while "),
+                    self.parenthesized(condition, body.span.start),
+                ]);
+                self.with_body(header, body)
+            }
+            StatementKind::Switch {
+                expression,
+                cases,
+                default,
+            } => {
+                let mut arms = Vec::new();
+                for case in cases {
+                    let values = case.values.iter().map(|v| self.expression(v)).collect();
+                    let header = Doc::concat(vec![
+                        Doc::text("case "),
+                        Doc::join(values, Doc::text(", ")),
+                        Doc::text(":"),
+                    ]);
+                    arms.push(Doc::HardLine);
+                    arms.push(self.with_body(header, &case.body));
+                }
+                if let Some(default) = default {
+                    arms.push(Doc::HardLine);
+                    arms.push(self.with_body(Doc::text("default:"), default));
+                }
+                Doc::concat(vec![
+                    Doc::text("switch ("),
+                    self.expression(expression),
+                    Doc::text(")"),
+                    self.brace_separator(),
+                    Doc::text("{"),
+                    Doc::indent(Doc::concat(arms)),
+                    Doc::HardLine,
+                    Doc::text("}"),
+                ])
+            }
+            StatementKind::Return(value) => match value {
+                Some(value) => Doc::concat(vec![
+                    Doc::text("return "),
+                    self.expression(value),
+                    Doc::text(";"),
+                ]),
+                None => Doc::text("return;"),
+            },
+            StatementKind::Break => Doc::text("break;"),
+            StatementKind::Continue => Doc::text("continue;"),
+            StatementKind::Block(stmts) => self.block(stmts, stmt.span.end),
+        }
+    }
+
+    /// セミコロンを付けずに文を整形する (for文のヘッダ用)
+    fn inline_statement(&mut self, stmt: &Statement) -> Doc {
+        match &stmt.kind {
+            StatementKind::VariableDeclaration {
+                type_name,
+                declarators,
+                mutability,
+            } => {
+                let prefix = match mutability {
+                    Mutability::Mutable => "",
+                    Mutability::Const => "const ",
+                    Mutability::Readonly => "readonly ",
+                };
+                let declarators = declarators
+                    .iter()
+                    .map(|decl| match &decl.value {
+                        Some(value) => Doc::concat(vec![
+                            Doc::text(format!("{} = ", decl.name)),
+                            self.expression(value),
+                        ]),
+                        None => Doc::text(decl.name.clone()),
+                    })
+                    .collect();
+                Doc::concat(vec![
+                    Doc::text(format!("{}{} ", prefix, type_name)),
+                    Doc::join(declarators, Doc::text(", ")),
+                ])
+            }
+            StatementKind::Expression(expr) => {
+                let doc = self.expression(expr);
+                // `{` で始まる式文はブロックと解釈されるため括弧で囲む
+                if starts_with_object_literal(expr) {
+                    Doc::concat(vec![Doc::text("("), doc, Doc::text(")")])
+                } else {
+                    doc
+                }
+            }
+            _ => self.statement(stmt),
+        }
+    }
+
+    /// ヘッダと本体の文を連結する (本体がブロックでなければ次の行へ字下げする)
+    ///
+    /// ヘッダと本体の間のコメントはヘッダの後ろに置く
+    fn with_body(&mut self, header: Doc, body: &Statement) -> Doc {
+        let (comments, after_line_comment) = self.trailing_comments(body.span.start);
+        match &body.kind {
+            StatementKind::Block(stmts) => {
+                let separator = if after_line_comment {
+                    Doc::HardLine
+                } else {
+                    self.brace_separator()
+                };
+                let block = self.block(stmts, body.span.end);
+                Doc::concat(vec![header, comments, separator, block])
+            }
+            _ => {
+                let body = self.statement(body);
+                Doc::concat(vec![
+                    header,
+                    comments,
+                    Doc::indent(Doc::concat(vec![Doc::HardLine, body])),
+                ])
+            }
+        }
+    }
+
+    /// `if` や `while` の括弧で囲まれた条件式を整形する (`)` の手前のコメントも含める)
+    fn parenthesized(&mut self, condition: &Expression, body_start: usize) -> Doc {
+        let close = self
+            .last_token_position(condition.span.end, body_start, ")")
+            .unwrap_or(body_start);
+        Doc::concat(vec![
+            Doc::text("("),
+            self.expression(condition),
+            self.trailing_comments(close).0,
+            Doc::text(")"),
+        ])
+    }
+
+    /// ブロック `{ ... }` を整形する (`end` はブロック終端の位置)
+    fn block(&mut self, stmts: &[Statement], end: usize) -> Doc {
+        let body = self.statement_list(stmts, end);
+        if matches!(&body, Doc::Concat(lines) if lines.is_empty()) {
+            return Doc::text("{}");
+        }
+        Doc::concat(vec![
+            Doc::text("{"),
+            Doc::indent(Doc::concat(vec![Doc::HardLine, body])),
+            Doc::HardLine,
+            Doc::text("}"),
+        ])
+    }
+
+    /// 波括弧の前の区切り
+    fn brace_separator(&self) -> Doc {
+        match self.options.brace_style {
+            BraceStyle::SameLine => Doc::text(" "),
+            BraceStyle::NextLine => Doc::HardLine,
+        }
+    }
+
+    // ===== 式 =====
+
+    /// 式を整形する (式の手前にある未出力のコメントも含める)
+    fn expression(&mut self, expr: &Expression) -> Doc {
+        let leading = self.leading_comments(expr.span.start);
+        let doc = self.expression_without_comments(expr);
+        if matches!(&leading, Doc::Concat(parts) if parts.is_empty()) {
+            doc
+        } else {
+            Doc::concat(vec![leading, doc])
+        }
+    }
+
+    fn expression_without_comments(&mut self, expr: &Expression) -> Doc {
+        match &expr.kind {
+            ExpressionKind::IntLiteral(value) => self.literal(expr.span, format_int(*value)),
+            ExpressionKind::FloatLiteral(value) => self.literal(expr.span, format_float(*value)),
+            ExpressionKind::StringLiteral(value) => {
+                // 値にエスケープされていない二重引用符を含む場合は単一引用符で囲む
+                let quote = if contains_unescaped(value, '"') && !contains_unescaped(value, '\'') {
+                    '\''
+                } else {
+                    '"'
+                };
+                self.literal(expr.span, format!("{}{}{}", quote, value, quote))
+            }
+            ExpressionKind::Boolean(value) => Doc::text(value.to_string()),
+            ExpressionKind::NullLiteral => Doc::text("null"),
+            ExpressionKind::Identifier(name) => Doc::text(name.clone()),
+            ExpressionKind::Prefix { operator, right } => Doc::concat(vec![
+                Doc::text(operator.as_str()),
+                self.operand(right, |p| p < Precedence::Prefix),
+            ]),
+            ExpressionKind::Infix {
+                left,
+                operator,
+                right,
+            } => {
+                // 左結合のため、右辺は同じ優先順位でも括弧が必要
+                let precedence = Precedence::of_infix(operator);
+                Doc::concat(vec![
+                    self.operand(left, |p| p < precedence),
+                    Doc::text(format!(" {} ", operator.as_str())),
+                    self.operand(right, |p| p <= precedence),
+                ])
+            }
+            ExpressionKind::Assignment { left, right } => Doc::concat(vec![
+                self.operand(left, |p| p <= Precedence::Assign),
+                Doc::text(" = "),
+                self.operand(right, |p| p < Precedence::Assign),
+            ]),
+            ExpressionKind::Call {
+                function,
+                arguments,
+            } => {
+                let function = self.operand(function, |p| p < Precedence::Call);
+                let args = self.expression_list(arguments, expr.span.end);
+                Doc::concat(vec![function, delimited_list("(", args, ")")])
+            }
+            ExpressionKind::New { class, arguments } => {
+                let class = self.operand(class, |p| p < Precedence::Call);
+                let args = self.expression_list(arguments, expr.span.end);
+                Doc::concat(vec![
+                    Doc::text("new "),
+                    class,
+                    delimited_list("(", args, ")"),
+                ])
+            }
+            ExpressionKind::Cast {
+                target_type,
+                expression,
+            } => Doc::concat(vec![
+                Doc::text(format!("({}) ", target_type)),
+                self.operand(expression, |p| p < Precedence::Prefix),
+            ]),
+            ExpressionKind::ArrayLiteral(elements) => {
+                let elements = self.expression_list(elements, expr.span.end);
+                delimited_list("[", elements, "]")
+            }
+            ExpressionKind::ObjectLiteral { pairs } => {
+                if pairs.is_empty() {
+                    return Doc::text("{}");
+                }
+                let pairs = pairs
+                    .iter()
+                    .map(|(key, value)| {
+                        Doc::concat(vec![
+                            self.expression(key),
+                            Doc::text(": "),
+                            self.expression(value),
+                        ])
+                    })
+                    .collect();
+                Doc::group(Doc::concat(vec![
+                    Doc::text("{"),
+                    Doc::indent(Doc::concat(vec![
+                        Doc::Line,
+                        Doc::join(pairs, Doc::concat(vec![Doc::text(","), Doc::Line])),
+                    ])),
+                    Doc::Line,
+                    Doc::text("}"),
+                ]))
+            }
+            ExpressionKind::MemberAccess { object, property } => {
+                let object = self.operand(object, |p| p < Precedence::Call);
+                match property {
+                    MemberProperty::Named(name) => {
+                        Doc::concat(vec![object, Doc::text(format!(".{}", name))])
+                    }
+                    MemberProperty::Computed(index) => Doc::concat(vec![
+                        object,
+                        Doc::text("["),
+                        self.expression(index),
+                        Doc::text("]"),
+                    ]),
+                }
+            }
+        }
+    }
+
+    /// 引数や配列の要素を整形する (`end` は閉じ括弧を含む式全体の終端)
+    fn expression_list(&mut self, exprs: &[Expression], end: usize) -> Vec<ListItem> {
+        let close = end.saturating_sub(1);
+        let mut items = Vec::new();
+        for (i, expr) in exprs.iter().enumerate() {
+            let doc = self.expression(expr);
+            let next = exprs.get(i + 1).map(|next| next.span.start);
+            items.push(self.list_item(doc, expr.span.end, next, close));
+        }
+        items
+    }
+
+    /// 部分式を整形し、`needs_parens` が真となる優先順位であれば括弧で囲む
+    fn operand(&mut self, expr: &Expression, needs_parens: impl Fn(Precedence) -> bool) -> Doc {
+        let doc = self.expression(expr);
+        if needs_parens(expression_precedence(expr)) {
+            Doc::concat(vec![Doc::text("("), doc, Doc::text(")")])
+        } else {
+            doc
+        }
+    }
+
+    /// リテラルは元の表記 (16進数や引用符の種類) を保持する
+    fn literal(&self, span: Span, fallback: String) -> Doc {
+        match self.source.get(span.start..span.end) {
+            Some(text) if !text.is_empty() && !text.contains('\n') => Doc::text(text),
+            _ => Doc::text(fallback),
+        }
+    }
+}
+
+/// エスケープされていない `quote` を含むかどうか (文字列リテラルの値はエスケープを含む表記のまま)
+fn contains_unescaped(value: &str, quote: char) -> bool {
+    let mut escaped = false;
+    for c in value.chars() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == quote {
+            return true;
+        }
+    }
+    false
+}
+
+fn comment_doc(comment: &Trivia) -> Doc {
+    // ブロックコメント内の改行は行単位に分割して出力する
+    let lines: Vec<Doc> = comment
+        .text
+        .split('\n')
+        .map(|line| Doc::text(line.trim_end()))
+        .collect();
+    Doc::join(lines, Doc::HardLine)
+}
+
+/// 区切りリストの要素
+struct ListItem {
+    doc: Doc,
+    /// 要素の後ろに続くコメント
+    comments: Doc,
+    /// `comments` が行コメントで終わるかどうか
+    line_comment: bool,
+}
+
+/// `(a, b)` や `[a, b]` のような区切りリストを整形する (収まらなければ1要素1行)
+///
+/// 要素の後ろのコメントは `,` の手前に置く。
+/// 行コメントの場合は `,` を含めないよう後ろに置き、リストを1要素1行にする。
+fn delimited_list(open: &str, items: Vec<ListItem>, close: &str) -> Doc {
+    if items.is_empty() {
+        return Doc::text(format!("{}{}", open, close));
+    }
+    let count = items.len();
+    let mut inner = vec![Doc::SoftLine];
+    let mut after_line_comment = false;
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            inner.push(if after_line_comment {
+                Doc::HardLine
+            } else {
+                Doc::Line
+            });
+        }
+        inner.push(item.doc);
+        let comma = Doc::text(if i + 1 < count { "," } else { "" });
+        if item.line_comment {
+            inner.push(comma);
+            inner.push(item.comments);
+        } else {
+            inner.push(item.comments);
+            inner.push(comma);
+        }
+        after_line_comment = item.line_comment;
+    }
+    Doc::group(Doc::concat(vec![
+        Doc::text(open),
+        Doc::indent(Doc::concat(inner)),
+        if after_line_comment {
+            Doc::HardLine
+        } else {
+            Doc::SoftLine
+        },
+        Doc::text(close),
+    ]))
+}
+
+/// 括弧の要否判定に用いる式の優先順位
+fn expression_precedence(expr: &Expression) -> Precedence {
+    match &expr.kind {
+        ExpressionKind::Assignment { .. } => Precedence::Assign,
+        ExpressionKind::Infix { operator, .. } => Precedence::of_infix(operator),
+        ExpressionKind::Prefix { .. } | ExpressionKind::Cast { .. } => Precedence::Prefix,
+        // 負の数値リテラルは `-` 前置演算子として出力される
+        ExpressionKind::IntLiteral(value) if *value < 0 => Precedence::Prefix,
+        ExpressionKind::FloatLiteral(value) if value.is_sign_negative() => Precedence::Prefix,
+        _ => Precedence::Call,
+    }
+}
+
+/// 本体の末尾が `else` を持たない if 文で終わるかどうか
+fn ends_with_dangling_if(stmt: &Statement) -> bool {
+    match &stmt.kind {
+        StatementKind::If { alternative, .. } => match alternative {
+            Some(alt) => ends_with_dangling_if(alt),
+            None => true,
+        },
+        StatementKind::For { body, .. }
+        | StatementKind::ForEach { body, .. }
+        | StatementKind::While { body, .. } => ends_with_dangling_if(body),
+        _ => false,
+    }
+}
+
+/// 式の先頭がオブジェクトリテラルかどうか
+fn starts_with_object_literal(expr: &Expression) -> bool {
+    match &expr.kind {
+        ExpressionKind::ObjectLiteral { .. } => true,
+        ExpressionKind::Infix { left, .. } | ExpressionKind::Assignment { left, .. } => {
+            starts_with_object_literal(left)
+        }
+        ExpressionKind::Call { function, .. } => starts_with_object_literal(function),
+        ExpressionKind::MemberAccess { object, .. } => starts_with_object_literal(object),
+        _ => false,
+    }
+}
+
+/// 整数の表記
+///
+/// `i64::MIN` の絶対値は整数リテラルで表せないため、再解析できるよう減算の式で表す
+fn format_int(value: i64) -> String {
+    if value == i64::MIN {
+        format!("({} - 1)", i64::MIN + 1)
+    } else {
+        value.to_string()
+    }
+}
+
+/// 浮動小数点数を常に小数点を含む表記にする (`1.0` が整数として再解析されないように)
+fn format_float(value: f64) -> String {
+    let s = value.to_string();
+    if s.contains('.') || !value.is_finite() {
+        s
+    } else {
+        format!("{}.0", s)
+    }
+}
@@ -0,0 +1,371 @@
+//! 増分構文解析
+//!
+//! `Document` はソースコードと直前の解析結果を保持し、編集のたびに影響を受けた文だけを解析し直す。
+//!
+//! 編集範囲を含む最も内側の文の並び (プログラム直下またはブロック) を求め、
+//! 編集範囲に接する文とその間の空白を元のソースの位置から解析し直す。
+//! 解析がちょうど次の未変更の文 (または閉じ括弧・入力の終端) の位置で終われば、
+//! それより後ろのトークン列は編集前と同じであるため、未変更の文は位置をずらして再利用する。
+//! 終わらなかった場合や直前の解析結果がエラーだった場合は全体を解析し直す。
+use serde::Serialize;
+
+use crate::common::Span;
+use crate::common::error::SnowFallError;
+use crate::compiler::ast::*;
+use crate::compiler::{Lexer, Parser, semantic};
+
+/// 編集の適用結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct EditResult {
+    /// 解析し直した範囲 (編集後のソースコード上の位置)
+    pub reparsed: Span,
+    /// 一部のみを解析し直したかどうか (`false` の場合は全体を解析し直した)
+    pub incremental: bool,
+}
+
+/// 編集に応じて増分解析するソースコード
+pub struct Document {
+    source: String,
+    /// 直前の構文解析の結果 (構文エラーがある場合は `None`)
+    program: Option<ProgramAst>,
+    /// 構文エラー、または意味解析のエラー
+    errors: Vec<SnowFallError>,
+    /// 次に割り当てるノードID
+    next_node_id: NodeId,
+    /// 構文エラーの上限 (`None` の場合は既定値)
+    max_errors: Option<usize>,
+}
+
+impl Document {
+    /// ソースコード全体を解析した文書を生成する
+    ///
+    /// `max_errors` は構文エラーの上限 (`None` の場合は既定値)
+    pub fn new(source: impl Into<String>, max_errors: Option<usize>) -> Self {
+        let mut document = Self {
+            source: source.into(),
+            program: None,
+            errors: Vec::new(),
+            next_node_id: NodeId(0),
+            max_errors,
+        };
+        document.parse_all();
+        document
+    }
+
+    /// 現在のソースコード
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// 現在の構文木 (構文エラーがある場合は `None`)
+    pub fn program(&self) -> Option<&ProgramAst> {
+        self.program.as_ref()
+    }
+
+    /// 構文エラー、または意味解析のエラー
+    pub fn errors(&self) -> &[SnowFallError] {
+        &self.errors
+    }
+
+    /// `range` (編集前のバイト位置) を `text` に置き換え、影響を受けた部分を解析し直す
+    ///
+    /// 範囲がソースコードの外にある場合や文字の途中を指す場合はエラーを返す。
+    ///
+    /// ```
+    /// use snowfall_core::common::Span;
+    /// use snowfall_core::compiler::incremental::Document;
+    ///
+    /// let mut document = Document::new("Int a = 1;\nsub f() {\n    print(a);\n}\n", None);
+    /// // `print(a)` を `print(a + 1)` に書き換える
+    /// let result = document.edit(Span { start: 32, end: 32 }, " + 1").unwrap();
+    /// assert!(result.incremental);
+    /// assert_eq!(&document.source()[result.reparsed.start..result.reparsed.end], "\n    print(a + 1);\n");
+    /// assert!(document.errors().is_empty());
+    /// ```
+    pub fn edit(&mut self, range: Span, text: &str) -> Result<EditResult, String> {
+        if range.start > range.end
+            || range.end > self.source.len()
+            || !self.source.is_char_boundary(range.start)
+            || !self.source.is_char_boundary(range.end)
+        {
+            return Err(format!(
+                "Invalid edit range: {}..{} (source length {})",
+                range.start,
+                range.end,
+                self.source.len()
+            ));
+        }
+
+        let old_len = self.source.len();
+        self.source.replace_range(range.start..range.end, text);
+        if let Some(reparsed) = self.reparse(range, text.len(), old_len) {
+            return Ok(EditResult {
+                reparsed,
+                incremental: true,
+            });
+        }
+
+        self.parse_all();
+        Ok(EditResult {
+            reparsed: Span {
+                start: 0,
+                end: self.source.len(),
+            },
+            incremental: false,
+        })
+    }
+
+    /// ソースコード全体を解析する
+    fn parse_all(&mut self) {
+        let mut parser = Parser::new(Lexer::new(&self.source));
+        if let Some(max_errors) = self.max_errors {
+            parser = parser.with_max_errors(max_errors);
+        }
+        let result = parser.parse_program();
+        self.next_node_id = parser.peek_node_id();
+        match result {
+            Ok(program) => self.set_program(program),
+            Err(errors) => {
+                self.program = None;
+                self.errors = errors;
+            }
+        }
+    }
+
+    fn set_program(&mut self, program: ProgramAst) {
+        self.errors = semantic::analyze(&program, &self.source);
+        self.program = Some(program);
+    }
+
+    /// 影響を受けた文の並びの一部のみを解析し直す
+    ///
+    /// `range` は編集前の位置、`inserted` は挿入した文字列の長さ。
+    /// 一部のみでは正しく解析できない場合は `None` を返す (構文木は変更しない)。
+    fn reparse(&mut self, range: Span, inserted: usize, old_len: usize) -> Option<Span> {
+        let program = self.program.as_ref()?;
+        let delta = inserted as isize - (range.end - range.start) as isize;
+
+        // 編集範囲を含む最も内側のブロック (括弧に接する編集はその外側で扱う)
+        let block = innermost_block(&program.statements, range, old_len);
+        let (statements, inner) = match block {
+            Some(block) => match &block.kind {
+                StatementKind::Block(statements) => (
+                    statements,
+                    Span {
+                        start: block.span.start + 1,
+                        end: block.span.end - 1,
+                    },
+                ),
+                _ => return None,
+            },
+            None => (
+                &program.statements,
+                Span {
+                    start: 0,
+                    end: old_len,
+                },
+            ),
+        };
+
+        // 編集範囲に接する文 `first..last` を解析し直す
+        let mut first = statements
+            .iter()
+            .position(|stmt| stmt.span.end >= range.start)
+            .unwrap_or(statements.len());
+        let last = statements
+            .iter()
+            .rposition(|stmt| stmt.span.start <= range.end)
+            .map_or(first, |index| (index + 1).max(first));
+        // 直前の文が後続のトークンで続きうる場合 (`;` がない式文など) はその文も含める
+        while first > 0 && !is_terminated(&statements[first - 1], &self.source) {
+            first -= 1;
+        }
+        let start = match first {
+            0 => inner.start,
+            _ => statements[first - 1].span.end,
+        };
+        let old_end = statements
+            .get(last)
+            .map_or(inner.end, |stmt| stmt.span.start);
+        let end = old_end.checked_add_signed(delta)?;
+
+        let mut parser =
+            Parser::new(Lexer::new_at(&self.source, start)).with_first_node_id(self.next_node_id);
+        if let Some(max_errors) = self.max_errors {
+            parser = parser.with_max_errors(max_errors);
+        }
+        let reparsed = parser.parse_statements_until(end).ok()?;
+        // 次の未変更のトークンの位置でちょうど終わっていなければ、後続の解析結果は再利用できない
+        if parser.current_span().start != end {
+            return None;
+        }
+        let first_new_id = self.next_node_id;
+        self.next_node_id = parser.peek_node_id();
+
+        let target = block.map(|block| block.span);
+        let mut program = self.program.take()?;
+        let mut splice = Splice {
+            target,
+            range: first..last,
+            replacement: Some(reparsed),
+            at: old_end,
+            delta,
+            first_new_id,
+        };
+        if target.is_none() {
+            splice.apply(&mut program.statements);
+        }
+        let mut program = splice.fold_program(program);
+        program.span = match (program.statements.first(), program.statements.last()) {
+            (Some(first), Some(last)) => first.span.cover(last.span),
+            _ => parser.current_span(),
+        };
+        self.set_program(program);
+        Some(Span { start, end })
+    }
+}
+
+/// 文の末尾が後続のトークンと結び付かないことが確かかどうか
+///
+/// `else` を伴わない if 文や `;` のない式文は、後続のトークンによって解析結果が変わりうる。
+fn is_terminated(stmt: &Statement, source: &str) -> bool {
+    match &stmt.kind {
+        StatementKind::Block(_)
+        | StatementKind::FunctionDeclaration { .. }
+        | StatementKind::ClassDeclaration { .. }
+        | StatementKind::Switch { .. } => true,
+        StatementKind::If {
+            alternative: Some(alternative),
+            ..
+        } => is_terminated(alternative, source),
+        StatementKind::If { .. } => false,
+        StatementKind::For { body, .. }
+        | StatementKind::ForEach { body, .. }
+        | StatementKind::While { body, .. } => is_terminated(body, source),
+        _ => source.as_bytes()[..stmt.span.end].ends_with(b";"),
+    }
+}
+
+/// 文の子の文 (ブロックの中身・関数の本体・分岐・ループの本体・クラスのメンバー)
+fn child_statements(stmt: &Statement) -> Vec<&Statement> {
+    match &stmt.kind {
+        StatementKind::FunctionDeclaration { body, .. } => vec![body],
+        StatementKind::ClassDeclaration { members, .. } => members.iter().collect(),
+        StatementKind::If {
+            consequence,
+            alternative,
+            ..
+        } => std::iter::once(consequence.as_ref())
+            .chain(alternative.as_deref())
+            .collect(),
+        StatementKind::For {
+            init, update, body, ..
+        } => init
+            .as_deref()
+            .into_iter()
+            .chain(update.as_deref())
+            .chain(std::iter::once(body.as_ref()))
+            .collect(),
+        StatementKind::ForEach { body, .. } | StatementKind::While { body, .. } => vec![body],
+        StatementKind::Switch { cases, default, .. } => cases
+            .iter()
+            .map(|case| case.body.as_ref())
+            .chain(default.as_deref())
+            .collect(),
+        StatementKind::Block(stmts) => stmts.iter().collect(),
+        StatementKind::VariableDeclaration { .. }
+        | StatementKind::Return(_)
+        | StatementKind::Break
+        | StatementKind::Continue
+        | StatementKind::Expression(_) => Vec::new(),
+    }
+}
+
+/// 括弧の内側に `range` を含む最も内側のブロック
+///
+/// 閉じ括弧がないまま入力の終端に達したブロックも受理されるため、
+/// 入力の終端 (`len`) で終わるブロックは閉じ括弧の位置が定まらないものとして対象にしない。
+fn innermost_block(stmts: &[Statement], range: Span, len: usize) -> Option<&Statement> {
+    stmts
+        .iter()
+        .find_map(|stmt| innermost_block_in(stmt, range, len))
+}
+
+fn innermost_block_in(stmt: &Statement, range: Span, len: usize) -> Option<&Statement> {
+    if range.start < stmt.span.start || stmt.span.end < range.end {
+        return None;
+    }
+    child_statements(stmt)
+        .into_iter()
+        .find_map(|child| innermost_block_in(child, range, len))
+        .or_else(|| {
+            let inside = stmt.span.start < range.start && range.end < stmt.span.end;
+            let closed = stmt.span.end < len;
+            (inside && closed && matches!(stmt.kind, StatementKind::Block(_))).then_some(stmt)
+        })
+}
+
+/// 解析し直した文を差し込み、それより後ろの位置をずらす
+struct Splice {
+    /// 差し込む先のブロックの範囲 (編集前の位置、プログラム直下の場合は `None`)
+    target: Option<Span>,
+    /// 置き換える文の添字の範囲
+    range: std::ops::Range<usize>,
+    replacement: Option<Vec<Statement>>,
+    /// この位置 (編集前) 以降の位置を `delta` だけずらす
+    at: usize,
+    delta: isize,
+    /// 解析し直した文に割り当てた最初のID (これ以降のIDのノードは編集後の位置を持つ)
+    first_new_id: NodeId,
+}
+
+impl Splice {
+    fn apply(&mut self, stmts: &mut Vec<Statement>) {
+        if let Some(replacement) = self.replacement.take() {
+            stmts.splice(self.range.clone(), replacement);
+        }
+    }
+
+    fn shift(&self, span: Span) -> Span {
+        let shift = |offset: usize| offset.saturating_add_signed(self.delta);
+        Span {
+            start: if span.start >= self.at {
+                shift(span.start)
+            } else {
+                span.start
+            },
+            end: if span.end > self.at {
+                shift(span.end)
+            } else {
+                span.end
+            },
+        }
+    }
+}
+
+impl Folder for Splice {
+    fn fold_statement(&mut self, mut stmt: Statement) -> Option<Statement> {
+        if stmt.id >= self.first_new_id {
+            return Some(stmt);
+        }
+        if Some(stmt.span) == self.target
+            && let StatementKind::Block(stmts) = &mut stmt.kind
+        {
+            self.apply(stmts);
+        }
+        let mut stmt = fold_statement_children(self, stmt);
+        stmt.span = self.shift(stmt.span);
+        if let StatementKind::FunctionDeclaration { params, .. } = &mut stmt.kind {
+            for param in params {
+                param.span = self.shift(param.span);
+            }
+        }
+        Some(stmt)
+    }
+
+    fn fold_expression(&mut self, expr: Expression) -> Expression {
+        let mut expr = fold_expression_children(self, expr);
+        expr.span = self.shift(expr.span);
+        expr
+    }
+}
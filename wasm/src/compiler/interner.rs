@@ -0,0 +1,67 @@
+use std::rc::Rc;
+
+use ahash::AHashMap;
+
+/// 文字列インターナーに登録された文字列の識別子
+///
+/// 同じ `Interner` から得たシンボル同士は、整数比較のみで文字列の一致を判定できる
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// 登録順の番号
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// 識別子や型名などの文字列を重複なく保持する
+///
+/// 同じ文字列は1度だけ確保され、以降は `Symbol` で参照する
+#[derive(Debug, Default, Clone)]
+pub struct Interner {
+    /// 文字列からシンボルへの対応
+    map: AHashMap<Rc<str>, Symbol>,
+    /// シンボルの番号から文字列への対応 (`map` のキーと同じ領域を共有する)
+    strings: Vec<Rc<str>>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 文字列を登録し、対応するシンボルを返す (登録済みであれば既存のシンボルを返す)
+    pub fn intern(&mut self, s: &str) -> Symbol {
+        if let Some(symbol) = self.map.get(s) {
+            return *symbol;
+        }
+        let symbol = Symbol(self.strings.len() as u32);
+        let s: Rc<str> = Rc::from(s);
+        self.strings.push(Rc::clone(&s));
+        self.map.insert(s, symbol);
+        symbol
+    }
+
+    /// 登録済みの文字列であればそのシンボルを返す
+    pub fn get(&self, s: &str) -> Option<Symbol> {
+        self.map.get(s).copied()
+    }
+
+    /// シンボルに対応する文字列を返す
+    ///
+    /// # Panics
+    /// 別の `Interner` から得たシンボルを渡した場合
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        &self.strings[symbol.index()]
+    }
+
+    /// 登録されている文字列の数
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
@@ -1,7 +1,9 @@
 use crate::{
     common::{
-        DelimiterToken, ErrorCode, KeywordToken, LiteralToken, OperatorToken, Token, TokenKind,
-        error::SnowFallError,
+        DelimiterToken, ErrorCode, KeywordToken, LiteralToken, OperatorToken, Span, Token,
+        TokenKind, TriviaToken,
+        error::{SnowFallError, SourceRange},
+        span::{LineCursor, offset_to_line_column},
     },
     create_token,
 };
@@ -19,22 +21,75 @@ pub struct Lexer<'a> {
     pub line: u32,
     /// 現在の列番号
     pub column: u32,
+    /// 空白・コメントをトリビアトークンとして出力するかどうか
+    emit_trivia: bool,
+    /// 字句エラーの行番号と文字数を求めるための位置
+    error_cursor: LineCursor,
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(input: &'a str) -> Self {
+        Self::new_at(input, 0)
+    }
+
+    /// `offset` (バイト位置) から読み始める字句解析器を生成します
+    ///
+    /// トークンの位置は入力全体の先頭からのオフセットになります。
+    /// ソースの一部だけを解析し直す場合に使用します。
+    pub fn new_at(input: &'a str, offset: usize) -> Self {
+        let offset = offset.min(input.len());
+        let (line, column) = offset_to_line_column(input, offset);
         let mut l = Lexer {
             input,
-            position: 0,
-            read_position: 0,
+            position: offset,
+            read_position: offset,
             ch: 0,
-            line: 1,
-            column: 1,
+            line,
+            column,
+            emit_trivia: false,
+            error_cursor: LineCursor::default(),
         };
         l.read_char();
         l
     }
 
+    /// 空白・改行・コメントを読み飛ばさず、トリビアトークンとして出力する字句解析器を生成します
+    ///
+    /// フォーマッタなどソースを完全に復元する必要があるツール向けです
+    pub fn new_with_trivia(input: &'a str) -> Self {
+        let mut l = Self::new(input);
+        l.emit_trivia = true;
+        l
+    }
+
+    /// `from` (バイト位置) 以降で最初に現れる識別子 `name` の範囲を求めます
+    ///
+    /// ASTは宣言の名前の位置を保持しないため、宣言の範囲内を探して名前の位置を特定する場合に使用します。
+    /// 字句解析エラーの位置より後ろは探しません。
+    pub fn find_identifier(input: &str, from: usize, name: &str) -> Option<Span> {
+        let mut lexer = Lexer::new_at(input, from);
+        loop {
+            let token = lexer.next_token().ok()?;
+            match token.kind {
+                TokenKind::Eof => return None,
+                TokenKind::Identifier(ref identifier) if identifier == name => {
+                    return Some(token.span);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// 解析中のソースコード全体
+    pub fn input(&self) -> &'a str {
+        self.input
+    }
+
+    /// 次に読み取る文字のバイト位置 (入力長を超えない)
+    pub fn position(&self) -> usize {
+        self.position.min(self.input.len())
+    }
+
     /// 次の文字を読み込み、`ch`フィールドを更新します
     fn read_char(&mut self) {
         if self.read_position >= self.input.len() {
@@ -73,9 +128,33 @@ impl<'a> Lexer<'a> {
 
     /// 次のトークンを取得します
     pub fn next_token(&mut self) -> Result<Token, SnowFallError> {
-        self.skip_whitespace();
+        if self.emit_trivia {
+            if let Some(tok) = self.read_trivia() {
+                return Ok(tok);
+            }
+        } else {
+            self.skip_whitespace();
+        }
 
+        // 字句エラーには読み進めた範囲を付与する
         let start_pos = self.position;
+        let error = match self.read_token(start_pos) {
+            Err(error) if error.span.is_none() => error,
+            result => return result,
+        };
+        let span = Span {
+            start: start_pos,
+            end: self.position().max(start_pos),
+        };
+        Err(error.with_range(SourceRange::with_cursor(
+            span,
+            self.input,
+            &mut self.error_cursor,
+        )))
+    }
+
+    /// 空白を読み飛ばした位置から1つのトークンを読み込みます
+    fn read_token(&mut self, start_pos: usize) -> Result<Token, SnowFallError> {
         let tok = match self.ch {
             b'=' => {
                 if self.peek_char() == b'=' {
@@ -368,20 +447,30 @@ impl<'a> Lexer<'a> {
                     "null" => TokenKind::Keyword(KeywordToken::Null),
                     "and" => TokenKind::Keyword(KeywordToken::And),
                     "or" => TokenKind::Keyword(KeywordToken::Or),
+                    "const" => TokenKind::Keyword(KeywordToken::Const),
+                    "readonly" => TokenKind::Keyword(KeywordToken::Readonly),
                     _ => TokenKind::Identifier(ident),
                 };
-                return Ok(create_token!(kind, start_pos, self.position + 1));
+                return Ok(create_token!(kind, start_pos, self.position));
             }
             0 => Ok(Token::eof(start_pos)),
             _ => {
-                let error_tok = self.ch;
-                self.read_char();
+                // 複数バイトの文字は1文字としてまとめて読み飛ばす
+                let error_char = self
+                    .input
+                    .get(self.position..)
+                    .and_then(|rest| rest.chars().next());
+                let width = error_char.map_or(1, char::len_utf8);
+                let error_char = error_char.unwrap_or(self.ch as char);
+                for _ in 0..width {
+                    self.read_char();
+                }
                 return Err(SnowFallError::new_compiler_error(
-                    Some(format!("Unexpected character: {}", error_tok as char)),
                     ErrorCode::UnexpectedCharacter,
                     self.line,
                     self.column,
-                ));
+                )
+                .with_arg("character", error_char.to_string()));
             }
         };
 
@@ -428,6 +517,58 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    /// 現在位置から始まるトリビア (空白・改行・コメント) を1つ読み取ります
+    fn read_trivia(&mut self) -> Option<Token> {
+        let start_pos = self.position;
+        let kind = match self.ch {
+            b'\n' => {
+                self.read_char();
+                TriviaToken::Newline
+            }
+            b'\r' if self.peek_char() == b'\n' => {
+                self.read_char();
+                self.read_char();
+                TriviaToken::Newline
+            }
+            b' ' | b'\t' | b'\r' => {
+                while matches!(self.ch, b' ' | b'\t')
+                    || (self.ch == b'\r' && self.peek_char() != b'\n')
+                {
+                    self.read_char();
+                }
+                TriviaToken::Whitespace
+            }
+            b'/' if self.peek_char() == b'/' => {
+                // 改行自体は次のトリビアとして扱う
+                while self.ch != b'\n' && self.ch != 0 {
+                    if self.ch == b'\r' && self.peek_char() == b'\n' {
+                        break;
+                    }
+                    self.read_char();
+                }
+                TriviaToken::LineComment
+            }
+            b'/' if self.peek_char() == b'*' => {
+                self.read_char(); // '/'を読み込む
+                self.read_char(); // '*'を読み込む
+                while !(self.ch == b'*' && self.peek_char() == b'/') && self.ch != 0 {
+                    self.read_char();
+                }
+                if self.ch != 0 {
+                    self.read_char(); // '*'を読み込む
+                    self.read_char(); // '/'を読み込む
+                }
+                TriviaToken::BlockComment
+            }
+            _ => return None,
+        };
+        Some(create_token!(
+            TokenKind::Trivia(kind),
+            start_pos,
+            self.position()
+        ))
+    }
+
     /// 数字リテラルを読み取ります (整数および浮動小数点数)
     fn read_number(&mut self) -> Result<Token, SnowFallError> {
         // 基数の接頭辞を確認する
@@ -451,24 +592,14 @@ impl<'a> Lexer<'a> {
                 b'_' => {
                     // 先頭 or '.' 直後は NG
                     if number_str.is_empty() || !prev_was_digit {
-                        return Err(SnowFallError::new_compiler_error(
-                            Some("Invalid number format: misplaced underscore".to_string()),
-                            ErrorCode::InvalidNumberFormat,
-                            self.line,
-                            self.column,
-                        ));
+                        return Err(self.number_error("misplaced_underscore"));
                     }
                     prev_was_underscore = true;
                 }
                 b'.' => {
                     // '_' 直後は NG
                     if prev_was_underscore {
-                        return Err(SnowFallError::new_compiler_error(
-                            Some("Invalid number format: misplaced underscore".to_string()),
-                            ErrorCode::InvalidNumberFormat,
-                            self.line,
-                            self.column,
-                        ));
+                        return Err(self.number_error("misplaced_underscore"));
                     }
                     dot_count += 1;
                     if dot_count > 1 {
@@ -490,33 +621,18 @@ impl<'a> Lexer<'a> {
 
         // 末尾 '_' は NG
         if prev_was_underscore {
-            return Err(SnowFallError::new_compiler_error(
-                Some("Invalid number format: misplaced underscore".to_string()),
-                ErrorCode::InvalidNumberFormat,
-                self.line,
-                self.column,
-            ));
+            return Err(self.number_error("misplaced_underscore"));
         }
 
         if dot_count >= 1 {
             let (int_str, frac_str) = match number_str.split_once('.') {
                 Some(v) => v,
                 None => {
-                    return Err(SnowFallError::new_compiler_error(
-                        Some("Invalid float format".to_string()),
-                        ErrorCode::InvalidNumberFormat,
-                        self.line,
-                        self.column,
-                    ));
+                    return Err(self.number_error("invalid_float"));
                 }
             };
             if int_str.is_empty() && frac_str.is_empty() {
-                return Err(SnowFallError::new_compiler_error(
-                    Some("Invalid float format".to_string()),
-                    ErrorCode::InvalidNumberFormat,
-                    self.line,
-                    self.column,
-                ));
+                return Err(self.number_error("invalid_float"));
             }
 
             if int_str.is_empty() {
@@ -529,39 +645,39 @@ impl<'a> Lexer<'a> {
                 Ok(f) => Ok(create_token!(
                     TokenKind::Literal(LiteralToken::Float(f)),
                     start_pos,
-                    self.position + 1
-                )),
-                Err(_) => Err(SnowFallError::new_compiler_error(
-                    Some(format!("Failed to parse float: {}", number_str)),
-                    ErrorCode::InvalidNumberFormat,
-                    self.line,
-                    self.column,
+                    self.position
                 )),
+                Err(_) => Err(self
+                    .number_error("unparsable")
+                    .with_arg("literal", number_str)),
             }
         } else {
             match number_str.parse::<i64>() {
                 Ok(i) => Ok(create_token!(
                     TokenKind::Literal(LiteralToken::Int(i)),
                     start_pos,
-                    self.position + 1
-                )),
-                Err(_) => Err(SnowFallError::new_compiler_error(
-                    Some(format!("Failed to parse integer: {}", number_str)),
-                    ErrorCode::InvalidNumberFormat,
-                    self.line,
-                    self.column,
+                    self.position
                 )),
+                Err(_) => Err(self
+                    .number_error("unparsable")
+                    .with_arg("literal", number_str)),
             }
         }
     }
 
+    /// 数値リテラルの形式エラーを生成します (`reason` はメッセージを選ぶための理由の識別子)
+    fn number_error(&self, reason: &str) -> SnowFallError {
+        SnowFallError::new_compiler_error(ErrorCode::InvalidNumberFormat, self.line, self.column)
+            .with_arg("reason", reason)
+    }
+
     /// 16進数リテラルを読み取ります
     fn read_hex_number(&mut self) -> Result<Token, SnowFallError> {
+        let start_pos = self.position;
+
         self.read_char(); // skip '0'
         self.read_char(); // skip 'x'
 
-        let start_pos = self.position;
-
         let mut dot_count: i32 = 0;
         let mut number_str = String::new();
 
@@ -573,24 +689,14 @@ impl<'a> Lexer<'a> {
                 b'_' => {
                     // 先頭 or '.' 直後は NG
                     if number_str.is_empty() || !prev_was_digit {
-                        return Err(SnowFallError::new_compiler_error(
-                            Some("Invalid hex format: misplaced underscore".to_string()),
-                            ErrorCode::InvalidNumberFormat,
-                            self.line,
-                            self.column,
-                        ));
+                        return Err(self.number_error("misplaced_underscore"));
                     }
                     prev_was_underscore = true;
                 }
                 b'.' => {
                     // '_' 直後は NG
                     if prev_was_underscore {
-                        return Err(SnowFallError::new_compiler_error(
-                            Some("Invalid hex format: misplaced underscore".to_string()),
-                            ErrorCode::InvalidNumberFormat,
-                            self.line,
-                            self.column,
-                        ));
+                        return Err(self.number_error("misplaced_underscore"));
                     }
                     dot_count += 1;
                     if dot_count > 1 {
@@ -612,24 +718,13 @@ impl<'a> Lexer<'a> {
 
         // 末尾 '_' は NG
         if prev_was_underscore {
-            return Err(SnowFallError::new_compiler_error(
-                Some("Invalid hex format: misplaced underscore".to_string()),
-                ErrorCode::InvalidNumberFormat,
-                self.line,
-                self.column,
-            ));
+            return Err(self.number_error("misplaced_underscore"));
         }
 
         if self.ch.is_ascii_alphabetic() {
-            return Err(SnowFallError::new_compiler_error(
-                Some(format!(
-                    "Invalid character in hex literal: {}",
-                    self.ch as char
-                )),
-                ErrorCode::InvalidNumberFormat,
-                self.line,
-                self.column,
-            ));
+            return Err(self
+                .number_error("invalid_digit")
+                .with_arg("digit", (self.ch as char).to_string()));
         }
 
         // 0xf.f のような16進浮動小数点数を処理します
@@ -638,33 +733,18 @@ impl<'a> Lexer<'a> {
             let (int_str, frac_str) = match number_str.split_once('.') {
                 Some(v) => v,
                 None => {
-                    return Err(SnowFallError::new_compiler_error(
-                        Some("Invalid hex float format".to_string()),
-                        ErrorCode::InvalidNumberFormat,
-                        self.line,
-                        self.column,
-                    ));
+                    return Err(self.number_error("invalid_float"));
                 }
             };
 
             if int_str.is_empty() {
-                return Err(SnowFallError::new_compiler_error(
-                    Some("Invalid hex float format".to_string()),
-                    ErrorCode::InvalidNumberFormat,
-                    self.line,
-                    self.column,
-                ));
+                return Err(self.number_error("invalid_float"));
             }
 
             let integer_part = match i64::from_str_radix(int_str, 16) {
                 Ok(v) => v as f64,
                 Err(_) => {
-                    return Err(SnowFallError::new_compiler_error(
-                        Some(format!("Failed to parse hex integer part: {}", int_str)),
-                        ErrorCode::InvalidNumberFormat,
-                        self.line,
-                        self.column,
-                    ));
+                    return Err(self.number_error("unparsable").with_arg("literal", int_str));
                 }
             };
 
@@ -675,12 +755,9 @@ impl<'a> Lexer<'a> {
                 let digit = match c.to_digit(16) {
                     Some(d) => d as f64,
                     None => {
-                        return Err(SnowFallError::new_compiler_error(
-                            Some(format!("Invalid hex digit in fractional part: {}", c)),
-                            ErrorCode::InvalidNumberFormat,
-                            self.line,
-                            self.column,
-                        ));
+                        return Err(self
+                            .number_error("invalid_digit")
+                            .with_arg("digit", c.to_string()));
                     }
                 };
                 fractional_part += digit / base;
@@ -690,7 +767,7 @@ impl<'a> Lexer<'a> {
             return Ok(create_token!(
                 TokenKind::Literal(LiteralToken::Float(integer_part + fractional_part)),
                 start_pos,
-                self.position + 1
+                self.position
             ));
         }
 
@@ -698,24 +775,21 @@ impl<'a> Lexer<'a> {
             Ok(i) => Ok(create_token!(
                 TokenKind::Literal(LiteralToken::Int(i)),
                 start_pos,
-                self.position + 1
-            )),
-            Err(_) => Err(SnowFallError::new_compiler_error(
-                Some(format!("Failed to parse hex integer: {}", number_str)),
-                ErrorCode::InvalidNumberFormat,
-                self.line,
-                self.column,
+                self.position
             )),
+            Err(_) => Err(self
+                .number_error("unparsable")
+                .with_arg("literal", number_str)),
         }
     }
 
     /// 2進数リテラルを読み取ります
     fn read_binary_number(&mut self) -> Result<Token, SnowFallError> {
+        let start_pos = self.position;
+
         self.read_char(); // skip '0'
         self.read_char(); // skip 'b'
 
-        let start_pos = self.position;
-
         let mut dot_count: i32 = 0;
         let mut number_str = String::new();
 
@@ -727,24 +801,14 @@ impl<'a> Lexer<'a> {
                 b'_' => {
                     // 先頭 or '.' 直後は NG
                     if number_str.is_empty() || !prev_was_digit {
-                        return Err(SnowFallError::new_compiler_error(
-                            Some("Invalid binary format: misplaced underscore".to_string()),
-                            ErrorCode::InvalidNumberFormat,
-                            self.line,
-                            self.column,
-                        ));
+                        return Err(self.number_error("misplaced_underscore"));
                     }
                     prev_was_underscore = true;
                 }
                 b'.' => {
                     // '_' 直後は NG
                     if prev_was_underscore {
-                        return Err(SnowFallError::new_compiler_error(
-                            Some("Invalid binary format: misplaced underscore".to_string()),
-                            ErrorCode::InvalidNumberFormat,
-                            self.line,
-                            self.column,
-                        ));
+                        return Err(self.number_error("misplaced_underscore"));
                     }
                     dot_count += 1;
                     if dot_count > 1 {
@@ -766,24 +830,13 @@ impl<'a> Lexer<'a> {
 
         // 末尾 '_' は NG
         if prev_was_underscore {
-            return Err(SnowFallError::new_compiler_error(
-                Some("Invalid binary format: misplaced underscore".to_string()),
-                ErrorCode::InvalidNumberFormat,
-                self.line,
-                self.column,
-            ));
+            return Err(self.number_error("misplaced_underscore"));
         }
 
         if self.ch.is_ascii_alphanumeric() {
-            return Err(SnowFallError::new_compiler_error(
-                Some(format!(
-                    "Invalid character in binary literal: {}",
-                    self.ch as char
-                )),
-                ErrorCode::InvalidNumberFormat,
-                self.line,
-                self.column,
-            ));
+            return Err(self
+                .number_error("invalid_digit")
+                .with_arg("digit", (self.ch as char).to_string()));
         }
 
         // 0b1.1 のような2進浮動小数点数を処理します
@@ -792,33 +845,18 @@ impl<'a> Lexer<'a> {
             let (int_str, frac_str) = match number_str.split_once('.') {
                 Some(v) => v,
                 None => {
-                    return Err(SnowFallError::new_compiler_error(
-                        Some("Invalid binary float format".to_string()),
-                        ErrorCode::InvalidNumberFormat,
-                        self.line,
-                        self.column,
-                    ));
+                    return Err(self.number_error("invalid_float"));
                 }
             };
 
             if int_str.is_empty() {
-                return Err(SnowFallError::new_compiler_error(
-                    Some("Invalid binary float format".to_string()),
-                    ErrorCode::InvalidNumberFormat,
-                    self.line,
-                    self.column,
-                ));
+                return Err(self.number_error("invalid_float"));
             }
 
             let integer_part = match i64::from_str_radix(int_str, 2) {
                 Ok(v) => v as f64,
                 Err(_) => {
-                    return Err(SnowFallError::new_compiler_error(
-                        Some(format!("Failed to parse binary integer part: {}", int_str)),
-                        ErrorCode::InvalidNumberFormat,
-                        self.line,
-                        self.column,
-                    ));
+                    return Err(self.number_error("unparsable").with_arg("literal", int_str));
                 }
             };
 
@@ -830,12 +868,9 @@ impl<'a> Lexer<'a> {
                     '0' => 0.0,
                     '1' => 1.0,
                     _ => {
-                        return Err(SnowFallError::new_compiler_error(
-                            Some(format!("Invalid binary digit in fractional part: {}", c)),
-                            ErrorCode::InvalidNumberFormat,
-                            self.line,
-                            self.column,
-                        ));
+                        return Err(self
+                            .number_error("invalid_digit")
+                            .with_arg("digit", c.to_string()));
                     }
                 };
                 fractional_part += digit / base;
@@ -845,7 +880,7 @@ impl<'a> Lexer<'a> {
             return Ok(create_token!(
                 TokenKind::Literal(LiteralToken::Float(integer_part + fractional_part)),
                 start_pos,
-                self.position + 1
+                self.position
             ));
         }
 
@@ -853,14 +888,11 @@ impl<'a> Lexer<'a> {
             Ok(i) => Ok(create_token!(
                 TokenKind::Literal(LiteralToken::Int(i)),
                 start_pos,
-                self.position + 1
-            )),
-            Err(_) => Err(SnowFallError::new_compiler_error(
-                Some(format!("Failed to parse binary integer: {}", number_str)),
-                ErrorCode::InvalidNumberFormat,
-                self.line,
-                self.column,
+                self.position
             )),
+            Err(_) => Err(self
+                .number_error("unparsable")
+                .with_arg("literal", number_str)),
         }
     }
 
@@ -878,7 +910,6 @@ impl<'a> Lexer<'a> {
 
         if self.ch == 0 {
             return Err(SnowFallError::new_compiler_error(
-                None,
                 ErrorCode::UnterminatedString,
                 self.line,
                 self.column,
@@ -887,10 +918,11 @@ impl<'a> Lexer<'a> {
 
         let s = self.input[position..self.position].to_string();
 
+        // 閉じ引用符は呼び出し元の `read_char` で消費される
         Ok(create_token!(
             TokenKind::Literal(LiteralToken::String(s)),
             start_pos,
-            self.position
+            self.position + 1
         ))
     }
 }
@@ -0,0 +1,183 @@
+//! 文と式の形だけで判定できるルール
+//!
+//! loose-equality / assignment-in-condition / constant-condition / empty-block / unreachable-code
+use super::{LintContext, Rule};
+use crate::common::Span;
+use crate::common::error::TextEdit;
+use crate::common::messages::Detail;
+use crate::compiler::ast::*;
+
+pub(super) fn check(program: &ProgramAst, context: &mut LintContext) {
+    let mut checker = Checker { context };
+    checker.check_statements(&program.statements);
+    checker.visit_program(program);
+}
+
+struct Checker<'c, 'a> {
+    context: &'c mut LintContext<'a>,
+}
+
+impl Checker<'_, '_> {
+    /// 必ず制御を外へ移す文より後ろの文を報告する
+    fn check_statements(&mut self, stmts: &[Statement]) {
+        let Some(exit) = stmts.iter().position(Statement::always_exits) else {
+            return;
+        };
+        let (Some(first), Some(last)) = (stmts.get(exit + 1), stmts.last()) else {
+            return;
+        };
+        let span = first.span.cover(last.span);
+        let source = self.context.source;
+        let exit_span = stmts[exit].span;
+        self.context
+            .report(Rule::UnreachableCode, span, |diagnostic| {
+                diagnostic
+                    .with_label(exit_span, Detail::UnreachableAfter, source)
+                    .with_suggestion(
+                        Detail::RemoveUnreachableCode,
+                        vec![TextEdit::replace(span, "", source)],
+                    )
+            });
+    }
+
+    /// if / while / for の条件式を検査する
+    fn check_condition(&mut self, condition: &Expression, is_loop: bool) {
+        if let ExpressionKind::Assignment { left, right } = &condition.kind {
+            let source = self.context.source;
+            let operator = find_operator(source, left.span, right.span, "=");
+            self.context
+                .report(
+                    Rule::AssignmentInCondition,
+                    condition.span,
+                    |diagnostic| match operator {
+                        Some(operator) => diagnostic.with_suggestion(
+                            Detail::UseStrictComparison,
+                            vec![TextEdit::replace(operator, "===", source)],
+                        ),
+                        None => diagnostic,
+                    },
+                );
+            return;
+        }
+
+        // 意図的な無限ループ `while (true)` は報告しない
+        if is_loop && condition.kind == ExpressionKind::Boolean(true) {
+            return;
+        }
+        if is_constant(condition) {
+            self.context
+                .report(Rule::ConstantCondition, condition.span, |diagnostic| {
+                    diagnostic
+                });
+        }
+    }
+
+    /// 空のブロックを報告する (理由のコメントがあるものを除く)
+    fn check_empty_block(&mut self, stmt: &Statement) {
+        let StatementKind::Block(stmts) = &stmt.kind else {
+            return;
+        };
+        if !stmts.is_empty() {
+            return;
+        }
+        let text = self
+            .context
+            .source
+            .get(stmt.span.start..stmt.span.end)
+            .unwrap_or("");
+        if text.contains("//") || text.contains("/*") {
+            return;
+        }
+        self.context
+            .report(Rule::EmptyBlock, stmt.span, |diagnostic| diagnostic);
+    }
+
+    fn check_loose_equality(&mut self, expr: &Expression) {
+        let ExpressionKind::Infix {
+            left,
+            operator,
+            right,
+        } = &expr.kind
+        else {
+            return;
+        };
+        let strict = match operator {
+            InfixOperator::Equals => InfixOperator::StrictEquals,
+            InfixOperator::NotEquals => InfixOperator::StrictNotEquals,
+            _ => return,
+        };
+        let source = self.context.source;
+        let operator_span = find_operator(source, left.span, right.span, operator.as_str());
+        self.context
+            .report(Rule::LooseEquality, expr.span, |diagnostic| {
+                let diagnostic = diagnostic
+                    .with_arg("operator", operator.as_str())
+                    .with_arg("strict", strict.as_str());
+                match operator_span {
+                    Some(span) => diagnostic.with_suggestion(
+                        Detail::ReplaceOperator,
+                        vec![TextEdit::replace(span, strict.as_str(), source)],
+                    ),
+                    None => diagnostic,
+                }
+            });
+    }
+}
+
+impl<'ast> Visitor<'ast> for Checker<'_, '_> {
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        self.check_empty_block(stmt);
+        match &stmt.kind {
+            StatementKind::Block(stmts) => self.check_statements(stmts),
+            StatementKind::If { condition, .. } => self.check_condition(condition, false),
+            StatementKind::While { condition, .. }
+            | StatementKind::For {
+                condition: Some(condition),
+                ..
+            } => self.check_condition(condition, true),
+            StatementKind::FunctionDeclaration { params, body, .. } => {
+                // 関数の本体は空でも報告しないため、ブロック自体の検査を飛ばす
+                for value in params.iter().filter_map(|p| p.value.as_ref()) {
+                    self.visit_expression(value);
+                }
+                if let StatementKind::Block(stmts) = &body.kind {
+                    self.check_statements(stmts);
+                }
+                walk_statement(self, body);
+                return;
+            }
+            _ => {}
+        }
+        walk_statement(self, stmt);
+    }
+
+    fn visit_expression(&mut self, expr: &'ast Expression) {
+        self.check_loose_equality(expr);
+        walk_expression(self, expr);
+    }
+}
+
+/// 変数を含まず、常に同じ値になる式かどうか
+fn is_constant(expr: &Expression) -> bool {
+    match &expr.kind {
+        ExpressionKind::IntLiteral(_)
+        | ExpressionKind::FloatLiteral(_)
+        | ExpressionKind::StringLiteral(_)
+        | ExpressionKind::Boolean(_)
+        | ExpressionKind::NullLiteral => true,
+        ExpressionKind::Prefix { right, .. } => is_constant(right),
+        ExpressionKind::Infix { left, right, .. } => is_constant(left) && is_constant(right),
+        ExpressionKind::Cast { expression, .. } => is_constant(expression),
+        _ => false,
+    }
+}
+
+/// 左右のオペランドの間にある演算子の範囲を求める
+fn find_operator(source: &str, left: Span, right: Span, operator: &str) -> Option<Span> {
+    let between = source.get(left.end..right.start)?;
+    let start = left.end + between.find(operator)?;
+    Some(Span {
+        start,
+        end: start + operator.len(),
+    })
+}
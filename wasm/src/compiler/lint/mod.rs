@@ -0,0 +1,229 @@
+//! リンター
+//!
+//! 構文解析・意味解析を通過したASTに対して、エラーではないが誤りの可能性が高い書き方を検出する。
+//! 各ルールは `LintConfig` で個別に無効化 (`off`)・警告 (`warn`)・エラー (`error`) を切り替えられる。
+//! `// snowfall-disable-next-line rule-a, rule-b` のコメントで次の行の診断を抑制できる
+//! (ルール名を省略した場合はすべてのルールを抑制する)。
+mod checks;
+mod scope;
+
+use ahash::AHashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::common::error::SnowFallError;
+use crate::common::messages::Locale;
+use crate::common::span::offset_to_line_column;
+use crate::common::{ErrorCode, Span, TriviaToken};
+use crate::compiler::Lexer;
+use crate::compiler::ast::ProgramAst;
+use crate::compiler::cst;
+
+/// 抑制コメントの接頭辞
+const DISABLE_NEXT_LINE: &str = "snowfall-disable-next-line";
+
+/// リントルール
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    /// 読み取られない変数
+    UnusedVariable,
+    /// 使われない引数 (最後に使われる引数より後ろのもの)
+    UnusedParameter,
+    /// 外側のスコープの名前を隠す宣言
+    Shadowing,
+    /// `==` / `!=` による比較
+    LooseEquality,
+    /// 条件式の中の代入
+    AssignmentInCondition,
+    /// 制御文の空のブロック
+    EmptyBlock,
+    /// `return` などの後の実行されない文
+    UnreachableCode,
+    /// 常に同じ値になる条件式
+    ConstantCondition,
+}
+
+impl Rule {
+    /// すべてのルール
+    pub const ALL: &'static [Rule] = &[
+        Rule::UnusedVariable,
+        Rule::UnusedParameter,
+        Rule::Shadowing,
+        Rule::LooseEquality,
+        Rule::AssignmentInCondition,
+        Rule::EmptyBlock,
+        Rule::UnreachableCode,
+        Rule::ConstantCondition,
+    ];
+
+    /// 設定や抑制コメントで使うルール名 (例: "unused-variable")
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::UnusedVariable => "unused-variable",
+            Rule::UnusedParameter => "unused-parameter",
+            Rule::Shadowing => "shadowing",
+            Rule::LooseEquality => "loose-equality",
+            Rule::AssignmentInCondition => "assignment-in-condition",
+            Rule::EmptyBlock => "empty-block",
+            Rule::UnreachableCode => "unreachable-code",
+            Rule::ConstantCondition => "constant-condition",
+        }
+    }
+
+    /// ルールが報告する診断のコード
+    pub fn code(self) -> ErrorCode {
+        match self {
+            Rule::UnusedVariable => ErrorCode::UnusedVariable,
+            Rule::UnusedParameter => ErrorCode::UnusedParameter,
+            Rule::Shadowing => ErrorCode::ShadowedName,
+            Rule::LooseEquality => ErrorCode::LooseEquality,
+            Rule::AssignmentInCondition => ErrorCode::AssignmentInCondition,
+            Rule::EmptyBlock => ErrorCode::EmptyBlock,
+            Rule::UnreachableCode => ErrorCode::UnreachableCode,
+            Rule::ConstantCondition => ErrorCode::ConstantCondition,
+        }
+    }
+}
+
+/// ルールの報告レベル
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLevel {
+    /// 検査しない
+    Off,
+    /// 警告として報告する
+    #[default]
+    Warn,
+    /// エラーとして報告する
+    Error,
+}
+
+/// リンターの設定
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LintConfig {
+    /// ルールごとの報告レベル (指定のないルールは `warn`)
+    pub rules: AHashMap<Rule, RuleLevel>,
+    /// 診断メッセージの言語
+    pub locale: Locale,
+}
+
+impl LintConfig {
+    /// ルールの報告レベル
+    pub fn level(&self, rule: Rule) -> RuleLevel {
+        self.rules.get(&rule).copied().unwrap_or_default()
+    }
+}
+
+/// 各ルールの検査で共有する状態
+pub(crate) struct LintContext<'a> {
+    pub source: &'a str,
+    config: &'a LintConfig,
+    diagnostics: Vec<SnowFallError>,
+}
+
+impl LintContext<'_> {
+    /// 診断を報告する
+    ///
+    /// `build` で引数・ラベル・修正案を設定する。無効なルールの場合は何もしない。
+    pub fn report(
+        &mut self,
+        rule: Rule,
+        span: Span,
+        build: impl FnOnce(SnowFallError) -> SnowFallError,
+    ) {
+        let diagnostic = match self.config.level(rule) {
+            RuleLevel::Off => return,
+            RuleLevel::Warn => {
+                SnowFallError::new_compiler_warning_at(rule.code(), span, self.source)
+            }
+            RuleLevel::Error => {
+                SnowFallError::new_compiler_error_at(rule.code(), span, self.source)
+            }
+        };
+        self.diagnostics
+            .push(build(diagnostic.with_context("rule", rule.name())));
+    }
+
+    /// `from` 以降で最初に現れる識別子 `name` の範囲を求める
+    pub fn find_identifier(&self, from: usize, name: &str) -> Option<Span> {
+        Lexer::find_identifier(self.source, from, name)
+    }
+}
+
+/// ASTを検査し、設定で有効なルールの診断を位置順に返す
+///
+/// ```
+/// use snowfall_core::compiler::lint::{LintConfig, lint};
+/// use snowfall_core::compiler::{Lexer, Parser};
+///
+/// let source = "sub f(Int x) {\n    if (x == 1) {}\n}";
+/// let program = Parser::new(Lexer::new(source)).parse_program().unwrap();
+/// let codes: Vec<_> = lint(&program, source, &LintConfig::default())
+///     .into_iter()
+///     .map(|diagnostic| diagnostic.code)
+///     .collect();
+/// assert_eq!(codes, ["SF0204", "SF0206"]);
+/// ```
+pub fn lint(program: &ProgramAst, source: &str, config: &LintConfig) -> Vec<SnowFallError> {
+    let mut context = LintContext {
+        source,
+        config,
+        diagnostics: Vec::new(),
+    };
+    scope::check(program, &mut context);
+    checks::check(program, &mut context);
+
+    let suppressions = suppressions(source);
+    let mut diagnostics: Vec<SnowFallError> = context
+        .diagnostics
+        .into_iter()
+        .filter(|diagnostic| !is_suppressed(diagnostic, &suppressions))
+        .collect();
+    diagnostics.sort_by_key(|diagnostic| diagnostic.span.map(|span| (span.start, span.end)));
+    for diagnostic in &mut diagnostics {
+        diagnostic.localize(config.locale);
+    }
+    diagnostics
+}
+
+/// 抑制コメントで指定された (行番号, ルール名の一覧) を求める
+///
+/// ルール名の一覧が空の場合はすべてのルールを抑制する。
+fn suppressions(source: &str) -> AHashMap<u32, Vec<String>> {
+    let mut suppressions = AHashMap::new();
+    let tokens = cst::lex_with_trivia(source);
+    let comments = tokens
+        .iter()
+        .flat_map(|token| token.leading_trivia.iter().chain(&token.trailing_trivia))
+        .filter(|trivia| trivia.kind == TriviaToken::LineComment);
+    for comment in comments {
+        let text = comment.text.trim_start_matches('/').trim();
+        let Some(rules) = text.strip_prefix(DISABLE_NEXT_LINE) else {
+            continue;
+        };
+        if !rules.is_empty() && !rules.starts_with(char::is_whitespace) {
+            continue;
+        }
+        let (line, _) = offset_to_line_column(source, comment.span.start);
+        let rules = rules
+            .split(',')
+            .map(str::trim)
+            .filter(|rule| !rule.is_empty())
+            .map(str::to_string)
+            .collect();
+        suppressions.insert(line + 1, rules);
+    }
+    suppressions
+}
+
+fn is_suppressed(diagnostic: &SnowFallError, suppressions: &AHashMap<u32, Vec<String>>) -> bool {
+    let Some(rules) = suppressions.get(&diagnostic.line) else {
+        return false;
+    };
+    let rule = diagnostic
+        .context
+        .as_ref()
+        .and_then(|context| context.get("rule"));
+    rules.is_empty() || rule.is_some_and(|rule| rules.contains(rule))
+}
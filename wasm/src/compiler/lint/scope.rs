@@ -0,0 +1,366 @@
+//! スコープ解析が必要なルール
+//!
+//! unused-variable / unused-parameter / shadowing
+//!
+//! スコープの区切り方は意味解析 (`semantic`) と同じ。
+//! 関数は宣言より前から呼び出せるため、その時点で解決できない参照はスコープを抜けるときに解決し直す。
+use ahash::AHashMap;
+
+use super::{LintContext, Rule};
+use crate::common::Span;
+use crate::common::messages::Detail;
+use crate::compiler::ast::*;
+
+pub(super) fn check(program: &ProgramAst, context: &mut LintContext) {
+    let mut analyzer = ScopeAnalyzer {
+        context,
+        scopes: vec![Scope::default()],
+    };
+    for stmt in &program.statements {
+        analyzer.check_statement(stmt);
+    }
+    analyzer.pop_scope();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SymbolKind {
+    Variable,
+    Parameter,
+    Function,
+    Class,
+    Field,
+}
+
+/// 宣言された名前
+struct Symbol {
+    name: String,
+    kind: SymbolKind,
+    /// 名前の範囲
+    span: Span,
+    /// 一度でも読み取られたかどうか
+    used: bool,
+}
+
+#[derive(Default)]
+struct Scope {
+    symbols: Vec<Symbol>,
+    /// 名前から `symbols` の添字 (同じスコープで再宣言した場合は後の宣言)
+    names: AHashMap<String, usize>,
+    /// このスコープ内で解決できなかった参照
+    unresolved: Vec<String>,
+}
+
+struct ScopeAnalyzer<'c, 'a> {
+    context: &'c mut LintContext<'a>,
+    scopes: Vec<Scope>,
+}
+
+impl ScopeAnalyzer<'_, '_> {
+    fn push_scope(&mut self) {
+        self.scopes.push(Scope::default());
+    }
+
+    /// スコープを抜け、未解決の参照を解決して使われなかった名前を報告する
+    fn pop_scope(&mut self) {
+        let Some(mut scope) = self.scopes.pop() else {
+            return;
+        };
+        for name in std::mem::take(&mut scope.unresolved) {
+            match scope.names.get(&name) {
+                Some(&index) => scope.symbols[index].used = true,
+                None => {
+                    if let Some(parent) = self.scopes.last_mut() {
+                        parent.unresolved.push(name);
+                    }
+                }
+            }
+        }
+
+        // 引数は最後に使われた引数より後ろのもののみ報告する
+        let last_used_param = scope
+            .symbols
+            .iter()
+            .rposition(|symbol| symbol.kind == SymbolKind::Parameter && symbol.used);
+        for (index, symbol) in scope.symbols.iter().enumerate() {
+            if symbol.used || symbol.name.starts_with('_') {
+                continue;
+            }
+            match symbol.kind {
+                SymbolKind::Variable => {
+                    self.context
+                        .report(Rule::UnusedVariable, symbol.span, |diagnostic| {
+                            diagnostic.with_arg("name", symbol.name.clone())
+                        });
+                }
+                SymbolKind::Parameter if last_used_param.is_none_or(|last| index > last) => {
+                    self.context
+                        .report(Rule::UnusedParameter, symbol.span, |diagnostic| {
+                            diagnostic.with_arg("name", symbol.name.clone())
+                        });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// 現在のスコープに名前を登録する
+    fn declare(&mut self, name: &str, kind: SymbolKind, span: Span) {
+        if matches!(kind, SymbolKind::Variable | SymbolKind::Parameter) {
+            self.check_shadowing(name, span);
+        }
+        let Some(scope) = self.scopes.last_mut() else {
+            return;
+        };
+        scope.names.insert(name.to_string(), scope.symbols.len());
+        scope.symbols.push(Symbol {
+            name: name.to_string(),
+            kind,
+            span,
+            used: false,
+        });
+    }
+
+    /// 外側のスコープに同じ名前の宣言があれば報告する (クラスのフィールドを除く)
+    fn check_shadowing(&mut self, name: &str, span: Span) {
+        let outer = self.scopes[..self.scopes.len().saturating_sub(1)]
+            .iter()
+            .rev()
+            .find_map(|scope| scope.names.get(name).map(|&index| &scope.symbols[index]));
+        let Some(outer) = outer else {
+            return;
+        };
+        if outer.kind == SymbolKind::Field {
+            return;
+        }
+        let outer_span = outer.span;
+        let source = self.context.source;
+        self.context.report(Rule::Shadowing, span, |diagnostic| {
+            diagnostic.with_arg("name", name).with_label(
+                outer_span,
+                Detail::FirstDeclaredHere,
+                source,
+            )
+        });
+    }
+
+    /// 名前の読み取りを記録する
+    fn reference(&mut self, name: &str) {
+        for scope in self.scopes.iter_mut().rev() {
+            if let Some(&index) = scope.names.get(name) {
+                scope.symbols[index].used = true;
+                return;
+            }
+        }
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.unresolved.push(name.to_string());
+        }
+    }
+
+    /// `from` 以降の名前の範囲 (見つからない場合は `fallback`)
+    fn name_span(&self, from: usize, name: &str, fallback: Span) -> Span {
+        self.context.find_identifier(from, name).unwrap_or(fallback)
+    }
+
+    fn check_statement(&mut self, stmt: &Statement) {
+        match &stmt.kind {
+            StatementKind::VariableDeclaration {
+                type_name,
+                declarators,
+                ..
+            } => {
+                let mut cursor = self
+                    .context
+                    .find_identifier(stmt.span.start, type_name)
+                    .map_or(stmt.span.start, |span| span.end);
+                for decl in declarators {
+                    let span = self.name_span(cursor, &decl.name, stmt.span);
+                    cursor = span.end;
+                    if let Some(value) = &decl.value {
+                        self.check_expression(value);
+                        cursor = cursor.max(value.span.end);
+                    }
+                    self.declare(&decl.name, SymbolKind::Variable, span);
+                }
+            }
+            StatementKind::FunctionDeclaration {
+                name, params, body, ..
+            } => {
+                let name_span = self.name_span(stmt.span.start, name, stmt.span);
+                self.declare(name, SymbolKind::Function, name_span);
+                self.push_scope();
+                let mut cursor = name_span.end;
+                for param in params {
+                    let type_end = self
+                        .context
+                        .find_identifier(cursor, &param.type_name)
+                        .map_or(cursor, |span| span.end);
+                    let span = self.name_span(type_end, &param.name, stmt.span);
+                    cursor = span.end;
+                    if let Some(value) = &param.value {
+                        self.check_expression(value);
+                        cursor = cursor.max(value.span.end);
+                    }
+                    self.declare(&param.name, SymbolKind::Parameter, span);
+                }
+                self.check_statement(body);
+                self.pop_scope();
+            }
+            StatementKind::ClassDeclaration { name, members, .. } => {
+                let name_span = self.name_span(stmt.span.start, name, stmt.span);
+                self.declare(name, SymbolKind::Class, name_span);
+                // フィールドはメソッド内からも参照できるよう先に登録する
+                self.push_scope();
+                for member in members {
+                    if let StatementKind::VariableDeclaration { declarators, .. } = &member.kind {
+                        for decl in declarators {
+                            let span = self.name_span(member.span.start, &decl.name, member.span);
+                            self.declare(&decl.name, SymbolKind::Field, span);
+                        }
+                    }
+                }
+                for member in members {
+                    match &member.kind {
+                        StatementKind::VariableDeclaration { declarators, .. } => {
+                            for value in declarators.iter().filter_map(|d| d.value.as_ref()) {
+                                self.check_expression(value);
+                            }
+                        }
+                        _ => self.check_statement(member),
+                    }
+                }
+                self.pop_scope();
+            }
+            StatementKind::If {
+                condition,
+                consequence,
+                alternative,
+            } => {
+                self.check_expression(condition);
+                self.check_statement(consequence);
+                if let Some(alt) = alternative {
+                    self.check_statement(alt);
+                }
+            }
+            StatementKind::For {
+                init,
+                condition,
+                update,
+                body,
+            } => {
+                self.push_scope();
+                if let Some(init) = init {
+                    self.check_statement(init);
+                }
+                if let Some(condition) = condition {
+                    self.check_expression(condition);
+                }
+                if let Some(update) = update {
+                    self.check_statement(update);
+                }
+                self.check_statement(body);
+                self.pop_scope();
+            }
+            StatementKind::ForEach {
+                binding,
+                iterable,
+                body,
+                ..
+            } => {
+                self.check_expression(iterable);
+                self.push_scope();
+                let span = self.name_span(stmt.span.start, &binding.name, stmt.span);
+                self.declare(&binding.name, SymbolKind::Variable, span);
+                self.check_statement(body);
+                self.pop_scope();
+            }
+            StatementKind::While { condition, body } => {
+                self.check_expression(condition);
+                self.check_statement(body);
+            }
+            StatementKind::Switch {
+                expression,
+                cases,
+                default,
+            } => {
+                self.check_expression(expression);
+                for case in cases {
+                    for value in &case.values {
+                        self.check_expression(value);
+                    }
+                    self.check_statement(&case.body);
+                }
+                if let Some(default) = default {
+                    self.check_statement(default);
+                }
+            }
+            StatementKind::Return(value) => {
+                if let Some(value) = value {
+                    self.check_expression(value);
+                }
+            }
+            StatementKind::Break | StatementKind::Continue => {}
+            StatementKind::Block(stmts) => {
+                self.push_scope();
+                for stmt in stmts {
+                    self.check_statement(stmt);
+                }
+                self.pop_scope();
+            }
+            StatementKind::Expression(expr) => self.check_expression(expr),
+        }
+    }
+
+    fn check_expression(&mut self, expr: &Expression) {
+        match &expr.kind {
+            ExpressionKind::IntLiteral(_)
+            | ExpressionKind::FloatLiteral(_)
+            | ExpressionKind::StringLiteral(_)
+            | ExpressionKind::Boolean(_)
+            | ExpressionKind::NullLiteral => {}
+            ExpressionKind::Identifier(name) => self.reference(name),
+            ExpressionKind::Prefix { right, .. } => self.check_expression(right),
+            ExpressionKind::Infix { left, right, .. } => {
+                self.check_expression(left);
+                self.check_expression(right);
+            }
+            ExpressionKind::Call {
+                function,
+                arguments,
+            }
+            | ExpressionKind::New {
+                class: function,
+                arguments,
+            } => {
+                self.check_expression(function);
+                for arg in arguments {
+                    self.check_expression(arg);
+                }
+            }
+            ExpressionKind::Cast { expression, .. } => self.check_expression(expression),
+            ExpressionKind::ArrayLiteral(elements) => {
+                for element in elements {
+                    self.check_expression(element);
+                }
+            }
+            ExpressionKind::ObjectLiteral { pairs } => {
+                for (key, value) in pairs {
+                    self.check_expression(key);
+                    self.check_expression(value);
+                }
+            }
+            ExpressionKind::MemberAccess { object, property } => {
+                self.check_expression(object);
+                if let MemberProperty::Computed(index) = property {
+                    self.check_expression(index);
+                }
+            }
+            ExpressionKind::Assignment { left, right } => {
+                // 変数への代入は読み取りとして扱わない
+                if !matches!(left.kind, ExpressionKind::Identifier(_)) {
+                    self.check_expression(left);
+                }
+                self.check_expression(right);
+            }
+        }
+    }
+}
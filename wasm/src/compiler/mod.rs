@@ -1,7 +1,19 @@
+pub mod arena;
 pub mod ast;
+pub mod completion;
+pub mod cst;
+pub mod formatter;
+pub mod incremental;
+pub mod interner;
 pub mod lexer;
+pub mod lint;
 pub mod normalizer;
 pub mod parser;
+pub mod resolver;
+pub mod semantic;
+pub mod semantic_tokens;
+pub mod side_table;
+pub mod unparser;
 
 pub use lexer::Lexer;
 pub use parser::Parser;
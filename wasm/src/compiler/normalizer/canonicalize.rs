@@ -0,0 +1,203 @@
+use std::cmp::Ordering;
+
+use super::effects::is_pure;
+use super::types::{TypeEnvironment, TypedFolder, ValueType, fold_statement_with_types};
+use crate::compiler::ast::*;
+
+/// オペランドをソートするためのキー
+#[derive(Debug, Eq)]
+enum SortKey {
+    Identifier(String),
+    Literal(String),
+    Other,
+}
+
+/// `SortKey` の全順序を定義
+impl Ord for SortKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (SortKey::Identifier(a), SortKey::Identifier(b)) => a.cmp(b),
+            (SortKey::Literal(a), SortKey::Literal(b)) => a.cmp(b),
+            (SortKey::Identifier(_), SortKey::Literal(_)) => Ordering::Less,
+            (SortKey::Literal(_), SortKey::Identifier(_)) => Ordering::Greater,
+            (SortKey::Other, SortKey::Other) => Ordering::Equal,
+            (_, SortKey::Other) => Ordering::Less,
+            (SortKey::Other, _) => Ordering::Greater,
+        }
+    }
+}
+
+/// `Ord` に基づく完全順序を常に返す
+impl PartialOrd for SortKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// `Ord` の結果が `Equal` かどうかで等価性を判定する
+impl PartialEq for SortKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+/// 式からソート用キーを生成する
+fn get_sort_key(expr: &Expression) -> SortKey {
+    match &expr.kind {
+        ExpressionKind::Identifier(name) => SortKey::Identifier(name.clone()),
+        ExpressionKind::IntLiteral(val) => SortKey::Literal(val.to_string()),
+        ExpressionKind::FloatLiteral(val) => SortKey::Literal(val.to_string()),
+        ExpressionKind::StringLiteral(val) => SortKey::Literal(val.clone()),
+        ExpressionKind::Boolean(val) => SortKey::Literal(val.to_string()),
+        ExpressionKind::NullLiteral => SortKey::Literal("null".to_string()),
+        _ => SortKey::Other,
+    }
+}
+
+/// 演算が可換であるかを判定する (オペランドの型は問わない)
+fn is_commutative(op: &InfixOperator) -> bool {
+    matches!(op, InfixOperator::Add | InfixOperator::Multiply)
+}
+
+/// 指定した演算子に対して、
+/// ネストされた同一演算子の式を再帰的にフラット化しオペランドを収集する
+///
+/// `(a + (b + c))` → `[a, b, c]`
+fn collect_operands(expr: Expression, op: &InfixOperator, operands: &mut Vec<Expression>) {
+    match expr {
+        Expression {
+            kind:
+                ExpressionKind::Infix {
+                    left,
+                    operator,
+                    right,
+                },
+            ..
+        } if &operator == op => {
+            collect_operands(*left, op, operands);
+            collect_operands(*right, op, operands);
+        }
+        _ => {
+            operands.push(expr);
+        }
+    }
+}
+
+/// `collect_operands` と同様にオペランドを収集する (式は消費しない)
+fn collect_operand_refs<'e>(
+    expr: &'e Expression,
+    op: &InfixOperator,
+    operands: &mut Vec<&'e Expression>,
+) {
+    match &expr.kind {
+        ExpressionKind::Infix {
+            left,
+            operator,
+            right,
+        } if operator == op => {
+            collect_operand_refs(left, op, operands);
+            collect_operand_refs(right, op, operands);
+        }
+        _ => operands.push(expr),
+    }
+}
+
+/// ソート済みオペランド列から左結合の AST を再構築する。
+///
+/// `a, b, c` → `((a op b) op c)`
+///
+/// 途中のノードの範囲は、含まれるオペランド全体を覆う範囲とする
+fn rebuild_tree(mut operands: Vec<Expression>, op: InfixOperator) -> Expression {
+    let mut left = operands.remove(0);
+    while !operands.is_empty() {
+        let right = operands.remove(0);
+        let span = left.span.cover(right.span);
+        left = Expression {
+            span,
+            id: NodeId::DUMMY,
+            kind: ExpressionKind::Infix {
+                left: Box::new(left),
+                operator: op.clone(),
+                right: Box::new(right),
+            },
+        };
+    }
+    left
+}
+
+/// 可換な演算のオペランドを並べ替え、同じ意味の式を同じ形にそろえるパス
+///
+/// `c + a + b` → `(a + b) + c`
+///
+/// 評価順や結果が変わらないよう、次の場合に限って並べ替える
+/// - 全てのオペランドに副作用がない
+/// - 全てのオペランドが `Int` (結合則も成り立つ)、またはオペランドが2つで共に数値型
+///
+/// 文字列の連結や型の分からないオペランドは並べ替えない
+pub(super) struct CommutativeCanonicalization {
+    types: TypeEnvironment,
+}
+
+impl CommutativeCanonicalization {
+    pub(super) fn new(program: &ProgramAst) -> Self {
+        Self {
+            types: TypeEnvironment::new(program),
+        }
+    }
+
+    /// オペランドを並べ替えても評価の結果と副作用が変わらないかを判定する
+    fn can_reorder(&self, operands: &[&Expression]) -> bool {
+        if !operands.iter().all(|operand| is_pure(operand)) {
+            return false;
+        }
+        let Some(types) = operands
+            .iter()
+            .map(|operand| self.types.infer(operand).filter(|t| t.is_numeric()))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return false;
+        };
+        // 浮動小数点数の演算は可換だが結合則は成り立たないため、3つ以上は並べ替えない
+        types.iter().all(|t| *t == ValueType::Int) || types.len() == 2
+    }
+}
+
+impl TypedFolder for CommutativeCanonicalization {
+    fn types(&mut self) -> &mut TypeEnvironment {
+        &mut self.types
+    }
+}
+
+impl Folder for CommutativeCanonicalization {
+    fn fold_statement(&mut self, stmt: Statement) -> Option<Statement> {
+        Some(fold_statement_with_types(self, stmt))
+    }
+
+    fn fold_expression(&mut self, expr: Expression) -> Expression {
+        let expr = fold_expression_children(self, expr);
+        let ExpressionKind::Infix { operator, .. } = &expr.kind else {
+            return expr;
+        };
+        if !is_commutative(operator) {
+            return expr;
+        }
+
+        // 並べ替えられない場合は結合の形も変えずにそのまま返す
+        let mut borrowed = Vec::new();
+        collect_operand_refs(&expr, operator, &mut borrowed);
+        if !self.can_reorder(&borrowed) {
+            return expr;
+        }
+
+        let operator = operator.clone();
+        let (span, id) = (expr.span, expr.id);
+        let mut operands = Vec::new();
+        collect_operands(expr, &operator, &mut operands);
+        operands.sort_by_key(get_sort_key);
+        // 再構築した式の根は元の式を置き換えるためIDと範囲を引き継ぐ
+        let mut rebuilt = rebuild_tree(operands, operator);
+        rebuilt.id = id;
+        rebuilt.span = span;
+        rebuilt
+    }
+}
@@ -0,0 +1,321 @@
+use ahash::AHashSet;
+
+use super::effects::{is_pure, is_pure_except, is_read_only};
+use super::names::{DeclaredNames, ReferencedNames};
+use super::types::{TypeEnvironment, TypedFolder, fold_statement_with_types};
+use crate::common::Span;
+use crate::compiler::ast::*;
+
+/// 一時変数名の接頭辞
+const TEMPORARY_PREFIX: &str = "_cse";
+
+/// 位置情報とIDを取り除いた式 (構造が同じ式の比較に使う)
+fn strip(expr: &Expression) -> Expression {
+    struct StripMetadata;
+
+    impl Folder for StripMetadata {
+        fn fold_expression(&mut self, expr: Expression) -> Expression {
+            let mut expr = fold_expression_children(self, expr);
+            expr.span = Span { start: 0, end: 0 };
+            expr.id = NodeId::DUMMY;
+            expr
+        }
+    }
+
+    StripMetadata.fold_expression(expr.clone())
+}
+
+/// 式のノード数を数える
+fn expression_size(expr: &Expression) -> usize {
+    struct NodeCount(usize);
+
+    impl<'ast> Visitor<'ast> for NodeCount {
+        fn visit_expression(&mut self, expr: &'ast Expression) {
+            self.0 += 1;
+            walk_expression(self, expr);
+        }
+    }
+
+    let mut count = NodeCount(0);
+    count.visit_expression(expr);
+    count.0
+}
+
+/// 式に含まれる識別子を収集する
+fn identifiers(expr: &Expression) -> AHashSet<String> {
+    let mut referenced = ReferencedNames::default();
+    referenced.visit_expression(expr);
+    referenced.counts.into_keys().collect()
+}
+
+/// 一時変数に置き換える候補となる部分式を出現順に収集する
+///
+/// 短絡評価される論理演算の右辺は評価されない場合があるため、候補の出現として数えない
+fn collect_candidates<'e>(expr: &'e Expression, candidates: &mut Vec<&'e Expression>) {
+    match &expr.kind {
+        ExpressionKind::IntLiteral(_)
+        | ExpressionKind::FloatLiteral(_)
+        | ExpressionKind::StringLiteral(_)
+        | ExpressionKind::Boolean(_)
+        | ExpressionKind::NullLiteral
+        | ExpressionKind::Identifier(_) => {}
+        ExpressionKind::Prefix { right, .. } => {
+            candidates.push(expr);
+            collect_candidates(right, candidates);
+        }
+        ExpressionKind::Infix {
+            left,
+            operator,
+            right,
+        } => {
+            candidates.push(expr);
+            collect_candidates(left, candidates);
+            if !matches!(
+                operator,
+                InfixOperator::LogicalAnd
+                    | InfixOperator::LogicalOr
+                    | InfixOperator::LogicalAndAlso
+                    | InfixOperator::LogicalOrElse
+            ) {
+                collect_candidates(right, candidates);
+            }
+        }
+        ExpressionKind::Cast { expression, .. } => {
+            candidates.push(expr);
+            collect_candidates(expression, candidates);
+        }
+        ExpressionKind::MemberAccess { object, property } => {
+            candidates.push(expr);
+            collect_candidates(object, candidates);
+            if let MemberProperty::Computed(index) = property {
+                collect_candidates(index, candidates);
+            }
+        }
+        ExpressionKind::ArrayLiteral(elements) => {
+            for element in elements {
+                collect_candidates(element, candidates);
+            }
+        }
+        ExpressionKind::ObjectLiteral { pairs } => {
+            for (key, value) in pairs {
+                collect_candidates(key, candidates);
+                collect_candidates(value, candidates);
+            }
+        }
+        // 書き換えを含む式は置き換えの対象外 (`is_read_only` で事前に除外する)
+        ExpressionKind::Call { .. }
+        | ExpressionKind::Assignment { .. }
+        | ExpressionKind::New { .. } => {}
+    }
+}
+
+/// 構造が `target` と同じ部分式を一時変数の参照に置き換える
+struct Replace<'a> {
+    target: &'a Expression,
+    name: &'a str,
+    /// 最初に置き換えた部分式 (一時変数の初期化式になる)
+    first: Option<Expression>,
+}
+
+impl Folder for Replace<'_> {
+    fn fold_expression(&mut self, expr: Expression) -> Expression {
+        if strip(&expr) != *self.target {
+            return fold_expression_children(self, expr);
+        }
+        let (span, id) = (expr.span, expr.id);
+        self.first.get_or_insert(expr);
+        Expression {
+            kind: ExpressionKind::Identifier(self.name.to_string()),
+            span,
+            id,
+        }
+    }
+}
+
+/// 1つの文の中で繰り返し現れる式を一時変数に置き換えるパス
+///
+/// `Int r = a.b * c + a.b * d;` → `const Int _cse0 = a.b; Int r = _cse0 * c + _cse0 * d;`
+///
+/// 一時変数は文の直前で宣言するため、文の並び (プログラム直下とブロック) にある次の文のみを対象とする
+/// - 式文、変数宣言、`return` 文、`if` 文の条件式
+///
+/// 評価結果が変わらないよう、次の条件を満たす場合のみ置き換える
+/// - 文の式が変数やオブジェクトを書き換えない (呼び出し・代入・インスタンス生成を含まない)
+/// - 一時変数の型が推論できる (宣言には型名が必要なため)
+/// - 実行時エラーの可能性がある式 (メンバーアクセスなど) は、それ以外の部分に実行時エラーの可能性がない
+///   (先に評価しても観測できる違いがない)
+///
+/// 型の推論には変数・引数・フィールドの宣言を使う。宣言のない変数を含む式は型が分からないため、
+/// `a.b * c + a.b * d;` は `a` がクラス型の変数や引数として宣言されている場合のみ置き換える
+pub(super) struct CommonSubexpressionElimination {
+    types: TypeEnvironment,
+    /// プログラム中で使われている名前 (一時変数名の衝突を避ける)
+    used_names: AHashSet<String>,
+    /// 次に試す一時変数の番号
+    next_temporary: usize,
+}
+
+impl CommonSubexpressionElimination {
+    pub(super) fn new(program: &ProgramAst) -> Self {
+        let mut declared = DeclaredNames::default();
+        declared.visit_program(program);
+        let mut referenced = ReferencedNames::default();
+        referenced.visit_program(program);
+        Self {
+            types: TypeEnvironment::new(program),
+            used_names: declared
+                .counts
+                .into_keys()
+                .chain(referenced.counts.into_keys())
+                .collect(),
+            next_temporary: 0,
+        }
+    }
+
+    /// 使われていない一時変数名を生成する
+    fn fresh_name(&mut self) -> String {
+        loop {
+            let name = format!("{TEMPORARY_PREFIX}{}", self.next_temporary);
+            self.next_temporary += 1;
+            if self.used_names.insert(name.clone()) {
+                return name;
+            }
+        }
+    }
+
+    /// 文の並びを書き換え、共通部分式の一時変数を各文の直前に挿入する
+    fn fold_list(&mut self, stmts: Vec<Statement>) -> Vec<Statement> {
+        let mut result = Vec::with_capacity(stmts.len());
+        for stmt in stmts {
+            let Some(mut stmt) = self.fold_statement(stmt) else {
+                continue;
+            };
+            while let Some(temporary) = self.eliminate(&mut stmt) {
+                result.push(temporary);
+            }
+            result.push(stmt);
+        }
+        result
+    }
+
+    /// 文の中で最も大きい共通部分式を1つ一時変数に置き換え、その宣言を返す
+    fn eliminate(&mut self, stmt: &mut Statement) -> Option<Statement> {
+        // 文の中で一度だけ評価される式
+        let (roots, declared): (Vec<&mut Expression>, Vec<String>) = match &mut stmt.kind {
+            StatementKind::Expression(expr) => (vec![expr], Vec::new()),
+            StatementKind::Return(Some(expr)) => (vec![expr], Vec::new()),
+            StatementKind::If { condition, .. } => (vec![condition], Vec::new()),
+            StatementKind::VariableDeclaration { declarators, .. } => {
+                let names = declarators.iter().map(|d| d.name.clone()).collect();
+                let values = declarators
+                    .iter_mut()
+                    .filter_map(|d| d.value.as_mut())
+                    .collect();
+                (values, names)
+            }
+            _ => return None,
+        };
+        if !roots.iter().all(|root| is_read_only(root)) {
+            return None;
+        }
+
+        let target = self.select_target(&roots, &declared)?;
+        let value_type = self.types.infer(&target)?;
+        let name = self.fresh_name();
+        let mut replace = Replace {
+            target: &target,
+            name: &name,
+            first: None,
+        };
+        for root in roots {
+            let expr = std::mem::replace(
+                root,
+                Expression {
+                    kind: ExpressionKind::NullLiteral,
+                    span: stmt.span,
+                    id: NodeId::DUMMY,
+                },
+            );
+            *root = replace.fold_expression(expr);
+        }
+        let value = replace.first?;
+
+        self.types.declare(name.clone(), Some(value_type.clone()));
+        Some(Statement {
+            span: value.span,
+            id: NodeId::DUMMY,
+            kind: StatementKind::VariableDeclaration {
+                type_name: value_type.type_name().to_string(),
+                declarators: vec![VariableDeclarator {
+                    name,
+                    value: Some(value),
+                }],
+                mutability: Mutability::Const,
+            },
+        })
+    }
+
+    /// 2回以上現れる部分式のうち、置き換えられる最も大きいものを選ぶ
+    fn select_target(&self, roots: &[&mut Expression], declared: &[String]) -> Option<Expression> {
+        let mut candidates = Vec::new();
+        for root in roots {
+            collect_candidates(root, &mut candidates);
+        }
+        let mut groups: Vec<(Expression, usize, usize)> = Vec::new();
+        for candidate in candidates {
+            let key = strip(candidate);
+            match groups.iter_mut().find(|(k, _, _)| *k == key) {
+                Some((_, count, _)) => *count += 1,
+                None => groups.push((key, 1, expression_size(candidate))),
+            }
+        }
+
+        groups
+            .into_iter()
+            .filter(|(key, count, _)| {
+                *count >= 2
+                    && self.types.infer(key).is_some()
+                    // 同じ文で宣言する変数を参照する式は、文の前に移動できない
+                    && identifiers(key).iter().all(|name| !declared.contains(name))
+                    && (is_pure(key)
+                        || roots
+                            .iter()
+                            .all(|root| is_pure_except(root, &|e| strip(e) == *key)))
+            })
+            // 大きい順 (同じ大きさなら先に現れた順)
+            .min_by_key(|(_, _, size)| std::cmp::Reverse(*size))
+            .map(|(key, _, _)| key)
+    }
+}
+
+impl TypedFolder for CommonSubexpressionElimination {
+    fn types(&mut self) -> &mut TypeEnvironment {
+        &mut self.types
+    }
+}
+
+impl Folder for CommonSubexpressionElimination {
+    fn fold_program(&mut self, program: ProgramAst) -> ProgramAst {
+        ProgramAst {
+            statements: self.fold_list(program.statements),
+            span: program.span,
+            id: program.id,
+        }
+    }
+
+    fn fold_statement(&mut self, stmt: Statement) -> Option<Statement> {
+        match stmt.kind {
+            StatementKind::Block(stmts) => {
+                self.types.push();
+                let stmts = self.fold_list(stmts);
+                self.types.pop();
+                Some(Statement {
+                    kind: StatementKind::Block(stmts),
+                    span: stmt.span,
+                    id: stmt.id,
+                })
+            }
+            _ => Some(fold_statement_with_types(self, stmt)),
+        }
+    }
+}
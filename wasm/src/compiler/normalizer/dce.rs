@@ -0,0 +1,155 @@
+use super::PassContext;
+use super::effects::is_pure;
+use crate::compiler::ast::*;
+
+/// 実行されない文や不要な文を削除するパス
+///
+/// - 条件が定数の `if` を畳み込み、実行されない分岐を警告に記録する
+/// - `while (false)` / `for (; false;)` を削除し、本体を警告に記録する
+/// - 到達不能な文を削除し、警告に記録する
+/// - 副作用のない式文と空のブロックを削除する
+pub(super) struct DeadCodeElimination<'a> {
+    pub(super) context: &'a mut PassContext,
+}
+
+impl DeadCodeElimination<'_> {
+    /// 文の並びから不要な文を取り除く
+    ///
+    /// - 必ず脱出する文 (`return` など) より後ろの文は到達不能として削除し、警告に記録する
+    /// - 空のブロックを削除する
+    fn eliminate_dead_statements(&mut self, mut statements: Vec<Statement>) -> Vec<Statement> {
+        if let Some(exit) = statements.iter().position(Statement::always_exits) {
+            let unreachable = statements.split_off(exit + 1);
+            if let (Some(first), Some(last)) = (unreachable.first(), unreachable.last()) {
+                self.context.unreachable.push(first.span.cover(last.span));
+            }
+        }
+        statements
+            .retain(|stmt| !matches!(&stmt.kind, StatementKind::Block(body) if body.is_empty()));
+        statements
+    }
+
+    /// 実行されない文を到達不能なコードとして記録する (空のブロックは記録しない)
+    ///
+    /// 文の中で既に記録した範囲は、この文の範囲にまとめる
+    fn record_unreachable(&mut self, stmt: &Statement) {
+        if matches!(&stmt.kind, StatementKind::Block(body) if body.is_empty()) {
+            return;
+        }
+        let span = stmt.span;
+        self.context
+            .unreachable
+            .retain(|inner| inner.start < span.start || span.end < inner.end);
+        self.context.unreachable.push(span);
+    }
+
+    /// 子ノードを処理した文のうち、不要なものを削除する
+    fn eliminate_statement(&mut self, stmt: Statement) -> Option<Statement> {
+        let Statement { kind, span, id } = fold_statement_children(self, stmt);
+        match kind {
+            StatementKind::Block(body) => {
+                let mut body = self.eliminate_dead_statements(body);
+                // 他に文を持たないブロックの入れ子はまとめる
+                if body.len() == 1
+                    && let StatementKind::Block(inner) = &mut body[0].kind
+                {
+                    body = std::mem::take(inner);
+                }
+                Some(Statement {
+                    kind: StatementKind::Block(body),
+                    span,
+                    id,
+                })
+            }
+            // `for (init; false;)` は初期化のみ実行される
+            StatementKind::For {
+                init,
+                condition:
+                    Some(Expression {
+                        kind: ExpressionKind::Boolean(false),
+                        ..
+                    }),
+                body,
+                ..
+            } => {
+                self.record_unreachable(&body);
+                init.filter(|init| has_effect(init))
+                    .map(|init| match init.kind {
+                        // 初期化で宣言した変数がループの外へ漏れないようにブロックで囲む
+                        StatementKind::VariableDeclaration { .. } => Statement {
+                            kind: StatementKind::Block(vec![*init]),
+                            span,
+                            id,
+                        },
+                        _ => *init,
+                    })
+            }
+            StatementKind::While {
+                condition:
+                    Expression {
+                        kind: ExpressionKind::Boolean(false),
+                        ..
+                    },
+                body,
+            } => {
+                self.record_unreachable(&body);
+                None
+            }
+            // 副作用のない式文は結果が使われないため削除する
+            StatementKind::Expression(expr) if is_pure(&expr) => None,
+            kind => Some(Statement { kind, span, id }),
+        }
+    }
+}
+
+impl Folder for DeadCodeElimination<'_> {
+    fn fold_program(&mut self, program: ProgramAst) -> ProgramAst {
+        let program = fold_program_children(self, program);
+        ProgramAst {
+            statements: self.eliminate_dead_statements(program.statements),
+            ..program
+        }
+    }
+
+    fn fold_statement(&mut self, stmt: Statement) -> Option<Statement> {
+        match stmt.kind {
+            StatementKind::If {
+                condition:
+                    Expression {
+                        kind: ExpressionKind::Boolean(value),
+                        ..
+                    },
+                consequence,
+                alternative,
+            } => {
+                if value {
+                    // if(true)なので、consequenceを返す
+                    if let Some(alt) = &alternative {
+                        self.record_unreachable(alt);
+                    }
+                    self.fold_statement(*consequence)
+                } else {
+                    // if(false)なので、alternativeを返す (elseがない場合は文自体を削除)
+                    self.record_unreachable(&consequence);
+                    alternative.and_then(|alt| self.fold_statement(*alt))
+                }
+            }
+            kind => self.eliminate_statement(Statement {
+                kind,
+                span: stmt.span,
+                id: stmt.id,
+            }),
+        }
+    }
+}
+
+/// 文の実行に副作用があるかどうかを判定する (宣言は初期化式に副作用がある場合のみ)
+fn has_effect(stmt: &Statement) -> bool {
+    match &stmt.kind {
+        StatementKind::Expression(expr) => !is_pure(expr),
+        StatementKind::VariableDeclaration { declarators, .. } => declarators
+            .iter()
+            .any(|decl| decl.value.as_ref().is_some_and(|value| !is_pure(value))),
+        _ => true,
+    }
+}
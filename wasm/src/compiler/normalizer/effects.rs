@@ -0,0 +1,78 @@
+use crate::compiler::ast::*;
+
+/// 式の評価に副作用がなく、実行時エラーも起こさないかどうかを判定する
+///
+/// 呼び出しや代入、インスタンス生成、メンバーアクセス (ゲッターやnull参照の可能性がある) を含む式、
+/// ゼロ除算の可能性がある `/` `%` は副作用ありとみなす
+pub(super) fn is_pure(expr: &Expression) -> bool {
+    is_pure_except(expr, &|_| false)
+}
+
+/// `excluded` が真を返す部分式を副作用のないものとみなして、`is_pure` と同じ判定を行う
+pub(super) fn is_pure_except(expr: &Expression, excluded: &impl Fn(&Expression) -> bool) -> bool {
+    if excluded(expr) {
+        return true;
+    }
+    match &expr.kind {
+        ExpressionKind::IntLiteral(_)
+        | ExpressionKind::FloatLiteral(_)
+        | ExpressionKind::StringLiteral(_)
+        | ExpressionKind::Boolean(_)
+        | ExpressionKind::NullLiteral
+        | ExpressionKind::Identifier(_) => true,
+        ExpressionKind::Prefix { right, .. } => is_pure_except(right, excluded),
+        ExpressionKind::Infix {
+            left,
+            operator,
+            right,
+        } => {
+            !matches!(operator, InfixOperator::Divide | InfixOperator::Modulo)
+                && is_pure_except(left, excluded)
+                && is_pure_except(right, excluded)
+        }
+        ExpressionKind::ArrayLiteral(elements) => {
+            elements.iter().all(|e| is_pure_except(e, excluded))
+        }
+        ExpressionKind::ObjectLiteral { pairs } => pairs
+            .iter()
+            .all(|(key, value)| is_pure_except(key, excluded) && is_pure_except(value, excluded)),
+        ExpressionKind::Call { .. }
+        | ExpressionKind::Cast { .. }
+        | ExpressionKind::Assignment { .. }
+        | ExpressionKind::MemberAccess { .. }
+        | ExpressionKind::New { .. } => false,
+    }
+}
+
+/// 式の評価が変数やオブジェクトを書き換えないかどうかを判定する
+///
+/// `is_pure` と異なり、実行時エラーの可能性があるメンバーアクセス・型変換・`/` `%` も許す
+pub(super) fn is_read_only(expr: &Expression) -> bool {
+    match &expr.kind {
+        ExpressionKind::IntLiteral(_)
+        | ExpressionKind::FloatLiteral(_)
+        | ExpressionKind::StringLiteral(_)
+        | ExpressionKind::Boolean(_)
+        | ExpressionKind::NullLiteral
+        | ExpressionKind::Identifier(_) => true,
+        ExpressionKind::Prefix { right, .. }
+        | ExpressionKind::Cast {
+            expression: right, ..
+        } => is_read_only(right),
+        ExpressionKind::Infix { left, right, .. } => is_read_only(left) && is_read_only(right),
+        ExpressionKind::ArrayLiteral(elements) => elements.iter().all(is_read_only),
+        ExpressionKind::ObjectLiteral { pairs } => pairs
+            .iter()
+            .all(|(key, value)| is_read_only(key) && is_read_only(value)),
+        ExpressionKind::MemberAccess { object, property } => {
+            is_read_only(object)
+                && match property {
+                    MemberProperty::Named(_) => true,
+                    MemberProperty::Computed(index) => is_read_only(index),
+                }
+        }
+        ExpressionKind::Call { .. }
+        | ExpressionKind::Assignment { .. }
+        | ExpressionKind::New { .. } => false,
+    }
+}
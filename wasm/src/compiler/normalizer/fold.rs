@@ -0,0 +1,214 @@
+use std::cmp::Ordering;
+
+use super::{OriginKind, PassContext};
+use crate::compiler::ast::*;
+
+/// 定数式を畳み込むパス
+///
+/// 畳み込んだ値は元の式のIDと範囲を引き継ぎ、材料となった値の範囲を由来として記録する
+pub(super) struct ConstantFolding<'a> {
+    pub(super) context: &'a mut PassContext,
+}
+
+impl Folder for ConstantFolding<'_> {
+    fn fold_expression(&mut self, expr: Expression) -> Expression {
+        let Expression { kind, span, id } = fold_expression_children(self, expr);
+        let folded = match &kind {
+            ExpressionKind::Prefix { operator, right } => {
+                fold_prefix(operator, &right.kind).map(|folded| {
+                    // リテラルに対する演算であれば `-1` のように式全体を由来とする
+                    let sources = self
+                        .context
+                        .take_sources(right)
+                        .unwrap_or_else(|| vec![span]);
+                    (folded, sources)
+                })
+            }
+            ExpressionKind::Infix {
+                left,
+                operator,
+                right,
+            } => fold_infix(&left.kind, operator, &right.kind).map(|folded| {
+                let mut sources = self
+                    .context
+                    .take_sources(left)
+                    .unwrap_or_else(|| vec![left.span]);
+                sources.extend(
+                    self.context
+                        .take_sources(right)
+                        .unwrap_or_else(|| vec![right.span]),
+                );
+                (folded, sources)
+            }),
+            _ => None,
+        };
+
+        match folded {
+            Some((kind, sources)) => {
+                self.context.record_origin(id, OriginKind::Folded, sources);
+                Expression { kind, span, id }
+            }
+            None => Expression { kind, span, id },
+        }
+    }
+}
+
+/// 定数の前置演算を畳み込む
+///
+/// オーバーフローなど畳み込めない場合は `None` を返し、式は実行時の評価に残す
+fn fold_prefix(operator: &PrefixOperator, right: &ExpressionKind) -> Option<ExpressionKind> {
+    match (operator, right) {
+        (PrefixOperator::Minus, ExpressionKind::IntLiteral(val)) => {
+            val.checked_neg().map(ExpressionKind::IntLiteral)
+        }
+        (PrefixOperator::Plus, ExpressionKind::IntLiteral(val)) => {
+            Some(ExpressionKind::IntLiteral(*val))
+        }
+        (PrefixOperator::BitwiseNot, ExpressionKind::IntLiteral(val)) => {
+            Some(ExpressionKind::IntLiteral(!val))
+        }
+        (PrefixOperator::Minus, ExpressionKind::FloatLiteral(val)) => {
+            Some(ExpressionKind::FloatLiteral(-val))
+        }
+        (PrefixOperator::Plus, ExpressionKind::FloatLiteral(val)) => {
+            Some(ExpressionKind::FloatLiteral(*val))
+        }
+        (PrefixOperator::Bang, ExpressionKind::Boolean(val)) => Some(ExpressionKind::Boolean(!val)),
+        _ => None,
+    }
+}
+
+/// 定数の中置演算を畳み込む
+///
+/// 型の組み合わせが対象外の場合や、オーバーフロー・ゼロ除算など実行時に評価すべき場合は
+/// `None` を返し、式は畳み込まずに残す
+fn fold_infix(
+    left: &ExpressionKind,
+    operator: &InfixOperator,
+    right: &ExpressionKind,
+) -> Option<ExpressionKind> {
+    match (left, right) {
+        (ExpressionKind::IntLiteral(l), ExpressionKind::IntLiteral(r)) => {
+            fold_int_infix(*l, operator, *r)
+        }
+        (ExpressionKind::FloatLiteral(l), ExpressionKind::FloatLiteral(r)) => {
+            fold_float_infix(*l, operator, *r)
+        }
+        // Int と Float の混在は Float として計算する (型の異なる値の厳密等価は判定しない)
+        (ExpressionKind::IntLiteral(l), ExpressionKind::FloatLiteral(r))
+            if !is_strict_equality(operator) =>
+        {
+            fold_float_infix(*l as f64, operator, *r)
+        }
+        (ExpressionKind::FloatLiteral(l), ExpressionKind::IntLiteral(r))
+            if !is_strict_equality(operator) =>
+        {
+            fold_float_infix(*l, operator, *r as f64)
+        }
+        (ExpressionKind::Boolean(l), ExpressionKind::Boolean(r)) => {
+            let value = match operator {
+                InfixOperator::LogicalAnd | InfixOperator::LogicalAndAlso => *l && *r,
+                InfixOperator::LogicalOr | InfixOperator::LogicalOrElse => *l || *r,
+                _ => compare(l.cmp(r), operator).filter(|_| is_equality(operator))?,
+            };
+            Some(ExpressionKind::Boolean(value))
+        }
+        // 文字列リテラルはエスケープを含むソース上の表記のまま保持しているため、
+        // エスケープを含む場合は値を比較・連結できない
+        (ExpressionKind::StringLiteral(l), ExpressionKind::StringLiteral(r))
+            if !l.contains('\\') && !r.contains('\\') =>
+        {
+            match operator {
+                InfixOperator::Add => {
+                    let value = format!("{}{}", l, r);
+                    // 両方の引用符を含む文字列はエスケープなしでは表記できない
+                    if value.contains('"') && value.contains('\'') {
+                        return None;
+                    }
+                    Some(ExpressionKind::StringLiteral(value))
+                }
+                _ => compare(l.cmp(r), operator).map(ExpressionKind::Boolean),
+            }
+        }
+        (ExpressionKind::NullLiteral, ExpressionKind::NullLiteral) if is_equality(operator) => {
+            compare(Ordering::Equal, operator).map(ExpressionKind::Boolean)
+        }
+        _ => None,
+    }
+}
+
+/// 整数同士の演算を畳み込む
+fn fold_int_infix(l: i64, operator: &InfixOperator, r: i64) -> Option<ExpressionKind> {
+    // 負のシフト量・指数は実行時の評価に任せる
+    let amount = || u32::try_from(r).ok();
+    let value = match operator {
+        InfixOperator::Add => l.checked_add(r)?,
+        InfixOperator::Subtract => l.checked_sub(r)?,
+        InfixOperator::Multiply => l.checked_mul(r)?,
+        InfixOperator::Divide => l.checked_div(r)?,
+        InfixOperator::Modulo => l.checked_rem(r)?,
+        InfixOperator::Power => l.checked_pow(amount()?)?,
+        InfixOperator::BitwiseAnd => l & r,
+        InfixOperator::BitwiseOr => l | r,
+        InfixOperator::BitwiseXor => l ^ r,
+        InfixOperator::BitwiseLeftShift | InfixOperator::BitwiseUnsignedLeftShift => {
+            l.checked_shl(amount()?)?
+        }
+        InfixOperator::BitwiseRightShift => l.checked_shr(amount()?)?,
+        InfixOperator::BitwiseUnsignedRightShift => (l as u64).checked_shr(amount()?)? as i64,
+        _ => return compare(l.cmp(&r), operator).map(ExpressionKind::Boolean),
+    };
+    Some(ExpressionKind::IntLiteral(value))
+}
+
+/// 浮動小数点数同士の演算を畳み込む
+///
+/// 結果が無限大や NaN になる場合はリテラルで表せないため畳み込まない
+fn fold_float_infix(l: f64, operator: &InfixOperator, r: f64) -> Option<ExpressionKind> {
+    let value = match operator {
+        InfixOperator::Add => l + r,
+        InfixOperator::Subtract => l - r,
+        InfixOperator::Multiply => l * r,
+        InfixOperator::Divide => l / r,
+        InfixOperator::Modulo => l % r,
+        InfixOperator::Power => l.powf(r),
+        _ => {
+            return compare(l.partial_cmp(&r)?, operator).map(ExpressionKind::Boolean);
+        }
+    };
+    value
+        .is_finite()
+        .then_some(ExpressionKind::FloatLiteral(value))
+}
+
+/// 比較・等価演算子であれば、比較結果から真偽値を求める
+fn compare(ordering: Ordering, operator: &InfixOperator) -> Option<bool> {
+    match operator {
+        InfixOperator::Equals | InfixOperator::StrictEquals => Some(ordering.is_eq()),
+        InfixOperator::NotEquals | InfixOperator::StrictNotEquals => Some(ordering.is_ne()),
+        InfixOperator::LessThan => Some(ordering.is_lt()),
+        InfixOperator::GreaterThan => Some(ordering.is_gt()),
+        InfixOperator::LessThanOrEqual => Some(ordering.is_le()),
+        InfixOperator::GreaterThanOrEqual => Some(ordering.is_ge()),
+        _ => None,
+    }
+}
+
+/// 等価演算子かどうか
+fn is_equality(operator: &InfixOperator) -> bool {
+    matches!(
+        operator,
+        InfixOperator::Equals
+            | InfixOperator::NotEquals
+            | InfixOperator::StrictEquals
+            | InfixOperator::StrictNotEquals
+    )
+}
+
+/// 厳密等価演算子かどうか
+fn is_strict_equality(operator: &InfixOperator) -> bool {
+    matches!(
+        operator,
+        InfixOperator::StrictEquals | InfixOperator::StrictNotEquals
+    )
+}
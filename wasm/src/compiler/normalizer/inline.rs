@@ -0,0 +1,232 @@
+use ahash::AHashMap;
+
+use super::names::{DeclaredNames, ReferencedNames};
+use super::propagate::is_constant_value;
+use super::types::{ValueType, infer_type};
+use super::{OriginKind, PassContext};
+use crate::common::Span;
+use crate::compiler::ast::*;
+
+/// インライン展開する関数の本体の式に含められるノード数の上限
+const MAX_INLINE_SIZE: usize = 16;
+
+/// インライン展開できる関数
+struct InlineCandidate {
+    params: Vec<Parameter>,
+    /// `return` する式
+    body: Expression,
+}
+
+/// 式のノード数を数える
+fn expression_size(expr: &Expression) -> usize {
+    match &expr.kind {
+        ExpressionKind::Prefix { right, .. } => expression_size(right) + 1,
+        ExpressionKind::Infix { left, right, .. } => {
+            expression_size(left) + expression_size(right) + 1
+        }
+        _ => 1,
+    }
+}
+
+/// 本体の式がリテラル・引数・演算子のみで構成されているかを判定する
+///
+/// 呼び出しを含まないため、展開した関数が再帰することはない
+fn is_inlinable_body(expr: &Expression, params: &[Parameter]) -> bool {
+    match &expr.kind {
+        ExpressionKind::IntLiteral(_)
+        | ExpressionKind::FloatLiteral(_)
+        | ExpressionKind::StringLiteral(_)
+        | ExpressionKind::Boolean(_) => true,
+        ExpressionKind::Identifier(name) => params.iter().any(|p| &p.name == name),
+        ExpressionKind::Prefix { right, .. } => is_inlinable_body(right, params),
+        ExpressionKind::Infix { left, right, .. } => {
+            is_inlinable_body(left, params) && is_inlinable_body(right, params)
+        }
+        _ => false,
+    }
+}
+
+/// リテラルが引数の型に一致するかを判定する (暗黙の型変換が必要な場合は展開しない)
+fn matches_param_type(value: &Expression, param: &Parameter) -> bool {
+    is_constant_value(&value.kind)
+        && infer_type(value, &|_: &str| None) == Some(ValueType::from_type_name(&param.type_name))
+}
+
+/// 関数宣言が展開の候補であれば、その引数と本体の式を返す
+///
+/// `return` 文1つだけからなる小さな `function` で、本体の式の型が戻り値の型と一致するものに限る
+fn inline_candidate(stmt: &Statement) -> Option<InlineCandidate> {
+    let StatementKind::FunctionDeclaration {
+        kind: FunctionKind::Function,
+        return_type: Some(return_type),
+        params,
+        body,
+        ..
+    } = &stmt.kind
+    else {
+        return None;
+    };
+    let returned = match &body.kind {
+        StatementKind::Block(stmts) if stmts.len() == 1 => &stmts[0],
+        _ => body,
+    };
+    let StatementKind::Return(Some(expr)) = &returned.kind else {
+        return None;
+    };
+    // 既定値は省略時に展開するため、引数の型に一致するリテラルでなければならない
+    let defaults_ok = params
+        .iter()
+        .all(|p| p.value.as_ref().is_none_or(|v| matches_param_type(v, p)));
+    let lookup = |name: &str| {
+        let param = params.iter().find(|p| p.name == name)?;
+        Some(ValueType::from_type_name(&param.type_name))
+    };
+    let return_type = ValueType::from_type_name(return_type);
+    (defaults_ok
+        && is_inlinable_body(expr, params)
+        && expression_size(expr) <= MAX_INLINE_SIZE
+        && infer_type(expr, &lookup) == Some(return_type))
+    .then(|| InlineCandidate {
+        params: params.clone(),
+        body: expr.clone(),
+    })
+}
+
+/// 本体の式の引数を実引数に置き換える
+///
+/// 展開した式のノード (実引数の複製を含む) は合成ノードとし、本体由来のノードは呼び出し式の範囲を持つ
+struct Substitution<'a> {
+    arguments: AHashMap<&'a str, &'a Expression>,
+    span: Span,
+}
+
+impl Folder for Substitution<'_> {
+    fn fold_expression(&mut self, expr: Expression) -> Expression {
+        if let ExpressionKind::Identifier(name) = &expr.kind
+            && let Some(argument) = self.arguments.get(name.as_str())
+        {
+            let mut argument = (*argument).clone();
+            argument.id = NodeId::DUMMY;
+            return argument;
+        }
+        let mut expr = fold_expression_children(self, expr);
+        expr.span = self.span;
+        expr.id = NodeId::DUMMY;
+        expr
+    }
+}
+
+/// 小さな関数の呼び出しを本体の式に展開するパス
+///
+/// 実引数が全てリテラルの呼び出しのみを展開し、展開後は後続の畳み込みで値になる。
+/// 名前の解決を単純にするため、プログラム直下で宣言され、同名の宣言が他にない関数のみを対象とする。
+/// 展開によって参照がなくなった関数は宣言ごと削除する
+pub(super) struct FunctionInlining<'a> {
+    pub(super) context: &'a mut PassContext,
+    candidates: AHashMap<String, InlineCandidate>,
+    /// 1回以上展開した関数名
+    inlined: Vec<String>,
+}
+
+impl<'a> FunctionInlining<'a> {
+    pub(super) fn new(context: &'a mut PassContext) -> Self {
+        Self {
+            context,
+            candidates: AHashMap::new(),
+            inlined: Vec::new(),
+        }
+    }
+
+    /// 呼び出しを展開できる場合は展開した式を返す
+    fn inline_call(
+        &mut self,
+        name: &str,
+        arguments: &[Expression],
+        span: Span,
+        id: NodeId,
+    ) -> Option<Expression> {
+        let candidate = self.candidates.get(name)?;
+        if arguments.len() > candidate.params.len() {
+            return None;
+        }
+        let mut bindings = AHashMap::new();
+        for (index, param) in candidate.params.iter().enumerate() {
+            let value = arguments.get(index).or(param.value.as_ref())?;
+            if !matches_param_type(value, param) {
+                return None;
+            }
+            bindings.insert(param.name.as_str(), value);
+        }
+
+        let mut substitution = Substitution {
+            arguments: bindings,
+            span,
+        };
+        let mut inlined = substitution.fold_expression(candidate.body.clone());
+        inlined.span = span;
+        inlined.id = id;
+
+        // 実引数が合成されたものであれば、その由来も展開した式の由来に含める
+        let mut sources = vec![candidate.body.span];
+        for argument in arguments {
+            sources.extend(self.context.take_sources(argument).unwrap_or_default());
+        }
+        self.context.record_origin(id, OriginKind::Inlined, sources);
+        if !self.inlined.iter().any(|n| n == name) {
+            self.inlined.push(name.to_string());
+        }
+        Some(inlined)
+    }
+}
+
+impl Folder for FunctionInlining<'_> {
+    fn fold_program(&mut self, program: ProgramAst) -> ProgramAst {
+        let mut declared = DeclaredNames::default();
+        declared.visit_program(&program);
+        self.candidates = program
+            .statements
+            .iter()
+            .filter_map(|stmt| match &stmt.kind {
+                StatementKind::FunctionDeclaration { name, .. }
+                    if declared.counts.get(name) == Some(&1) =>
+                {
+                    Some((name.clone(), inline_candidate(stmt)?))
+                }
+                _ => None,
+            })
+            .collect();
+        if self.candidates.is_empty() {
+            return program;
+        }
+
+        let mut program = fold_program_children(self, program);
+
+        // 展開によって参照がなくなった関数を削除する
+        let mut referenced = ReferencedNames::default();
+        referenced.visit_program(&program);
+        let unused: Vec<&String> = self
+            .inlined
+            .iter()
+            .filter(|name| !referenced.counts.contains_key(name.as_str()))
+            .collect();
+        program.statements.retain(|stmt| match &stmt.kind {
+            StatementKind::FunctionDeclaration { name, .. } => !unused.contains(&name),
+            _ => true,
+        });
+        program
+    }
+
+    fn fold_expression(&mut self, expr: Expression) -> Expression {
+        let expr = fold_expression_children(self, expr);
+        if let ExpressionKind::Call {
+            function,
+            arguments,
+        } = &expr.kind
+            && let ExpressionKind::Identifier(name) = &function.kind
+            && let Some(inlined) = self.inline_call(name, arguments, expr.span, expr.id)
+        {
+            return inlined;
+        }
+        expr
+    }
+}
@@ -0,0 +1,176 @@
+mod canonicalize;
+mod cse;
+mod dce;
+mod effects;
+mod fold;
+mod inline;
+mod names;
+mod pass_manager;
+mod propagate;
+mod strength;
+mod types;
+
+use ahash::AHashMap;
+use serde::Serialize;
+
+use crate::common::error::{SnowFallError, TextEdit};
+use crate::common::messages::Detail;
+use crate::common::{ErrorCode, Span};
+use crate::compiler::ast::*;
+use crate::compiler::side_table::SideTable;
+
+pub use pass_manager::{OptLevel, OptimizationOptions, Pass, PassDump};
+
+use pass_manager::PassManager;
+
+/// 正規化によって合成されたノードの由来
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Origin {
+    pub kind: OriginKind,
+    /// 値の元になったソースコード上の範囲
+    pub sources: Vec<Span>,
+}
+
+/// 合成の種類
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum OriginKind {
+    /// 定数式を畳み込んだ値
+    Folded,
+    /// 定数の参照を値に置き換えたもの (`sources` は定数の初期化式)
+    ConstantReference,
+    /// 関数呼び出しを本体の式に展開したもの (`sources` は関数の本体の式)
+    Inlined,
+}
+
+/// 正規化の結果
+pub struct Normalized {
+    pub program: ProgramAst,
+    /// 合成したノードの由来
+    ///
+    /// 畳み込んだ定数などのノードは元の式のIDと範囲を引き継ぐ。
+    /// 由来の表にはそのノードの値がソースコード上のどこから来たかを記録する
+    pub origins: SideTable<Origin>,
+    /// 到達不能なコードなどの警告
+    pub warnings: Vec<SnowFallError>,
+    /// 各パスの実行後のAST (`dump_passes` が有効な場合のみ)
+    pub dumps: Vec<PassDump>,
+}
+
+/// 正規化処理のエントリポイント (既定の設定で全てのパスを実行する)
+///
+/// - `const` 宣言や再代入されない変数の値を後続の式へ伝播
+/// - リテラルを引数とする小さな関数の呼び出しを展開
+/// - 定数式を畳み込み、演算を単純化し、可換な演算のオペランドを並べ替える
+/// - 文の中で繰り返し現れる式を一時変数に置き換える
+/// - 不要になった文 (到達不能な文、実行されないループ、副作用のない式文、空のブロック) を削除
+pub fn normalize(program: ProgramAst) -> ProgramAst {
+    let mut context = PassContext::default();
+    PassManager::new(&OptimizationOptions::default()).run(program, &mut context)
+}
+
+/// 設定に従って正規化を行い、合成したノードの由来と警告も返す
+///
+/// `source` は警告の位置の算出に使用する
+pub fn normalize_with_diagnostics(
+    program: ProgramAst,
+    source: &str,
+    options: &OptimizationOptions,
+) -> Normalized {
+    let mut context = PassContext::default();
+    let mut manager = PassManager::new(options);
+    let program = manager.run(program, &mut context);
+    let warnings = context
+        .unreachable
+        .iter()
+        .map(|span| {
+            SnowFallError::new_compiler_warning_at(ErrorCode::UnreachableCode, *span, source)
+                .with_suggestion(
+                    Detail::RemoveUnreachableCode,
+                    vec![TextEdit::replace(*span, "", source)],
+                )
+        })
+        .collect();
+    Normalized {
+        program,
+        origins: context.origins,
+        warnings,
+        dumps: manager.dumps,
+    }
+}
+
+/// パス間で共有する解析結果
+#[derive(Default)]
+struct PassContext {
+    /// 合成したノードの由来
+    origins: SideTable<Origin>,
+    /// 削除した到達不能なコードの範囲
+    unreachable: Vec<Span>,
+}
+
+impl PassContext {
+    /// 合成したノードの由来を記録する
+    fn record_origin(&mut self, id: NodeId, kind: OriginKind, sources: Vec<Span>) {
+        if !id.is_dummy() {
+            self.origins.insert(id, Origin { kind, sources });
+        }
+    }
+
+    /// 畳み込みの材料になった式が合成されたものであれば、その由来の範囲を返す
+    ///
+    /// 材料の式は木から消えるため、記録していた由来も取り除く
+    fn take_sources(&mut self, expr: &Expression) -> Option<Vec<Span>> {
+        if expr.id.is_dummy() {
+            return None;
+        }
+        self.origins.remove(expr.id).map(|origin| origin.sources)
+    }
+}
+
+/// 式を複製し、複製したノードを全て合成ノードとする (元の式とIDが重複しないようにする)
+fn duplicate(expr: &Expression) -> Expression {
+    struct Synthesize;
+
+    impl Folder for Synthesize {
+        fn fold_expression(&mut self, expr: Expression) -> Expression {
+            let mut expr = fold_expression_children(self, expr);
+            expr.id = NodeId::DUMMY;
+            expr
+        }
+    }
+
+    Synthesize.fold_expression(expr.clone())
+}
+
+/// 名前に対応する情報のスコープ管理
+struct Scopes<T> {
+    scopes: Vec<AHashMap<String, T>>,
+}
+
+impl<T> Scopes<T> {
+    /// ルートスコープのみを持つ状態で作成する
+    fn new() -> Self {
+        Self {
+            scopes: vec![AHashMap::new()],
+        }
+    }
+
+    fn push(&mut self) {
+        self.scopes.push(AHashMap::new());
+    }
+
+    fn pop(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// 現在のスコープに名前を登録する
+    fn declare(&mut self, name: String, value: T) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name, value);
+        }
+    }
+
+    /// 内側のスコープから順に名前を探す
+    fn lookup(&self, name: &str) -> Option<&T> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+}
@@ -0,0 +1,52 @@
+use ahash::AHashMap;
+
+use crate::compiler::ast::*;
+
+/// 宣言された名前の出現回数を数える (変数・関数・クラス・引数・ループ変数)
+#[derive(Default)]
+pub(super) struct DeclaredNames {
+    pub(super) counts: AHashMap<String, usize>,
+}
+
+impl DeclaredNames {
+    fn add(&mut self, name: &str) {
+        *self.counts.entry(name.to_string()).or_default() += 1;
+    }
+}
+
+impl<'ast> Visitor<'ast> for DeclaredNames {
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        match &stmt.kind {
+            StatementKind::VariableDeclaration { declarators, .. } => {
+                for decl in declarators {
+                    self.add(&decl.name);
+                }
+            }
+            StatementKind::FunctionDeclaration { name, params, .. } => {
+                self.add(name);
+                for param in params {
+                    self.add(&param.name);
+                }
+            }
+            StatementKind::ClassDeclaration { name, .. } => self.add(name),
+            StatementKind::ForEach { binding, .. } => self.add(&binding.name),
+            _ => {}
+        }
+        walk_statement(self, stmt);
+    }
+}
+
+/// 識別子として参照される名前を数える (代入先も含む)
+#[derive(Default)]
+pub(super) struct ReferencedNames {
+    pub(super) counts: AHashMap<String, usize>,
+}
+
+impl<'ast> Visitor<'ast> for ReferencedNames {
+    fn visit_expression(&mut self, expr: &'ast Expression) {
+        if let ExpressionKind::Identifier(name) = &expr.kind {
+            *self.counts.entry(name.clone()).or_default() += 1;
+        }
+        walk_expression(self, expr);
+    }
+}
@@ -0,0 +1,161 @@
+use serde::{Deserialize, Serialize};
+
+use super::PassContext;
+use super::canonicalize::CommutativeCanonicalization;
+use super::cse::CommonSubexpressionElimination;
+use super::dce::DeadCodeElimination;
+use super::fold::ConstantFolding;
+use super::inline::FunctionInlining;
+use super::propagate::ConstantPropagation;
+use super::strength::StrengthReduction;
+use crate::compiler::ast::{Folder, ProgramAst};
+
+/// 正規化を構成する個々のパス
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Pass {
+    /// `const` 宣言や再代入されない変数の値を後続の式へ伝播する
+    ConstantPropagation,
+    /// 小さな関数の呼び出しを本体の式に展開する
+    FunctionInlining,
+    /// 定数式を畳み込む
+    ConstantFolding,
+    /// 演算をより単純な演算に置き換える (`x * 2` → `x + x` など)
+    StrengthReduction,
+    /// 可換な演算のオペランドを並べ替える (副作用のないオペランドのみ)
+    CommutativeCanonicalization,
+    /// 文の中で繰り返し現れる式を一時変数に置き換える (式の評価回数が変わる)
+    CommonSubexpressionElimination,
+    /// 実行されない文や不要な文を削除する
+    DeadCodeElimination,
+}
+
+impl Pass {
+    /// パスを1回実行する
+    fn run(self, program: ProgramAst, context: &mut PassContext) -> ProgramAst {
+        match self {
+            Pass::ConstantPropagation => ConstantPropagation::new(context).fold_program(program),
+            Pass::FunctionInlining => FunctionInlining::new(context).fold_program(program),
+            Pass::ConstantFolding => ConstantFolding { context }.fold_program(program),
+            Pass::StrengthReduction => StrengthReduction::new(&program).fold_program(program),
+            Pass::CommonSubexpressionElimination => {
+                CommonSubexpressionElimination::new(&program).fold_program(program)
+            }
+            Pass::CommutativeCanonicalization => {
+                CommutativeCanonicalization::new(&program).fold_program(program)
+            }
+            Pass::DeadCodeElimination => DeadCodeElimination { context }.fold_program(program),
+        }
+    }
+}
+
+/// 最適化レベル
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OptLevel {
+    /// 正規化を行わない
+    O0,
+    /// 式の評価順や評価回数を変えないパスのみ実行する
+    O1,
+    /// 全てのパスを実行する
+    #[default]
+    O2,
+}
+
+impl OptLevel {
+    /// レベルに対応するパスの並び
+    pub fn passes(self) -> &'static [Pass] {
+        match self {
+            OptLevel::O0 => &[],
+            OptLevel::O1 => &[
+                Pass::ConstantPropagation,
+                Pass::FunctionInlining,
+                Pass::ConstantFolding,
+                Pass::StrengthReduction,
+                Pass::DeadCodeElimination,
+            ],
+            OptLevel::O2 => &[
+                Pass::ConstantPropagation,
+                Pass::FunctionInlining,
+                Pass::ConstantFolding,
+                Pass::StrengthReduction,
+                Pass::CommutativeCanonicalization,
+                Pass::CommonSubexpressionElimination,
+                Pass::DeadCodeElimination,
+            ],
+        }
+    }
+}
+
+/// 正規化の設定
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OptimizationOptions {
+    /// 最適化レベル
+    pub level: OptLevel,
+    /// 実行するパスの並び (指定した場合は `level` より優先する)
+    pub passes: Option<Vec<Pass>>,
+    /// 各パスの実行後のASTを記録する (デバッグ用)
+    pub dump_passes: bool,
+}
+
+impl OptimizationOptions {
+    /// 実行するパスの並び
+    pub fn passes(&self) -> &[Pass] {
+        self.passes.as_deref().unwrap_or(self.level.passes())
+    }
+}
+
+/// パス実行後のASTの記録
+#[derive(Debug, Clone, Serialize)]
+pub struct PassDump {
+    pub pass: Pass,
+    /// パスの並びを繰り返した回数 (0ベース)
+    pub iteration: u32,
+    pub program: ProgramAst,
+}
+
+/// パスの並びを繰り返す上限
+///
+/// 定数の伝播と畳み込みは交互に行う必要があるため (`const Int B = A + 1;` など)、
+/// 木が変化しなくなるまで並び全体を繰り返す
+const MAX_ITERATIONS: u32 = 8;
+
+/// 設定されたパスを順に実行する
+pub(super) struct PassManager<'a> {
+    options: &'a OptimizationOptions,
+    /// 各パスの実行後のAST (`dump_passes` が有効な場合のみ)
+    pub(super) dumps: Vec<PassDump>,
+}
+
+impl<'a> PassManager<'a> {
+    pub(super) fn new(options: &'a OptimizationOptions) -> Self {
+        Self {
+            options,
+            dumps: Vec::new(),
+        }
+    }
+
+    /// 木が変化しなくなるまでパスの並びを繰り返し実行する
+    pub(super) fn run(&mut self, mut program: ProgramAst, context: &mut PassContext) -> ProgramAst {
+        let passes = self.options.passes();
+        if passes.is_empty() {
+            return program;
+        }
+        for iteration in 0..MAX_ITERATIONS {
+            let before = program.clone();
+            for pass in passes {
+                program = pass.run(program, context);
+                if self.options.dump_passes {
+                    self.dumps.push(PassDump {
+                        pass: *pass,
+                        iteration,
+                        program: program.clone(),
+                    });
+                }
+            }
+            if program == before {
+                break;
+            }
+        }
+        program
+    }
+}
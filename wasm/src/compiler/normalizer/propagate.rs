@@ -0,0 +1,253 @@
+use ahash::AHashSet;
+
+use super::{OriginKind, PassContext, Scopes};
+use crate::common::Span;
+use crate::compiler::ast::*;
+
+/// 伝播可能な定数
+#[derive(Debug, Clone)]
+struct Constant {
+    value: ExpressionKind,
+    /// 初期化式の範囲
+    span: Span,
+}
+
+/// 伝播可能な定数値 (リテラル) かどうかを判定する
+pub(super) fn is_constant_value(kind: &ExpressionKind) -> bool {
+    matches!(
+        kind,
+        ExpressionKind::IntLiteral(_)
+            | ExpressionKind::FloatLiteral(_)
+            | ExpressionKind::StringLiteral(_)
+            | ExpressionKind::Boolean(_)
+            | ExpressionKind::NullLiteral
+    )
+}
+
+/// 識別子への代入を走査し、再代入される名前を収集する
+#[derive(Default)]
+struct ReassignedNames {
+    names: AHashSet<String>,
+}
+
+impl<'ast> Visitor<'ast> for ReassignedNames {
+    fn visit_expression(&mut self, expr: &'ast Expression) {
+        if let ExpressionKind::Assignment { left, .. } = &expr.kind
+            && let ExpressionKind::Identifier(name) = &left.kind
+        {
+            self.names.insert(name.clone());
+        }
+        walk_expression(self, expr);
+    }
+}
+
+/// 変数の値を後続の式へ伝播するパス
+///
+/// `const` 宣言に加え、プログラム中で一度も再代入されない変数の初期値も伝播する。
+/// 再代入の有無はスコープを区別せず名前単位で判定する (同名の変数のどれかに代入があれば伝播しない)
+pub(super) struct ConstantPropagation<'a> {
+    context: &'a mut PassContext,
+    /// 再代入される変数名
+    reassigned: AHashSet<String>,
+    /// クラスのメンバー宣言を処理中か (可変なフィールドはメソッドから書き換えられるため伝播しない)
+    in_class_body: bool,
+    /// 伝播可能な定数のスコープ
+    ///
+    /// 値が `None` の場合は、同名の非定数宣言によって外側の定数が隠蔽されていることを表す
+    scopes: Scopes<Option<Constant>>,
+}
+
+impl<'a> ConstantPropagation<'a> {
+    pub(super) fn new(context: &'a mut PassContext) -> Self {
+        Self {
+            context,
+            reassigned: AHashSet::new(),
+            in_class_body: false,
+            scopes: Scopes::new(),
+        }
+    }
+
+    /// 新しいスコープ内で文の子ノードを正規化する
+    ///
+    /// `shadowed` に渡した名前は、外側の定数を隠蔽する宣言としてスコープに登録する
+    fn fold_in_scope<'b>(
+        &mut self,
+        stmt: Statement,
+        shadowed: impl IntoIterator<Item = &'b String>,
+    ) -> Statement {
+        self.scopes.push();
+        for name in shadowed {
+            self.scopes.declare(name.clone(), None);
+        }
+        let stmt = fold_statement_children(self, stmt);
+        self.scopes.pop();
+        stmt
+    }
+
+    /// 宣言の可変性と再代入の有無から、変数の初期値を伝播できるかを判定する
+    fn is_propagatable(&self, name: &str, mutability: Mutability) -> bool {
+        mutability.is_immutable() || (!self.in_class_body && !self.reassigned.contains(name))
+    }
+}
+
+impl Folder for ConstantPropagation<'_> {
+    fn fold_program(&mut self, program: ProgramAst) -> ProgramAst {
+        let mut collector = ReassignedNames::default();
+        collector.visit_program(&program);
+        self.reassigned = collector.names;
+        fold_program_children(self, program)
+    }
+
+    fn fold_statement(&mut self, stmt: Statement) -> Option<Statement> {
+        match stmt.kind {
+            StatementKind::VariableDeclaration { .. } => {
+                let stmt = fold_statement_children(self, stmt);
+                if let StatementKind::VariableDeclaration {
+                    declarators,
+                    mutability,
+                    ..
+                } = &stmt.kind
+                {
+                    for decl in declarators {
+                        // 再代入されず、値がリテラルに畳み込めたものだけを伝播対象とする
+                        let value = decl
+                            .value
+                            .as_ref()
+                            .filter(|v| {
+                                self.is_propagatable(&decl.name, *mutability)
+                                    && is_constant_value(&v.kind)
+                            })
+                            .map(|v| Constant {
+                                value: v.kind.clone(),
+                                span: v.span,
+                            });
+                        self.scopes.declare(decl.name.clone(), value);
+                    }
+                }
+                Some(stmt)
+            }
+            StatementKind::FunctionDeclaration { ref params, .. } => {
+                let names: Vec<String> = params.iter().map(|p| p.name.clone()).collect();
+                let in_class_body = std::mem::replace(&mut self.in_class_body, false);
+                let stmt = self.fold_in_scope(stmt, &names);
+                self.in_class_body = in_class_body;
+                Some(stmt)
+            }
+            StatementKind::ClassDeclaration { ref members, .. } => {
+                // フィールド名はメソッド内で外側の定数を隠蔽する
+                let names: Vec<String> = members
+                    .iter()
+                    .filter_map(|member| match &member.kind {
+                        StatementKind::VariableDeclaration { declarators, .. } => {
+                            Some(declarators.iter().map(|d| d.name.clone()))
+                        }
+                        _ => None,
+                    })
+                    .flatten()
+                    .collect();
+                let in_class_body = std::mem::replace(&mut self.in_class_body, true);
+                let stmt = self.fold_in_scope(stmt, &names);
+                self.in_class_body = in_class_body;
+                Some(stmt)
+            }
+            StatementKind::ForEach {
+                binding,
+                iterable,
+                kind,
+                body,
+            } => {
+                // 反復対象はループ変数のスコープ外で評価される
+                let iterable = self.fold_expression(iterable);
+                self.scopes.push();
+                self.scopes.declare(binding.name.clone(), None);
+                let body = fold_required_statement(self, *body);
+                self.scopes.pop();
+                Some(Statement {
+                    kind: StatementKind::ForEach {
+                        binding,
+                        iterable,
+                        kind,
+                        body,
+                    },
+                    span: stmt.span,
+                    id: stmt.id,
+                })
+            }
+            StatementKind::Block(_) | StatementKind::For { .. } => {
+                Some(self.fold_in_scope(stmt, &[]))
+            }
+            _ => Some(fold_statement_children(self, stmt)),
+        }
+    }
+
+    fn fold_expression(&mut self, expr: Expression) -> Expression {
+        match expr.kind {
+            // 定数の参照はその値に置き換える
+            ExpressionKind::Identifier(name) => {
+                match self.scopes.lookup(&name).cloned().flatten() {
+                    Some(constant) => {
+                        self.context.record_origin(
+                            expr.id,
+                            OriginKind::ConstantReference,
+                            vec![constant.span],
+                        );
+                        Expression {
+                            kind: constant.value,
+                            span: expr.span,
+                            id: expr.id,
+                        }
+                    }
+                    None => Expression {
+                        kind: ExpressionKind::Identifier(name),
+                        span: expr.span,
+                        id: expr.id,
+                    },
+                }
+            }
+            // 代入先の識別子は値に置き換えない
+            ExpressionKind::Assignment { left, right } => {
+                let left = match left.kind {
+                    ExpressionKind::Identifier(_) => left,
+                    _ => Box::new(self.fold_expression(*left)),
+                };
+                Expression {
+                    kind: ExpressionKind::Assignment {
+                        left,
+                        right: Box::new(self.fold_expression(*right)),
+                    },
+                    span: expr.span,
+                    id: expr.id,
+                }
+            }
+            // 呼び出す関数の名前は、同名の定数があっても値に置き換えない
+            ExpressionKind::Call {
+                function,
+                arguments,
+            } => {
+                let function = match function.kind {
+                    ExpressionKind::Identifier(_) => function,
+                    _ => Box::new(self.fold_expression(*function)),
+                };
+                Expression {
+                    kind: ExpressionKind::Call {
+                        function,
+                        arguments: arguments
+                            .into_iter()
+                            .map(|arg| self.fold_expression(arg))
+                            .collect(),
+                    },
+                    span: expr.span,
+                    id: expr.id,
+                }
+            }
+            kind => fold_expression_children(
+                self,
+                Expression {
+                    kind,
+                    span: expr.span,
+                    id: expr.id,
+                },
+            ),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use super::duplicate;
+use super::effects::is_pure;
+use super::types::{TypeEnvironment, TypedFolder, ValueType, fold_statement_with_types};
+use crate::compiler::ast::*;
+
+/// リテラルが指定した整数値と等しいかを判定する (`Float` のリテラルも比較する)
+fn is_literal(expr: &Expression, value: i64) -> bool {
+    match &expr.kind {
+        ExpressionKind::IntLiteral(v) => *v == value,
+        ExpressionKind::FloatLiteral(v) => *v == value as f64,
+        _ => false,
+    }
+}
+
+/// 演算をより単純な演算に置き換えるパス
+///
+/// - `x * 1` / `1 * x` → `x` (演算結果の型が `x` の型と同じ場合のみ)
+/// - `x + 0` / `0 + x` → `x` (`Int` のみ。`Float` では `-0.0 + 0` が `0.0` になるため)
+/// - `x * 2` / `2 * x` → `x + x` (演算結果の型が `x` の型と同じ場合のみ)
+/// - `x ** 2` → `x * x` (`Int` のみ。`Float` の累乗は丸めが一致する保証がないため)
+///
+/// オペランドを複製する変換は、`x` に副作用がない場合のみ行う
+pub(super) struct StrengthReduction {
+    types: TypeEnvironment,
+}
+
+impl StrengthReduction {
+    pub(super) fn new(program: &ProgramAst) -> Self {
+        Self {
+            types: TypeEnvironment::new(program),
+        }
+    }
+
+    /// 置き換え後の式を返す (置き換えられない場合は `None`)
+    fn reduce(&self, expr: &Expression) -> Option<Expression> {
+        let ExpressionKind::Infix {
+            left,
+            operator,
+            right,
+        } = &expr.kind
+        else {
+            return None;
+        };
+        let result_type = self.types.infer(expr)?;
+        // 定数側を右に寄せて判定する (累乗は可換でないため左右を入れ替えない)
+        let (operand, constant) = match operator {
+            InfixOperator::Power => (left, right),
+            _ if matches!(
+                left.kind,
+                ExpressionKind::IntLiteral(_) | ExpressionKind::FloatLiteral(_)
+            ) =>
+            {
+                (right, left)
+            }
+            _ => (left, right),
+        };
+        // 置き換え後の式は `operand` の型を持つため、演算結果の型と一致しなければならない
+        if self.types.infer(operand)? != result_type {
+            return None;
+        }
+
+        let doubled = |operator: InfixOperator| Expression {
+            kind: ExpressionKind::Infix {
+                left: operand.clone(),
+                operator,
+                right: Box::new(duplicate(operand)),
+            },
+            span: expr.span,
+            id: expr.id,
+        };
+        match operator {
+            InfixOperator::Multiply if is_literal(constant, 1) => Some((**operand).clone()),
+            InfixOperator::Add if result_type == ValueType::Int && is_literal(constant, 0) => {
+                Some((**operand).clone())
+            }
+            InfixOperator::Multiply if is_literal(constant, 2) && is_pure(operand) => {
+                Some(doubled(InfixOperator::Add))
+            }
+            InfixOperator::Power
+                if result_type == ValueType::Int && is_literal(constant, 2) && is_pure(operand) =>
+            {
+                Some(doubled(InfixOperator::Multiply))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl TypedFolder for StrengthReduction {
+    fn types(&mut self) -> &mut TypeEnvironment {
+        &mut self.types
+    }
+}
+
+impl Folder for StrengthReduction {
+    fn fold_statement(&mut self, stmt: Statement) -> Option<Statement> {
+        Some(fold_statement_with_types(self, stmt))
+    }
+
+    fn fold_expression(&mut self, expr: Expression) -> Expression {
+        let expr = fold_expression_children(self, expr);
+        self.reduce(&expr).unwrap_or(expr)
+    }
+}
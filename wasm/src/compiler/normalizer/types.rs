@@ -0,0 +1,332 @@
+use ahash::AHashMap;
+
+use super::Scopes;
+use crate::compiler::ast::*;
+
+/// 正規化で扱う値の型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum ValueType {
+    Int,
+    Float,
+    String,
+    Bool,
+    /// クラスなど組み込みでない型
+    Named(String),
+}
+
+impl ValueType {
+    pub(super) fn from_type_name(name: &str) -> Self {
+        match name {
+            "Int" => ValueType::Int,
+            "Float" => ValueType::Float,
+            "String" => ValueType::String,
+            "Bool" => ValueType::Bool,
+            _ => ValueType::Named(name.to_string()),
+        }
+    }
+
+    /// 宣言に使う型名
+    pub(super) fn type_name(&self) -> &str {
+        match self {
+            ValueType::Int => "Int",
+            ValueType::Float => "Float",
+            ValueType::String => "String",
+            ValueType::Bool => "Bool",
+            ValueType::Named(name) => name,
+        }
+    }
+
+    pub(super) fn is_numeric(&self) -> bool {
+        matches!(self, ValueType::Int | ValueType::Float)
+    }
+
+    /// 算術演算の結果の型 (`Int` 同士は `Int`、`Float` を含めば `Float`)
+    fn arithmetic(left: &Self, right: &Self) -> Option<Self> {
+        match (left, right) {
+            (ValueType::Int, ValueType::Int) => Some(ValueType::Int),
+            _ if left.is_numeric() && right.is_numeric() => Some(ValueType::Float),
+            _ => None,
+        }
+    }
+}
+
+/// 型推論で識別子やフィールドの型を求める
+pub(super) trait TypeLookup {
+    /// 変数の型
+    fn variable(&self, name: &str) -> Option<ValueType>;
+
+    /// クラスのフィールドの型
+    fn field(&self, _class: &str, _field: &str) -> Option<ValueType> {
+        None
+    }
+}
+
+/// 変数の型のみを返す関数はそのまま型の参照先として使える
+impl<F: Fn(&str) -> Option<ValueType>> TypeLookup for F {
+    fn variable(&self, name: &str) -> Option<ValueType> {
+        self(name)
+    }
+}
+
+/// 式の型を推論する
+///
+/// 型の分からない部分式を含む場合は `None` を返す
+pub(super) fn infer_type(expr: &Expression, lookup: &impl TypeLookup) -> Option<ValueType> {
+    match &expr.kind {
+        ExpressionKind::IntLiteral(_) => Some(ValueType::Int),
+        ExpressionKind::FloatLiteral(_) => Some(ValueType::Float),
+        ExpressionKind::StringLiteral(_) => Some(ValueType::String),
+        ExpressionKind::Boolean(_) => Some(ValueType::Bool),
+        ExpressionKind::Identifier(name) => lookup.variable(name),
+        ExpressionKind::MemberAccess {
+            object,
+            property: MemberProperty::Named(field),
+        } => match infer_type(object, lookup)? {
+            ValueType::Named(class) => lookup.field(&class, field),
+            _ => None,
+        },
+        ExpressionKind::Prefix { operator, right } => {
+            let right = infer_type(right, lookup)?;
+            match operator {
+                PrefixOperator::Plus | PrefixOperator::Minus if right.is_numeric() => Some(right),
+                PrefixOperator::Bang if right == ValueType::Bool => Some(right),
+                PrefixOperator::BitwiseNot if right == ValueType::Int => Some(right),
+                _ => None,
+            }
+        }
+        ExpressionKind::Infix {
+            left,
+            operator,
+            right,
+        } => {
+            let (left, right) = (infer_type(left, lookup)?, infer_type(right, lookup)?);
+            match operator {
+                InfixOperator::Add if left == ValueType::String && right == ValueType::String => {
+                    Some(ValueType::String)
+                }
+                InfixOperator::Add
+                | InfixOperator::Subtract
+                | InfixOperator::Multiply
+                | InfixOperator::Divide
+                | InfixOperator::Modulo
+                | InfixOperator::Power => ValueType::arithmetic(&left, &right),
+                InfixOperator::Equals
+                | InfixOperator::NotEquals
+                | InfixOperator::StrictEquals
+                | InfixOperator::StrictNotEquals => Some(ValueType::Bool),
+                InfixOperator::LessThan
+                | InfixOperator::GreaterThan
+                | InfixOperator::LessThanOrEqual
+                | InfixOperator::GreaterThanOrEqual => {
+                    ValueType::arithmetic(&left, &right).map(|_| ValueType::Bool)
+                }
+                InfixOperator::LogicalAnd
+                | InfixOperator::LogicalOr
+                | InfixOperator::LogicalAndAlso
+                | InfixOperator::LogicalOrElse => {
+                    (left == ValueType::Bool && right == ValueType::Bool).then_some(ValueType::Bool)
+                }
+                InfixOperator::BitwiseAnd
+                | InfixOperator::BitwiseOr
+                | InfixOperator::BitwiseXor
+                | InfixOperator::BitwiseLeftShift
+                | InfixOperator::BitwiseRightShift
+                | InfixOperator::BitwiseUnsignedLeftShift
+                | InfixOperator::BitwiseUnsignedRightShift => {
+                    (left == ValueType::Int && right == ValueType::Int).then_some(ValueType::Int)
+                }
+            }
+        }
+        _ => None,
+    }
+}
+
+/// クラスのフィールドの型を収集する (同名のクラスが複数ある場合は曖昧なため除外する)
+#[derive(Default)]
+struct ClassFields {
+    fields: AHashMap<String, Option<AHashMap<String, ValueType>>>,
+}
+
+impl<'ast> Visitor<'ast> for ClassFields {
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        if let StatementKind::ClassDeclaration { name, members, .. } = &stmt.kind {
+            let fields = members
+                .iter()
+                .filter_map(|member| match &member.kind {
+                    StatementKind::VariableDeclaration {
+                        type_name,
+                        declarators,
+                        ..
+                    } => Some(
+                        declarators
+                            .iter()
+                            .map(|d| (d.name.clone(), ValueType::from_type_name(type_name))),
+                    ),
+                    _ => None,
+                })
+                .flatten()
+                .collect();
+            self.fields
+                .entry(name.clone())
+                .and_modify(|entry| *entry = None)
+                .or_insert(Some(fields));
+        }
+        walk_statement(self, stmt);
+    }
+}
+
+/// 宣言から変数とフィールドの型を追跡する環境
+pub(super) struct TypeEnvironment {
+    /// 宣言された変数の型 (型の分からない宣言は `None`)
+    variables: Scopes<Option<ValueType>>,
+    /// クラス名ごとのフィールドの型
+    fields: AHashMap<String, AHashMap<String, ValueType>>,
+}
+
+impl TypeEnvironment {
+    pub(super) fn new(program: &ProgramAst) -> Self {
+        let mut classes = ClassFields::default();
+        classes.visit_program(program);
+        Self {
+            variables: Scopes::new(),
+            fields: classes
+                .fields
+                .into_iter()
+                .filter_map(|(name, fields)| Some((name, fields?)))
+                .collect(),
+        }
+    }
+
+    pub(super) fn push(&mut self) {
+        self.variables.push();
+    }
+
+    pub(super) fn pop(&mut self) {
+        self.variables.pop();
+    }
+
+    /// 現在のスコープに変数を登録する
+    pub(super) fn declare(&mut self, name: String, value_type: Option<ValueType>) {
+        self.variables.declare(name, value_type);
+    }
+
+    pub(super) fn infer(&self, expr: &Expression) -> Option<ValueType> {
+        infer_type(expr, self)
+    }
+}
+
+impl TypeLookup for TypeEnvironment {
+    fn variable(&self, name: &str) -> Option<ValueType> {
+        self.variables.lookup(name)?.clone()
+    }
+
+    fn field(&self, class: &str, field: &str) -> Option<ValueType> {
+        self.fields.get(class)?.get(field).cloned()
+    }
+}
+
+/// 宣言に応じて型の環境を更新しながら木を書き換えるパス
+pub(super) trait TypedFolder: Folder {
+    fn types(&mut self) -> &mut TypeEnvironment;
+}
+
+/// 宣言された変数の型をスコープに登録しながら、文の子ノードを書き換える
+pub(super) fn fold_statement_with_types<F: TypedFolder + ?Sized>(
+    folder: &mut F,
+    stmt: Statement,
+) -> Statement {
+    match stmt.kind {
+        StatementKind::VariableDeclaration { .. } => {
+            let stmt = fold_statement_children(folder, stmt);
+            if let StatementKind::VariableDeclaration {
+                type_name,
+                declarators,
+                ..
+            } = &stmt.kind
+            {
+                for decl in declarators {
+                    folder.types().declare(
+                        decl.name.clone(),
+                        Some(ValueType::from_type_name(type_name)),
+                    );
+                }
+            }
+            stmt
+        }
+        StatementKind::FunctionDeclaration { ref params, .. } => {
+            let declared: Vec<_> = params
+                .iter()
+                .map(|p| {
+                    (
+                        p.name.clone(),
+                        Some(ValueType::from_type_name(&p.type_name)),
+                    )
+                })
+                .collect();
+            fold_in_scope(folder, stmt, declared)
+        }
+        StatementKind::ClassDeclaration { ref members, .. } => {
+            // フィールドはメソッド内から参照できる
+            let declared: Vec<_> = members
+                .iter()
+                .filter_map(|member| match &member.kind {
+                    StatementKind::VariableDeclaration {
+                        type_name,
+                        declarators,
+                        ..
+                    } => Some(
+                        declarators
+                            .iter()
+                            .map(|d| (d.name.clone(), Some(ValueType::from_type_name(type_name)))),
+                    ),
+                    _ => None,
+                })
+                .flatten()
+                .collect();
+            fold_in_scope(folder, stmt, declared)
+        }
+        StatementKind::ForEach {
+            binding,
+            iterable,
+            kind,
+            body,
+        } => {
+            // 反復対象はループ変数のスコープ外で評価される
+            let iterable = folder.fold_expression(iterable);
+            folder.types().push();
+            let binding_type = binding.type_name.as_deref().map(ValueType::from_type_name);
+            folder.types().declare(binding.name.clone(), binding_type);
+            let body = fold_required_statement(folder, *body);
+            folder.types().pop();
+            Statement {
+                kind: StatementKind::ForEach {
+                    binding,
+                    iterable,
+                    kind,
+                    body,
+                },
+                span: stmt.span,
+                id: stmt.id,
+            }
+        }
+        StatementKind::Block(_) | StatementKind::For { .. } => {
+            fold_in_scope(folder, stmt, Vec::new())
+        }
+        _ => fold_statement_children(folder, stmt),
+    }
+}
+
+/// 新しいスコープに変数を登録してから、文の子ノードを書き換える
+fn fold_in_scope<F: TypedFolder + ?Sized>(
+    folder: &mut F,
+    stmt: Statement,
+    declared: Vec<(String, Option<ValueType>)>,
+) -> Statement {
+    folder.types().push();
+    for (name, value_type) in declared {
+        folder.types().declare(name, value_type);
+    }
+    let stmt = fold_statement_children(folder, stmt);
+    folder.types().pop();
+    stmt
+}
@@ -1,16 +1,20 @@
-use crate::common::error::SnowFallError;
+use std::cell::Cell;
+
+use crate::common::error::{SnowFallError, SnowFallErrorContext, TextEdit};
+use crate::common::messages::{Detail, Locale};
 use crate::common::{
     DelimiterToken, ErrorCode, KeywordToken, LiteralToken, OperatorToken, Span, Token, TokenKind,
 };
 use crate::compiler::Lexer;
 use crate::compiler::ast::{
-    Binding, Expression, ExpressionKind, ForEachKind, FunctionKind, InfixOperator, Parameter,
-    PrefixOperator, ProgramAst, Statement, StatementKind, VariableDeclarator,
+    Binding, Expression, ExpressionKind, ForEachKind, FunctionKind, InfixOperator, MemberProperty,
+    Mutability, NodeId, Parameter, PrefixOperator, ProgramAst, Statement, StatementKind,
+    VariableDeclarator,
 };
 
 /// 演算の優先順位
-#[derive(PartialEq, PartialOrd)]
-enum Precedence {
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub(crate) enum Precedence {
     /// 最低優先度
     Lowest,
 
@@ -51,6 +55,38 @@ enum Precedence {
     Call,
 }
 
+impl Precedence {
+    /// AST上の中置演算子に対応する優先順位を返す
+    ///
+    /// `token_precedence` と同じ対応関係を保つこと
+    pub(crate) fn of_infix(op: &InfixOperator) -> Self {
+        match op {
+            InfixOperator::LogicalOr | InfixOperator::LogicalOrElse => Precedence::LogicalOr,
+            InfixOperator::LogicalAnd | InfixOperator::LogicalAndAlso => Precedence::LogicalAnd,
+            InfixOperator::BitwiseOr => Precedence::BitOr,
+            InfixOperator::BitwiseXor => Precedence::BitXor,
+            InfixOperator::BitwiseAnd => Precedence::BitAnd,
+            InfixOperator::Equals
+            | InfixOperator::NotEquals
+            | InfixOperator::StrictEquals
+            | InfixOperator::StrictNotEquals => Precedence::Equals,
+            InfixOperator::LessThan
+            | InfixOperator::GreaterThan
+            | InfixOperator::LessThanOrEqual
+            | InfixOperator::GreaterThanOrEqual => Precedence::LessGreater,
+            InfixOperator::BitwiseLeftShift
+            | InfixOperator::BitwiseRightShift
+            | InfixOperator::BitwiseUnsignedLeftShift
+            | InfixOperator::BitwiseUnsignedRightShift => Precedence::Shift,
+            InfixOperator::Add | InfixOperator::Subtract => Precedence::Sum,
+            InfixOperator::Multiply | InfixOperator::Divide | InfixOperator::Modulo => {
+                Precedence::Product
+            }
+            InfixOperator::Power => Precedence::Power,
+        }
+    }
+}
+
 /// 中置演算子用の一時enum
 enum InfixOpToken {
     Operator(OperatorToken),
@@ -60,6 +96,12 @@ enum InfixOpToken {
 /// パーサ内部で使用するResult型
 type ParseResult<T> = Result<T, SnowFallError>;
 
+/// 既定の報告するエラーの上限
+pub const DEFAULT_MAX_ERRORS: usize = 100;
+
+/// まとめた `UnexpectedCharacter` のメッセージに含める文字数の上限
+const MAX_ECHOED_CHARACTERS: usize = 8;
+
 /// 字句解析器(Lexer)を入力としてASTを構築する構文解析器
 pub struct Parser<'a> {
     /// 字句解析器
@@ -70,6 +112,16 @@ pub struct Parser<'a> {
     peek_token: Token,
     /// パース中に蓄積されたエラー
     errors: Vec<SnowFallError>,
+    /// 報告するエラーの上限 (超えた場合は解析を打ち切る)
+    max_errors: usize,
+    /// エラーが上限に達したかどうか
+    too_many_errors: bool,
+    /// 現在トークンより前にある、閉じられていない `{` の数 (エラーからの復帰に使用する)
+    brace_depth: usize,
+    /// 次に割り当てるノードID
+    ///
+    /// トークンを借用したままIDを割り当てられるよう `Cell` で保持する
+    next_node_id: Cell<u32>,
 }
 
 impl<'a> Parser<'a> {
@@ -79,14 +131,56 @@ impl<'a> Parser<'a> {
             cur_token: Token::eof(0),
             peek_token: Token::eof(0),
             errors: Vec::new(),
+            max_errors: DEFAULT_MAX_ERRORS,
+            too_many_errors: false,
+            brace_depth: 0,
+            next_node_id: Cell::new(0),
         };
         p.next_token();
         p.next_token();
         p
     }
 
+    /// 報告するエラーの上限を設定する (1未満は1とみなす)
+    ///
+    /// 上限を超えると解析を打ち切り、最後に `TooManyErrors` を報告する。
+    pub fn with_max_errors(mut self, max_errors: usize) -> Self {
+        self.max_errors = max_errors.max(1);
+        // 先読みした時点で上限を超えている場合は超えた分を捨てる
+        if self.errors.len() > self.max_errors {
+            let dropped = self.errors.split_off(self.max_errors);
+            self.report_too_many_errors(&dropped[0]);
+        }
+        self
+    }
+
+    /// 最初に割り当てるノードIDを設定する
+    ///
+    /// 既存の構文木の一部を解析し直す場合に、IDが重複しないよう使用する。
+    pub fn with_first_node_id(self, id: NodeId) -> Self {
+        self.next_node_id.set(id.0);
+        self
+    }
+
+    /// 次に割り当てるノードID
+    pub fn peek_node_id(&self) -> NodeId {
+        NodeId(self.next_node_id.get())
+    }
+
+    /// 現在のトークンの範囲
+    pub fn current_span(&self) -> Span {
+        self.cur_token.span
+    }
+
     /// トークンを1つ進める
     fn next_token(&mut self) {
+        match self.cur_token.kind {
+            TokenKind::Delimiter(DelimiterToken::LBrace) => self.brace_depth += 1,
+            TokenKind::Delimiter(DelimiterToken::RBrace) => {
+                self.brace_depth = self.brace_depth.saturating_sub(1)
+            }
+            _ => {}
+        }
         self.cur_token = self.peek_token.clone();
         loop {
             match self.lexer.next_token() {
@@ -94,15 +188,118 @@ impl<'a> Parser<'a> {
                     self.peek_token = token;
                     break;
                 }
-                Err(e) => {
-                    self.errors.push(e);
-                }
+                Err(e) => self.push_error(e),
             }
         }
     }
 
+    /// エラーを蓄積する
+    ///
+    /// 空白のみを挟んで連続する `UnexpectedCharacter` は1つの範囲にまとめ、
+    /// 既に報告した位置のエラーは捨てる。上限を超えた場合は `TooManyErrors` を1度だけ報告する。
+    fn push_error(&mut self, error: SnowFallError) {
+        if self.too_many_errors {
+            return;
+        }
+        if self.merge_unexpected_character(&error) {
+            return;
+        }
+        if self
+            .errors
+            .iter()
+            .any(|e| (e.line, e.column) == (error.line, error.column))
+        {
+            return;
+        }
+        // 入力の末尾まで続くエラー (閉じていない文字列など) より後ろのエラーは、その結果にすぎない
+        let end = self.lexer.input().len();
+        if let Some(span) = error.span
+            && self.errors.iter().any(|e| {
+                e.span
+                    .is_some_and(|s| s.end >= end && s.start <= span.start)
+            })
+        {
+            return;
+        }
+        if self.errors.len() >= self.max_errors {
+            self.report_too_many_errors(&error);
+            return;
+        }
+        self.errors.push(error);
+    }
+
+    /// 上限を超えたことを `dropped` (最初に捨てたエラー) の位置に報告する
+    fn report_too_many_errors(&mut self, dropped: &SnowFallError) {
+        self.too_many_errors = true;
+        let mut error = SnowFallError::new_compiler_error(
+            ErrorCode::TooManyErrors,
+            dropped.line,
+            dropped.column,
+        );
+        if let Some(span) = dropped.span {
+            error = error.with_span(span, self.lexer.input());
+        }
+        self.errors
+            .push(error.with_arg("max", self.max_errors.to_string()));
+    }
+
+    /// 直前のエラーと連続する `UnexpectedCharacter` であれば範囲を広げてまとめる
+    fn merge_unexpected_character(&mut self, error: &SnowFallError) -> bool {
+        let code = ErrorCode::UnexpectedCharacter.to_str();
+        let input = self.lexer.input();
+        let Some(last) = self.errors.last_mut() else {
+            return false;
+        };
+        let (Some(last_span), Some(span)) = (last.span, error.span) else {
+            return false;
+        };
+        if last.code != code || error.code != code || span.start < last_span.end {
+            return false;
+        }
+        let between = input.get(last_span.end..span.start).unwrap_or("");
+        if !between.chars().all(char::is_whitespace) {
+            return false;
+        }
+
+        // 範囲と個数だけを更新し、メッセージに含める文字は先頭の数文字に留める
+        let count = last
+            .context
+            .as_ref()
+            .and_then(|context| context.get("count"))
+            .and_then(|count| count.parse::<usize>().ok())
+            .unwrap_or(1)
+            + 1;
+        last.span = Some(last_span.cover(span));
+        last.end_line = error.end_line;
+        last.end_column = error.end_column;
+        let mut characters: String = input
+            .get(last_span.start..span.end)
+            .unwrap_or("")
+            .chars()
+            .take(MAX_ECHOED_CHARACTERS + 1)
+            .collect();
+        if characters.chars().count() > MAX_ECHOED_CHARACTERS {
+            characters.pop();
+            characters.push('…');
+        }
+        let context = last
+            .context
+            .get_or_insert_with(SnowFallErrorContext::default);
+        context.insert("character".to_string(), characters);
+        context.insert("count".to_string(), count.to_string());
+        last.localize(Locale::En);
+        true
+    }
+
     // ===== ヘルパーメソッド =====
 
+    /// 構文木内で一意なノードIDを割り当てる
+    fn next_node_id(&self) -> NodeId {
+        let id = self.next_node_id.get();
+        self.next_node_id.set(id + 1);
+        NodeId(id)
+    }
+
     /// 次のトークンが指定した `TokenKind` と一致するか判定する
     fn peek_token_is(&self, kind: &TokenKind) -> bool {
         match (&self.peek_token.kind, kind) {
@@ -114,24 +311,48 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// 次のトークンが型名であれば消費する (`const` や `readonly` の後)
+    fn expect_type_name(&mut self) -> ParseResult<()> {
+        if !self.peek_token_is(&TokenKind::Identifier(String::new())) {
+            return Err(self.error_at(self.peek_token.span, ErrorCode::ExpectedTypeName));
+        }
+        self.next_token();
+        Ok(())
+    }
+
     /// 次のトークンが期待通りであれば消費する
     fn expect_peek(&mut self, expected: TokenKind) -> ParseResult<()> {
         if self.peek_token_is(&expected) {
             self.next_token();
             Ok(())
         } else {
-            Err(SnowFallError::new_compiler_error(
-                Some(format!(
-                    "Expected next token to be {:?}, got {:?} instead",
-                    expected, self.peek_token.kind
-                )),
-                ErrorCode::UnexpectedToken,
-                self.lexer.line,
-                self.lexer.column,
-            ))
+            let error = self
+                .error_at(self.peek_token.span, ErrorCode::UnexpectedToken)
+                .with_arg("expected", format!("{:?}", expected))
+                .with_arg("found", format!("{:?}", self.peek_token.kind));
+            // 文末の `;` 忘れは直前のトークンの後ろに挿入する修正案を示す
+            if expected == TokenKind::Delimiter(DelimiterToken::Semicolon) {
+                let offset = self.cur_token.span.end;
+                return Err(error
+                    .with_label(
+                        self.cur_token.span,
+                        Detail::ExpectedSemicolonAfter,
+                        self.lexer.input(),
+                    )
+                    .with_suggestion(
+                        Detail::InsertSemicolon,
+                        vec![TextEdit::insert(offset, ";", self.lexer.input())],
+                    ));
+            }
+            Err(error)
         }
     }
 
+    /// 指定した範囲を対象とするコンパイルエラーを生成する
+    fn error_at(&self, span: Span, code: ErrorCode) -> SnowFallError {
+        SnowFallError::new_compiler_error_at(code, span, self.lexer.input())
+    }
+
     /// 現在トークンの優先順位を取得する
     fn cur_precedence(&self) -> Precedence {
         self.token_precedence(&self.cur_token.kind)
@@ -172,7 +393,9 @@ impl<'a> Parser<'a> {
 
                 _ => Precedence::Lowest,
             },
-            TokenKind::Delimiter(DelimiterToken::LParen) => Precedence::Call,
+            TokenKind::Delimiter(
+                DelimiterToken::LParen | DelimiterToken::LBracket | DelimiterToken::Dot,
+            ) => Precedence::Call,
             TokenKind::Keyword(kw) => match kw {
                 KeywordToken::Or => Precedence::LogicalOr,
                 KeywordToken::And => Precedence::LogicalAnd,
@@ -188,33 +411,92 @@ impl<'a> Parser<'a> {
     /// ソース全体を解析し `Program` を生成する
     pub fn parse_program(&mut self) -> Result<ProgramAst, Vec<SnowFallError>> {
         let mut statements = Vec::new();
+        let (span, id) = self.parse_program_with(|stmt| statements.push(stmt))?;
+        Ok(ProgramAst {
+            id,
+            statements,
+            span,
+        })
+    }
+
+    /// ソース全体を解析し、プログラム直下の文を解析した順に `on_statement` へ渡す
+    ///
+    /// 文の並びを保持しないため、呼び出し側で文ごとに別の形式へ変換できる
+    /// (構文エラーを検出した後の文は渡さない)。
+    /// 成功した場合はプログラム全体の範囲とノードIDを返す。
+    pub fn parse_program_with(
+        &mut self,
+        mut on_statement: impl FnMut(Statement),
+    ) -> Result<(Span, NodeId), Vec<SnowFallError>> {
         let start = self.cur_token.span.start;
+        let mut end = start;
 
         while self.cur_token.kind != TokenKind::Eof {
+            let depth = self.brace_depth;
             match self.parse_statement() {
-                Ok(stmt) => statements.push(stmt),
+                Ok(stmt) if self.errors.is_empty() => {
+                    end = stmt.span.end;
+                    on_statement(stmt);
+                }
+                Ok(_) => {}
                 Err(e) => {
-                    self.errors.push(e);
-                    self.next_token(); // 簡易的なエラーリカバリ
+                    self.push_error(e);
+                    self.synchronize(depth);
                 }
             }
+            if self.too_many_errors {
+                break;
+            }
             self.next_token();
         }
 
         if !self.errors.is_empty() {
             return Err(self.errors.drain(..).collect());
         }
+        Ok((Span { start, end }, self.next_node_id()))
+    }
+
+    /// エラーが発生した文の終わり (`;` または文の中で開いた `{` を閉じる `}`) まで読み飛ばす
+    ///
+    /// `depth` は文の先頭での `brace_depth`。後続の文から余分なエラーが報告されないようにする
+    fn synchronize(&mut self, depth: usize) {
+        while self.cur_token.kind != TokenKind::Eof {
+            match self.cur_token.kind {
+                TokenKind::Delimiter(DelimiterToken::Semicolon) if self.brace_depth <= depth => {
+                    return;
+                }
+                TokenKind::Delimiter(DelimiterToken::RBrace) if self.brace_depth <= depth + 1 => {
+                    return;
+                }
+                _ => self.next_token(),
+            }
+        }
+    }
 
-        let end = if !statements.is_empty() {
-            statements.last().unwrap().span.end
-        } else {
-            start
-        };
+    /// 現在のトークンが `end` (バイト位置) より前にある間、文を解析する
+    ///
+    /// ソースの一部を解析し直す場合に使用する。エラーが発生した時点で解析を中止する。
+    /// 解析後の位置は `current_span` で確認できる。
+    pub fn parse_statements_until(
+        &mut self,
+        end: usize,
+    ) -> Result<Vec<Statement>, Vec<SnowFallError>> {
+        let mut statements = Vec::new();
+        while self.cur_token.kind != TokenKind::Eof && self.cur_token.span.start < end {
+            match self.parse_statement() {
+                Ok(stmt) => statements.push(stmt),
+                Err(e) => {
+                    self.push_error(e);
+                    break;
+                }
+            }
+            self.next_token();
+        }
 
-        Ok(ProgramAst {
-            statements,
-            span: Span { start, end },
-        })
+        if !self.errors.is_empty() {
+            return Err(self.errors.drain(..).collect());
+        }
+        Ok(statements)
     }
 
     /// 1文（Statement）を解析する
@@ -227,11 +509,13 @@ impl<'a> Parser<'a> {
             TokenKind::Keyword(KeywordToken::If) => self.parse_if_statement(),
             TokenKind::Keyword(KeywordToken::While) => self.parse_while_statement(),
             TokenKind::Keyword(KeywordToken::Return) => self.parse_return_statement(),
+            TokenKind::Keyword(KeywordToken::Const) => self.parse_const_declaration(),
             TokenKind::Delimiter(DelimiterToken::LBrace) => self.parse_block_statement(),
             TokenKind::Identifier(_) => {
                 // "Int a" のように「識別子 -> 識別子」なら変数宣言とみなす
                 if self.is_variable_declaration() {
-                    self.parse_variable_declaration()
+                    let start = self.cur_token.span.start;
+                    self.parse_variable_declaration(start, Mutability::Mutable)
                 } else {
                     self.parse_expression_statement()
                 }
@@ -255,6 +539,7 @@ impl<'a> Parser<'a> {
         }
 
         Ok(Statement {
+            id: self.next_node_id(),
             kind: StatementKind::Block(statements),
             span: Span {
                 start,
@@ -273,6 +558,7 @@ impl<'a> Parser<'a> {
         }
 
         Ok(Statement {
+            id: self.next_node_id(),
             kind: StatementKind::Expression(expr),
             span: Span {
                 start,
@@ -288,23 +574,30 @@ impl<'a> Parser<'a> {
     }
 
     /// 変数宣言: `Int a = 1, b = 2;`
-    fn parse_variable_declaration(&mut self) -> ParseResult<Statement> {
-        let start = self.cur_token.span.start;
-
+    ///
+    /// `start` には修飾子 (`const` / `readonly`) を含めた宣言の開始位置を渡す
+    fn parse_variable_declaration(
+        &mut self,
+        start: usize,
+        mutability: Mutability,
+    ) -> ParseResult<Statement> {
         // 1. 型名を取得 (例: "Int")
         let type_name = if let TokenKind::Identifier(ref s) = self.cur_token.kind {
             s.clone()
         } else {
-            return Err(SnowFallError::new_compiler_error(
-                None,
-                ErrorCode::ExpectedTypeName,
-                self.lexer.line,
-                self.lexer.column,
-            ));
+            return Err(self.error_at(self.cur_token.span, ErrorCode::ExpectedTypeName));
         };
 
         let mut declarators = Vec::new();
 
+        // `const MAX = 1;` のように型名が欠けている場合は、変数名を型名として読んでいる
+        if mutability.is_immutable()
+            && (self.peek_token_is(&TokenKind::Operator(OperatorToken::Assign))
+                || self.peek_token_is(&TokenKind::Delimiter(DelimiterToken::Semicolon)))
+        {
+            return Err(self.error_at(self.cur_token.span, ErrorCode::ExpectedTypeName));
+        }
+
         // 2. 変数リストを解析
         loop {
             // 変数名へ移動
@@ -315,12 +608,20 @@ impl<'a> Parser<'a> {
                 unreachable!()
             };
 
+            let name_span = self.cur_token.span;
+
             // 初期化式があるかチェック
             let mut value = None;
             if self.peek_token_is(&TokenKind::Operator(OperatorToken::Assign)) {
                 self.next_token(); // Skip '='
                 self.next_token(); // Expr の開始位置に移動
                 value = Some(self.parse_expression(Precedence::Lowest)?);
+            } else if mutability == Mutability::Const {
+                // 定数は宣言時に必ず初期化されなければならない
+                return Err(self
+                    .error_at(name_span, ErrorCode::MissingConstInitializer)
+                    .with_arg("name", var_name)
+                    .with_note(Detail::ConstInitializerRequired));
             }
 
             declarators.push(VariableDeclarator {
@@ -339,9 +640,11 @@ impl<'a> Parser<'a> {
         self.expect_peek(TokenKind::Delimiter(DelimiterToken::Semicolon))?;
 
         Ok(Statement {
+            id: self.next_node_id(),
             kind: StatementKind::VariableDeclaration {
                 type_name,
                 declarators,
+                mutability,
             },
             span: Span {
                 start,
@@ -350,21 +653,31 @@ impl<'a> Parser<'a> {
         })
     }
 
+    /// 定数宣言: `const Int MAX = 10;`
+    fn parse_const_declaration(&mut self) -> ParseResult<Statement> {
+        let start = self.cur_token.span.start;
+        self.expect_type_name()?;
+
+        // 初期化式のない宣言は `parse_variable_declaration` で報告する
+        self.parse_variable_declaration(start, Mutability::Const)
+    }
+
     /// 関数宣言: `function Int main() {}`
     fn parse_function_declaration(&mut self) -> ParseResult<Statement> {
         let start = self.cur_token.span.start;
 
         // functionキーワードの次は戻り値の型 (Intなど)
-        self.expect_peek(TokenKind::Identifier("".to_string()))?;
-        let return_type = if let TokenKind::Identifier(ref s) = self.cur_token.kind {
-            Some(s.clone())
-        } else {
-            return Err(SnowFallError::new_compiler_error(
-                None,
-                ErrorCode::ExpectedReturnType,
-                self.lexer.line,
-                self.lexer.column,
-            ));
+        if !self.peek_token_is(&TokenKind::Identifier(String::new())) {
+            return Err(self.error_at(self.peek_token.span, ErrorCode::ExpectedReturnType));
+        }
+        self.next_token();
+        // `function f()` のように戻り値の型が欠けている場合は、関数名を型名として読んでいる
+        if self.peek_token_is(&TokenKind::Delimiter(DelimiterToken::LParen)) {
+            return Err(self.error_at(self.cur_token.span, ErrorCode::ExpectedReturnType));
+        }
+        let return_type = match &self.cur_token.kind {
+            TokenKind::Identifier(s) => Some(s.clone()),
+            _ => None,
         };
 
         // 関数名
@@ -381,6 +694,7 @@ impl<'a> Parser<'a> {
         let body = Box::new(self.parse_block_statement()?);
 
         Ok(Statement {
+            id: self.next_node_id(),
             kind: StatementKind::FunctionDeclaration {
                 kind: FunctionKind::Function,
                 name,
@@ -413,6 +727,7 @@ impl<'a> Parser<'a> {
         let body = Box::new(self.parse_block_statement()?);
 
         Ok(Statement {
+            id: self.next_node_id(),
             kind: StatementKind::FunctionDeclaration {
                 kind: FunctionKind::Sub,
                 name,
@@ -440,16 +755,12 @@ impl<'a> Parser<'a> {
         self.next_token();
 
         loop {
+            let start = self.cur_token.span.start;
             // 型名
             let type_name = if let TokenKind::Identifier(ref s) = self.cur_token.kind {
                 s.clone()
             } else {
-                return Err(SnowFallError::new_compiler_error(
-                    None,
-                    ErrorCode::ExpectedParameterType,
-                    self.lexer.line,
-                    self.lexer.column,
-                ));
+                return Err(self.error_at(self.cur_token.span, ErrorCode::ExpectedParameterType));
             };
 
             // パラメータ名
@@ -471,6 +782,10 @@ impl<'a> Parser<'a> {
                 name,
                 type_name,
                 value,
+                span: Span {
+                    start,
+                    end: self.cur_token.span.end,
+                },
             });
 
             if self.peek_token_is(&TokenKind::Delimiter(DelimiterToken::Comma)) {
@@ -501,6 +816,7 @@ impl<'a> Parser<'a> {
         };
 
         Ok(Statement {
+            id: self.next_node_id(),
             kind: StatementKind::Return(value),
             span: Span {
                 start,
@@ -528,6 +844,7 @@ impl<'a> Parser<'a> {
         }
 
         Ok(Statement {
+            id: self.next_node_id(),
             kind: StatementKind::If {
                 condition,
                 consequence,
@@ -552,12 +869,9 @@ impl<'a> Parser<'a> {
                 let name = if let TokenKind::Identifier(ref s) = self.cur_token.kind {
                     s.clone()
                 } else {
-                    return Err(SnowFallError::new_compiler_error(
-                        None,
-                        ErrorCode::ExpectedIdentifierInForEach,
-                        self.lexer.line,
-                        self.lexer.column,
-                    ));
+                    return Err(
+                        self.error_at(self.cur_token.span, ErrorCode::ExpectedIdentifierInForEach)
+                    );
                 };
                 Binding {
                     name,
@@ -570,12 +884,9 @@ impl<'a> Parser<'a> {
                 TokenKind::Keyword(KeywordToken::In) => ForEachKind::In,
                 TokenKind::Keyword(KeywordToken::Of) => ForEachKind::Of,
                 _ => {
-                    return Err(SnowFallError::new_compiler_error(
-                        None,
-                        ErrorCode::ExpectedInOrOfInForEach,
-                        self.lexer.line,
-                        self.lexer.column,
-                    ));
+                    return Err(
+                        self.error_at(self.cur_token.span, ErrorCode::ExpectedInOrOfInForEach)
+                    );
                 }
             };
             self.next_token();
@@ -586,6 +897,7 @@ impl<'a> Parser<'a> {
             let body = Box::new(self.parse_statement()?);
 
             Ok(Statement {
+                id: self.next_node_id(),
                 kind: StatementKind::ForEach {
                     binding,
                     iterable,
@@ -598,41 +910,45 @@ impl<'a> Parser<'a> {
                 },
             })
         } else {
-            // for 文
+            // for 文 (各節は省略できる。省略した場合は現在のトークンが区切り記号になる)
             // 初期化
             let init = if self.cur_token.kind != TokenKind::Delimiter(DelimiterToken::Semicolon) {
                 // ここではセミコロンを消費しないバージョンの文解析が必要
-                if self.is_variable_declaration() {
-                    Some(Box::new(self.parse_variable_declaration_for_for()?))
+                let init = if self.is_variable_declaration() {
+                    self.parse_variable_declaration_for_for()?
                 } else {
-                    Some(Box::new(self.parse_expression_statement_for_for()?))
-                }
+                    self.parse_expression_statement_for_for()?
+                };
+                self.expect_peek(TokenKind::Delimiter(DelimiterToken::Semicolon))?;
+                Some(Box::new(init))
             } else {
                 None
             };
-            self.expect_peek(TokenKind::Delimiter(DelimiterToken::Semicolon))?;
             self.next_token();
 
             // 条件
             let condition =
                 if self.cur_token.kind != TokenKind::Delimiter(DelimiterToken::Semicolon) {
-                    Some(self.parse_expression(Precedence::Lowest)?)
+                    let condition = self.parse_expression(Precedence::Lowest)?;
+                    self.expect_peek(TokenKind::Delimiter(DelimiterToken::Semicolon))?;
+                    Some(condition)
                 } else {
                     None
                 };
-            self.expect_peek(TokenKind::Delimiter(DelimiterToken::Semicolon))?;
             self.next_token();
 
             // 更新
             let update = if self.cur_token.kind != TokenKind::Delimiter(DelimiterToken::RParen) {
-                Some(Box::new(self.parse_expression_statement_for_for()?))
+                let update = self.parse_expression_statement_for_for()?;
+                self.expect_peek(TokenKind::Delimiter(DelimiterToken::RParen))?;
+                Some(Box::new(update))
             } else {
                 None
             };
-            self.expect_peek(TokenKind::Delimiter(DelimiterToken::RParen))?;
             self.next_token();
             let body = Box::new(self.parse_statement()?);
             Ok(Statement {
+                id: self.next_node_id(),
                 kind: StatementKind::For {
                     init,
                     condition,
@@ -692,6 +1008,7 @@ impl<'a> Parser<'a> {
         let start = self.cur_token.span.start;
         let expr = self.parse_expression(Precedence::Lowest)?;
         Ok(Statement {
+            id: self.next_node_id(),
             kind: StatementKind::Expression(expr),
             span: Span {
                 start,
@@ -706,12 +1023,7 @@ impl<'a> Parser<'a> {
         let type_name = if let TokenKind::Identifier(ref s) = self.cur_token.kind {
             s.clone()
         } else {
-            return Err(SnowFallError::new_compiler_error(
-                None,
-                ErrorCode::ExpectedTypeName,
-                self.lexer.line,
-                self.lexer.column,
-            ));
+            return Err(self.error_at(self.cur_token.span, ErrorCode::ExpectedTypeName));
         };
 
         let mut declarators = Vec::new();
@@ -743,9 +1055,11 @@ impl<'a> Parser<'a> {
         }
 
         Ok(Statement {
+            id: self.next_node_id(),
             kind: StatementKind::VariableDeclaration {
                 type_name,
                 declarators,
+                mutability: Mutability::Mutable,
             },
             span: Span {
                 start,
@@ -765,6 +1079,7 @@ impl<'a> Parser<'a> {
         let body = Box::new(self.parse_statement()?);
 
         Ok(Statement {
+            id: self.next_node_id(),
             kind: StatementKind::While { condition, body },
             span: Span {
                 start,
@@ -806,22 +1121,28 @@ impl<'a> Parser<'a> {
                 TokenKind::Keyword(KeywordToken::Sub) => {
                     members.push(self.parse_sub_declaration()?);
                 }
+                // フィールド `Int x = 1;`
+                TokenKind::Identifier(_) if self.is_variable_declaration() => {
+                    let start = self.cur_token.span.start;
+                    members.push(self.parse_variable_declaration(start, Mutability::Mutable)?);
+                }
+                // 読み取り専用フィールド `readonly Int x = 1;`
+                TokenKind::Keyword(KeywordToken::Readonly) => {
+                    let start = self.cur_token.span.start;
+                    self.expect_type_name()?;
+                    members.push(self.parse_variable_declaration(start, Mutability::Readonly)?);
+                }
                 _ => {
-                    return Err(SnowFallError::new_compiler_error(
-                        Some(format!(
-                            "Expected 'function' or 'sub' for class member, got {:?}",
-                            self.cur_token.kind
-                        )),
-                        ErrorCode::ExpectedMemberForClass,
-                        self.lexer.line,
-                        self.lexer.column,
-                    ));
+                    return Err(self
+                        .error_at(self.cur_token.span, ErrorCode::ExpectedMemberForClass)
+                        .with_arg("found", format!("{:?}", self.cur_token.kind)));
                 }
             }
         }
         self.expect_peek(TokenKind::Delimiter(DelimiterToken::RBrace))?;
 
         Ok(Statement {
+            id: self.next_node_id(),
             kind: StatementKind::ClassDeclaration {
                 name,
                 superclass,
@@ -839,19 +1160,23 @@ impl<'a> Parser<'a> {
         // Prefix
         let mut left = match &self.cur_token.kind {
             TokenKind::Identifier(s) => Expression {
+                id: self.next_node_id(),
                 kind: ExpressionKind::Identifier(s.clone()),
                 span: self.cur_token.span,
             },
             TokenKind::Literal(lit) => self.parse_literal(lit)?,
             TokenKind::Keyword(KeywordToken::True) => Expression {
+                id: self.next_node_id(),
                 kind: ExpressionKind::Boolean(true),
                 span: self.cur_token.span,
             },
             TokenKind::Keyword(KeywordToken::False) => Expression {
+                id: self.next_node_id(),
                 kind: ExpressionKind::Boolean(false),
                 span: self.cur_token.span,
             },
             TokenKind::Keyword(KeywordToken::Null) => Expression {
+                id: self.next_node_id(),
                 kind: ExpressionKind::NullLiteral,
                 span: self.cur_token.span,
             },
@@ -864,16 +1189,21 @@ impl<'a> Parser<'a> {
             TokenKind::Delimiter(DelimiterToken::LParen) => self.parse_grouped()?,
             TokenKind::Delimiter(DelimiterToken::LBracket) => self.parse_array()?,
             TokenKind::Delimiter(DelimiterToken::LBrace) => self.parse_object()?, // またはblock
+            // 式の終わりを表すトークンの場合は、式そのものが欠けている
+            TokenKind::Eof
+            | TokenKind::Delimiter(
+                DelimiterToken::Semicolon
+                | DelimiterToken::Comma
+                | DelimiterToken::RParen
+                | DelimiterToken::RBracket
+                | DelimiterToken::RBrace,
+            ) => {
+                return Err(self.error_at(self.cur_token.span, ErrorCode::ExpectedExpression));
+            }
             _ => {
-                return Err(SnowFallError::new_compiler_error(
-                    Some(format!(
-                        "Unexpected token for expression: {:?}",
-                        self.cur_token
-                    )),
-                    ErrorCode::UnexpectedTokenForExpression,
-                    self.lexer.line,
-                    self.lexer.column,
-                ));
+                return Err(self
+                    .error_at(self.cur_token.span, ErrorCode::UnexpectedTokenForExpression)
+                    .with_arg("token", format!("{:?}", self.cur_token)));
             }
         };
 
@@ -882,7 +1212,8 @@ impl<'a> Parser<'a> {
             && precedence < self.peek_precedence()
         {
             match self.peek_token.kind {
-                TokenKind::Operator(_) => {
+                TokenKind::Operator(_)
+                | TokenKind::Keyword(KeywordToken::And | KeywordToken::Or) => {
                     self.next_token();
                     left = self.parse_infix(left)?;
                 }
@@ -914,6 +1245,7 @@ impl<'a> Parser<'a> {
             LiteralToken::Boolean(v) => ExpressionKind::Boolean(*v),
         };
         Ok(Expression {
+            id: self.next_node_id(),
             kind,
             span: self.cur_token.span,
         })
@@ -932,6 +1264,7 @@ impl<'a> Parser<'a> {
         self.next_token();
         let right = self.parse_expression(Precedence::Prefix)?;
         Ok(Expression {
+            id: self.next_node_id(),
             kind: ExpressionKind::Prefix {
                 operator,
                 right: Box::new(right),
@@ -1001,22 +1334,26 @@ impl<'a> Parser<'a> {
         let precedence = self.cur_precedence();
 
         // 代入演算子の場合の特別処理（右結合）
-        match op_token {
-            InfixOpToken::Operator(OperatorToken::Assign) => {
-                self.next_token();
-                let right = self.parse_expression(Precedence::Lowest)?;
-                return Ok(Expression {
-                    kind: ExpressionKind::Assignment {
-                        left: Box::new(left),
-                        right: Box::new(right),
-                    },
-                    span: Span {
-                        start,
-                        end: self.cur_token.span.end,
-                    },
-                });
+        if let InfixOpToken::Operator(OperatorToken::Assign) = op_token {
+            self.next_token();
+            let right = self.parse_expression(Precedence::Lowest)?;
+            // 代入できるのは識別子とメンバーアクセスのみ (右辺まで読み進めてから報告する)
+            if !left.is_assignable() {
+                return Err(self
+                    .error_at(left.span, ErrorCode::InvalidAssignmentTarget)
+                    .with_note(Detail::AssignableTargets));
             }
-            _ => {}
+            return Ok(Expression {
+                id: self.next_node_id(),
+                kind: ExpressionKind::Assignment {
+                    left: Box::new(left),
+                    right: Box::new(right),
+                },
+                span: Span {
+                    start,
+                    end: self.cur_token.span.end,
+                },
+            });
         }
 
         let operator = self.convert_infix_operator(op_token);
@@ -1024,6 +1361,7 @@ impl<'a> Parser<'a> {
         let right = self.parse_expression(precedence)?;
 
         Ok(Expression {
+            id: self.next_node_id(),
             kind: ExpressionKind::Infix {
                 left: Box::new(left),
                 operator,
@@ -1049,6 +1387,7 @@ impl<'a> Parser<'a> {
         let start = function.span.start;
         let arguments = self.parse_expression_list(DelimiterToken::RParen)?;
         Ok(Expression {
+            id: self.next_node_id(),
             kind: ExpressionKind::Call {
                 function: Box::new(function),
                 arguments,
@@ -1068,9 +1407,10 @@ impl<'a> Parser<'a> {
         self.expect_peek(TokenKind::Delimiter(DelimiterToken::RBracket))?;
 
         Ok(Expression {
-            kind: ExpressionKind::Index {
-                left: Box::new(left),
-                index: Box::new(index),
+            id: self.next_node_id(),
+            kind: ExpressionKind::MemberAccess {
+                object: Box::new(left),
+                property: MemberProperty::Computed(Box::new(index)),
             },
             span: Span {
                 start,
@@ -1091,9 +1431,10 @@ impl<'a> Parser<'a> {
         };
 
         Ok(Expression {
-            kind: ExpressionKind::Member {
-                left: Box::new(left),
-                property: prop,
+            id: self.next_node_id(),
+            kind: ExpressionKind::MemberAccess {
+                object: Box::new(left),
+                property: MemberProperty::Named(prop),
             },
             span: Span {
                 start,
@@ -1127,6 +1468,7 @@ impl<'a> Parser<'a> {
         let start = self.cur_token.span.start;
         let elements = self.parse_expression_list(DelimiterToken::RBracket)?;
         Ok(Expression {
+            id: self.next_node_id(),
             kind: ExpressionKind::ArrayLiteral(elements),
             span: Span {
                 start,
@@ -1159,6 +1501,7 @@ impl<'a> Parser<'a> {
         self.expect_peek(TokenKind::Delimiter(DelimiterToken::RBrace))?;
 
         Ok(Expression {
+            id: self.next_node_id(),
             kind: ExpressionKind::ObjectLiteral { pairs },
             span: Span {
                 start,
@@ -0,0 +1,833 @@
+//! 名前解決
+//!
+//! 識別子の参照を宣言に対応付け、宣言 (シンボル)・参照・スコープの一覧を求める。
+//! 定義へ移動や参照の検索などのエディタ支援で使用する。
+//!
+//! スコープの区切り方は意味解析 (`semantic`) と同じ。
+//! 関数は宣言より前から呼び出せるため、その時点で解決できない参照はスコープを抜けるときに解決し直す。
+use ahash::AHashMap;
+use serde::Serialize;
+
+use crate::common::Span;
+use crate::compiler::Lexer;
+use crate::compiler::arena::*;
+use crate::compiler::ast::{FunctionKind, Mutability, NodeId};
+use crate::compiler::interner::Symbol as Name;
+use crate::compiler::side_table::SideTable;
+
+/// シンボルの識別子 (`Resolution::symbols` の添字)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(transparent)]
+pub struct SymbolId(pub usize);
+
+/// スコープの識別子 (`Resolution::scopes` の添字)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(transparent)]
+pub struct ScopeId(pub usize);
+
+/// シンボルの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SymbolKind {
+    Variable,
+    /// `const` 宣言された変数
+    Constant,
+    Parameter,
+    Function,
+    Sub,
+    Class,
+    Field,
+    /// クラスのメンバーの関数
+    Method,
+}
+
+impl SymbolKind {
+    /// 宣言より前から参照できるかどうか
+    pub fn is_hoisted(self) -> bool {
+        matches!(
+            self,
+            SymbolKind::Function | SymbolKind::Sub | SymbolKind::Class | SymbolKind::Method
+        )
+    }
+}
+
+/// 宣言された名前
+#[derive(Debug, Clone, Serialize)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// 名前の範囲
+    pub span: Span,
+    /// 宣言全体の範囲 (関数やクラスは本体を含む)
+    pub declaration: Span,
+    /// 宣言された型 (変数・引数は型名、関数は戻り値の型、クラスは継承元のクラス名)
+    pub type_name: Option<String>,
+    /// 初期化式を除いた宣言の表記 (例: `function Int add(Int a, Int b)`)
+    pub signature: String,
+    /// 再代入できないかどうか (`const` 変数と `readonly` フィールド)
+    pub readonly: bool,
+    /// 宣言されたスコープ
+    pub scope: ScopeId,
+    /// 宣言を囲む関数またはクラス
+    pub container: Option<SymbolId>,
+}
+
+/// 名前の参照
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Reference {
+    /// 参照している識別子の範囲
+    pub span: Span,
+    pub symbol: SymbolId,
+    /// 代入先としての参照かどうか
+    pub write: bool,
+}
+
+/// 名前の有効範囲
+#[derive(Debug, Clone, Serialize)]
+pub struct Scope {
+    pub span: Span,
+    /// 外側のスコープ (プログラム全体のスコープでは `None`)
+    pub parent: Option<ScopeId>,
+    /// このスコープで宣言されたシンボル (宣言順)
+    pub symbols: Vec<SymbolId>,
+}
+
+/// 名前解決の結果
+#[derive(Debug, Clone)]
+pub struct Resolution {
+    pub symbols: Vec<Symbol>,
+    /// 解決できた参照 (位置順)
+    pub references: Vec<Reference>,
+    /// スコープ (最初の要素がプログラム全体のスコープ)
+    pub scopes: Vec<Scope>,
+    /// 識別子の式ノードが参照するシンボル
+    pub resolved: SideTable<SymbolId>,
+    /// 解決できなかった識別子の名前と範囲 (組み込み関数など)
+    pub unresolved: Vec<(String, Span)>,
+    /// 型名として書かれた識別子の範囲 (組み込み型を含む、位置順)
+    pub type_names: Vec<Span>,
+}
+
+impl Resolution {
+    pub fn symbol(&self, id: SymbolId) -> &Symbol {
+        &self.symbols[id.0]
+    }
+
+    /// `offset` にある名前 (宣言または参照) が指すシンボル
+    ///
+    /// 名前の直後の位置も名前の上として扱う
+    pub fn symbol_at(&self, offset: usize) -> Option<SymbolId> {
+        self.name_at(offset).map(|(symbol, _)| symbol)
+    }
+
+    /// `offset` にある名前が指すシンボルと、その名前の範囲
+    pub fn name_at(&self, offset: usize) -> Option<(SymbolId, Span)> {
+        let contains = |span: Span| span.start <= offset && offset <= span.end;
+        self.symbols
+            .iter()
+            .position(|symbol| contains(symbol.span))
+            .map(|index| (SymbolId(index), self.symbols[index].span))
+            .or_else(|| {
+                self.references
+                    .iter()
+                    .find(|reference| contains(reference.span))
+                    .map(|reference| (reference.symbol, reference.span))
+            })
+    }
+
+    /// シンボルへの参照 (位置順、宣言自体は含まない)
+    pub fn references_to(&self, id: SymbolId) -> impl Iterator<Item = &Reference> {
+        self.references
+            .iter()
+            .filter(move |reference| reference.symbol == id)
+    }
+
+    /// `offset` を含む最も内側のスコープ
+    pub fn scope_at(&self, offset: usize) -> ScopeId {
+        // 内側のスコープほど後に作られるため、含むもののうち最後のものを選ぶ
+        self.scopes
+            .iter()
+            .rposition(|scope| scope.span.start <= offset && offset <= scope.span.end)
+            .map_or(ScopeId(0), ScopeId)
+    }
+
+    /// クラスのフィールドとメソッド (宣言順、継承元のメンバーは後ろに続く)
+    ///
+    /// 派生クラスで同じ名前のメンバーを宣言している場合、継承元のメンバーは含まない。
+    pub fn members(&self, class: SymbolId) -> Vec<SymbolId> {
+        let mut members: Vec<SymbolId> = Vec::new();
+        let mut visited = Vec::new();
+        let mut current = Some(class);
+        while let Some(class) = current.filter(|class| !visited.contains(class)) {
+            visited.push(class);
+            for (index, symbol) in self.symbols.iter().enumerate() {
+                let is_member = matches!(symbol.kind, SymbolKind::Field | SymbolKind::Method)
+                    && symbol.container == Some(class);
+                if is_member && members.iter().all(|&m| self.symbol(m).name != symbol.name) {
+                    members.push(SymbolId(index));
+                }
+            }
+            current = self
+                .symbol(class)
+                .type_name
+                .as_deref()
+                .and_then(|name| self.class_named(name));
+        }
+        members
+    }
+
+    /// ソースコードの編集に合わせて位置をずらす (`range` は編集前の範囲、`inserted` は挿入した文字列のバイト数)
+    ///
+    /// 構文エラーで解析し直せない間も直前の結果を使い続けるためのもの。
+    /// 編集した範囲と重なる参照は取り除き、宣言やスコープの範囲は編集に合わせて伸縮させる。
+    ///
+    /// ```
+    /// use snowfall_core::common::Span;
+    /// use snowfall_core::compiler::arena::AstArena;
+    /// use snowfall_core::compiler::resolver::resolve;
+    ///
+    /// let source = "Int a = 1;\nprint(a);";
+    /// let mut resolution = resolve(&AstArena::parse(source).unwrap(), source);
+    ///
+    /// // 先頭に `Int b = ;\n` (10バイト) を挿入する
+    /// resolution.shift(Span { start: 0, end: 0 }, 10);
+    /// let a = resolution.symbol_at(27).unwrap();
+    /// assert_eq!(resolution.symbol(a).span, Span { start: 14, end: 15 });
+    /// ```
+    pub fn shift(&mut self, range: Span, inserted: usize) {
+        let moved = |offset: usize| offset - range.end + range.start + inserted;
+        // 開始位置は編集位置と同じであれば挿入した文字列の後ろへ、終了位置は前に置く
+        let start = |offset: usize| {
+            if offset >= range.end {
+                moved(offset)
+            } else if offset <= range.start {
+                offset
+            } else {
+                range.start + inserted
+            }
+        };
+        let end = |offset: usize| {
+            if offset <= range.start {
+                offset
+            } else if offset >= range.end {
+                moved(offset)
+            } else {
+                range.start + inserted
+            }
+        };
+        let span = |span: Span| Span {
+            start: start(span.start),
+            end: end(span.end).max(start(span.start)),
+        };
+        let overlaps = |span: &Span| span.start < range.end && range.start < span.end;
+
+        for symbol in &mut self.symbols {
+            symbol.span = span(symbol.span);
+            symbol.declaration = span(symbol.declaration);
+        }
+        for scope in &mut self.scopes {
+            scope.span = span(scope.span);
+        }
+        self.references
+            .retain(|reference| !overlaps(&reference.span));
+        for reference in &mut self.references {
+            reference.span = span(reference.span);
+        }
+        self.unresolved.retain(|(_, name)| !overlaps(name));
+        for (_, name) in &mut self.unresolved {
+            *name = span(*name);
+        }
+        self.type_names.retain(|name| !overlaps(name));
+        for name in &mut self.type_names {
+            *name = span(*name);
+        }
+    }
+
+    /// `offset` を囲む最も内側のクラス (`this` の型)
+    pub fn enclosing_class(&self, offset: usize) -> Option<SymbolId> {
+        self.symbols
+            .iter()
+            .enumerate()
+            .filter(|(_, symbol)| {
+                symbol.kind == SymbolKind::Class
+                    && symbol.declaration.start <= offset
+                    && offset <= symbol.declaration.end
+            })
+            .max_by_key(|(_, symbol)| symbol.declaration.start)
+            .map(|(index, _)| SymbolId(index))
+    }
+
+    /// 名前からクラスを探す
+    pub fn class_named(&self, name: &str) -> Option<SymbolId> {
+        self.symbols
+            .iter()
+            .position(|symbol| symbol.kind == SymbolKind::Class && symbol.name == name)
+            .map(SymbolId)
+    }
+
+    /// `offset` の位置から参照できるシンボル (内側のスコープから順)
+    ///
+    /// 関数とクラス以外は `offset` より前で宣言されたもののみを含む。
+    /// 外側の同じ名前のシンボルは内側のシンボルに隠されるため含まない。
+    pub fn visible_at(&self, offset: usize) -> Vec<SymbolId> {
+        let mut visible: Vec<SymbolId> = Vec::new();
+        let mut scope = Some(self.scope_at(offset));
+        while let Some(id) = scope {
+            let current = &self.scopes[id.0];
+            for &symbol_id in current.symbols.iter().rev() {
+                let symbol = self.symbol(symbol_id);
+                let declared = symbol.kind.is_hoisted() || symbol.span.end <= offset;
+                if declared && visible.iter().all(|&v| self.symbol(v).name != symbol.name) {
+                    visible.push(symbol_id);
+                }
+            }
+            scope = current.parent;
+        }
+        visible
+    }
+}
+
+/// プログラム全体の名前を解決する
+///
+/// 名前はアリーナのシンボルで比較するため、参照ごとに文字列を確保しない。
+///
+/// ```
+/// use snowfall_core::compiler::arena::AstArena;
+/// use snowfall_core::compiler::resolver::{SymbolKind, resolve};
+///
+/// let source = "print(add(1, 2));\nfunction Int add(Int a, Int b) {\n    return a + b;\n}";
+/// let arena = AstArena::parse(source).unwrap();
+/// let resolution = resolve(&arena, source);
+///
+/// // 宣言より前の呼び出しも解決する
+/// let add = resolution.symbol_at(7).unwrap();
+/// assert_eq!(resolution.symbol(add).kind, SymbolKind::Function);
+/// assert_eq!(resolution.symbol(add).signature, "function Int add(Int a, Int b)");
+/// assert_eq!(resolution.references_to(add).count(), 1);
+/// assert_eq!(resolution.unresolved[0].0, "print");
+/// ```
+pub fn resolve(arena: &AstArena, source: &str) -> Resolution {
+    let mut resolver = Resolver {
+        source,
+        arena,
+        resolution: Resolution {
+            symbols: Vec::new(),
+            references: Vec::new(),
+            scopes: Vec::new(),
+            resolved: SideTable::new(),
+            unresolved: Vec::new(),
+            type_names: Vec::new(),
+        },
+        frames: Vec::new(),
+        containers: Vec::new(),
+    };
+    resolver.push_scope(Span {
+        start: 0,
+        end: source.len().max(arena.span().end),
+    });
+    for &stmt in arena.root() {
+        resolver.resolve_statement(stmt);
+    }
+    resolver.pop_scope();
+
+    let mut resolution = resolver.resolution;
+    resolution
+        .references
+        .sort_by_key(|reference| (reference.span.start, reference.span.end));
+    resolution.type_names.sort_by_key(|span| span.start);
+    resolution
+}
+
+/// 解決を待っている参照
+struct Pending {
+    name: Name,
+    span: Span,
+    /// 識別子の式ノード (型名の参照の場合は `None`)
+    node: Option<NodeId>,
+    write: bool,
+}
+
+/// 解析中のスコープ
+struct Frame {
+    id: ScopeId,
+    /// 名前から最後に宣言されたシンボル
+    names: AHashMap<Name, SymbolId>,
+    /// このスコープ内で解決できなかった参照
+    pending: Vec<Pending>,
+}
+
+struct Resolver<'a> {
+    source: &'a str,
+    arena: &'a AstArena,
+    resolution: Resolution,
+    frames: Vec<Frame>,
+    /// 宣言を囲む関数・クラスのスタック
+    containers: Vec<SymbolId>,
+}
+
+impl<'a> Resolver<'a> {
+    /// シンボルに対応する名前
+    fn name(&self, name: Name) -> &'a str {
+        self.arena.resolve(name)
+    }
+
+    fn push_scope(&mut self, span: Span) {
+        let id = ScopeId(self.resolution.scopes.len());
+        self.resolution.scopes.push(Scope {
+            span,
+            parent: self.frames.last().map(|frame| frame.id),
+            symbols: Vec::new(),
+        });
+        self.frames.push(Frame {
+            id,
+            names: AHashMap::new(),
+            pending: Vec::new(),
+        });
+    }
+
+    /// スコープを抜け、未解決の参照をこのスコープの名前で解決し直す
+    fn pop_scope(&mut self) {
+        let Some(frame) = self.frames.pop() else {
+            return;
+        };
+        for pending in frame.pending {
+            match frame.names.get(&pending.name) {
+                Some(&symbol) => self.record(pending, symbol),
+                None => match self.frames.last_mut() {
+                    Some(parent) => parent.pending.push(pending),
+                    // 型名の参照は組み込み型の場合があるため記録しない
+                    None if pending.node.is_some() => {
+                        let name = self.name(pending.name).to_string();
+                        self.resolution.unresolved.push((name, pending.span));
+                    }
+                    None => {}
+                },
+            }
+        }
+    }
+
+    /// 現在のスコープにシンボルを登録する
+    fn declare(
+        &mut self,
+        name: Name,
+        kind: SymbolKind,
+        span: Span,
+        declaration: Span,
+        type_name: Option<Name>,
+        signature: String,
+    ) -> SymbolId {
+        let id = SymbolId(self.resolution.symbols.len());
+        let Some(frame) = self.frames.last_mut() else {
+            return id;
+        };
+        frame.names.insert(name, id);
+        self.resolution.scopes[frame.id.0].symbols.push(id);
+        self.resolution.symbols.push(Symbol {
+            name: self.arena.resolve(name).to_string(),
+            kind,
+            span,
+            declaration,
+            type_name: type_name.map(|type_name| self.arena.resolve(type_name).to_string()),
+            signature,
+            readonly: false,
+            scope: frame.id,
+            container: self.containers.last().copied(),
+        });
+        id
+    }
+
+    /// 名前の参照を記録する (解決できない場合はスコープを抜けるときに解決し直す)
+    fn reference(&mut self, pending: Pending) {
+        let found = self
+            .frames
+            .iter()
+            .rev()
+            .find_map(|frame| frame.names.get(&pending.name).copied());
+        match found {
+            Some(symbol) => self.record(pending, symbol),
+            None => {
+                if let Some(frame) = self.frames.last_mut() {
+                    frame.pending.push(pending);
+                }
+            }
+        }
+    }
+
+    fn record(&mut self, pending: Pending, symbol: SymbolId) {
+        match pending.node {
+            Some(node) => {
+                self.resolution.resolved.insert(node, symbol);
+            }
+            // 型名はクラスを指す場合のみ参照とする
+            None if self.resolution.symbol(symbol).kind != SymbolKind::Class => return,
+            None => {}
+        }
+        self.resolution.references.push(Reference {
+            span: pending.span,
+            symbol,
+            write: pending.write,
+        });
+    }
+
+    /// `from` 以降の型名を記録し (クラスの場合は参照とする)、型名の直後の位置を返す
+    fn type_reference(&mut self, from: usize, type_name: Name) -> usize {
+        let Some(span) = Lexer::find_identifier(self.source, from, self.name(type_name)) else {
+            return from;
+        };
+        self.resolution.type_names.push(span);
+        self.reference(Pending {
+            name: type_name,
+            span,
+            node: None,
+            write: false,
+        });
+        span.end
+    }
+
+    /// `from` 以降の名前の範囲 (見つからない場合は `fallback`)
+    fn name_span(&self, from: usize, name: Name, fallback: Span) -> Span {
+        Lexer::find_identifier(self.source, from, self.name(name)).unwrap_or(fallback)
+    }
+
+    fn resolve_statement(&mut self, id: StmtId) {
+        let stmt = self.arena.statement(id);
+        match &stmt.kind {
+            ArenaStatementKind::VariableDeclaration {
+                type_name,
+                declarators,
+                mutability,
+            } => self.resolve_variables(stmt, *type_name, declarators, *mutability, false),
+            ArenaStatementKind::FunctionDeclaration { .. } => self.resolve_function(stmt, false),
+            ArenaStatementKind::ClassDeclaration {
+                name,
+                superclass,
+                members,
+            } => {
+                let name_span = self.name_span(stmt.span.start, *name, stmt.span);
+                let mut signature = format!("class {}", self.name(*name));
+                if let Some(superclass) = *superclass {
+                    signature.push_str(&format!(" extends {}", self.name(superclass)));
+                    self.type_reference(name_span.end, superclass);
+                }
+                let class = self.declare(
+                    *name,
+                    SymbolKind::Class,
+                    name_span,
+                    stmt.span,
+                    *superclass,
+                    signature,
+                );
+                self.containers.push(class);
+                self.push_scope(stmt.span);
+                // メソッドから後ろで宣言されたフィールドへの参照は、クラスのスコープを抜けるときに解決する
+                for &member_id in members {
+                    let member = self.arena.statement(member_id);
+                    match &member.kind {
+                        ArenaStatementKind::VariableDeclaration {
+                            type_name,
+                            declarators,
+                            mutability,
+                        } => self.resolve_variables(
+                            member,
+                            *type_name,
+                            declarators,
+                            *mutability,
+                            true,
+                        ),
+                        ArenaStatementKind::FunctionDeclaration { .. } => {
+                            self.resolve_function(member, true)
+                        }
+                        _ => self.resolve_statement(member_id),
+                    }
+                }
+                self.pop_scope();
+                self.containers.pop();
+            }
+            ArenaStatementKind::If {
+                condition,
+                consequence,
+                alternative,
+            } => {
+                self.resolve_expression(*condition);
+                self.resolve_statement(*consequence);
+                if let Some(alt) = *alternative {
+                    self.resolve_statement(alt);
+                }
+            }
+            ArenaStatementKind::For {
+                init,
+                condition,
+                update,
+                body,
+            } => {
+                self.push_scope(stmt.span);
+                if let Some(init) = *init {
+                    self.resolve_statement(init);
+                }
+                if let Some(condition) = *condition {
+                    self.resolve_expression(condition);
+                }
+                if let Some(update) = *update {
+                    self.resolve_statement(update);
+                }
+                self.resolve_statement(*body);
+                self.pop_scope();
+            }
+            ArenaStatementKind::ForEach {
+                binding,
+                iterable,
+                body,
+                ..
+            } => {
+                self.resolve_expression(*iterable);
+                self.push_scope(stmt.span);
+                let cursor = match binding.type_name {
+                    Some(type_name) => self.type_reference(stmt.span.start, type_name),
+                    None => stmt.span.start,
+                };
+                let span = self.name_span(cursor, binding.name, stmt.span);
+                let signature = match binding.type_name {
+                    Some(type_name) => {
+                        format!("{} {}", self.name(type_name), self.name(binding.name))
+                    }
+                    None => self.name(binding.name).to_string(),
+                };
+                self.declare(
+                    binding.name,
+                    SymbolKind::Variable,
+                    span,
+                    stmt.span,
+                    binding.type_name,
+                    signature,
+                );
+                self.resolve_statement(*body);
+                self.pop_scope();
+            }
+            ArenaStatementKind::While { condition, body } => {
+                self.resolve_expression(*condition);
+                self.resolve_statement(*body);
+            }
+            ArenaStatementKind::Switch {
+                expression,
+                cases,
+                default,
+            } => {
+                self.resolve_expression(*expression);
+                for case in cases {
+                    for &value in &case.values {
+                        self.resolve_expression(value);
+                    }
+                    self.resolve_statement(case.body);
+                }
+                if let Some(default) = *default {
+                    self.resolve_statement(default);
+                }
+            }
+            ArenaStatementKind::Return(value) => {
+                if let Some(value) = *value {
+                    self.resolve_expression(value);
+                }
+            }
+            ArenaStatementKind::Break | ArenaStatementKind::Continue => {}
+            ArenaStatementKind::Block(stmts) => {
+                self.push_scope(stmt.span);
+                for &stmt in stmts {
+                    self.resolve_statement(stmt);
+                }
+                self.pop_scope();
+            }
+            ArenaStatementKind::Expression(expr) => self.resolve_expression(*expr),
+        }
+    }
+
+    /// 変数宣言 (`is_field` が真の場合はクラスのフィールド) を解決する
+    fn resolve_variables(
+        &mut self,
+        stmt: &ArenaStatement,
+        type_name: Name,
+        declarators: &[ArenaDeclarator],
+        mutability: Mutability,
+        is_field: bool,
+    ) {
+        let kind = match (is_field, mutability) {
+            (true, _) => SymbolKind::Field,
+            (false, Mutability::Const) => SymbolKind::Constant,
+            (false, _) => SymbolKind::Variable,
+        };
+        let keyword = match mutability {
+            Mutability::Mutable => "",
+            Mutability::Const => "const ",
+            Mutability::Readonly => "readonly ",
+        };
+        let mut cursor = self.type_reference(stmt.span.start, type_name);
+        for decl in declarators {
+            let span = self.name_span(cursor, decl.name, stmt.span);
+            cursor = span.end;
+            if let Some(value) = decl.value {
+                self.resolve_expression(value);
+                cursor = cursor.max(self.arena.expression(value).span.end);
+            }
+            let signature = format!(
+                "{}{} {}",
+                keyword,
+                self.name(type_name),
+                self.name(decl.name)
+            );
+            let symbol = self.declare(decl.name, kind, span, stmt.span, Some(type_name), signature);
+            self.resolution.symbols[symbol.0].readonly = mutability.is_immutable();
+        }
+    }
+
+    /// 関数宣言 (`is_method` が真の場合はクラスのメソッド) を解決する
+    fn resolve_function(&mut self, stmt: &ArenaStatement, is_method: bool) {
+        let ArenaStatementKind::FunctionDeclaration {
+            kind,
+            name,
+            return_type,
+            params,
+            body,
+        } = &stmt.kind
+        else {
+            return;
+        };
+        let name_start = match *return_type {
+            Some(return_type) => self.type_reference(stmt.span.start, return_type),
+            None => stmt.span.start,
+        };
+        let name_span = self.name_span(name_start, *name, stmt.span);
+        let params_signature = params
+            .iter()
+            .map(|param| format!("{} {}", self.name(param.type_name), self.name(param.name)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let function_name = self.name(*name);
+        let (symbol_kind, signature) = match (kind, return_type) {
+            (FunctionKind::Function, Some(return_type)) => (
+                SymbolKind::Function,
+                format!(
+                    "function {} {}({})",
+                    self.name(*return_type),
+                    function_name,
+                    params_signature
+                ),
+            ),
+            (FunctionKind::Function, None) => (
+                SymbolKind::Function,
+                format!("function {}({})", function_name, params_signature),
+            ),
+            (FunctionKind::Sub, _) => (
+                SymbolKind::Sub,
+                format!("sub {}({})", function_name, params_signature),
+            ),
+        };
+        let symbol_kind = if is_method {
+            SymbolKind::Method
+        } else {
+            symbol_kind
+        };
+        let function = self.declare(
+            *name,
+            symbol_kind,
+            name_span,
+            stmt.span,
+            *return_type,
+            signature,
+        );
+
+        self.containers.push(function);
+        self.push_scope(stmt.span);
+        let mut cursor = name_span.end;
+        for param in params {
+            let type_end = self.type_reference(cursor, param.type_name);
+            let span = self.name_span(type_end, param.name, stmt.span);
+            cursor = span.end;
+            if let Some(value) = param.value {
+                self.resolve_expression(value);
+                cursor = cursor.max(self.arena.expression(value).span.end);
+            }
+            let signature = format!("{} {}", self.name(param.type_name), self.name(param.name));
+            self.declare(
+                param.name,
+                SymbolKind::Parameter,
+                span,
+                span,
+                Some(param.type_name),
+                signature,
+            );
+        }
+        self.resolve_statement(*body);
+        self.pop_scope();
+        self.containers.pop();
+    }
+
+    fn resolve_expression(&mut self, id: ExprId) {
+        let expr = self.arena.expression(id);
+        match &expr.kind {
+            ArenaExpressionKind::IntLiteral(_)
+            | ArenaExpressionKind::FloatLiteral(_)
+            | ArenaExpressionKind::StringLiteral(_)
+            | ArenaExpressionKind::Boolean(_)
+            | ArenaExpressionKind::NullLiteral => {}
+            ArenaExpressionKind::Identifier(name) => self.reference(Pending {
+                name: *name,
+                span: expr.span,
+                node: Some(expr.id),
+                write: false,
+            }),
+            ArenaExpressionKind::Prefix { right, .. } => self.resolve_expression(*right),
+            ArenaExpressionKind::Infix { left, right, .. } => {
+                self.resolve_expression(*left);
+                self.resolve_expression(*right);
+            }
+            ArenaExpressionKind::Call {
+                function,
+                arguments,
+            }
+            | ArenaExpressionKind::New {
+                class: function,
+                arguments,
+            } => {
+                self.resolve_expression(*function);
+                for &arg in arguments {
+                    self.resolve_expression(arg);
+                }
+            }
+            ArenaExpressionKind::Cast {
+                target_type,
+                expression,
+            } => {
+                self.type_reference(expr.span.start, *target_type);
+                self.resolve_expression(*expression);
+            }
+            ArenaExpressionKind::ArrayLiteral(elements) => {
+                for &element in elements {
+                    self.resolve_expression(element);
+                }
+            }
+            ArenaExpressionKind::ObjectLiteral { pairs } => {
+                for &(key, value) in pairs {
+                    self.resolve_expression(key);
+                    self.resolve_expression(value);
+                }
+            }
+            ArenaExpressionKind::MemberAccess { object, property } => {
+                self.resolve_expression(*object);
+                if let ArenaMemberProperty::Computed(index) = *property {
+                    self.resolve_expression(index);
+                }
+            }
+            ArenaExpressionKind::Assignment { left, right } => {
+                let target = self.arena.expression(*left);
+                match &target.kind {
+                    ArenaExpressionKind::Identifier(name) => self.reference(Pending {
+                        name: *name,
+                        span: target.span,
+                        node: Some(target.id),
+                        write: true,
+                    }),
+                    _ => self.resolve_expression(*left),
+                }
+                self.resolve_expression(*right);
+            }
+        }
+    }
+}
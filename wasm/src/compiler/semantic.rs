@@ -0,0 +1,396 @@
+use ahash::AHashMap;
+
+use crate::common::ErrorCode;
+use crate::common::Span;
+use crate::common::error::{SnowFallError, TextEdit};
+use crate::common::messages::Detail;
+use crate::compiler::ast::*;
+
+/// 構文解析後のASTに対して意味的な検査を行う
+///
+/// 現時点では以下を検査する
+/// - `const` 変数および `readonly` フィールドへの再代入
+///   (フィールドはメソッド内の名前、`this.x`、型の分かる変数を通した `p.x` への代入を検査する)
+pub fn analyze(program: &ProgramAst, source: &str) -> Vec<SnowFallError> {
+    let mut classes = Classes::default();
+    classes.visit_program(program);
+    let mut analyzer = SemanticAnalyzer {
+        source,
+        scopes: vec![AHashMap::new()],
+        classes: classes
+            .classes
+            .into_iter()
+            .filter_map(|(name, class)| Some((name, class?)))
+            .collect(),
+        current_class: None,
+        errors: Vec::new(),
+    };
+    for stmt in &program.statements {
+        analyzer.check_statement(stmt);
+    }
+    analyzer.errors
+}
+
+/// 名前の宣言情報
+#[derive(Clone)]
+struct Declaration {
+    mutability: Mutability,
+    /// 宣言した文の範囲
+    span: Span,
+    /// 宣言された型名 (メンバーアクセスの対象のクラスを求めるのに使う)
+    type_name: Option<String>,
+}
+
+/// クラスの親クラスとフィールドの宣言
+struct ClassInfo {
+    superclass: Option<String>,
+    fields: AHashMap<String, Declaration>,
+}
+
+/// プログラム中のクラスを収集する (同名のクラスが複数ある場合は曖昧なため除外する)
+#[derive(Default)]
+struct Classes {
+    classes: AHashMap<String, Option<ClassInfo>>,
+}
+
+impl<'ast> Visitor<'ast> for Classes {
+    fn visit_statement(&mut self, stmt: &'ast Statement) {
+        if let StatementKind::ClassDeclaration {
+            name,
+            superclass,
+            members,
+        } = &stmt.kind
+        {
+            let mut fields = AHashMap::new();
+            for member in members {
+                if let StatementKind::VariableDeclaration {
+                    type_name,
+                    declarators,
+                    mutability,
+                } = &member.kind
+                {
+                    for decl in declarators {
+                        let declaration = Declaration {
+                            mutability: *mutability,
+                            span: member.span,
+                            type_name: Some(type_name.clone()),
+                        };
+                        fields.insert(decl.name.clone(), declaration);
+                    }
+                }
+            }
+            let class = ClassInfo {
+                superclass: superclass.clone(),
+                fields,
+            };
+            self.classes
+                .entry(name.clone())
+                .and_modify(|entry| *entry = None)
+                .or_insert(Some(class));
+        }
+        walk_statement(self, stmt);
+    }
+}
+
+/// 意味解析器の内部状態
+struct SemanticAnalyzer<'a> {
+    /// エラー位置の算出に使用する元のソースコード
+    source: &'a str,
+    /// 宣言された名前のスコープスタック
+    scopes: Vec<AHashMap<String, Declaration>>,
+    /// クラス名ごとの宣言
+    classes: AHashMap<String, ClassInfo>,
+    /// 検査中のメンバーを持つクラス (`this` の参照先)
+    current_class: Option<String>,
+    /// 検出したエラー
+    errors: Vec<SnowFallError>,
+}
+
+impl SemanticAnalyzer<'_> {
+    fn push_scope(&mut self) {
+        self.scopes.push(AHashMap::new());
+    }
+
+    fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// 現在のスコープに名前を登録する
+    fn declare(&mut self, name: &str, mutability: Mutability, span: Span, type_name: Option<&str>) {
+        if let Some(scope) = self.scopes.last_mut() {
+            let declaration = Declaration {
+                mutability,
+                span,
+                type_name: type_name.map(str::to_string),
+            };
+            scope.insert(name.to_string(), declaration);
+        }
+    }
+
+    /// 内側のスコープから順に名前を探す
+    fn lookup(&self, name: &str) -> Option<&Declaration> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    /// `this.x` や `p.x` のフィールドの宣言を求める (親クラスのフィールドも探す)
+    ///
+    /// 対象のクラスは `this` (検査中のクラス) または宣言された型名から求める
+    fn field(&self, object: &Expression, field: &str) -> Option<&Declaration> {
+        let ExpressionKind::Identifier(name) = &object.kind else {
+            return None;
+        };
+        let mut class = if name == "this" {
+            self.current_class.as_deref()?
+        } else {
+            self.lookup(name)?.type_name.as_deref()?
+        };
+        // 継承が循環していても、クラスの数を超えてたどらない
+        for _ in 0..self.classes.len() {
+            let info = self.classes.get(class)?;
+            if let Some(declaration) = info.fields.get(field) {
+                return Some(declaration);
+            }
+            class = info.superclass.as_deref()?;
+        }
+        None
+    }
+
+    fn check_statement(&mut self, stmt: &Statement) {
+        match &stmt.kind {
+            StatementKind::VariableDeclaration {
+                type_name,
+                declarators,
+                mutability,
+            } => {
+                for decl in declarators {
+                    if let Some(value) = &decl.value {
+                        self.check_expression(value);
+                    }
+                    self.declare(&decl.name, *mutability, stmt.span, Some(type_name));
+                }
+            }
+            StatementKind::FunctionDeclaration { params, body, .. } => {
+                self.push_scope();
+                for param in params {
+                    if let Some(value) = &param.value {
+                        self.check_expression(value);
+                    }
+                    self.declare(
+                        &param.name,
+                        Mutability::Mutable,
+                        stmt.span,
+                        Some(&param.type_name),
+                    );
+                }
+                self.check_statement(body);
+                self.pop_scope();
+            }
+            StatementKind::ClassDeclaration { name, members, .. } => {
+                // フィールドはメソッド内からも参照できるよう先に登録する
+                self.push_scope();
+                for member in members {
+                    if let StatementKind::VariableDeclaration {
+                        type_name,
+                        declarators,
+                        mutability,
+                    } = &member.kind
+                    {
+                        for decl in declarators {
+                            self.declare(&decl.name, *mutability, member.span, Some(type_name));
+                        }
+                    }
+                }
+                let outer_class = self.current_class.replace(name.clone());
+                for member in members {
+                    match &member.kind {
+                        StatementKind::VariableDeclaration { declarators, .. } => {
+                            for value in declarators.iter().filter_map(|d| d.value.as_ref()) {
+                                self.check_expression(value);
+                            }
+                        }
+                        _ => self.check_statement(member),
+                    }
+                }
+                self.current_class = outer_class;
+                self.pop_scope();
+            }
+            StatementKind::If {
+                condition,
+                consequence,
+                alternative,
+            } => {
+                self.check_expression(condition);
+                self.check_statement(consequence);
+                if let Some(alt) = alternative {
+                    self.check_statement(alt);
+                }
+            }
+            StatementKind::For {
+                init,
+                condition,
+                update,
+                body,
+            } => {
+                self.push_scope();
+                if let Some(init) = init {
+                    self.check_statement(init);
+                }
+                if let Some(condition) = condition {
+                    self.check_expression(condition);
+                }
+                if let Some(update) = update {
+                    self.check_statement(update);
+                }
+                self.check_statement(body);
+                self.pop_scope();
+            }
+            StatementKind::ForEach {
+                binding,
+                iterable,
+                body,
+                ..
+            } => {
+                self.check_expression(iterable);
+                self.push_scope();
+                self.declare(&binding.name, Mutability::Mutable, stmt.span, None);
+                self.check_statement(body);
+                self.pop_scope();
+            }
+            StatementKind::While { condition, body } => {
+                self.check_expression(condition);
+                self.check_statement(body);
+            }
+            StatementKind::Switch {
+                expression,
+                cases,
+                default,
+            } => {
+                self.check_expression(expression);
+                for case in cases {
+                    for value in &case.values {
+                        self.check_expression(value);
+                    }
+                    self.check_statement(&case.body);
+                }
+                if let Some(default) = default {
+                    self.check_statement(default);
+                }
+            }
+            StatementKind::Return(value) => {
+                if let Some(value) = value {
+                    self.check_expression(value);
+                }
+            }
+            StatementKind::Break | StatementKind::Continue => {}
+            StatementKind::Block(stmts) => {
+                self.push_scope();
+                for stmt in stmts {
+                    self.check_statement(stmt);
+                }
+                self.pop_scope();
+            }
+            StatementKind::Expression(expr) => self.check_expression(expr),
+        }
+    }
+
+    fn check_expression(&mut self, expr: &Expression) {
+        match &expr.kind {
+            ExpressionKind::IntLiteral(_)
+            | ExpressionKind::FloatLiteral(_)
+            | ExpressionKind::StringLiteral(_)
+            | ExpressionKind::Boolean(_)
+            | ExpressionKind::NullLiteral
+            | ExpressionKind::Identifier(_) => {}
+            ExpressionKind::Prefix { right, .. } => self.check_expression(right),
+            ExpressionKind::Infix { left, right, .. } => {
+                self.check_expression(left);
+                self.check_expression(right);
+            }
+            ExpressionKind::Call {
+                function,
+                arguments,
+            }
+            | ExpressionKind::New {
+                class: function,
+                arguments,
+            } => {
+                self.check_expression(function);
+                for arg in arguments {
+                    self.check_expression(arg);
+                }
+            }
+            ExpressionKind::Cast { expression, .. } => self.check_expression(expression),
+            ExpressionKind::ArrayLiteral(elements) => {
+                for element in elements {
+                    self.check_expression(element);
+                }
+            }
+            ExpressionKind::ObjectLiteral { pairs } => {
+                for (key, value) in pairs {
+                    self.check_expression(key);
+                    self.check_expression(value);
+                }
+            }
+            ExpressionKind::MemberAccess { object, property } => {
+                self.check_expression(object);
+                if let MemberProperty::Computed(index) = property {
+                    self.check_expression(index);
+                }
+            }
+            ExpressionKind::Assignment { left, right } => {
+                self.check_assignment_target(left);
+                self.check_expression(left);
+                self.check_expression(right);
+            }
+        }
+    }
+
+    /// 代入先が再代入不可の名前やフィールドでないかを検査する
+    fn check_assignment_target(&mut self, target: &Expression) {
+        let (name, declaration) = match &target.kind {
+            ExpressionKind::Identifier(name) => (name, self.lookup(name)),
+            ExpressionKind::MemberAccess {
+                object,
+                property: MemberProperty::Named(field),
+            } => (field, self.field(object, field)),
+            _ => return,
+        };
+        let Some(declaration) = declaration.filter(|d| d.mutability.is_immutable()).cloned() else {
+            return;
+        };
+
+        let keyword = if declaration.mutability == Mutability::Readonly {
+            "readonly"
+        } else {
+            "const"
+        };
+        let mut error = SnowFallError::new_compiler_error_at(
+            ErrorCode::AssignmentToConstant,
+            target.span,
+            self.source,
+        )
+        .with_arg("name", name.clone())
+        .with_arg("kind", keyword)
+        .with_label(declaration.span, Detail::DeclaredHere, self.source);
+
+        // 宣言の先頭のキーワードを取り除けば再代入できるようになる
+        let declared = &self.source[declaration.span.start..];
+        if let Some(rest) = declared.strip_prefix(keyword) {
+            let removed = rest
+                .find(|c: char| !c.is_whitespace())
+                .map_or(declared.len(), |offset| keyword.len() + offset);
+            error = error.with_suggestion(
+                Detail::RemoveKeyword,
+                vec![TextEdit::replace(
+                    Span {
+                        start: declaration.span.start,
+                        end: declaration.span.start + removed,
+                    },
+                    "",
+                    self.source,
+                )],
+            );
+        }
+        self.errors.push(error);
+    }
+}
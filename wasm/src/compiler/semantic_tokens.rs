@@ -0,0 +1,253 @@
+//! シンタックスハイライト用のトークンの分類 (セマンティックトークン)
+//!
+//! 字句解析の結果を名前解決の結果で補い、型名・変数・引数・関数など字句だけでは区別できない識別子を分類する。
+//! 構文エラーがあり名前解決できない場合は、前後のトークンから推定する。
+use ahash::AHashMap;
+use serde::Serialize;
+
+use crate::common::{DelimiterToken, LiteralToken, Span, TokenKind, TriviaToken};
+use crate::compiler::cst::{self, CstToken};
+use crate::compiler::resolver::{Resolution, SymbolKind};
+
+/// トークンの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SemanticTokenKind {
+    Keyword,
+    /// 型名 (クラス名の場合は `Class`)
+    Type,
+    Variable,
+    Parameter,
+    /// 関数・サブルーチン・メソッド
+    Function,
+    Class,
+    /// フィールドとメンバーアクセスのプロパティ
+    Property,
+    Number,
+    String,
+    Comment,
+    Operator,
+}
+
+impl SemanticTokenKind {
+    /// すべての種類 (LSPの凡例の順)
+    pub const ALL: &'static [SemanticTokenKind] = &[
+        SemanticTokenKind::Keyword,
+        SemanticTokenKind::Type,
+        SemanticTokenKind::Variable,
+        SemanticTokenKind::Parameter,
+        SemanticTokenKind::Function,
+        SemanticTokenKind::Class,
+        SemanticTokenKind::Property,
+        SemanticTokenKind::Number,
+        SemanticTokenKind::String,
+        SemanticTokenKind::Comment,
+        SemanticTokenKind::Operator,
+    ];
+
+    /// LSPのトークンの種類の名前
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SemanticTokenKind::Keyword => "keyword",
+            SemanticTokenKind::Type => "type",
+            SemanticTokenKind::Variable => "variable",
+            SemanticTokenKind::Parameter => "parameter",
+            SemanticTokenKind::Function => "function",
+            SemanticTokenKind::Class => "class",
+            SemanticTokenKind::Property => "property",
+            SemanticTokenKind::Number => "number",
+            SemanticTokenKind::String => "string",
+            SemanticTokenKind::Comment => "comment",
+            SemanticTokenKind::Operator => "operator",
+        }
+    }
+}
+
+/// トークンの修飾子
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SemanticTokenModifier {
+    /// 名前を宣言している位置
+    Declaration,
+    /// `const` 変数と `readonly` フィールド
+    Readonly,
+    /// 静的なメンバー (言語に静的メンバーがないため現在は出力しない)
+    Static,
+}
+
+impl SemanticTokenModifier {
+    /// すべての修飾子 (LSPの凡例の順)
+    pub const ALL: &'static [SemanticTokenModifier] = &[
+        SemanticTokenModifier::Declaration,
+        SemanticTokenModifier::Readonly,
+        SemanticTokenModifier::Static,
+    ];
+
+    /// LSPの修飾子の名前
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SemanticTokenModifier::Declaration => "declaration",
+            SemanticTokenModifier::Readonly => "readonly",
+            SemanticTokenModifier::Static => "static",
+        }
+    }
+}
+
+/// 分類したトークン
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SemanticToken {
+    pub span: Span,
+    pub kind: SemanticTokenKind,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub modifiers: Vec<SemanticTokenModifier>,
+}
+
+/// ソースコードのトークンを位置順に分類する
+///
+/// `resolution` はソースコード全体の名前解決の結果 (構文エラーがある場合は `None`)。
+/// 区切り記号と空白はハイライトの対象外のため含まない。
+///
+/// ```
+/// use snowfall_core::compiler::arena::AstArena;
+/// use snowfall_core::compiler::resolver::resolve;
+/// use snowfall_core::compiler::semantic_tokens::{SemanticTokenKind, semantic_tokens};
+///
+/// let source = "Int a = 1;\nprint(a);";
+/// let arena = AstArena::parse(source).unwrap();
+/// let kinds: Vec<_> = semantic_tokens(source, Some(&resolve(&arena, source)))
+///     .into_iter()
+///     .map(|token| (&source[token.span.start..token.span.end], token.kind))
+///     .collect();
+/// assert_eq!(kinds[0], ("Int", SemanticTokenKind::Type));
+/// assert_eq!(kinds[1], ("a", SemanticTokenKind::Variable));
+/// assert_eq!(kinds[4], ("print", SemanticTokenKind::Function));
+/// ```
+pub fn semantic_tokens(source: &str, resolution: Option<&Resolution>) -> Vec<SemanticToken> {
+    let names = resolution.map(NameTable::new);
+    let tokens = cst::lex_with_trivia(source);
+    let mut result = Vec::new();
+
+    for (index, token) in tokens.iter().enumerate() {
+        push_comments(&mut result, &token.leading_trivia);
+        let classified = match &token.kind {
+            TokenKind::Keyword(_) | TokenKind::Literal(LiteralToken::Boolean(_)) => {
+                Some((SemanticTokenKind::Keyword, Vec::new()))
+            }
+            TokenKind::Literal(LiteralToken::Int(_) | LiteralToken::Float(_)) => {
+                Some((SemanticTokenKind::Number, Vec::new()))
+            }
+            TokenKind::Literal(LiteralToken::String(_)) => {
+                Some((SemanticTokenKind::String, Vec::new()))
+            }
+            TokenKind::Operator(_) => Some((SemanticTokenKind::Operator, Vec::new())),
+            TokenKind::Identifier(_) => Some(classify_identifier(&tokens, index, names.as_ref())),
+            TokenKind::Delimiter(_) | TokenKind::Trivia(_) | TokenKind::Eof => None,
+        };
+        if let Some((kind, modifiers)) = classified {
+            result.push(SemanticToken {
+                span: token.span,
+                kind,
+                modifiers,
+            });
+        }
+        push_comments(&mut result, &token.trailing_trivia);
+    }
+    result
+}
+
+fn push_comments(result: &mut Vec<SemanticToken>, trivia: &[cst::Trivia]) {
+    let comments = trivia.iter().filter(|trivia| {
+        matches!(
+            trivia.kind,
+            TriviaToken::LineComment | TriviaToken::BlockComment
+        )
+    });
+    for comment in comments {
+        result.push(SemanticToken {
+            span: comment.span,
+            kind: SemanticTokenKind::Comment,
+            modifiers: Vec::new(),
+        });
+    }
+}
+
+/// 名前解決の結果を識別子の開始位置から引く表
+struct NameTable<'a> {
+    resolution: &'a Resolution,
+    /// 開始位置から (シンボルの添字, 宣言かどうか)
+    names: AHashMap<usize, (usize, bool)>,
+    types: AHashMap<usize, Span>,
+}
+
+impl<'a> NameTable<'a> {
+    fn new(resolution: &'a Resolution) -> Self {
+        let mut names = AHashMap::new();
+        for reference in &resolution.references {
+            names.insert(reference.span.start, (reference.symbol.0, false));
+        }
+        for (index, symbol) in resolution.symbols.iter().enumerate() {
+            names.insert(symbol.span.start, (index, true));
+        }
+        let types = resolution
+            .type_names
+            .iter()
+            .map(|span| (span.start, *span))
+            .collect();
+        Self {
+            resolution,
+            names,
+            types,
+        }
+    }
+}
+
+/// 識別子を分類する
+///
+/// 名前解決の結果 → 型名の位置 → 前後のトークンの順で判定する。
+fn classify_identifier(
+    tokens: &[CstToken],
+    index: usize,
+    names: Option<&NameTable>,
+) -> (SemanticTokenKind, Vec<SemanticTokenModifier>) {
+    let span = tokens[index].span;
+    if let Some(names) = names {
+        let symbol = names
+            .names
+            .get(&span.start)
+            .map(|&(symbol, declaration)| (&names.resolution.symbols[symbol], declaration))
+            .filter(|(symbol, declaration)| !declaration || symbol.span == span);
+        if let Some((symbol, declaration)) = symbol {
+            let mut modifiers = Vec::new();
+            if declaration {
+                modifiers.push(SemanticTokenModifier::Declaration);
+            }
+            if symbol.readonly {
+                modifiers.push(SemanticTokenModifier::Readonly);
+            }
+            let kind = match symbol.kind {
+                SymbolKind::Variable | SymbolKind::Constant => SemanticTokenKind::Variable,
+                SymbolKind::Parameter => SemanticTokenKind::Parameter,
+                SymbolKind::Function | SymbolKind::Sub | SymbolKind::Method => {
+                    SemanticTokenKind::Function
+                }
+                SymbolKind::Class => SemanticTokenKind::Class,
+                SymbolKind::Field => SemanticTokenKind::Property,
+            };
+            return (kind, modifiers);
+        }
+        if names.types.get(&span.start) == Some(&span) {
+            return (SemanticTokenKind::Type, Vec::new());
+        }
+    }
+
+    let previous = index.checked_sub(1).map(|i| &tokens[i].kind);
+    let next = tokens.get(index + 1).map(|token| &token.kind);
+    let kind = match (previous, next) {
+        (Some(TokenKind::Delimiter(DelimiterToken::Dot)), _) => SemanticTokenKind::Property,
+        (_, Some(TokenKind::Delimiter(DelimiterToken::LParen))) => SemanticTokenKind::Function,
+        // 名前解決できた場合、型名はすべて記録済み
+        (_, Some(TokenKind::Identifier(_))) if names.is_none() => SemanticTokenKind::Type,
+        _ => SemanticTokenKind::Variable,
+    };
+    (kind, Vec::new())
+}
@@ -0,0 +1,73 @@
+use ahash::AHashMap;
+
+use crate::compiler::ast::NodeId;
+
+/// ASTノードに対する解析結果を保持する表
+///
+/// 名前解決の結果や推論した型、定数値などを `NodeId` をキーとして格納する。
+/// 各解析は木を変更せずに結果を共有できる。
+///
+/// ```
+/// use snowfall_core::compiler::ast::NodeId;
+/// use snowfall_core::compiler::side_table::SideTable;
+///
+/// let mut types: SideTable<&str> = SideTable::new();
+/// types.insert(NodeId(0), "Int");
+/// assert_eq!(types.get(NodeId(0)), Some(&"Int"));
+/// assert_eq!(types.get(NodeId(1)), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SideTable<T> {
+    entries: AHashMap<NodeId, T>,
+}
+
+impl<T> SideTable<T> {
+    pub fn new() -> Self {
+        Self {
+            entries: AHashMap::new(),
+        }
+    }
+
+    /// 値を登録し、既に登録されていた値があれば返す
+    ///
+    /// 合成ノードのID (`NodeId::DUMMY`) は一意でないため登録できない
+    pub fn insert(&mut self, id: NodeId, value: T) -> Option<T> {
+        debug_assert!(!id.is_dummy(), "合成ノードには解析結果を登録できない");
+        self.entries.insert(id, value)
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&T> {
+        self.entries.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut T> {
+        self.entries.get_mut(&id)
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.entries.contains_key(&id)
+    }
+
+    pub fn remove(&mut self, id: NodeId) -> Option<T> {
+        self.entries.remove(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 登録されている全ての組を返す (順序は不定)
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, &T)> {
+        self.entries.iter().map(|(id, value)| (*id, value))
+    }
+}
+
+impl<T> Default for SideTable<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
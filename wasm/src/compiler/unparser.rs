@@ -0,0 +1,15 @@
+//! ASTからソースコードへの逆変換
+//!
+//! 正規化後のASTなど、元のソースと対応しないASTを読みやすいSnowFallソースとして出力する。
+//! 括弧はパーサーの `Precedence` に基づき必要な箇所にのみ付与する。
+use crate::compiler::ast::ProgramAst;
+use crate::compiler::formatter::{self, FormatOptions};
+
+/// ASTをSnowFallのソースコードへ変換する
+///
+/// 出力を再度構文解析すると、位置情報を除いて同じASTが得られる。
+/// ただし正規化で生じた負の数値リテラルは前置 `-` として出力されるため、
+/// 再解析後は `Prefix` 式となる。
+pub fn unparse(program: &ProgramAst) -> String {
+    formatter::format_program(program, "", Vec::new(), &FormatOptions::default())
+}
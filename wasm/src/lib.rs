@@ -1,3 +1,5 @@
+#![allow(clippy::result_large_err, reason = "SnowFallErrorは値渡しで扱う設計")]
+
 extern crate wasm_bindgen;
 use serde::{Deserialize, Serialize};
 use std::mem;
@@ -8,11 +10,21 @@ extern crate console_error_panic_hook;
 
 pub mod common;
 pub mod compiler;
+pub mod lsp;
 
+use crate::common::diagnostic::{self, RenderOptions};
 use crate::common::error::SnowFallError;
-use crate::common::{Token, TokenKind, constants};
-use crate::compiler::ast::ProgramAst;
-use crate::compiler::{Lexer, Parser, normalizer};
+use crate::common::messages::Locale;
+use crate::common::{ErrorCode, Span, Token, TokenKind, constants};
+use crate::compiler::arena::AstArena;
+use crate::compiler::ast::{NodeId, ProgramAst};
+use crate::compiler::cst::ConcreteSyntaxTree;
+use crate::compiler::formatter::{self, FormatOptions};
+use crate::compiler::incremental::Document;
+use crate::compiler::lint::LintConfig;
+use crate::compiler::normalizer::{OptimizationOptions, Origin, PassDump};
+use crate::compiler::{Lexer, Parser, lint as linter, normalizer, resolver, semantic, unparser};
+use crate::lsp::LanguageServer;
 
 /// ライブラリの初期化時に一度だけ呼び出されるべき関数
 #[wasm_bindgen(start)]
@@ -31,6 +43,10 @@ pub fn allocate_memory(size: usize) -> *mut u8 {
 
 /// Wasmモジュール内の確保されたメモリを解放する
 #[wasm_bindgen]
+#[allow(
+    clippy::not_unsafe_ptr_arg_deref,
+    reason = "JS側からは allocate_memory で確保したポインタのみが渡される"
+)]
 pub fn free_memory(ptr: *mut u8, size: usize) {
     unsafe {
         let _ = Vec::from_raw_parts(ptr, 0, size);
@@ -75,14 +91,53 @@ pub struct ParserResult {
     errors: Option<Vec<SnowFallError>>,
 }
 
+/// ソースコードを構文解析し、意味解析まで行ったASTを返す
+///
+/// `max_errors` は構文エラーの上限 (`None` の場合は既定値)
+fn parse_and_analyze(
+    source: &str,
+    max_errors: Option<usize>,
+) -> Result<ProgramAst, Vec<SnowFallError>> {
+    let lexer = Lexer::new(source);
+    let mut parser = Parser::new(lexer);
+    if let Some(max_errors) = max_errors {
+        parser = parser.with_max_errors(max_errors);
+    }
+    let program = parser.parse_program()?;
+
+    let errors = semantic::analyze(&program, source);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(program)
+}
+
+/// `undefined` または `null` の場合は既定値とする `CompileOptions` を読み取る
+fn compile_options(options: JsValue) -> Result<CompileOptions, JsValue> {
+    if options.is_undefined() || options.is_null() {
+        return Ok(CompileOptions::default());
+    }
+    serde_wasm_bindgen::from_value(options)
+        .map_err(|e| JsValue::from_str(&format!("Invalid compile options: {}", e)))
+}
+
+/// 診断のメッセージを指定した言語にする
+fn localize(mut errors: Vec<SnowFallError>, locale: Locale) -> Vec<SnowFallError> {
+    for error in &mut errors {
+        error.localize(locale);
+    }
+    errors
+}
+
 /// ソースコードを受け取り、解析したASTを返す
+///
+/// `options` は診断の言語 (`locale`) と構文エラーの上限 (`max_errors`) のみを参照する
 #[wasm_bindgen]
 #[allow(deprecated, reason = "Dev関数では許容")]
 #[deprecated(since = "1.0.0", note = "本番環境での使用は非推奨")]
-pub fn parser(source: &str) -> Result<JsValue, JsValue> {
-    let lexer = Lexer::new(source);
-    let mut parser = Parser::new(lexer);
-    let result = parser.parse_program();
+pub fn parser(source: &str, options: JsValue) -> Result<JsValue, JsValue> {
+    let options = compile_options(options)?;
+    let result = parse_and_analyze(source, options.max_errors);
 
     let compile_result = match result {
         Ok(program) => ParserResult {
@@ -91,7 +146,7 @@ pub fn parser(source: &str) -> Result<JsValue, JsValue> {
         },
         Err(errors) => ParserResult {
             ast: None,
-            errors: Some(errors),
+            errors: Some(localize(errors, options.locale)),
         },
     };
 
@@ -99,26 +154,151 @@ pub fn parser(source: &str) -> Result<JsValue, JsValue> {
         .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
 }
 
+/// 編集に応じて増分解析する文書のハンドル
+///
+/// ソースコードはWasm側で保持するため、編集ごとに全文を受け渡す必要はない。
+/// 位置はすべてUTF-8のバイト単位。使用後は `free()` で解放すること。
+#[wasm_bindgen]
+pub struct SnowFallDocument {
+    document: Document,
+    locale: Locale,
+}
+
+#[wasm_bindgen]
+impl SnowFallDocument {
+    /// ソースコード全体を解析した文書を生成する
+    ///
+    /// `options` は診断の言語 (`locale`) と構文エラーの上限 (`max_errors`) のみを参照する
+    #[wasm_bindgen(constructor)]
+    pub fn new(source: &str, options: JsValue) -> Result<SnowFallDocument, JsValue> {
+        let options = compile_options(options)?;
+        Ok(SnowFallDocument {
+            document: Document::new(source, options.max_errors),
+            locale: options.locale,
+        })
+    }
+
+    /// 現在のソースコード
+    pub fn source(&self) -> String {
+        self.document.source().to_string()
+    }
+
+    /// `start..end` を `text` に置き換え、影響を受けた部分を解析し直す
+    pub fn apply_edit(&mut self, start: usize, end: usize, text: &str) -> Result<JsValue, JsValue> {
+        let result = self
+            .document
+            .edit(Span { start, end }, text)
+            .map_err(|e| JsValue::from_str(&e))?;
+        serde_wasm_bindgen::to_value(&result)
+            .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+    }
+
+    /// 現在の解析結果 (`parser` と同じ形式)
+    pub fn result(&self) -> Result<JsValue, JsValue> {
+        let errors = self.document.errors();
+        let result = if errors.is_empty() {
+            ParserResult {
+                ast: self.document.program().cloned(),
+                errors: None,
+            }
+        } else {
+            ParserResult {
+                ast: None,
+                errors: Some(localize(errors.to_vec(), self.locale)),
+            }
+        };
+        serde_wasm_bindgen::to_value(&result)
+            .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+    }
+}
+
+/// LSP互換のサーバーのハンドル
+///
+/// JSON-RPCのメッセージの本文を文字列で、またはヘッダ付きのメッセージをバイト列で受け渡しする。
+/// 使用後は `free()` で解放すること。
+#[wasm_bindgen]
+pub struct SnowFallLanguageServer {
+    server: LanguageServer,
+}
+
+#[wasm_bindgen]
+impl SnowFallLanguageServer {
+    #[wasm_bindgen(constructor)]
+    #[allow(
+        clippy::new_without_default,
+        reason = "JS側からはコンストラクタとしてのみ使用する"
+    )]
+    pub fn new() -> SnowFallLanguageServer {
+        SnowFallLanguageServer {
+            server: LanguageServer::new(),
+        }
+    }
+
+    /// メッセージの本文を1つ処理し、送り返すメッセージの本文の配列を返す
+    pub fn handle(&mut self, message: &str) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.server.handle(message))
+            .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+    }
+
+    /// ヘッダ付きのメッセージのバイト列を受け取り、送り返すヘッダ付きのメッセージのバイト列を返す
+    ///
+    /// メッセージの途中で区切られたバイト列は、続きを受け取るまで保持する
+    pub fn receive(&mut self, data: &[u8]) -> Vec<u8> {
+        self.server.receive(data).into_bytes()
+    }
+
+    /// `exit` を受け取った場合の終了コード (受け取っていない場合は `undefined`)
+    pub fn exit_code(&self) -> Option<i32> {
+        self.server.exit_code()
+    }
+}
+
+#[derive(Serialize)]
+pub struct NormalizeResult {
+    ast: Option<ProgramAst>,
+    /// 正規化で合成したノードの由来 (ノードID順)
+    origins: Option<Vec<(NodeId, Origin)>>,
+    /// 到達不能なコードなどの警告
+    warnings: Option<Vec<SnowFallError>>,
+    /// 各パスの実行後のAST (`dump_passes` が有効な場合のみ)
+    dumps: Option<Vec<PassDump>>,
+    errors: Option<Vec<SnowFallError>>,
+}
+
 /// ソースコードを受け取り、正規化したASTを返す
+///
+/// `options` が `undefined` または `null` の場合は既定の設定 (全てのパスを実行) を使用する
 #[wasm_bindgen]
 #[allow(deprecated, reason = "Dev関数では許容")]
 #[deprecated(since = "1.0.0", note = "本番環境での使用は非推奨")]
-pub fn normalize(source: &str) -> Result<JsValue, JsValue> {
-    let lexer = Lexer::new(source);
-    let mut parser = Parser::new(lexer);
-    let result = parser.parse_program();
+pub fn normalize(source: &str, options: JsValue) -> Result<JsValue, JsValue> {
+    let options = compile_options(options)?;
+    let result = parse_and_analyze(source, options.max_errors);
 
     let compile_result = match result {
         Ok(program) => {
-            let normalized_program = normalizer::normalize(program);
-            ParserResult {
-                ast: Some(normalized_program),
+            let normalized =
+                normalizer::normalize_with_diagnostics(program, source, &options.optimization);
+            let mut origins: Vec<(NodeId, Origin)> = normalized
+                .origins
+                .iter()
+                .map(|(id, origin)| (id, origin.clone()))
+                .collect();
+            origins.sort_by_key(|(id, _)| *id);
+            NormalizeResult {
+                ast: Some(normalized.program),
+                origins: Some(origins),
+                warnings: Some(localize(normalized.warnings, options.locale)),
+                dumps: options.optimization.dump_passes.then_some(normalized.dumps),
                 errors: None,
             }
         }
-        Err(errors) => ParserResult {
+        Err(errors) => NormalizeResult {
             ast: None,
-            errors: Some(errors),
+            origins: None,
+            warnings: None,
+            dumps: None,
+            errors: Some(localize(errors, options.locale)),
         },
     };
 
@@ -126,7 +306,207 @@ pub fn normalize(source: &str) -> Result<JsValue, JsValue> {
         .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
 }
 
-#[derive(Serialize, Deserialize)]
+/// ソースコードを受け取り、AST(`normalize` が真の場合は正規化後)をソースコードへ戻した結果を返す
+///
+/// `options` は診断の言語 (`locale`) と構文エラーの上限 (`max_errors`) のみを参照する
+#[wasm_bindgen]
+#[allow(deprecated, reason = "Dev関数では許容")]
+#[deprecated(since = "1.0.0", note = "本番環境での使用は非推奨")]
+pub fn unparse(source: &str, normalize: bool, options: JsValue) -> Result<JsValue, JsValue> {
+    let options = compile_options(options)?;
+    let result = parse_and_analyze(source, options.max_errors);
+
+    let unparse_result = match result {
+        Ok(program) => {
+            let program = if normalize {
+                normalizer::normalize(program)
+            } else {
+                program
+            };
+            FormatResult {
+                code: Some(unparser::unparse(&program)),
+                errors: None,
+            }
+        }
+        Err(errors) => FormatResult {
+            code: None,
+            errors: Some(localize(errors, options.locale)),
+        },
+    };
+
+    serde_wasm_bindgen::to_value(&unparse_result)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+#[derive(Serialize)]
+pub struct CstResult {
+    cst: ConcreteSyntaxTree,
+    errors: Option<Vec<SnowFallError>>,
+}
+
+/// ソースコードを受け取り、トリビア(空白・コメント)を保持した具象構文木を返す
+///
+/// 構文エラーがある場合もトークンのみの木を返すため、ソースは常に復元可能
+#[wasm_bindgen]
+#[allow(deprecated, reason = "Dev関数では許容")]
+#[deprecated(since = "1.0.0", note = "本番環境での使用は非推奨")]
+pub fn cst(source: &str) -> Result<JsValue, JsValue> {
+    let lexer = Lexer::new(source);
+    let mut parser = Parser::new(lexer);
+    let result = parser.parse_program();
+
+    let cst_result = match result {
+        Ok(program) => CstResult {
+            cst: ConcreteSyntaxTree::build(source, Some(&program)),
+            errors: None,
+        },
+        Err(errors) => CstResult {
+            cst: ConcreteSyntaxTree::build(source, None),
+            errors: Some(errors),
+        },
+    };
+
+    serde_wasm_bindgen::to_value(&cst_result)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+#[derive(Serialize)]
+pub struct FormatResult {
+    code: Option<String>,
+    errors: Option<Vec<SnowFallError>>,
+}
+
+/// ソースコードを整形する
+///
+/// `options` が `undefined` または `null` の場合は既定の設定を使用する
+#[wasm_bindgen]
+pub fn format(source: &str, options: JsValue) -> Result<JsValue, JsValue> {
+    let options: FormatOptions = if options.is_undefined() || options.is_null() {
+        FormatOptions::default()
+    } else {
+        serde_wasm_bindgen::from_value(options)
+            .map_err(|e| JsValue::from_str(&format!("Invalid format options: {}", e)))?
+    };
+
+    let format_result = match formatter::format(source, &options) {
+        Ok(code) => FormatResult {
+            code: Some(code),
+            errors: None,
+        },
+        Err(errors) => FormatResult {
+            code: None,
+            errors: Some(localize(errors, options.locale)),
+        },
+    };
+
+    serde_wasm_bindgen::to_value(&format_result)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+#[derive(Serialize)]
+pub struct LintResult {
+    /// 有効なルールの診断 (位置順)
+    diagnostics: Vec<SnowFallError>,
+    /// 構文エラーまたは意味解析のエラー (この場合は診断を返さない)
+    errors: Option<Vec<SnowFallError>>,
+}
+
+/// ソースコードをリントする
+///
+/// `config` が `undefined` または `null` の場合はすべてのルールを警告として検査する
+#[wasm_bindgen]
+pub fn lint(source: &str, config: JsValue) -> Result<JsValue, JsValue> {
+    let config: LintConfig = if config.is_undefined() || config.is_null() {
+        LintConfig::default()
+    } else {
+        serde_wasm_bindgen::from_value(config)
+            .map_err(|e| JsValue::from_str(&format!("Invalid lint config: {}", e)))?
+    };
+
+    let lint_result = match parse_and_analyze(source, None) {
+        Ok(program) => LintResult {
+            diagnostics: linter::lint(&program, source, &config),
+            errors: None,
+        },
+        Err(errors) => LintResult {
+            diagnostics: Vec::new(),
+            errors: Some(localize(errors, config.locale)),
+        },
+    };
+
+    serde_wasm_bindgen::to_value(&lint_result)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+/// ソースコードのトークンをシンタックスハイライト用に分類する
+///
+/// 構文エラーがある場合も字句解析の結果から分類する (識別子の種類は前後のトークンから推定する)
+#[wasm_bindgen]
+pub fn semantic_tokens(source: &str) -> Result<JsValue, JsValue> {
+    let arena = AstArena::parse(source).ok();
+    let resolution = arena.map(|arena| resolver::resolve(&arena, source));
+    let tokens = compiler::semantic_tokens::semantic_tokens(source, resolution.as_ref());
+
+    serde_wasm_bindgen::to_value(&tokens)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+/// `offset` (UTF-8のバイト位置) に入力する補完候補を求める
+///
+/// 入力途中で構文エラーがある場合も、カーソルを含む文を除いて解析し直した結果から名前を補完する
+#[wasm_bindgen]
+pub fn complete(source: &str, offset: usize) -> Result<JsValue, JsValue> {
+    let list = compiler::completion::complete(source, offset);
+
+    serde_wasm_bindgen::to_value(&list)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+/// 診断の一覧をソースコードの抜粋付きのレポートに整形する
+///
+/// `options` が `undefined` または `null` の場合は色付けせず、ファイル名を `<input>` とする
+#[wasm_bindgen]
+pub fn render_diagnostics(
+    source: &str,
+    errors: JsValue,
+    options: JsValue,
+) -> Result<String, JsValue> {
+    let errors: Vec<SnowFallError> = serde_wasm_bindgen::from_value(errors)
+        .map_err(|e| JsValue::from_str(&format!("Invalid diagnostics: {}", e)))?;
+    let options: RenderOptions = if options.is_undefined() || options.is_null() {
+        RenderOptions::default()
+    } else {
+        serde_wasm_bindgen::from_value(options)
+            .map_err(|e| JsValue::from_str(&format!("Invalid render options: {}", e)))?
+    };
+    Ok(diagnostic::render_diagnostics(source, &errors, &options))
+}
+
+/// エラーコード (例: "SF0010") の詳しい説明を返す
+///
+/// 未知のコードの場合は `undefined` を返す
+#[wasm_bindgen]
+pub fn explain(code: &str) -> Result<JsValue, JsValue> {
+    serde_wasm_bindgen::to_value(&ErrorCode::from_code(code).map(|code| code.info()))
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+/// すべてのエラーコードの説明をコード番号順に返す
+#[wasm_bindgen]
+pub fn error_codes() -> Result<JsValue, JsValue> {
+    let infos: Vec<_> = ErrorCode::ALL.iter().map(ErrorCode::info).collect();
+    serde_wasm_bindgen::to_value(&infos)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
 pub struct CompileOptions {
     pub debug_info: bool,
+    /// 正規化 (最適化) で実行するパスの設定
+    pub optimization: OptimizationOptions,
+    /// 診断メッセージの言語
+    pub locale: Locale,
+    /// 報告する構文エラーの上限 (`None` の場合は100件)
+    pub max_errors: Option<usize>,
 }
@@ -0,0 +1,92 @@
+//! バイト位置とLSPの位置 (行とUTF-16の列) の相互変換
+use super::protocol::{Position, Range};
+use crate::common::Span;
+
+/// 各行の先頭のバイト位置の表
+///
+/// ```
+/// use snowfall_core::lsp::line_index::LineIndex;
+/// use snowfall_core::lsp::protocol::Position;
+///
+/// // "é" はUTF-8で2バイト、UTF-16で1単位
+/// let source = "Int a;\nString é = \"x\";";
+/// let index = LineIndex::new(source);
+/// let position = Position { line: 1, character: 10 };
+/// assert_eq!(index.offset(position), 18);
+/// assert_eq!(index.position(18), position);
+/// ```
+pub struct LineIndex<'a> {
+    source: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        Self {
+            source,
+            line_starts,
+        }
+    }
+
+    /// バイト位置をLSPの位置に変換する (文字の途中を指す場合は文字の先頭とする)
+    pub fn position(&self, offset: usize) -> Position {
+        let mut offset = offset.min(self.source.len());
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line = self
+            .line_starts
+            .partition_point(|&start| start <= offset)
+            .saturating_sub(1);
+        let line_start = self.line_starts[line];
+        let character = self.source[line_start..offset].encode_utf16().count();
+        Position {
+            line: line as u32,
+            character: character as u32,
+        }
+    }
+
+    /// LSPの位置をバイト位置に変換する
+    ///
+    /// 存在しない行は入力の終端、行末を超える列は行末 (改行の直前) とする。
+    pub fn offset(&self, position: Position) -> usize {
+        let Some(&line_start) = self.line_starts.get(position.line as usize) else {
+            return self.source.len();
+        };
+        let line = &self.source[line_start..];
+        let line = &line[..line.find('\n').unwrap_or(line.len())];
+        let mut units = 0;
+        for (index, ch) in line.char_indices() {
+            if units >= position.character as usize {
+                return line_start + index;
+            }
+            units += ch.len_utf16();
+        }
+        line_start + line.len()
+    }
+
+    pub fn range(&self, span: Span) -> Range {
+        Range {
+            start: self.position(span.start),
+            end: self.position(span.end),
+        }
+    }
+
+    pub fn span(&self, range: Range) -> Span {
+        Span {
+            start: self.offset(range.start),
+            end: self.offset(range.end),
+        }
+    }
+
+    /// 1ベースの (行, バイト単位の列) をバイト位置に変換する
+    pub fn offset_of_line_column(&self, line: u32, column: u32) -> usize {
+        let Some(&line_start) = self.line_starts.get(line.saturating_sub(1) as usize) else {
+            return self.source.len();
+        };
+        (line_start + column.saturating_sub(1) as usize).min(self.source.len())
+    }
+}
@@ -0,0 +1,736 @@
+//! Language Server Protocol
+//!
+//! エディタ支援のためのLSP互換のサーバー。
+//! JSON-RPCのメッセージの本文を `LanguageServer::handle` に渡すと、送り返す応答と通知の本文を返す。
+//! 入出力はメモリ上で完結する。ヘッダ付きのメッセージのバイト列は `LanguageServer::receive` で扱う
+//! (ヘッダとの変換は `transport` で行う)。
+//!
+//! 対応する機能
+//! - 診断 (構文エラー・意味解析のエラー・リンターの診断) の通知
+//! - ホバー (宣言の型の表示)
+//! - 定義へ移動・参照の検索
+//! - ドキュメントシンボル (関数・サブルーチン・クラス)
+//! - 補完 (入力途中の構文エラーがあるコードを含む)
+//! - セマンティックトークン (文書全体)
+//!
+//! 文書は増分構文解析 (`incremental`) で保持し、変更は差分のまま適用する。
+//! 適用できない変更 (開始と終了が逆転した範囲など) を受け取った場合は、全文を置き換える変更を受け取るまで文書への要求を拒否する。
+pub mod line_index;
+pub mod protocol;
+pub mod transport;
+
+use ahash::AHashMap;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+use crate::common::error::{Severity, SnowFallError};
+use crate::common::messages::Locale;
+use crate::common::{Span, constants};
+use crate::compiler::arena::AstArena;
+use crate::compiler::completion::{self, CompletionKind};
+use crate::compiler::incremental::Document;
+use crate::compiler::lint::{self, LintConfig, Rule, RuleLevel};
+use crate::compiler::resolver::{self, Resolution, SymbolId, SymbolKind};
+use crate::compiler::semantic_tokens::{self, SemanticTokenKind, SemanticTokenModifier};
+use line_index::LineIndex;
+use protocol::*;
+use transport::{MessageReader, encode_message};
+
+/// 診断の発行元として表示する名前
+const SOURCE: &str = "snowfall";
+
+/// `initialize` の `initializationOptions` で指定する設定
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ServerOptions {
+    /// 診断メッセージの言語
+    pub locale: Locale,
+    /// 報告する構文エラーの上限 (`None` の場合は既定値)
+    pub max_errors: Option<usize>,
+    /// リントルールごとの報告レベル (指定のないルールは `warn`)
+    pub lint: AHashMap<Rule, RuleLevel>,
+}
+
+/// サーバーの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// `initialize` を待っている
+    Uninitialized,
+    Running,
+    /// `shutdown` を受け取り、`exit` を待っている
+    ShuttingDown,
+    /// `exit` を受け取った (以降のメッセージは無視する)
+    Exited {
+        code: i32,
+    },
+}
+
+/// 開いている文書
+struct OpenDocument {
+    document: Document,
+    version: i32,
+    /// 名前解決の結果
+    ///
+    /// 構文エラーがある間は、最後に解析できたときの結果を編集に合わせてずらしたもの
+    /// (一度も解析できていない場合は `None`)
+    resolution: Option<Resolution>,
+    /// 適用できない変更を受け取り、クライアントと内容が食い違っているか
+    ///
+    /// 全文を置き換える変更を受け取るまで、差分の変更と文書への要求を拒否する
+    out_of_sync: bool,
+}
+
+impl OpenDocument {
+    fn new(text: String, version: i32, max_errors: Option<usize>) -> Self {
+        let mut document = Self {
+            document: Document::new(text, max_errors),
+            version,
+            resolution: None,
+            out_of_sync: false,
+        };
+        document.update_resolution();
+        document
+    }
+
+    /// 構文解析に成功していれば名前を解決し直す (失敗していれば直前の結果を残す)
+    ///
+    /// 増分解析の構文木は `ProgramAst` のため、アリーナ形式へ変換してから解決する
+    fn update_resolution(&mut self) {
+        if let Some(program) = self.document.program() {
+            let arena = AstArena::from_program(program);
+            self.resolution = Some(resolver::resolve(&arena, self.document.source()));
+        }
+    }
+}
+
+/// LSP互換のサーバー
+///
+/// ```
+/// use snowfall_core::lsp::LanguageServer;
+///
+/// let mut server = LanguageServer::new();
+/// server.handle(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#);
+/// let messages = server.handle(
+///     r#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///a.sf","languageId":"snowfall","version":1,"text":"Int a = ;"}}}"#,
+/// );
+/// // 開いた文書の診断が通知される
+/// assert!(messages[0].contains("textDocument/publishDiagnostics"));
+/// assert!(messages[0].contains("SF0018"));
+/// ```
+pub struct LanguageServer {
+    state: State,
+    options: ServerOptions,
+    documents: AHashMap<String, OpenDocument>,
+    /// `receive` で受け取った、まだ揃っていないメッセージ
+    reader: MessageReader,
+}
+
+impl Default for LanguageServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LanguageServer {
+    pub fn new() -> Self {
+        Self {
+            state: State::Uninitialized,
+            options: ServerOptions::default(),
+            documents: AHashMap::new(),
+            reader: MessageReader::default(),
+        }
+    }
+
+    /// `exit` を受け取った場合の終了コード (`shutdown` の後なら0、そうでなければ1)
+    pub fn exit_code(&self) -> Option<i32> {
+        match self.state {
+            State::Exited { code } => Some(code),
+            _ => None,
+        }
+    }
+
+    /// JSON-RPCのメッセージの本文を1つ処理し、送り返すメッセージの本文を返す
+    ///
+    /// 要求には必ず1つの応答を返す。通知に対しては診断の通知などを返す場合がある。
+    pub fn handle(&mut self, message: &str) -> Vec<String> {
+        if matches!(self.state, State::Exited { .. }) {
+            return Vec::new();
+        }
+        let message: IncomingMessage = match serde_json::from_str(message) {
+            Ok(message) => message,
+            Err(e) => {
+                let error =
+                    ResponseError::new(error_codes::PARSE_ERROR, format!("Invalid message: {}", e));
+                return vec![response(Value::Null, Err(error))];
+            }
+        };
+
+        match (message.id, message.method) {
+            (Some(id), Some(method)) => {
+                let result = self.handle_request(&method, message.params);
+                vec![response(id, result)]
+            }
+            (None, Some(method)) => self.handle_notification(&method, message.params),
+            // クライアントからの応答 (サーバーから要求を送らないため無視する)
+            (Some(_), None) => Vec::new(),
+            (None, None) => {
+                let error =
+                    ResponseError::new(error_codes::INVALID_REQUEST, "Missing method and id");
+                vec![response(Value::Null, Err(error))]
+            }
+        }
+    }
+
+    /// ヘッダ付きのメッセージのバイト列を受け取り、送り返すヘッダ付きのメッセージを返す
+    ///
+    /// メッセージが揃うまでバイト列を蓄え、揃ったものから順に `handle` で処理する。
+    /// ヘッダが不正なメッセージには `PARSE_ERROR` の応答を返す。
+    ///
+    /// ```
+    /// use snowfall_core::lsp::LanguageServer;
+    /// use snowfall_core::lsp::transport::encode_message;
+    ///
+    /// let mut server = LanguageServer::new();
+    /// let data = encode_message(r#"{"jsonrpc":"2.0","id":1,"method":"shutdown"}"#);
+    /// let (first, rest) = data.as_bytes().split_at(20);
+    /// assert_eq!(server.receive(first), "");
+    /// assert!(server.receive(rest).starts_with("Content-Length: "));
+    /// ```
+    pub fn receive(&mut self, data: &[u8]) -> String {
+        self.reader.push(data);
+        let mut output = String::new();
+        loop {
+            let messages = match self.reader.next_message() {
+                Ok(Some(body)) => self.handle(&body),
+                Ok(None) => break,
+                Err(e) => {
+                    let error = ResponseError::new(error_codes::PARSE_ERROR, e);
+                    vec![response(Value::Null, Err(error))]
+                }
+            };
+            for message in messages {
+                output.push_str(&encode_message(&message));
+            }
+        }
+        output
+    }
+
+    fn handle_request(&mut self, method: &str, params: Value) -> Result<Value, ResponseError> {
+        match (self.state, method) {
+            (State::Uninitialized, "initialize") => {
+                let options = params
+                    .get("initializationOptions")
+                    .filter(|options| !options.is_null())
+                    .cloned()
+                    .map(serde_json::from_value::<ServerOptions>)
+                    .transpose()
+                    .map_err(invalid_params)?;
+                self.options = options.unwrap_or_default();
+                self.state = State::Running;
+                Ok(initialize_result())
+            }
+            (State::Uninitialized, _) => Err(ResponseError::new(
+                error_codes::SERVER_NOT_INITIALIZED,
+                "Server is not initialized",
+            )),
+            (_, "initialize") => Err(ResponseError::new(
+                error_codes::INVALID_REQUEST,
+                "Server is already initialized",
+            )),
+            (State::ShuttingDown, _) => Err(ResponseError::new(
+                error_codes::INVALID_REQUEST,
+                "Server is shutting down",
+            )),
+            (_, "shutdown") => {
+                self.state = State::ShuttingDown;
+                Ok(Value::Null)
+            }
+            (_, "textDocument/hover") => self.hover(parse_params(params)?),
+            (_, "textDocument/definition") => self.definition(parse_params(params)?),
+            (_, "textDocument/references") => self.references(parse_params(params)?),
+            (_, "textDocument/documentSymbol") => self.document_symbols(parse_params(params)?),
+            (_, "textDocument/completion") => self.completion(parse_params(params)?),
+            (_, "textDocument/semanticTokens/full") => self.semantic_tokens(parse_params(params)?),
+            _ => Err(ResponseError::new(
+                error_codes::METHOD_NOT_FOUND,
+                format!("Unknown method: {}", method),
+            )),
+        }
+    }
+
+    /// 通知を処理する (不正な通知は応答できないため無視する)
+    fn handle_notification(&mut self, method: &str, params: Value) -> Vec<String> {
+        if method == "exit" {
+            let code = if self.state == State::ShuttingDown {
+                0
+            } else {
+                1
+            };
+            self.state = State::Exited { code };
+            return Vec::new();
+        }
+        if self.state != State::Running {
+            return Vec::new();
+        }
+
+        match method {
+            "textDocument/didOpen" => {
+                let Ok(params) = serde_json::from_value::<DidOpenTextDocumentParams>(params) else {
+                    return Vec::new();
+                };
+                let item = params.text_document;
+                let document = OpenDocument::new(item.text, item.version, self.options.max_errors);
+                self.documents.insert(item.uri.clone(), document);
+                self.publish_diagnostics(&item.uri)
+            }
+            "textDocument/didChange" => {
+                let Ok(params) = serde_json::from_value::<DidChangeTextDocumentParams>(params)
+                else {
+                    return Vec::new();
+                };
+                let uri = params.text_document.uri;
+                let max_errors = self.options.max_errors;
+                let Some(open) = self.documents.get_mut(&uri) else {
+                    return Vec::new();
+                };
+                let mut rejected = None;
+                for change in params.content_changes {
+                    match change.range {
+                        Some(_) if open.out_of_sync => {}
+                        Some(range) => {
+                            let span = LineIndex::new(open.document.source()).span(range);
+                            // 位置は行末などに丸めてあるが、開始と終了が逆転した範囲は適用できない
+                            match open.document.edit(span, &change.text) {
+                                Ok(_) => {
+                                    if let Some(resolution) = &mut open.resolution {
+                                        resolution.shift(span, change.text.len());
+                                    }
+                                }
+                                Err(message) => {
+                                    open.out_of_sync = true;
+                                    rejected = Some(message);
+                                }
+                            }
+                        }
+                        None => {
+                            open.document = Document::new(change.text, max_errors);
+                            open.resolution = None;
+                            open.out_of_sync = false;
+                        }
+                    }
+                }
+                open.version = params.text_document.version;
+                open.update_resolution();
+                let mut messages = Vec::new();
+                if let Some(message) = rejected {
+                    messages.push(notification(
+                        "window/logMessage",
+                        LogMessageParams {
+                            kind: 1,
+                            message: format!(
+                                "{} in {}; send the full text to resynchronize",
+                                message, uri
+                            ),
+                        },
+                    ));
+                }
+                messages.extend(self.publish_diagnostics(&uri));
+                messages
+            }
+            "textDocument/didClose" => {
+                let Ok(params) = serde_json::from_value::<DidCloseTextDocumentParams>(params)
+                else {
+                    return Vec::new();
+                };
+                let uri = params.text_document.uri;
+                self.documents.remove(&uri);
+                // 閉じた文書の診断を消す
+                vec![notification(
+                    "textDocument/publishDiagnostics",
+                    PublishDiagnosticsParams {
+                        uri,
+                        version: None,
+                        diagnostics: Vec::new(),
+                    },
+                )]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn document(&self, uri: &str) -> Result<&OpenDocument, ResponseError> {
+        match self.documents.get(uri) {
+            Some(open) if open.out_of_sync => Err(ResponseError::new(
+                error_codes::INVALID_PARAMS,
+                format!("Document is out of sync: {}", uri),
+            )),
+            Some(open) => Ok(open),
+            None => Err(ResponseError::new(
+                error_codes::INVALID_PARAMS,
+                format!("Unknown document: {}", uri),
+            )),
+        }
+    }
+
+    /// 文書の診断を通知する
+    fn publish_diagnostics(&self, uri: &str) -> Vec<String> {
+        let Some(open) = self.documents.get(uri) else {
+            return Vec::new();
+        };
+        let source = open.document.source();
+        let mut errors = open.document.errors().to_vec();
+        if open.out_of_sync {
+            // 内容が食い違っている間は、古い内容に対する診断を消す
+            errors.clear();
+        } else if errors.is_empty()
+            && let Some(program) = open.document.program()
+        {
+            let config = LintConfig {
+                rules: self.options.lint.clone(),
+                locale: self.options.locale,
+            };
+            errors = lint::lint(program, source, &config);
+        }
+
+        let index = LineIndex::new(source);
+        let diagnostics = errors
+            .iter_mut()
+            .map(|error| {
+                error.localize(self.options.locale);
+                to_diagnostic(error, uri, &index)
+            })
+            .collect();
+        vec![notification(
+            "textDocument/publishDiagnostics",
+            PublishDiagnosticsParams {
+                uri: uri.to_string(),
+                version: Some(open.version),
+                diagnostics,
+            },
+        )]
+    }
+
+    /// 名前の上の位置であれば、その名前が指すシンボルを求める
+    fn name_at(
+        &self,
+        params: &TextDocumentPositionParams,
+    ) -> Result<Option<NameAt<'_>>, ResponseError> {
+        let open = self.document(&params.text_document.uri)?;
+        let Some(resolution) = open.resolution.as_ref() else {
+            return Ok(None);
+        };
+        let index = LineIndex::new(open.document.source());
+        let offset = index.offset(params.position);
+        Ok(resolution.name_at(offset).map(|(symbol, span)| NameAt {
+            index,
+            resolution,
+            symbol,
+            span,
+        }))
+    }
+
+    fn hover(&self, params: TextDocumentPositionParams) -> Result<Value, ResponseError> {
+        let Some(name) = self.name_at(&params)? else {
+            return Ok(Value::Null);
+        };
+        let symbol = name.resolution.symbol(name.symbol);
+        let text = match symbol.kind {
+            SymbolKind::Variable | SymbolKind::Parameter | SymbolKind::Field => {
+                format!("({}) {}", symbol_kind_label(symbol.kind), symbol.signature)
+            }
+            _ => symbol.signature.clone(),
+        };
+        to_value(Hover {
+            contents: MarkupContent {
+                kind: "markdown",
+                value: format!("```snowfall\n{}\n```", text),
+            },
+            range: name.index.range(name.span),
+        })
+    }
+
+    fn definition(&self, params: TextDocumentPositionParams) -> Result<Value, ResponseError> {
+        let Some(name) = self.name_at(&params)? else {
+            return Ok(Value::Null);
+        };
+        to_value(Location {
+            uri: params.text_document.uri.clone(),
+            range: name.index.range(name.resolution.symbol(name.symbol).span),
+        })
+    }
+
+    fn references(&self, params: ReferenceParams) -> Result<Value, ResponseError> {
+        let position = TextDocumentPositionParams {
+            text_document: params.text_document,
+            position: params.position,
+        };
+        let Some(name) = self.name_at(&position)? else {
+            return Ok(json!([]));
+        };
+        let declaration = params
+            .context
+            .include_declaration
+            .then(|| name.resolution.symbol(name.symbol).span);
+        let locations: Vec<Location> = declaration
+            .into_iter()
+            .chain(name.resolution.references_to(name.symbol).map(|r| r.span))
+            .map(|span| Location {
+                uri: position.text_document.uri.clone(),
+                range: name.index.range(span),
+            })
+            .collect();
+        to_value(locations)
+    }
+
+    fn document_symbols(&self, params: DocumentSymbolParams) -> Result<Value, ResponseError> {
+        let open = self.document(&params.text_document.uri)?;
+        let Some(resolution) = open.resolution.as_ref() else {
+            return Ok(json!([]));
+        };
+        let index = LineIndex::new(open.document.source());
+        to_value(document_symbols(resolution, &index, None))
+    }
+
+    /// 補完 (`completion`) の候補を順位の順に返す
+    ///
+    /// エディタが名前順に並べ替えないよう、順位を `sortText` に設定する。
+    fn completion(&self, params: TextDocumentPositionParams) -> Result<Value, ResponseError> {
+        let open = self.document(&params.text_document.uri)?;
+        let source = open.document.source();
+        let offset = LineIndex::new(source).offset(params.position);
+
+        let items: Vec<CompletionItem> = completion::complete(source, offset)
+            .items
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| CompletionItem {
+                label: item.label,
+                kind: completion_kind(item.kind),
+                detail: item.detail,
+                sort_text: format!("{:04}", index),
+            })
+            .collect();
+        to_value(items)
+    }
+
+    /// 文書全体のセマンティックトークンを相対位置で符号化する
+    ///
+    /// 各トークンは (行の差, 列の差, 長さ, 種類, 修飾子のビット集合) の5つの整数で表す。
+    /// 複数行にまたがるトークン (ブロックコメント) は行ごとに分割する。
+    fn semantic_tokens(&self, params: SemanticTokensParams) -> Result<Value, ResponseError> {
+        let open = self.document(&params.text_document.uri)?;
+        let source = open.document.source();
+        let index = LineIndex::new(source);
+
+        let mut data: Vec<u32> = Vec::new();
+        let mut previous = Position {
+            line: 0,
+            character: 0,
+        };
+        for token in semantic_tokens::semantic_tokens(source, open.resolution.as_ref()) {
+            let kind = SemanticTokenKind::ALL
+                .iter()
+                .position(|&kind| kind == token.kind)
+                .unwrap_or_default() as u32;
+            let modifiers = token.modifiers.iter().fold(0u32, |bits, modifier| {
+                let bit = SemanticTokenModifier::ALL
+                    .iter()
+                    .position(|m| m == modifier)
+                    .unwrap_or_default();
+                bits | (1 << bit)
+            });
+
+            let mut start = token.span.start;
+            for line in source[token.span.start..token.span.end].split_inclusive('\n') {
+                let text = line.trim_end_matches(['\r', '\n']);
+                let position = index.position(start);
+                start += line.len();
+                if text.is_empty() {
+                    continue;
+                }
+                let delta_line = position.line - previous.line;
+                let delta_start = if delta_line == 0 {
+                    position.character - previous.character
+                } else {
+                    position.character
+                };
+                let length = text.encode_utf16().count() as u32;
+                data.extend([delta_line, delta_start, length, kind, modifiers]);
+                previous = position;
+            }
+        }
+        Ok(json!({ "data": data }))
+    }
+}
+
+/// 名前の上の位置で求めたシンボル
+struct NameAt<'a> {
+    index: LineIndex<'a>,
+    resolution: &'a Resolution,
+    symbol: SymbolId,
+    /// 位置にある名前 (宣言または参照) の範囲
+    span: Span,
+}
+
+/// `initialize` の応答 (サーバーの機能)
+fn initialize_result() -> Value {
+    let token_types: Vec<&str> = SemanticTokenKind::ALL.iter().map(|k| k.as_str()).collect();
+    let token_modifiers: Vec<&str> = SemanticTokenModifier::ALL
+        .iter()
+        .map(|m| m.as_str())
+        .collect();
+    json!({
+        "capabilities": {
+            "positionEncoding": "utf-16",
+            // 1: 全文, 2: 差分
+            "textDocumentSync": { "openClose": true, "change": 2 },
+            "hoverProvider": true,
+            "definitionProvider": true,
+            "referencesProvider": true,
+            "documentSymbolProvider": true,
+            "completionProvider": { "triggerCharacters": ["."] },
+            "semanticTokensProvider": {
+                "legend": {
+                    "tokenTypes": token_types,
+                    "tokenModifiers": token_modifiers,
+                },
+                "full": true,
+            },
+        },
+        "serverInfo": { "name": SOURCE, "version": constants::VERSION },
+    })
+}
+
+/// `container` 直下の関数・サブルーチン・クラスを階層にまとめる
+fn document_symbols(
+    resolution: &Resolution,
+    index: &LineIndex,
+    container: Option<SymbolId>,
+) -> Vec<DocumentSymbol> {
+    resolution
+        .symbols
+        .iter()
+        .enumerate()
+        .filter(|(_, symbol)| symbol.container == container)
+        .filter_map(|(id, symbol)| {
+            let kind = match symbol.kind {
+                SymbolKind::Class => 5,
+                SymbolKind::Method => 6,
+                SymbolKind::Function | SymbolKind::Sub => 12,
+                _ => return None,
+            };
+            Some(DocumentSymbol {
+                name: symbol.name.clone(),
+                detail: symbol.signature.clone(),
+                kind,
+                range: index.range(symbol.declaration),
+                selection_range: index.range(symbol.span),
+                children: document_symbols(resolution, index, Some(SymbolId(id))),
+            })
+        })
+        .collect()
+}
+
+fn to_diagnostic(error: &SnowFallError, uri: &str, index: &LineIndex) -> Diagnostic {
+    let range = match error.span {
+        Some(span) => index.range(span),
+        None => {
+            let start = index.offset_of_line_column(error.line, error.column);
+            let end = index
+                .offset_of_line_column(error.end_line, error.end_column)
+                .max(start);
+            index.range(Span { start, end })
+        }
+    };
+    let severity = match error.severity {
+        Severity::Error => 1,
+        Severity::Warning => 2,
+        Severity::Info => 3,
+        Severity::Hint => 4,
+    };
+    Diagnostic {
+        range,
+        severity,
+        code: error.code.clone(),
+        source: SOURCE,
+        message: error.message.clone(),
+        related_information: error
+            .labels
+            .iter()
+            .map(|label| DiagnosticRelatedInformation {
+                location: Location {
+                    uri: uri.to_string(),
+                    range: index.range(label.range.span),
+                },
+                message: label.message.clone(),
+            })
+            .collect(),
+    }
+}
+
+/// ホバーで表示するシンボルの種類
+fn symbol_kind_label(kind: SymbolKind) -> &'static str {
+    match kind {
+        SymbolKind::Variable => "variable",
+        SymbolKind::Constant => "constant",
+        SymbolKind::Parameter => "parameter",
+        SymbolKind::Function => "function",
+        SymbolKind::Sub => "sub",
+        SymbolKind::Class => "class",
+        SymbolKind::Field => "field",
+        SymbolKind::Method => "method",
+    }
+}
+
+/// 補完候補の種類
+fn completion_kind(kind: CompletionKind) -> CompletionItemKind {
+    match kind {
+        CompletionKind::Method => 2,
+        CompletionKind::Function => 3,
+        CompletionKind::Field => 5,
+        CompletionKind::Variable | CompletionKind::Parameter => 6,
+        CompletionKind::Class | CompletionKind::Type => 7,
+        CompletionKind::Keyword => 14,
+        CompletionKind::Constant => 21,
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, ResponseError> {
+    serde_json::from_value(params).map_err(invalid_params)
+}
+
+fn invalid_params(e: serde_json::Error) -> ResponseError {
+    ResponseError::new(
+        error_codes::INVALID_PARAMS,
+        format!("Invalid params: {}", e),
+    )
+}
+
+fn to_value(value: impl serde::Serialize) -> Result<Value, ResponseError> {
+    // 定義した型のシリアライズは失敗しない
+    Ok(serde_json::to_value(value).unwrap_or(Value::Null))
+}
+
+fn response(id: Value, result: Result<Value, ResponseError>) -> String {
+    let (result, error) = match result {
+        Ok(result) => (Some(result), None),
+        Err(error) => (None, Some(error)),
+    };
+    let message = ResponseMessage {
+        jsonrpc: "2.0",
+        id,
+        result,
+        error,
+    };
+    serde_json::to_string(&message).unwrap_or_default()
+}
+
+fn notification(method: &'static str, params: impl serde::Serialize) -> String {
+    let message = NotificationMessage {
+        jsonrpc: "2.0",
+        method,
+        params: serde_json::to_value(params).unwrap_or(Value::Null),
+    };
+    serde_json::to_string(&message).unwrap_or_default()
+}
@@ -0,0 +1,75 @@
+//! LSPの基本プロトコルのメッセージの組み立てと分解
+//!
+//! 各メッセージは `Content-Length: <本文のバイト数>\r\n\r\n<本文>` の形式で送受信される。
+//! 入出力の手段は扱わず、受け取ったバイト列をメモリ上で蓄えて本文を取り出す。
+
+const CONTENT_LENGTH: &str = "content-length:";
+
+/// 本文にヘッダを付けたメッセージを組み立てる
+pub fn encode_message(body: &str) -> String {
+    format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+}
+
+/// 受け取ったバイト列から順にメッセージの本文を取り出す
+///
+/// ```
+/// use snowfall_core::lsp::transport::{MessageReader, encode_message};
+///
+/// let data = encode_message(r#"{"jsonrpc":"2.0","method":"initialized"}"#);
+/// let (first, rest) = data.as_bytes().split_at(10);
+///
+/// let mut reader = MessageReader::default();
+/// reader.push(first);
+/// assert_eq!(reader.next_message(), Ok(None));
+/// reader.push(rest);
+/// assert_eq!(
+///     reader.next_message(),
+///     Ok(Some(r#"{"jsonrpc":"2.0","method":"initialized"}"#.to_string()))
+/// );
+/// ```
+#[derive(Debug, Default)]
+pub struct MessageReader {
+    buffer: Vec<u8>,
+}
+
+impl MessageReader {
+    /// 受け取ったバイト列を追加する
+    pub fn push(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// 次のメッセージの本文を取り出す
+    ///
+    /// メッセージ全体がまだ揃っていない場合は `None` を返す。
+    /// ヘッダが不正な場合 (`Content-Length` がない、または扱えない大きさ) はヘッダを読み飛ばしてエラーを返す。
+    pub fn next_message(&mut self) -> Result<Option<String>, String> {
+        let Some(header_end) = self
+            .buffer
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")
+        else {
+            return Ok(None);
+        };
+        let body_start = header_end + 4;
+
+        let header = String::from_utf8_lossy(&self.buffer[..header_end]).into_owned();
+        let length = header.split("\r\n").find_map(|line| {
+            let lower = line.to_ascii_lowercase();
+            lower
+                .strip_prefix(CONTENT_LENGTH)
+                .and_then(|value| value.trim().parse::<usize>().ok())
+        });
+        let Some(body_end) = length.and_then(|length| body_start.checked_add(length)) else {
+            self.buffer.drain(..body_start);
+            return Err(format!("Invalid Content-Length header: {:?}", header));
+        };
+
+        if self.buffer.len() < body_end {
+            return Ok(None);
+        }
+        let body: Vec<u8> = self.buffer.drain(..body_end).skip(body_start).collect();
+        String::from_utf8(body)
+            .map(Some)
+            .map_err(|e| format!("Invalid UTF-8 in message body: {}", e))
+    }
+}